@@ -19,21 +19,20 @@ fn bench_app() -> Router {
         .merge(delay::router())
         .merge(redirect::router())
         .merge(cookies::router())
-        .layer(middleware::from_fn(timing_middleware))
+        .layer(middleware::from_fn(|req, next| {
+            timing_middleware(req, next, Default::default())
+        }))
 }
 
 /// Builds the full application router exactly as the binary wires it (metrics,
 /// chaos-gate, timing, trace, compression toggle, CORS, normalize-path,
 /// request-id), for the bare-vs-full-stack comparison.
 fn bench_full_app() -> Router {
-    let config = rucho::utils::config::Config::default();
-    rucho::app::build_app(
-        Some(Arc::new(rucho::utils::metrics::Metrics::new())),
-        config.compression_enabled,
-        Arc::new(config.chaos.clone()),
-        config.max_body_size_bytes,
-        config.request_id_enabled,
-    )
+    rucho::app::build_app(rucho::app::AppOptions {
+        metrics: Some(Arc::new(rucho::utils::metrics::Metrics::new())),
+        trust_proxy: true,
+        ..rucho::app::AppOptions::default()
+    })
 }
 
 fn bench_get_healthz(c: &mut Criterion) {
@@ -0,0 +1,65 @@
+//! Build script: records the build metadata `/version` reports.
+//!
+//! Sets, for `env!` in the crate:
+//! - `RUCHO_GIT_COMMIT`: `git rev-parse --short=12 HEAD`, or the
+//!   `RUCHO_GIT_COMMIT` environment variable when set (for builds without a
+//!   `.git`, such as Docker's), or `unknown`.
+//! - `RUCHO_BUILD_TIMESTAMP`: Unix seconds of the build, or
+//!   `SOURCE_DATE_EPOCH` when set, so reproducible builds stay reproducible.
+//! - `RUCHO_RUSTC_VERSION`: `rustc --version` of the compiler in use.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Trimmed stdout of `program args`, if it ran successfully.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    Some(stdout.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn main() {
+    let commit = std::env::var("RUCHO_GIT_COMMIT")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .or_else(|| command_output("git", &["rev-parse", "--short=12", "HEAD"]))
+        .unwrap_or_else(|| "unknown".to_string());
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs())
+        });
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version =
+        command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=RUCHO_GIT_COMMIT={commit}");
+    println!("cargo:rustc-env=RUCHO_BUILD_TIMESTAMP={timestamp}");
+    println!("cargo:rustc-env=RUCHO_RUSTC_VERSION={rustc_version}");
+
+    // Rerun when the checked-out commit moves. A path that doesn't exist
+    // would rerun the script on every build, so only name the ones that do.
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=RUCHO_GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let head = Path::new(".git/HEAD");
+    if head.exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        if let Some(reference) = std::fs::read_to_string(head)
+            .ok()
+            .and_then(|head| head.strip_prefix("ref: ").map(|r| r.trim().to_string()))
+        {
+            let reference = Path::new(".git").join(reference);
+            if reference.exists() {
+                println!("cargo:rerun-if-changed={}", reference.display());
+            }
+        }
+    }
+}
@@ -5,67 +5,283 @@
 //! tests can exercise the *real* app — middleware and all — via the same
 //! function the server uses, rather than a hand-rolled minimal router.
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Duration;
 
-use axum::{extract::DefaultBodyLimit, middleware, routing::get, Router};
+use arc_swap::ArcSwap;
+
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{header, HeaderValue},
+    middleware,
+    routing::get,
+    Extension, Router,
+};
+use tower::Layer;
 use tower_http::{
     compression::CompressionLayer,
-    cors::CorsLayer,
     normalize_path::NormalizePathLayer,
-    trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer},
+    set_header::SetResponseHeaderLayer,
+    trace::{DefaultOnRequest, DefaultOnResponse, TraceLayer},
 };
 use tracing::Level;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::openapi::ApiDoc;
-use crate::server::chaos_layer::chaos_middleware;
+use crate::routes::core_routes::{FailMidstream, RequestLimits, TrustProxy, WelcomeMessage};
+use crate::routes::healthz::{Readiness, StartTime};
+use crate::routes::introspect::EnvAllowList;
+use crate::routes::static_files::StaticFiles;
+use crate::server::access_log::{access_log_middleware, AccessLog};
+use crate::server::allow_layer::{
+    allow_middleware, with_allow_responses, with_preflight_only_cors,
+};
+use crate::server::chaos_layer::{chaos_middleware, ChaosRng};
+use crate::server::client_cert_layer::client_cert_middleware;
+use crate::server::cors::CorsConfig;
+use crate::server::date_layer::date_middleware;
 use crate::server::metrics_layer::metrics_middleware;
-use crate::server::request_id::request_id_middleware;
+use crate::server::panic_layer::catch_panic_layer;
+use crate::server::rate_limit_layer::{rate_limit_middleware, RateLimiter};
+use crate::server::request_id::{make_request_span, request_id_middleware};
+use crate::server::scenario_layer::scenario_middleware;
+use crate::server::shutdown::StreamingShutdown;
+use crate::server::timeout_layer::timeout_middleware;
 use crate::server::timing_layer::timing_middleware;
-use crate::utils::config::ChaosConfig;
+use crate::server::trace_context::trace_context_middleware;
+use crate::server::transform_layer::{parse_pipeline, transform_middleware, Transform};
+use crate::utils::clock::Clock;
+use crate::utils::config::{ChaosConfig, Config, Scenario};
 use crate::utils::metrics::Metrics;
+use crate::utils::signature::SigningKey;
 
-/// Builds the Axum application with all routes and middleware.
+/// Everything [`build_app`] can be configured with, one field per optional
+/// feature. [`Default`] is the app a default [`Config`] describes, without
+/// metrics, rate limiting or an access log; override only what you need:
 ///
-/// If `metrics` is `Some`, enables the `/metrics` endpoint and metrics-collection
-/// middleware. If `compression_enabled` is true, enables gzip/brotli response
-/// compression. If chaos mode is enabled, adds chaos middleware for resilience
-/// testing. `max_body_size_bytes` caps request body size via `DefaultBodyLimit`;
-/// requests with larger bodies receive 413 Payload Too Large. If
-/// `request_id_enabled` is true, adds the outermost request-id middleware that
-/// stamps an `X-Request-Id` correlation header on every response.
-pub fn build_app(
-    metrics: Option<Arc<Metrics>>,
-    compression_enabled: bool,
-    chaos: Arc<ChaosConfig>,
-    max_body_size_bytes: usize,
-    request_id_enabled: bool,
-) -> Router {
-    let mut app = Router::new()
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+/// ```
+/// use rucho::app::{build_app, AppOptions};
+///
+/// let app = build_app(AppOptions {
+///     trust_proxy: true,
+///     ..AppOptions::default()
+/// });
+/// ```
+pub struct AppOptions {
+    /// Enables the `/metrics` endpoint and the metrics-collection middleware.
+    pub metrics: Option<Arc<Metrics>>,
+    /// Enables gzip/brotli response compression.
+    pub compression_enabled: bool,
+    /// Read on every request; requests pass through while chaos is disabled,
+    /// so a config reload can swap it.
+    pub chaos: Arc<ArcSwap<ChaosConfig>>,
+    /// Caps request body size via `DefaultBodyLimit`; larger bodies get 413
+    /// Payload Too Large, and `/anything?limits=true` reports it.
+    pub max_body_size_bytes: usize,
+    /// Adds the outermost request-id middleware that stamps an `X-Request-Id`
+    /// correlation header on every response.
+    pub request_id_enabled: bool,
+    /// Rejects requests over its per-client-IP limit with 429 before they
+    /// reach any other layer but request-id.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Requests under any of these prefixes are rejected with 403 unless their
+    /// TLS connection presented a verified client certificate.
+    pub client_cert_paths: Vec<String>,
+    /// `/anything?sign=true` responses carry an `X-Signature` HMAC of their
+    /// body made with it.
+    pub signing_key: Option<SigningKey>,
+    /// `/anything?fail_midstream=true` aborts its response halfway through
+    /// the body.
+    pub fail_midstream_enabled: bool,
+    /// The pipeline response bodies run through, unless a request names
+    /// another in `X-Transform`.
+    pub transforms: Vec<Transform>,
+    /// A request's `X-Rucho-Scenario` header selects one of these.
+    pub scenarios: BTreeMap<String, Scenario>,
+    /// Streaming responses register with it, so shutdown can cut them after
+    /// its grace.
+    pub streaming: StreamingShutdown,
+    /// Request timing, `/delay` and the `Date` header read the time from it;
+    /// pass the same clock to `Metrics::with_clock` to keep the metrics window
+    /// on it too.
+    pub clock: Clock,
+    /// `/ip`, `/get` and the rate limiter take the client IP from
+    /// `X-Forwarded-For` / `X-Real-IP`.
+    pub trust_proxy: bool,
+    /// CORS headers and preflight answers follow these allow-lists.
+    pub cors: CorsConfig,
+    /// Every response's `Server` header; an empty one sends none.
+    pub server_header: String,
+    /// What `/` answers with.
+    pub welcome_message: String,
+    /// A directory served under its prefix.
+    pub static_files: Option<StaticFiles>,
+    /// Every request gets a line in it.
+    pub access_log: Option<Arc<AccessLog>>,
+    /// What `/readyz` reports.
+    pub readiness: Readiness,
+    /// `/env` shows the environment variables whose names start with one of
+    /// these.
+    pub env_allow_prefixes: Vec<String>,
+    /// A request whose handler hasn't responded by then is answered 504.
+    pub request_timeout: Option<Duration>,
+}
+
+impl AppOptions {
+    /// The options `config` describes. Metrics, rate limiting and the access
+    /// log need more than the config to set up, so they are left off.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            metrics: None,
+            compression_enabled: config.compression_enabled,
+            chaos: Arc::new(ArcSwap::from_pointee(config.chaos.clone())),
+            max_body_size_bytes: config.max_body_size_bytes,
+            request_id_enabled: config.request_id_enabled,
+            rate_limiter: None,
+            client_cert_paths: config.ssl_client_cert_paths.clone(),
+            signing_key: config.signing_secret.as_deref().and_then(SigningKey::new),
+            fail_midstream_enabled: config.fail_midstream_enabled,
+            transforms: parse_pipeline(config.response_transforms.iter().map(String::as_str))
+                .unwrap_or_default(),
+            scenarios: config.scenarios.clone(),
+            streaming: StreamingShutdown::with_max_streams(
+                Duration::from_secs(config.streaming_shutdown_grace_secs),
+                config.max_streaming_responses,
+            ),
+            clock: Clock::default(),
+            trust_proxy: config.trust_proxy,
+            cors: config.cors(),
+            server_header: config.server_header.clone(),
+            welcome_message: config.welcome_message.clone(),
+            static_files: config.static_files(),
+            access_log: None,
+            readiness: Readiness::default(),
+            env_allow_prefixes: config.env_allow_prefixes.clone(),
+            request_timeout: config.request_timeout(),
+        }
+    }
+}
+
+impl Default for AppOptions {
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+/// Builds the Axum application with all routes and middleware, as described
+/// by `options`.
+pub fn build_app(options: AppOptions) -> Router {
+    let AppOptions {
+        metrics,
+        compression_enabled,
+        chaos,
+        max_body_size_bytes,
+        request_id_enabled,
+        rate_limiter,
+        client_cert_paths,
+        signing_key,
+        fail_midstream_enabled,
+        transforms,
+        scenarios,
+        streaming,
+        clock,
+        trust_proxy,
+        cors,
+        server_header,
+        welcome_message,
+        static_files,
+        access_log,
+        readiness,
+        env_allow_prefixes,
+        request_timeout,
+    } = options;
+
+    let routes = Router::new()
         .merge(crate::routes::core_routes::router())
         .merge(crate::routes::healthz::router())
         .merge(crate::routes::delay::router())
         .merge(crate::routes::redirect::router())
         .merge(crate::routes::cookies::router())
+        .merge(crate::routes::countdown::router())
         .merge(crate::routes::base64::router())
         .merge(crate::routes::bytes::router())
         .merge(crate::routes::cache::router())
         .merge(crate::routes::drip::router())
+        .merge(crate::routes::events::router())
+        .merge(crate::routes::echo::router())
+        .merge(crate::routes::dump::router())
         .merge(crate::routes::encoding::router())
         .merge(crate::routes::response_headers::router())
         .merge(crate::routes::content_types::router())
         .merge(crate::routes::image::router())
+        .merge(crate::routes::introspect::router())
+        .merge(crate::routes::kv::router())
+        .merge(crate::routes::long_poll::router())
+        .merge(crate::routes::resource::router())
         .merge(crate::routes::range::router())
+        .merge(crate::routes::time::router())
+        .merge(crate::routes::ws::router())
+        .fallback(crate::routes::core_routes::not_found_handler);
+    let routes = match static_files {
+        Some(static_files) => routes.merge(crate::routes::static_files::router(&static_files)),
+        None => routes,
+    };
+
+    // Before the `/batch` clone below, so signed sub-requests work too.
+    let routes = match signing_key {
+        Some(key) => routes.layer(Extension(key)),
+        None => routes,
+    };
+    let routes = if fail_midstream_enabled {
+        routes.layer(Extension(FailMidstream))
+    } else {
+        routes
+    };
+    let routes = routes
+        .layer(Extension(streaming))
+        .layer(Extension(readiness))
+        .layer(Extension(StartTime(clock.now())))
+        .layer(Extension(clock.clone()))
+        .layer(Extension(TrustProxy(trust_proxy)))
+        .layer(Extension(WelcomeMessage(welcome_message.into())))
+        .layer(Extension(EnvAllowList(env_allow_prefixes.into())))
+        .layer(Extension(RequestLimits {
+            max_body_size_bytes,
+        }));
+
+    // `/batch` dispatches to a clone of the routes above (without itself).
+    let app = routes
+        .clone()
+        .merge(crate::routes::batch::router(routes))
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .layer(DefaultBodyLimit::max(max_body_size_bytes));
+    // Inside metrics, so a panic's 500 is counted as a failure.
+    let mut app = with_allow_responses(app).layer(catch_panic_layer());
+
+    // Scenarios sit inside metrics, so a scenario's status is what gets counted.
+    let scenarios = Arc::new(scenarios);
+    app = app.layer(middleware::from_fn(move |req, next| {
+        let scenarios = scenarios.clone();
+        async move { scenario_middleware(req, next, scenarios).await }
+    }));
+
+    // The timeout covers scenario delays too, and sits inside metrics so a
+    // 504 is counted.
+    if let Some(timeout) = request_timeout {
+        app = app.layer(middleware::from_fn(move |req, next| {
+            timeout_middleware(req, next, timeout)
+        }));
+    }
 
     // Add metrics endpoint and middleware if enabled
     if let Some(metrics) = metrics {
         app = app
-            .route(
+            .route_service(
                 "/metrics",
-                get(crate::routes::metrics::get_metrics).with_state(metrics.clone()),
+                middleware::from_fn(allow_middleware)
+                    .layer(get(crate::routes::metrics::get_metrics).with_state(metrics.clone())),
             )
             .layer(middleware::from_fn(move |req, next| {
                 let metrics = metrics.clone();
@@ -73,24 +289,43 @@ pub fn build_app(
             }));
     }
 
+    // Transforms see the handler's body; chaos corruption applies on top.
+    let transforms = Arc::new(transforms);
+    app = app.layer(middleware::from_fn(move |req, next| {
+        let transforms = transforms.clone();
+        async move { transform_middleware(req, next, transforms).await }
+    }));
+
     // Middleware order (innermost to outermost):
-    // routes → chaos → timing → trace → compression → cors → normalize-path → request-id
+    // routes → allow → catch-panic → scenario → timeout → metrics → transform → chaos → timing → trace → compression
+    //   → cors → normalize-path → trace-context → client-cert → rate-limit → request-id → access-log
+    //   → server-header → date
     // Chaos sits inside timing so duration_ms honestly reflects chaos delays.
-    let app = if chaos.is_enabled() {
-        app.layer(middleware::from_fn(move |req, next| {
-            let chaos = chaos.clone();
-            async move { chaos_middleware(req, next, chaos).await }
-        }))
-    } else {
-        app
-    };
+    // One RNG for the whole app, so a configured seed drives every request. It
+    // is seeded at startup; a reloaded `chaos_seed` takes effect on restart.
+    let rng = Arc::new(ChaosRng::new(chaos.load().seed));
+    let app = app.layer(middleware::from_fn(move |req, next| {
+        let (chaos, rng) = (chaos.load_full(), rng.clone());
+        async move {
+            if chaos.is_enabled() {
+                chaos_middleware(req, next, chaos, rng).await
+            } else {
+                next.run(req).await
+            }
+        }
+    }));
 
-    let app = app.layer(middleware::from_fn(timing_middleware)).layer(
-        TraceLayer::new_for_http()
-            .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
-            .on_request(DefaultOnRequest::new().level(Level::INFO))
-            .on_response(DefaultOnResponse::new().level(Level::INFO)),
-    );
+    let date_clock = clock.clone();
+    let app = app
+        .layer(middleware::from_fn(move |req, next| {
+            timing_middleware(req, next, clock.clone())
+        }))
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(make_request_span)
+                .on_request(DefaultOnRequest::new().level(Level::INFO))
+                .on_response(DefaultOnResponse::new().level(Level::INFO)),
+        );
 
     // Conditionally add compression layer
     let app = if compression_enabled {
@@ -100,15 +335,70 @@ pub fn build_app(
         app
     };
 
-    let app = app
-        .layer(CorsLayer::permissive())
-        .layer(NormalizePathLayer::trim_trailing_slash());
+    // Trace-context sits outside TraceLayer so the request/response events are
+    // emitted inside the `trace_context` span carrying the inbound trace id.
+    // CORS answers real preflights only, so other OPTIONS reach the routes.
+    let app = with_preflight_only_cors(app, &cors)
+        .layer(NormalizePathLayer::trim_trailing_slash())
+        .layer(middleware::from_fn(trace_context_middleware));
+
+    // Client-cert enforcement rejects guarded paths before any real work is done.
+    let app = if client_cert_paths.is_empty() {
+        app
+    } else {
+        let guarded = Arc::new(client_cert_paths);
+        app.layer(middleware::from_fn(move |req, next| {
+            let guarded = guarded.clone();
+            async move { client_cert_middleware(req, next, guarded).await }
+        }))
+    };
+
+    // Rate limiting sits outside everything but request-id, so a rejected
+    // request costs as little as possible and its 429 still gets an id.
+    let app = match rate_limiter {
+        Some(limiter) => app.layer(middleware::from_fn(move |req, next| {
+            let limiter = limiter.clone();
+            async move { rate_limit_middleware(req, next, limiter).await }
+        })),
+        None => app,
+    };
 
     // Request-id is outermost (when enabled) so every response — including 404s,
     // 413s, and CORS preflights — carries an X-Request-Id correlation header.
-    if request_id_enabled {
+    let app = if request_id_enabled {
         app.layer(middleware::from_fn(request_id_middleware))
     } else {
         app
-    }
+    };
+
+    // Access logging sits outside request-id so it sees the X-Request-Id, and
+    // outside rate limiting so 429s are logged too.
+    let app = match access_log {
+        Some(log) => app.layer(middleware::from_fn(move |req, next| {
+            access_log_middleware(req, next, log.clone())
+        })),
+        None => app,
+    };
+
+    // An empty `server_header` sends none. A handler's own `Server` (e.g. from
+    // `/response-headers?Server=...`) is kept.
+    let app = if server_header.is_empty() {
+        app
+    } else {
+        match HeaderValue::from_str(&server_header) {
+            Ok(value) => app.layer(SetResponseHeaderLayer::if_not_present(
+                header::SERVER,
+                value,
+            )),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid server_header {:?}", server_header);
+                app
+            }
+        }
+    };
+
+    // Date is outermost, so every response carries one, even in-process.
+    app.layer(middleware::from_fn(move |req, next| {
+        date_middleware(req, next, date_clock.clone())
+    }))
 }
@@ -1,11 +1,18 @@
 //! CLI command definitions and handlers.
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process;
+use utoipa::OpenApi;
+
+use crate::openapi::ApiDoc;
+use crate::utils::config::{Config, ConfigSources};
 
 use crate::utils::pid::{
-    check_process_running, read_pid_file, remove_pid_file, stop_process, write_pid_file, PidError,
-    StopResult,
+    check_process_running, read_pid_file, reload_process, remove_pid_file, stop_process,
+    write_pid_file, PidError, StopResult,
 };
 
 /// Represents the command line arguments passed to the application.
@@ -15,31 +22,110 @@ pub struct Args {
     /// The subcommand to execute.
     #[command(subcommand)]
     pub command: CliCommand,
+    /// Log level for this run (`error`, `warn`, `info`, `debug`, `trace`),
+    /// overriding `log_level` from the config file and `RUCHO_LOG_LEVEL`.
+    #[arg(long, global = true, value_name = "LEVEL")]
+    pub log_level: Option<String>,
+    /// Log more: `-v` for debug, `-vv` for trace. `--log-level` wins over it.
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+}
+
+impl Args {
+    /// The log level asked for on the command line, if any: `--log-level` as
+    /// given, otherwise `debug` for `-v` and `trace` for `-vv` or more.
+    pub fn log_level_override(&self) -> Option<String> {
+        match (&self.log_level, self.verbose) {
+            (Some(level), _) => Some(level.clone()),
+            (None, 0) => None,
+            (None, 1) => Some("debug".to_string()),
+            (None, _) => Some("trace".to_string()),
+        }
+    }
+}
+
+/// Resolves the log level to run with: the command-line override when there
+/// is one, otherwise `configured`, which already reflects
+/// `RUCHO_LOG_LEVEL` over the config file over the default.
+pub fn resolve_log_level(cli_override: Option<&str>, configured: &str) -> String {
+    cli_override.unwrap_or(configured).to_string()
 }
 
 /// Defines the available subcommands for the CLI.
 #[derive(Parser, Debug)]
 pub enum CliCommand {
     /// Starts the Rucho server.
-    Start {},
+    Start {
+        /// Run without writing a PID file (for Docker, systemd `Type=simple`,
+        /// or non-root users). `rucho stop`/`status`/`reload` then can't find
+        /// the server; stop it with a signal instead.
+        #[arg(long)]
+        foreground: bool,
+    },
     /// Stops the Rucho server.
     Stop {},
     /// Checks the status of the Rucho server.
     Status {},
+    /// Tells the running Rucho server to reload its configuration (SIGHUP).
+    Reload {},
     /// Displays the version of Rucho.
     Version {},
+    /// Prints the effective configuration, with the source each setting came
+    /// from (default, etc, cwd, env, or cli).
+    Config {},
+    /// Writes the OpenAPI spec (as served at `/api-docs/openapi.json`)
+    /// without starting the server, e.g. to generate client SDKs in CI.
+    Openapi {
+        /// File to write the spec to; stdout when omitted.
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+        /// Write YAML instead of JSON.
+        #[arg(long)]
+        yaml: bool,
+    },
+    /// Prints a shell completion script for `rucho` to stdout.
+    Completions {
+        /// Shell to generate completions for.
+        #[arg(value_enum)]
+        shell: Shell,
+    },
 }
 
-/// Handles the start command by writing the PID file at `pid_path`.
+/// Handles the start command by writing the PID file at `pid_path`, unless
+/// `foreground` is set, in which case no PID file is written at all.
+///
+/// Returns `false` when the PID file names another process that is still
+/// running: the server is already up, so the file is left untouched and the
+/// caller must not start a second instance. A stale PID file (its process is
+/// gone) is overwritten.
 ///
 /// A write failure (read-only filesystem, missing parent directory, …) is
 /// **non-fatal**: it logs a warning and the server still starts. The PID file
 /// only backs `rucho stop`/`status`; a containerized server is stopped with a
 /// signal (SIGTERM / Ctrl+C), so a missing PID file is acceptable there.
-pub fn handle_start_command(pid_path: &str) {
-    println!("Starting server...");
+pub fn handle_start_command(pid_path: &str, foreground: bool) -> bool {
     let pid = process::id();
+    if foreground {
+        println!("Starting server...");
+        println!(
+            "Running in the foreground (PID {}); no PID file written.",
+            pid
+        );
+        return true;
+    }
 
+    if let Ok(existing) = read_pid_file(pid_path) {
+        // A container restart can hand us the PID recorded by our predecessor.
+        if existing != pid as usize && check_process_running(existing) {
+            eprintln!(
+                "Server already running (PID {}). Stop it with 'rucho stop' first.",
+                existing
+            );
+            return false;
+        }
+    }
+
+    println!("Starting server...");
     match write_pid_file(pid_path, pid) {
         Ok(()) => println!("Server PID {} written to {}", pid, pid_path),
         Err(e) => eprintln!(
@@ -48,6 +134,7 @@ pub fn handle_start_command(pid_path: &str) {
             pid_path, e
         ),
     }
+    true
 }
 
 /// Handles the stop command, reading the PID from `pid_path`.
@@ -126,7 +213,289 @@ pub fn handle_status_command(pid_path: &str) {
     }
 }
 
+/// Handles the reload command: sends SIGHUP to the process whose PID is in
+/// `pid_path`, so it re-reads its config (log level, chaos) and TLS files.
+pub fn handle_reload_command(pid_path: &str) {
+    match read_pid_file(pid_path) {
+        Ok(pid_val) => match reload_process(pid_val) {
+            Ok(()) => println!("Reload signal sent to process {}.", pid_val),
+            Err(PidError::ProcessNotFound(_)) => eprintln!(
+                "Error: Process {} not found. Is the server running? Start it with 'rucho start'.",
+                pid_val
+            ),
+            Err(e) => eprintln!("Error: {}", e),
+        },
+        Err(e) => {
+            if matches!(e, PidError::ReadFailed(_)) {
+                eprintln!(
+                    "Error: Server not running (PID file {} not found).",
+                    pid_path
+                );
+            } else {
+                eprintln!("Error: {}", e);
+            }
+        }
+    }
+}
+
 /// Handles the version command.
 pub fn handle_version_command() {
     println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
 }
+
+/// Handles the config command by writing every setting of `config` to `out`
+/// in config-file form, each followed by the source it came from.
+///
+/// Unset optional settings are written as comments, and `signing_secret` is
+/// masked.
+pub fn handle_config_command(config: &Config, sources: &ConfigSources, out: &mut dyn Write) {
+    let _ = writeln!(
+        out,
+        "# Effective configuration. Sources, lowest precedence first: default, \
+         etc (/etc/rucho/rucho.conf), cwd (./rucho.conf), env (RUCHO_*), cli."
+    );
+    for (key, value) in config.entries() {
+        let source = sources.get(&key);
+        let _ = match value {
+            None => writeln!(out, "# {} is unset", key),
+            Some(_) if key == "signing_secret" => {
+                writeln!(out, "{} = ********  # {}", key, source)
+            }
+            Some(value) => writeln!(out, "{} = {}  # {}", key, value, source),
+        };
+    }
+}
+
+/// Handles the completions command by writing the `shell` completion script
+/// for `rucho` to `out`.
+///
+/// The script is generated from [`Args`], so it always matches the current
+/// subcommands and flags.
+pub fn handle_completions_command(shell: Shell, out: &mut dyn Write) {
+    clap_complete::generate(shell, &mut Args::command(), env!("CARGO_PKG_NAME"), out);
+}
+
+/// Renders the OpenAPI spec as pretty-printed JSON, or YAML if `yaml` is set.
+pub fn render_openapi(yaml: bool) -> Result<String, String> {
+    let spec = ApiDoc::openapi();
+    if yaml {
+        spec.to_yaml().map_err(|e| e.to_string())
+    } else {
+        spec.to_pretty_json().map_err(|e| e.to_string())
+    }
+}
+
+/// Handles the openapi command: writes the spec to `out`, or to stdout when
+/// it's `None`.
+///
+/// Returns `false` after printing the error when the spec can't be rendered
+/// or the file can't be written.
+pub fn handle_openapi_command(out: Option<&Path>, yaml: bool) -> bool {
+    let spec = match render_openapi(yaml) {
+        Ok(spec) => spec,
+        Err(e) => {
+            eprintln!("Error: could not render the OpenAPI spec: {}", e);
+            return false;
+        }
+    };
+    match out {
+        None => {
+            println!("{}", spec);
+            true
+        }
+        Some(path) => match std::fs::write(path, spec + "\n") {
+            Ok(()) => {
+                eprintln!("OpenAPI spec written to {}", path.display());
+                true
+            }
+            Err(e) => {
+                eprintln!("Error: could not write {}: {}", path.display(), e);
+                false
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::config::ConfigSource;
+    use tempfile::TempDir;
+
+    #[test]
+    fn start_parses_foreground_flag() {
+        let args = Args::try_parse_from(["rucho", "start", "--foreground"]).unwrap();
+        assert!(matches!(
+            args.command,
+            CliCommand::Start { foreground: true }
+        ));
+        let args = Args::try_parse_from(["rucho", "start"]).unwrap();
+        assert!(matches!(
+            args.command,
+            CliCommand::Start { foreground: false }
+        ));
+    }
+
+    #[test]
+    fn log_level_flags_parse_before_or_after_the_subcommand() {
+        let args = Args::try_parse_from(["rucho", "start"]).unwrap();
+        assert_eq!(args.log_level_override(), None);
+        let args = Args::try_parse_from(["rucho", "--log-level", "warn", "start"]).unwrap();
+        assert_eq!(args.log_level_override().as_deref(), Some("warn"));
+        let args = Args::try_parse_from(["rucho", "start", "-v"]).unwrap();
+        assert_eq!(args.log_level_override().as_deref(), Some("debug"));
+        let args = Args::try_parse_from(["rucho", "-vv", "start"]).unwrap();
+        assert_eq!(args.log_level_override().as_deref(), Some("trace"));
+        let args =
+            Args::try_parse_from(["rucho", "-vvv", "start", "--log-level", "error"]).unwrap();
+        assert_eq!(args.log_level_override().as_deref(), Some("error"));
+    }
+
+    #[test]
+    fn cli_log_level_wins_over_the_env_var() {
+        // What `Config` resolves to with `RUCHO_LOG_LEVEL=warn` in the
+        // environment (env over file over default is covered in config.rs).
+        let configured = "warn";
+        assert_eq!(resolve_log_level(None, configured), "warn");
+
+        let args = Args::try_parse_from(["rucho", "start", "-v"]).unwrap();
+        let level = resolve_log_level(args.log_level_override().as_deref(), configured);
+        assert_eq!(level, "debug");
+        let args = Args::try_parse_from(["rucho", "--log-level", "error", "start"]).unwrap();
+        let level = resolve_log_level(args.log_level_override().as_deref(), configured);
+        assert_eq!(level, "error");
+    }
+
+    #[test]
+    fn config_prints_each_setting_with_its_source() {
+        let config = Config {
+            log_level: "debug".to_string(),
+            signing_secret: Some("s3cret".to_string()),
+            ..Config::default()
+        };
+        let mut sources = ConfigSources::default();
+        sources.set("log_level", ConfigSource::Env);
+        sources.set("signing_secret", ConfigSource::Cwd);
+
+        let mut out = Vec::new();
+        handle_config_command(&config, &sources, &mut out);
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines.contains(&"log_level = debug  # env"), "{out}");
+        assert!(lines.contains(&"log_format = text  # default"), "{out}");
+        assert!(lines.contains(&"signing_secret = ********  # cwd"), "{out}");
+        assert!(lines.contains(&"# ssl_cert is unset"), "{out}");
+        assert!(!out.contains("s3cret"));
+    }
+
+    #[test]
+    fn openapi_parses_out_and_yaml() {
+        let args = Args::try_parse_from(["rucho", "openapi"]).unwrap();
+        assert!(matches!(
+            args.command,
+            CliCommand::Openapi {
+                out: None,
+                yaml: false
+            }
+        ));
+        let args =
+            Args::try_parse_from(["rucho", "openapi", "--out", "spec.yaml", "--yaml"]).unwrap();
+        assert!(matches!(
+            args.command,
+            CliCommand::Openapi { out: Some(path), yaml: true } if path == Path::new("spec.yaml")
+        ));
+    }
+
+    #[test]
+    fn openapi_writes_a_parsable_spec_as_json_or_yaml() {
+        let dir = TempDir::new().unwrap();
+
+        let json_path = dir.path().join("openapi.json");
+        assert!(handle_openapi_command(Some(&json_path), false));
+        let spec: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        let yaml_path = dir.path().join("openapi.yaml");
+        assert!(handle_openapi_command(Some(&yaml_path), true));
+        let from_yaml: serde_json::Value =
+            serde_yaml::from_str(&std::fs::read_to_string(&yaml_path).unwrap()).unwrap();
+        assert_eq!(from_yaml, spec, "YAML and JSON describe the same document");
+
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+        assert_eq!(spec["info"]["title"], env!("CARGO_PKG_NAME"));
+        assert!(spec["paths"]["/get"]["get"].is_object());
+
+        let missing_dir = dir.path().join("no/such/dir/openapi.json");
+        assert!(!handle_openapi_command(Some(&missing_dir), false));
+    }
+
+    #[test]
+    fn completions_cover_every_subcommand() {
+        let args = Args::try_parse_from(["rucho", "completions", "zsh"]).unwrap();
+        assert!(matches!(
+            args.command,
+            CliCommand::Completions { shell: Shell::Zsh }
+        ));
+        assert!(Args::try_parse_from(["rucho", "completions", "tcsh"]).is_err());
+
+        let mut script = Vec::new();
+        handle_completions_command(Shell::Bash, &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("_rucho()"), "a bash completion function");
+        for subcommand in [
+            "start",
+            "stop",
+            "status",
+            "reload",
+            "version",
+            "openapi",
+            "completions",
+        ] {
+            assert!(script.contains(subcommand), "{subcommand} is missing");
+        }
+        assert!(script.contains("--foreground"));
+    }
+
+    #[test]
+    fn foreground_start_writes_no_pid_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rucho.pid");
+        let path = path.to_str().unwrap();
+
+        assert!(handle_start_command(path, true));
+        assert!(
+            read_pid_file(path).is_err(),
+            "no PID file in foreground mode"
+        );
+
+        assert!(handle_start_command(path, false));
+        assert_eq!(read_pid_file(path).unwrap(), process::id() as usize);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn start_refuses_when_pid_file_names_running_process() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rucho.pid");
+        let path = path.to_str().unwrap();
+        let mut running = process::Command::new("sleep").arg("30").spawn().unwrap();
+        write_pid_file(path, running.id()).unwrap();
+
+        let started = handle_start_command(path, false);
+        running.kill().unwrap();
+        running.wait().unwrap();
+
+        assert!(!started, "must not start over a running server");
+        assert_eq!(read_pid_file(path).unwrap(), running.id() as usize);
+    }
+
+    #[test]
+    fn start_overwrites_stale_pid_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rucho.pid");
+        let path = path.to_str().unwrap();
+        write_pid_file(path, 999_999_999).unwrap();
+
+        assert!(handle_start_command(path, false));
+        assert_eq!(read_pid_file(path).unwrap(), process::id() as usize);
+    }
+}
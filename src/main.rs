@@ -6,60 +6,116 @@
 
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use arc_swap::ArcSwap;
 use clap::Parser;
 use tracing::Level;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::reload;
+use tracing_subscriber::util::SubscriberInitExt;
 
-use rucho::app::build_app;
+use rucho::app::{build_app, AppOptions};
 use rucho::cli::{
     commands::{
-        handle_start_command, handle_status_command, handle_stop_command, handle_version_command,
+        handle_completions_command, handle_config_command, handle_openapi_command,
+        handle_reload_command, handle_start_command, handle_status_command, handle_stop_command,
+        handle_version_command, resolve_log_level,
     },
     Args, CliCommand,
 };
-use rucho::utils::config::Config;
+use rucho::routes::healthz::Readiness;
+use rucho::server::access_log::AccessLog;
+use rucho::server::rate_limit_layer::RateLimiter;
+use rucho::server::shutdown::StreamingShutdown;
+use rucho::server::transform_layer::parse_pipeline;
+use rucho::server::ReloadHandles;
+use rucho::utils::clock::Clock;
+use rucho::utils::config::{Config, ConfigError, ConfigSource};
 use rucho::utils::metrics::Metrics;
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
-    let config = Config::load();
+    let (mut config, mut sources, load_errors) = Config::load_with_sources();
+    for error in &load_errors {
+        let severity = if error.is_fatal() { "error" } else { "warning" };
+        eprintln!("Configuration {}: {}", severity, error);
+    }
+    // The command line's log level wins over the configured one.
+    let log_level_override = args.log_level_override();
+    if log_level_override.is_some() {
+        sources.set("log_level", ConfigSource::Cli);
+    }
+    config.log_level = resolve_log_level(log_level_override.as_deref(), &config.log_level);
+
+    // `rucho config` prints what was loaded even when it's invalid, since
+    // that's when it's needed; the exit status still reports the problem.
+    let validation = config.validate();
+    if let CliCommand::Config {} = args.command {
+        handle_config_command(&config, &sources, &mut std::io::stdout());
+    }
+    if load_errors.iter().any(ConfigError::is_fatal) {
+        std::process::exit(1);
+    }
 
     // Validate configuration
-    if let Err(e) = config.validate() {
+    if let Err(e) = validation {
         eprintln!("Configuration error: {}", e);
         std::process::exit(1);
     }
 
-    // Initialize tracing with configured log level
+    // Initialize tracing with the resolved log level
     let log_level = Level::from_str(&config.log_level.to_uppercase()).unwrap_or_else(|_| {
         eprintln!(
-            "Warning: Invalid log level '{}' in config, defaulting to INFO.",
+            "Warning: Invalid log level '{}', defaulting to INFO.",
             config.log_level
         );
         Level::INFO
     });
-    let builder = tracing_subscriber::fmt().with_max_level(log_level);
+    // The filter sits behind a reload layer so SIGHUP can change it.
+    let (level_filter, log_level_handle) =
+        reload::Layer::new(config.log_targets(LevelFilter::from_level(log_level)));
+    let registry = tracing_subscriber::registry().with(level_filter);
     match config.log_format.to_lowercase().as_str() {
-        "json" => builder.json().init(),
-        "text" => builder.init(),
+        "json" => registry
+            .with(tracing_subscriber::fmt::layer().json())
+            .init(),
+        "text" => registry.with(tracing_subscriber::fmt::layer()).init(),
         other => {
             eprintln!("Warning: Invalid log_format '{other}' in config, defaulting to text.");
-            builder.init();
+            registry.with(tracing_subscriber::fmt::layer()).init();
         }
     }
 
     // Dispatch command
     match args.command {
-        CliCommand::Start {} => {
-            // A PID-write failure is non-fatal (read-only FS, missing dir): the
-            // server still starts and can be stopped with a signal.
-            handle_start_command(&config.pid_file);
+        CliCommand::Start { foreground } => {
+            // Refuses only when the PID file names a live process. A PID-write
+            // failure is non-fatal (read-only FS, missing dir): the server
+            // still starts and can be stopped with a signal.
+            if !handle_start_command(&config.pid_file, foreground) {
+                std::process::exit(1);
+            }
+
+            // One clock for request timing, `/delay` and the metrics window.
+            let clock = Clock::default();
 
             // Create metrics store if enabled
             let metrics = if config.metrics_enabled {
-                tracing::info!("Metrics endpoint enabled at /metrics");
-                Some(Arc::new(Metrics::new()))
+                tracing::info!(
+                    "Metrics endpoint enabled at /metrics (rolling window: {} x {}s)",
+                    config.metrics_window_buckets,
+                    config.metrics_bucket_seconds,
+                );
+                Some(Arc::new(
+                    Metrics::with_window(
+                        config.metrics_window_buckets,
+                        Duration::from_secs(config.metrics_bucket_seconds),
+                    )
+                    .with_clock(clock.clone()),
+                ))
             } else {
                 None
             };
@@ -77,19 +133,95 @@ async fn main() {
                 tracing::info!("Chaos mode enabled: {}", config.chaos.modes.join(", "));
             }
 
-            let chaos = Arc::new(config.chaos.clone());
-            let app = build_app(
+            let rate_limiter = if config.rate_limit_rps > 0 {
+                let limiter = RateLimiter::new(config.rate_limit_rps, config.rate_limit_burst)
+                    .with_trust_proxy(config.rate_limit_trust_proxy);
+                tracing::info!(
+                    "Rate limiting enabled: {} req/s per client IP (burst {})",
+                    config.rate_limit_rps,
+                    limiter.burst(),
+                );
+                Some(Arc::new(limiter))
+            } else {
+                None
+            };
+
+            // Already checked by `config.validate()`.
+            let transforms = parse_pipeline(config.response_transforms.iter().map(String::as_str))
+                .unwrap_or_default();
+            if !transforms.is_empty() {
+                tracing::info!(
+                    "Response transforms enabled: {}",
+                    config.response_transforms.join(", ")
+                );
+            }
+
+            let access_log = match &config.access_log {
+                Some(path) => {
+                    // Both were already checked by `config.validate()`.
+                    let format = config.access_log_format.parse().unwrap_or_default();
+                    let rotation = config.access_log_rotation.parse().unwrap_or_default();
+                    match AccessLog::open(path, format, rotation, clock.clone()) {
+                        Ok(log) => {
+                            let log = log
+                                .with_exclude(config.access_log_exclude.clone())
+                                .with_sample_rate(config.access_log_sample_rate);
+                            tracing::info!(
+                                "Access log: {} ({}, rotation {})",
+                                path,
+                                config.access_log_format,
+                                config.access_log_rotation,
+                            );
+                            Some(Arc::new(log))
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to open access log {}: {}", path, e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                None => None,
+            };
+
+            let streaming = StreamingShutdown::with_max_streams(
+                Duration::from_secs(config.streaming_shutdown_grace_secs),
+                config.max_streaming_responses,
+            );
+            let chaos = Arc::new(ArcSwap::from_pointee(config.chaos.clone()));
+            // Set by `run_server` once the listeners are bound.
+            let readiness = Readiness::new();
+            let app = build_app(AppOptions {
                 metrics,
-                config.compression_enabled,
+                chaos: chaos.clone(),
+                rate_limiter,
+                transforms,
+                streaming: streaming.clone(),
+                clock: clock.clone(),
+                access_log,
+                readiness: readiness.clone(),
+                ..AppOptions::from_config(&config)
+            });
+            let reload = ReloadHandles {
+                log_level: log_level_handle,
+                log_level_override,
                 chaos,
-                config.max_body_size_bytes,
-                config.request_id_enabled,
-            );
-            rucho::server::run_server(&config, app).await;
+            };
+            rucho::server::run_server(&config, app, streaming, reload, readiness).await;
         }
         CliCommand::Stop {} => handle_stop_command(&config.pid_file),
         CliCommand::Status {} => handle_status_command(&config.pid_file),
+        CliCommand::Reload {} => handle_reload_command(&config.pid_file),
         CliCommand::Version {} => handle_version_command(),
+        // Printed above, before the configuration checks.
+        CliCommand::Config {} => {}
+        CliCommand::Completions { shell } => {
+            handle_completions_command(shell, &mut std::io::stdout())
+        }
+        CliCommand::Openapi { out, yaml } => {
+            if !handle_openapi_command(out.as_deref(), yaml) {
+                std::process::exit(1);
+            }
+        }
     }
 }
 
@@ -29,6 +29,8 @@ use crate::routes::core_routes::EndpointInfo;
         crate::routes::core_routes::endpoints_handler,
         crate::routes::delay::delay_handler,
         crate::routes::healthz::healthz_handler,
+        crate::routes::healthz::livez_handler,
+        crate::routes::healthz::readyz_handler,
         crate::routes::redirect::redirect_handler,
         crate::routes::cookies::cookies_handler,
         crate::routes::cookies::set_cookies_handler,
@@ -36,9 +38,14 @@ use crate::routes::core_routes::EndpointInfo;
         crate::routes::cookies::delete_cookies_method_handler,
         crate::routes::base64::base64_handler,
         crate::routes::bytes::bytes_handler,
+        crate::routes::bytes::stream_bytes_handler,
         crate::routes::cache::cache_handler,
         crate::routes::cache::cache_seconds_handler,
         crate::routes::drip::drip_handler,
+        crate::routes::events::events_handler,
+        crate::routes::echo::echo_handler,
+        crate::routes::dump::dump_handler,
+        crate::routes::batch::batch_handler,
         crate::routes::encoding::gzip_handler,
         crate::routes::encoding::deflate_handler,
         crate::routes::encoding::brotli_handler,
@@ -46,18 +53,103 @@ use crate::routes::core_routes::EndpointInfo;
         crate::routes::content_types::xml_handler,
         crate::routes::content_types::html_handler,
         crate::routes::image::image_handler,
+        crate::routes::introspect::env_handler,
+        crate::routes::introspect::hostname_handler,
+        crate::routes::introspect::version_handler,
         crate::routes::range::range_handler,
+        crate::routes::countdown::countdown_handler,
+        crate::routes::long_poll::long_poll_handler,
+        crate::routes::long_poll::long_poll_trigger_handler,
+        crate::routes::resource::delete_resource_handler,
+        crate::routes::resource::reset_resources_handler,
+        crate::routes::kv::kv_put_handler,
+        crate::routes::kv::kv_get_handler,
+        crate::routes::kv::kv_delete_handler,
         crate::routes::core_routes::uuid_handler,
+        crate::routes::core_routes::uuid_bulk_handler,
+        crate::routes::time::time_handler,
+        crate::routes::ws::ws_handler,
         crate::routes::core_routes::ip_handler,
         crate::routes::core_routes::user_agent_handler,
         crate::routes::core_routes::headers_handler,
         crate::routes::metrics::get_metrics,
     ),
     components(
-        schemas(EndpointInfo, crate::routes::core_routes::Payload)
+        schemas(
+            EndpointInfo,
+            crate::routes::core_routes::Payload,
+            crate::routes::batch::BatchRequest
+        )
     ),
     tags(
         (name = "Rucho", description = "Rucho API")
     )
 )]
 pub struct ApiDoc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::core_routes::API_ENDPOINTS;
+    use serde_json::Value;
+
+    /// The generated spec's `paths` object, round-tripped through JSON.
+    fn spec_paths() -> serde_json::Map<String, Value> {
+        let json = ApiDoc::openapi().to_json().unwrap();
+        let spec: Value = serde_json::from_str(&json).unwrap();
+        spec["paths"].as_object().unwrap().clone()
+    }
+
+    /// `/kv/:key` and `/anything/*path` in OpenAPI's `{param}` form.
+    fn openapi_path(route: &str) -> String {
+        route
+            .split('/')
+            .map(|segment| match segment.strip_prefix([':', '*']) {
+                Some(param) => format!("{{{param}}}"),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    #[test]
+    fn every_listed_endpoint_is_documented_with_its_method() {
+        let paths = spec_paths();
+        let endpoints = serde_json::to_value(API_ENDPOINTS).unwrap();
+        for endpoint in endpoints.as_array().unwrap() {
+            let route = endpoint["path"].as_str().unwrap();
+            if route == "/swagger-ui" {
+                continue; // Serves the spec itself.
+            }
+            let path = openapi_path(route);
+            let Some(operations) = paths.get(&path) else {
+                panic!("{route} is missing from ApiDoc (looked for {path})");
+            };
+            let method = endpoint["method"].as_str().unwrap().to_ascii_lowercase();
+            if method != "any" {
+                assert!(
+                    operations.get(&method).is_some(),
+                    "{route} has no {method} operation in ApiDoc"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn non_core_endpoints_are_documented() {
+        let paths = spec_paths();
+        for path in [
+            "/cookies",
+            "/cookies/set",
+            "/cookies/delete",
+            "/redirect/{n}",
+            "/metrics",
+            "/ip",
+            "/uuid",
+            "/headers",
+            "/user-agent",
+        ] {
+            assert!(paths.contains_key(path), "{path} is missing from ApiDoc");
+        }
+    }
+}
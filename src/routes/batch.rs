@@ -0,0 +1,311 @@
+//! Batch endpoint: `POST /batch`.
+//!
+//! Accepts a JSON array of sub-requests and executes each one, in order,
+//! against the in-process router (via `oneshot` on a clone), returning a JSON
+//! array of sub-responses. Lets clients exercise batch/pipelining semantics
+//! without a real batching upstream.
+//!
+//! Sub-requests are dispatched to the route handlers directly: they skip the
+//! outer middleware (metrics, chaos, compression, CORS) and the router passed
+//! in does not include `/batch` itself, so batches can't nest.
+//!
+//! Each sub-response is buffered whole, so its body is capped at
+//! `MAX_BATCH_RESPONSE_BYTES` and the sub-request at
+//! `BATCH_SUB_REQUEST_TIMEOUT_SECS`; past either, its entry reports an error
+//! instead.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Json, State},
+    http::{HeaderName, HeaderValue, Method, Request, StatusCode},
+    response::Response,
+    routing::post,
+    Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use crate::utils::constants::{
+    BATCH_SUB_REQUEST_TIMEOUT_SECS, MAX_BATCH_RESPONSE_BYTES, MAX_BATCH_SIZE,
+};
+use crate::utils::error_response::format_error_response;
+use crate::utils::headers::headers_to_json;
+use crate::utils::json_response::format_json_response;
+
+/// One sub-request in a batch.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct BatchRequest {
+    /// HTTP method (default `GET`).
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// Request path including any query string, e.g. `/status/500?x=1`.
+    pub path: String,
+    /// Request headers.
+    #[serde(default)]
+    pub headers: serde_json::Map<String, Value>,
+    /// Request body. Strings are sent verbatim; any other JSON value is
+    /// serialized and sent with `Content-Type: application/json` (unless a
+    /// content type was given in `headers`).
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub body: Option<Value>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// Builds the `http::Request` for a sub-request, or a message explaining why
+/// it's invalid.
+fn build_request(sub: BatchRequest) -> Result<Request<Body>, String> {
+    let method = Method::from_bytes(sub.method.as_bytes())
+        .map_err(|_| format!("invalid method '{}'", sub.method))?;
+    if !sub.path.starts_with('/') {
+        return Err(format!("path '{}' must start with '/'", sub.path));
+    }
+
+    let mut builder = Request::builder().method(method).uri(&sub.path);
+    for (name, value) in &sub.headers {
+        let value = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("invalid header name '{name}'"))?;
+        let value = HeaderValue::from_str(&value)
+            .map_err(|_| format!("invalid value for header '{name}'"))?;
+        builder = builder.header(name, value);
+    }
+
+    let body = match sub.body {
+        None => Body::empty(),
+        Some(Value::String(s)) => Body::from(s),
+        Some(other) => {
+            let has_content_type = builder
+                .headers_ref()
+                .is_some_and(|h| h.contains_key(http::header::CONTENT_TYPE));
+            if !has_content_type {
+                builder = builder.header(http::header::CONTENT_TYPE, "application/json");
+            }
+            Body::from(other.to_string())
+        }
+    };
+
+    builder
+        .body(body)
+        .map_err(|e| format!("invalid request: {e}"))
+}
+
+/// Converts a sub-response into its JSON representation. JSON bodies are
+/// embedded as JSON; anything else as a (lossy UTF-8) string. A body that
+/// fails or grows past `MAX_BATCH_RESPONSE_BYTES` is reported as an `error`
+/// in its place.
+async fn describe_response(response: Response) -> Value {
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_BATCH_RESPONSE_BYTES).await else {
+        return json!({
+            "status": parts.status.as_u16(),
+            "headers": headers_to_json(&parts.headers),
+            "error": format!(
+                "sub-response body failed or exceeded {MAX_BATCH_RESPONSE_BYTES} bytes"
+            ),
+        });
+    };
+    let body = serde_json::from_slice::<Value>(&bytes)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()));
+
+    json!({
+        "status": parts.status.as_u16(),
+        "headers": headers_to_json(&parts.headers),
+        "body": body,
+    })
+}
+
+/// Executes a batch of sub-requests against the in-process router.
+///
+/// Sub-requests run sequentially in array order, and the response array is in
+/// the same order. An invalid sub-request (bad method, path, or header) yields
+/// a `{"status": 400, "error": ...}` entry rather than failing the whole batch,
+/// and one that outlives `BATCH_SUB_REQUEST_TIMEOUT_SECS` a `{"status": 504,
+/// "error": ...}` entry. Batches larger than `MAX_BATCH_SIZE` are rejected
+/// with 400.
+#[utoipa::path(
+    post,
+    path = "/batch",
+    request_body = Vec<BatchRequest>,
+    responses(
+        (status = 200, description = "Array of sub-responses (status, headers, body) in request order", body = serde_json::Value),
+        (status = 400, description = "Body is not a JSON array of sub-requests, or the batch is too large")
+    )
+)]
+pub async fn batch_handler(
+    State(app): State<Router>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    body: Result<Json<Vec<BatchRequest>>, axum::extract::rejection::JsonRejection>,
+) -> Response {
+    let Ok(Json(batch)) = body else {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            "Body must be a JSON array of {method, path, headers, body} objects",
+        );
+    };
+    if batch.len() > MAX_BATCH_SIZE {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "Batch size {} exceeds the maximum of {MAX_BATCH_SIZE}",
+                batch.len()
+            ),
+        );
+    }
+
+    let mut results = Vec::with_capacity(batch.len());
+    for sub in batch {
+        let mut request = match build_request(sub) {
+            Ok(request) => request,
+            Err(error) => {
+                results.push(json!({ "status": 400, "error": error }));
+                continue;
+            }
+        };
+        // Handlers like /ip read the peer address; sub-requests inherit ours.
+        if let Some(info) = connect_info {
+            request.extensions_mut().insert(info);
+        }
+        let sub_response = async {
+            let response = match app.clone().oneshot(request).await {
+                Ok(response) => response,
+                Err(never) => match never {},
+            };
+            describe_response(response).await
+        };
+        let timeout = Duration::from_secs(BATCH_SUB_REQUEST_TIMEOUT_SECS);
+        results.push(
+            tokio::time::timeout(timeout, sub_response)
+                .await
+                .unwrap_or_else(|_| {
+                    json!({
+                        "status": 504,
+                        "error": format!(
+                            "sub-request timed out after {BATCH_SUB_REQUEST_TIMEOUT_SECS}s"
+                        ),
+                    })
+                }),
+        );
+    }
+
+    format_json_response(Value::Array(results))
+}
+
+/// Creates the router for `/batch`, dispatching sub-requests to `app`.
+///
+/// `app` should be the merged route handlers *without* `/batch`, so a batch
+/// can't recursively contain batches.
+pub fn router(app: Router) -> Router {
+    Router::new().route("/batch", post(batch_handler).with_state(app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    fn test_app() -> Router {
+        let inner = Router::new()
+            .route("/ok", get(|| async { "fine" }))
+            .route(
+                "/huge",
+                get(|| async { vec![b'x'; MAX_BATCH_RESPONSE_BYTES + 1] }),
+            )
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(BATCH_SUB_REQUEST_TIMEOUT_SECS + 1))
+                        .await;
+                    "late"
+                }),
+            )
+            .route("/json", post(|Json(v): Json<Value>| async move { Json(v) }));
+        router(inner)
+    }
+
+    async fn run(body: &str) -> (StatusCode, Value) {
+        let request = Request::post("/batch")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = test_app().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_sub_requests_run_in_order() {
+        let (status, body) = run(r#"[{"path": "/ok"},
+                {"method": "POST", "path": "/json", "body": {"a": 1}},
+                {"path": "/missing"}]"#)
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["status"], 200);
+        assert_eq!(body[0]["body"], "fine");
+        assert_eq!(body[1]["status"], 200);
+        assert_eq!(body[1]["body"], json!({"a": 1}));
+        assert_eq!(body[2]["status"], 404);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_sub_request_is_reported_inline() {
+        let (status, body) = run(r#"[{"path": "no-slash"}, {"path": "/ok"}]"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["status"], 400);
+        assert!(body[0]["error"].as_str().unwrap().contains("no-slash"));
+        assert_eq!(body[1]["status"], 200);
+    }
+
+    #[tokio::test]
+    async fn test_batch_cannot_nest() {
+        let (_, body) = run(r#"[{"method": "POST", "path": "/batch", "body": []}]"#).await;
+        assert_eq!(body[0]["status"], 404);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_oversized_and_malformed_batches() {
+        let oversized = format!(
+            "[{}]",
+            vec![r#"{"path":"/ok"}"#; MAX_BATCH_SIZE + 1].join(",")
+        );
+        assert_eq!(run(&oversized).await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(run(r#"{"path": "/ok"}"#).await.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_sub_response_is_reported_inline() {
+        let (status, body) = run(r#"[{"path": "/huge"}, {"path": "/ok"}]"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["status"], 200);
+        assert!(body[0].get("body").is_none());
+        assert!(body[0]["error"].as_str().unwrap().contains("exceeded"));
+        assert_eq!(body[1]["body"], "fine");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_sub_request_times_out_inline() {
+        let start = tokio::time::Instant::now();
+        let (status, body) = run(r#"[{"path": "/slow"}, {"path": "/ok"}]"#).await;
+        assert_eq!(
+            start.elapsed(),
+            Duration::from_secs(BATCH_SUB_REQUEST_TIMEOUT_SECS)
+        );
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["status"], 504);
+        assert!(body[0]["error"].as_str().unwrap().contains("timed out"));
+        assert_eq!(body[1]["body"], "fine");
+    }
+}
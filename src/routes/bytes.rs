@@ -1,19 +1,32 @@
-//! Bytes endpoint — returns N random bytes as `application/octet-stream`.
+//! Bytes endpoints — return N random bytes as `application/octet-stream`.
 //!
 //! Provides a controllable upstream emitting arbitrary-sized binary bodies,
 //! useful for exercising gateway proxy behavior: response buffering, chunked
 //! transfer, binary integrity, and compression-plugin behavior on
 //! incompressible data.
+//!
+//! `/bytes/:n` sends the body in one piece with a `Content-Length`;
+//! `/stream-bytes/:n` streams it in `chunk_size` pieces (chunked transfer, no
+//! `Content-Length`), for clients that read incrementally.
 
 use axum::{
+    body::Body,
+    extract::{Extension, Path, Query},
     http::{header, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
     Router,
 };
-use rand::RngCore;
+use futures_util::stream::{self, Stream};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use serde::Deserialize;
 
-use crate::utils::{constants::MAX_BYTES_RESPONSE_SIZE, error_response::format_error_response};
+use crate::server::shutdown::StreamingShutdown;
+use crate::utils::{
+    constants::{DEFAULT_STREAM_CHUNK_SIZE, MAX_BUFFER_SIZE, MAX_BYTES_RESPONSE_SIZE},
+    error_response::format_error_response,
+};
 
 /// Returns `n` random bytes as the response body.
 ///
@@ -49,9 +62,106 @@ pub async fn bytes_handler(axum::extract::Path(n): axum::extract::Path<usize>) -
     ([(header::CONTENT_TYPE, "application/octet-stream")], buf).into_response()
 }
 
-/// Creates and returns the Axum router for the bytes endpoint.
+/// Query parameters for `/stream-bytes/:n`.
+#[derive(Debug, Deserialize)]
+pub struct StreamBytesParams {
+    #[serde(default = "default_chunk_size")]
+    chunk_size: usize,
+    seed: Option<u64>,
+}
+
+fn default_chunk_size() -> usize {
+    DEFAULT_STREAM_CHUNK_SIZE
+}
+
+/// Streams `n` random bytes in chunks of `chunk_size` (the last may be short).
+///
+/// `n` is capped at `MAX_BYTES_RESPONSE_SIZE` (10 MiB) like `/bytes/:n`, and
+/// `chunk_size` must be between 1 and `MAX_BUFFER_SIZE` (64 KiB); either out of
+/// range returns 400. With `?seed=` the bytes come from a `StdRng` seeded with
+/// it, so the same `n`, `chunk_size` and `seed` always stream the same body;
+/// without one they are seeded from entropy. Only one chunk is generated at a
+/// time.
+///
+/// When the app provides a [`StreamingShutdown`], the stream is cut once its
+/// shutdown grace runs out, and the request gets 503 if
+/// `max_streaming_responses` streams are already open.
+#[utoipa::path(
+    get,
+    path = "/stream-bytes/{n}",
+    params(
+        ("n" = usize, Path, description = "Number of random bytes to stream (max 10485760)"),
+        ("chunk_size" = Option<usize>, Query, description = "Bytes per chunk (default 1024, max 65536)"),
+        ("seed" = Option<u64>, Query, description = "Seed for reproducible bytes (default: random)")
+    ),
+    responses(
+        (status = 200, description = "n random bytes streamed in chunk_size chunks", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 400, description = "n exceeds MAX_BYTES_RESPONSE_SIZE or chunk_size out of range"),
+        (status = 503, description = "Too many streaming responses in flight")
+    )
+)]
+pub async fn stream_bytes_handler(
+    Path(n): Path<usize>,
+    Query(params): Query<StreamBytesParams>,
+    streaming: Option<Extension<StreamingShutdown>>,
+) -> Response {
+    if n > MAX_BYTES_RESPONSE_SIZE {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("Requested {n} bytes exceeds maximum of {MAX_BYTES_RESPONSE_SIZE} bytes"),
+        );
+    }
+    if params.chunk_size == 0 || params.chunk_size > MAX_BUFFER_SIZE {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "chunk_size={} must be between 1 and {MAX_BUFFER_SIZE}",
+                params.chunk_size
+            ),
+        );
+    }
+
+    let rng = match params.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let stream = build_byte_stream(n, params.chunk_size, rng);
+    let body = match streaming {
+        Some(Extension(streaming)) => match streaming.guard(stream) {
+            Ok(guarded) => Body::from_stream(guarded),
+            Err(e) => {
+                return format_error_response(StatusCode::SERVICE_UNAVAILABLE, &e.to_string())
+            }
+        },
+        None => Body::from_stream(stream),
+    };
+
+    ([(header::CONTENT_TYPE, "application/octet-stream")], body).into_response()
+}
+
+/// Builds the stream for `/stream-bytes`: `n` bytes from `rng`, `chunk_size`
+/// at a time.
+fn build_byte_stream(
+    n: usize,
+    chunk_size: usize,
+    rng: StdRng,
+) -> impl Stream<Item = Result<Vec<u8>, std::io::Error>> {
+    stream::unfold((n, rng), move |(remaining, mut rng)| async move {
+        if remaining == 0 {
+            return None;
+        }
+        let mut chunk = vec![0u8; remaining.min(chunk_size)];
+        rng.fill_bytes(&mut chunk);
+        let remaining = remaining - chunk.len();
+        Some((Ok::<_, std::io::Error>(chunk), (remaining, rng)))
+    })
+}
+
+/// Creates and returns the Axum router for the bytes endpoints.
 pub fn router() -> Router {
-    Router::new().route("/bytes/:n", get(bytes_handler))
+    Router::new()
+        .route("/bytes/:n", get(bytes_handler))
+        .route("/stream-bytes/:n", get(stream_bytes_handler))
 }
 
 #[cfg(test)]
@@ -141,4 +251,70 @@ mod tests {
         // Axum's Path<usize> extraction failure returns 400.
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
+
+    async fn stream_bytes(uri: &str) -> Response {
+        router()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn stream_yields_chunk_size_pieces_totalling_n() {
+        use futures_util::StreamExt;
+
+        let chunks: Vec<Vec<u8>> = build_byte_stream(2500, 1024, StdRng::seed_from_u64(1))
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        let sizes: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [1024, 1024, 452]);
+
+        let empty: Vec<_> = build_byte_stream(0, 1024, StdRng::seed_from_u64(1))
+            .collect()
+            .await;
+        assert!(empty.is_empty());
+    }
+
+    #[tokio::test]
+    async fn stream_bytes_streams_n_bytes_without_content_length() {
+        let response = stream_bytes("/stream-bytes/5000?chunk_size=100").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/octet-stream"
+        );
+        assert!(response.headers().get(header::CONTENT_LENGTH).is_none());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.len(), 5000);
+    }
+
+    #[tokio::test]
+    async fn stream_bytes_seed_is_reproducible() {
+        let body = |uri: &'static str| async move {
+            axum::body::to_bytes(stream_bytes(uri).await.into_body(), usize::MAX)
+                .await
+                .unwrap()
+        };
+        let a = body("/stream-bytes/3000?seed=42").await;
+        assert_eq!(a, body("/stream-bytes/3000?seed=42").await);
+        assert_ne!(a, body("/stream-bytes/3000?seed=43").await);
+    }
+
+    #[tokio::test]
+    async fn stream_bytes_rejects_out_of_range_parameters() {
+        for uri in [
+            format!("/stream-bytes/{}", MAX_BYTES_RESPONSE_SIZE + 1),
+            "/stream-bytes/10?chunk_size=0".to_string(),
+            format!("/stream-bytes/10?chunk_size={}", MAX_BUFFER_SIZE + 1),
+        ] {
+            assert_eq!(
+                stream_bytes(&uri).await.status(),
+                StatusCode::BAD_REQUEST,
+                "{uri}"
+            );
+        }
+    }
 }
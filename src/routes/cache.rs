@@ -11,14 +11,14 @@
 
 use axum::{
     extract::Path,
-    http::{header, HeaderMap, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
     Router,
 };
 use serde_json::json;
 
-use crate::routes::core_routes::serialize_headers;
+use crate::utils::headers::headers_to_json;
 use crate::utils::json_response::format_json_response;
 
 /// Stable strong ETag for the cacheable resource (fixed so revalidation is
@@ -31,7 +31,7 @@ const CACHE_LAST_MODIFIED: &str = "Mon, 01 Jan 2024 00:00:00 GMT";
 fn cache_body(headers: &HeaderMap) -> serde_json::Value {
     json!({
         "method": "GET",
-        "headers": serialize_headers(headers),
+        "headers": headers_to_json(headers),
     })
 }
 
@@ -57,15 +57,10 @@ pub async fn cache_handler(headers: HeaderMap) -> Response {
 
     let mut response = format_json_response(cache_body(&headers));
     let h = response.headers_mut();
-    h.insert(
-        header::ETAG,
-        CACHE_ETAG.parse().expect("infallible: static ETag value"),
-    );
+    h.insert(header::ETAG, HeaderValue::from_static(CACHE_ETAG));
     h.insert(
         header::LAST_MODIFIED,
-        CACHE_LAST_MODIFIED
-            .parse()
-            .expect("infallible: static Last-Modified value"),
+        HeaderValue::from_static(CACHE_LAST_MODIFIED),
     );
     response
 }
@@ -79,12 +74,9 @@ pub async fn cache_handler(headers: HeaderMap) -> Response {
 )]
 pub async fn cache_seconds_handler(Path(n): Path<u64>, headers: HeaderMap) -> Response {
     let mut response = format_json_response(cache_body(&headers));
-    response.headers_mut().insert(
-        header::CACHE_CONTROL,
-        format!("public, max-age={n}")
-            .parse()
-            .expect("infallible: cache-control header value"),
-    );
+    if let Ok(value) = HeaderValue::try_from(format!("public, max-age={n}")) {
+        response.headers_mut().insert(header::CACHE_CONTROL, value);
+    }
     response
 }
 
@@ -1,11 +1,25 @@
+use crate::server::request_id::RequestId;
+use crate::server::shutdown::StreamingShutdown;
 use crate::server::tls::TlsConnectionInfo;
 use crate::utils::{
-    error_response::format_error_response, json_response::format_json_response_with_timing,
+    clock::Clock,
+    constants::{
+        DEFAULT_MAX_BODY_SIZE_BYTES, DEFAULT_STATUS_RETRY_AFTER_SECONDS, DEFAULT_WELCOME_MESSAGE,
+        HTTP1_MAX_HEADERS, MAX_DELAY_SECONDS, MAX_FRAGMENTS, MAX_PRELOAD_LINKS, MAX_REDIRECT_HOPS,
+        MAX_STATUS_WEIGHT, MAX_UUID_COUNT,
+    },
+    error_response::{format_error_response, format_error_response_with_path},
+    headers::{header_value_to_json, headers_to_json},
+    json_response::{
+        format_json_response, format_json_response_with_etag, format_json_response_with_timing,
+        format_response, preferred_media_type, with_content_etag,
+    },
+    signature::{sign_response, SigningKey},
     timing::RequestTiming,
 };
 use axum::{
-    extract::Json,
-    http::{HeaderMap, StatusCode},
+    extract::{FromRequest, Json, Path, Query},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     routing::{any, delete, get, head, options, patch, post, put},
     Extension, Router,
@@ -22,30 +36,137 @@ use uuid::Uuid;
 #[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct Payload(serde_json::Value);
 
-/// Serializes HTTP headers into a JSON object.
-///
-/// Converts an Axum `HeaderMap` into a `serde_json::Value` where each header
-/// name becomes a key and its value becomes a string value. Invalid UTF-8
-/// header values are replaced with `<invalid utf8>`.
-///
-/// # Arguments
-///
-/// * `headers` - Reference to the HeaderMap to serialize
+/// Adds the request's correlation id (set by the request-id middleware) to an
+/// echo body as `request_id`, so a response can be matched to its log lines
+/// without reading headers. A no-op when the middleware is off or `payload`
+/// isn't an object.
+fn with_request_id(
+    mut payload: serde_json::Value,
+    request_id: Option<Extension<RequestId>>,
+) -> serde_json::Value {
+    if let (Some(Extension(id)), Some(obj)) = (request_id, payload.as_object_mut()) {
+        obj.insert("request_id".to_string(), json!(id.as_str()));
+    }
+    payload
+}
+
+/// The banner `/` serves; layered on by `build_app` from `welcome_message`.
+/// [`DEFAULT_WELCOME_MESSAGE`] when absent.
+#[derive(Debug, Clone)]
+pub struct WelcomeMessage(pub std::sync::Arc<str>);
+
+impl Default for WelcomeMessage {
+    fn default() -> Self {
+        Self(std::sync::Arc::from(DEFAULT_WELCOME_MESSAGE))
+    }
+}
+
+/// Whether `/ip` and `/get` believe `X-Forwarded-For` / `X-Real-IP` when
+/// reporting the client IP; layered on by `build_app` from `trust_proxy`.
+/// Trusted when absent.
+#[derive(Debug, Clone, Copy)]
+pub struct TrustProxy(pub bool);
+
+impl Default for TrustProxy {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Where a request came from, as reported by `/ip`.
+#[derive(Debug, PartialEq)]
+struct ClientAddress {
+    /// The client IP: `"unknown"` if it can't be determined.
+    origin: String,
+    /// Proxies between the client and rucho, nearest the client first; the
+    /// last entry is the socket peer.
+    via: Vec<String>,
+}
+
+/// Works out the client IP and the proxy chain it came through.
 ///
-/// # Returns
+/// With `trust_proxy`, the left-most X-Forwarded-For entry is the client and
+/// the remaining entries, then the TCP peer, are the `via` hops; without
+/// X-Forwarded-For, X-Real-IP names the client. Without `trust_proxy` both
+/// headers are ignored, since any client can send them, and the peer address is
+/// the origin. `"unknown"` if the server wasn't bound with `ConnectInfo`.
+fn client_address(
+    headers: &HeaderMap,
+    connect_info: Option<axum::extract::ConnectInfo<std::net::SocketAddr>>,
+    trust_proxy: bool,
+) -> ClientAddress {
+    let peer = connect_info.map(|ci| ci.0.ip().to_string());
+    let mut chain: Vec<String> = Vec::new();
+    if trust_proxy {
+        // Several X-Forwarded-For lines form one list, in order.
+        chain = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|hop| !hop.is_empty())
+            .map(str::to_string)
+            .collect();
+        if chain.is_empty() {
+            if let Some(real_ip) = headers
+                .get("x-real-ip")
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+                .filter(|ip| !ip.is_empty())
+            {
+                chain.push(real_ip.to_string());
+            }
+        }
+    }
+    chain.extend(peer);
+    if chain.is_empty() {
+        chain.push("unknown".to_string());
+    }
+    let origin = chain.remove(0);
+    ClientAddress { origin, via: chain }
+}
+
+/// Parses a raw query string into httpbin-style `args`: each key maps to its
+/// value, or to an array of values when the key repeats (`?a=1&a=2`).
 ///
-/// A `serde_json::Value` containing the headers as a JSON object
-pub(crate) fn serialize_headers(headers: &HeaderMap) -> serde_json::Value {
-    headers
-        .iter()
-        .map(|(k, v)| {
-            (
-                k.to_string(),
-                serde_json::Value::String(v.to_str().unwrap_or("<invalid utf8>").to_string()),
-            )
-        })
-        .collect::<serde_json::Map<_, _>>()
-        .into()
+/// Keys and values are form-decoded (`+` is a space, `%XX` is unescaped,
+/// invalid UTF-8 is replaced); a key without `=` maps to `""`.
+fn query_args(query: &str) -> serde_json::Value {
+    let decode = |s: &str| {
+        percent_encoding::percent_decode_str(&s.replace('+', " "))
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+    let mut args = serde_json::Map::new();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = serde_json::Value::String(decode(value));
+        match args.entry(decode(key)) {
+            serde_json::map::Entry::Vacant(slot) => {
+                slot.insert(value);
+            }
+            serde_json::map::Entry::Occupied(mut slot) => match slot.get_mut() {
+                serde_json::Value::Array(values) => values.push(value),
+                first => *first = json!([first.take(), value]),
+            },
+        }
+    }
+    args.into()
+}
+
+/// Reconstructs the absolute URL the client requested: `https` when the
+/// connection is TLS, the `Host` header (or the HTTP/2 `:authority`), then the
+/// path and query exactly as received.
+fn request_url(uri: &axum::http::Uri, headers: &HeaderMap, https: bool) -> String {
+    let scheme = if https { "https" } else { "http" };
+    let host = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| uri.authority().map(|a| a.as_str()))
+        .unwrap_or("localhost");
+    let path_and_query = uri.path_and_query().map_or("/", |pq| pq.as_str());
+    format!("{scheme}://{host}{path_and_query}")
 }
 
 /// Maps an [`axum::http::Version`] to its canonical wire string (e.g.
@@ -79,20 +200,202 @@ pub(crate) fn is_http1(version: axum::http::Version) -> bool {
     version == Version::HTTP_11 || version == Version::HTTP_10
 }
 
-/// Scans a raw URL query string for a `connection=close` directive.
+/// Whether a raw URL query string sets `name` to `true` or `1`, as in
+/// `/anything?sign=true`.
 ///
-/// The key match is exact (`connection`); the value match is ASCII
-/// case-insensitive (`close`/`CLOSE`). Parses the raw query string directly
-/// rather than via a `Query` extractor so `/anything` never rejects an
-/// otherwise-odd query string (preserving its permissive echo behavior).
+/// The key match is exact; the value match is ASCII case-insensitive
+/// (`true`/`TRUE`). Parses the raw query string directly rather than via a
+/// `Query` extractor so `/anything` never rejects an otherwise-odd query
+/// string (preserving its permissive echo behavior).
+pub(crate) fn query_flag(query: &str, name: &str) -> bool {
+    query_values(query, name).any(|value| value.eq_ignore_ascii_case("true") || value == "1")
+}
+
+/// The value of every `name=<value>` pair in a raw URL query string, in order.
+fn query_values<'a>(query: &'a str, name: &'a str) -> impl Iterator<Item = &'a str> {
+    query
+        .split('&')
+        .filter_map(move |pair| pair.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// Whether a raw URL query string carries a `connection=close` directive
+/// (value ASCII case-insensitive).
 pub(crate) fn wants_connection_close(query: &str) -> bool {
-    query.split('&').any(|pair| {
-        let mut kv = pair.splitn(2, '=');
-        matches!(
-            (kv.next(), kv.next()),
-            (Some("connection"), Some(value)) if value.eq_ignore_ascii_case("close")
-        )
-    })
+    query_values(query, "connection").any(|value| value.eq_ignore_ascii_case("close"))
+}
+
+/// Scans a raw URL query string for a `header_delay_ms=<n>` directive.
+///
+/// Returns the requested delay clamped to the delay cap (`MAX_DELAY_SECONDS`,
+/// in milliseconds), or `None` when absent or not a non-negative integer. The
+/// first valid occurrence wins. Parsed from the raw query for the same reason
+/// as [`query_flag`]: `/anything` must never reject a query.
+pub(crate) fn header_delay_ms(query: &str) -> Option<u64> {
+    query_values(query, "header_delay_ms")
+        .find_map(|value| value.parse::<u64>().ok())
+        .map(|ms| ms.min(MAX_DELAY_SECONDS * 1000))
+}
+
+/// Marker extension enabling `/anything?fail_midstream=true`; layered on by
+/// `build_app` when `fail_midstream_enabled` is set.
+#[derive(Debug, Clone, Copy)]
+pub struct FailMidstream;
+
+/// Rewrites `response` to stream the first half of its body, then fail the
+/// body stream, so the client sees valid-looking but truncated content.
+///
+/// Over HTTP/1.1 the response is chunked and the connection is dropped
+/// mid-body; over HTTP/2 the stream is reset.
+pub(crate) async fn fail_midstream(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return format_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to read response body for fail_midstream",
+        );
+    };
+    let sent = bytes.slice(..bytes.len() / 2);
+    // Yield before failing so hyper flushes the headers and first chunk;
+    // an immediate error would discard the response unsent.
+    let stream = futures_util::StreamExt::chain(
+        futures_util::stream::once(async move { Ok::<_, std::io::Error>(sent) }),
+        futures_util::stream::once(async {
+            tokio::task::yield_now().await;
+            Err(std::io::Error::other("fail_midstream: body aborted"))
+        }),
+    );
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, axum::body::Body::from_stream(stream))
+}
+
+/// Rewrites `response` to stream its body in tiny chunks, yielding between
+/// them so each is flushed on its own.
+///
+/// Chunks are one byte unless that would take more than [`MAX_FRAGMENTS`]
+/// chunks, in which case they grow to fit. The chunk size is reported in
+/// `X-Fragment-Bytes`.
+pub(crate) async fn fragment(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return format_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to read response body for fragment",
+        );
+    };
+    let chunk_len = bytes.len().div_ceil(MAX_FRAGMENTS).max(1);
+    let stream = futures_util::stream::unfold(0usize, move |offset| {
+        let bytes = bytes.clone();
+        async move {
+            if offset >= bytes.len() {
+                return None;
+            }
+            // Pending once per chunk, so hyper flushes before taking the next.
+            if offset > 0 {
+                tokio::task::yield_now().await;
+            }
+            let end = (offset + chunk_len).min(bytes.len());
+            Some((Ok::<_, std::io::Error>(bytes.slice(offset..end)), end))
+        }
+    });
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert("x-fragment-bytes", header::HeaderValue::from(chunk_len));
+    Response::from_parts(parts, axum::body::Body::from_stream(stream))
+}
+
+/// Whether a raw URL query string carries an `echo=stream` directive (value
+/// ASCII case-insensitive).
+pub(crate) fn wants_stream_echo(query: &str) -> bool {
+    query_values(query, "echo").any(|value| value.eq_ignore_ascii_case("stream"))
+}
+
+/// Builds the `?echo=stream` response: `metadata` as one line of compact JSON,
+/// then the request body piped through chunk by chunk as it arrives.
+///
+/// Compact JSON escapes newlines, so the first `\n` ends the metadata frame.
+/// The body is never collected, so it isn't held to `max_body_size_bytes`.
+/// When the app provides a [`StreamingShutdown`], the stream is cut once its
+/// shutdown grace runs out, and the request gets 503 if
+/// `max_streaming_responses` streams are already open.
+fn stream_echo(
+    metadata: &serde_json::Value,
+    body: axum::body::Body,
+    streaming: Option<Extension<StreamingShutdown>>,
+) -> Response {
+    let Ok(mut frame) = serde_json::to_vec(metadata) else {
+        return format_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to serialize stream echo metadata",
+        );
+    };
+    frame.push(b'\n');
+    let stream = futures_util::StreamExt::chain(
+        futures_util::stream::once(
+            async move { Ok::<_, axum::Error>(axum::body::Bytes::from(frame)) },
+        ),
+        body.into_data_stream(),
+    );
+    let body = match streaming {
+        Some(Extension(streaming)) => match streaming.guard(stream) {
+            Ok(guarded) => axum::body::Body::from_stream(guarded),
+            Err(e) => {
+                return format_error_response(StatusCode::SERVICE_UNAVAILABLE, &e.to_string())
+            }
+        },
+        None => axum::body::Body::from_stream(stream),
+    };
+    ([(header::CONTENT_TYPE, "application/octet-stream")], body).into_response()
+}
+
+/// Configured request limits reported by `/anything?limits=true`; layered on
+/// by `build_app`.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestLimits {
+    /// The `max_body_size_bytes` the body limit layer enforces.
+    pub max_body_size_bytes: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_body_size_bytes: DEFAULT_MAX_BODY_SIZE_BYTES,
+        }
+    }
+}
+
+impl RequestLimits {
+    /// The limits as the `limits` object of an `/anything` response. A limit
+    /// rucho does not enforce is `null`.
+    fn to_json(self) -> serde_json::Value {
+        json!({
+            "max_body_size_bytes": self.max_body_size_bytes,
+            "max_headers": HTTP1_MAX_HEADERS,
+            "max_query_params": null,
+            "max_delay_seconds": MAX_DELAY_SECONDS,
+            "max_redirect_hops": MAX_REDIRECT_HOPS,
+        })
+    }
+}
+
+/// Collects the `preload=<target>` values from a raw URL query string, in
+/// order, up to `MAX_PRELOAD_LINKS`.
+///
+/// Targets are used verbatim inside `Link: <target>; rel=preload`, so values
+/// that are empty or contain whitespace, control characters, `<`, `>`, or `,`
+/// (which would break out of the link) are skipped.
+pub(crate) fn preload_targets(query: &str) -> Vec<&str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.strip_prefix("preload="))
+        .filter(|target| {
+            !target.is_empty()
+                && target
+                    .bytes()
+                    .all(|b| b.is_ascii_graphic() && !matches!(b, b'<' | b'>' | b','))
+        })
+        .take(MAX_PRELOAD_LINKS)
+        .collect()
 }
 
 /// Represents information about an API endpoint.
@@ -113,7 +416,7 @@ pub struct EndpointInfo {
 ///
 /// This array is used by the `/endpoints` handler to provide a discoverable list
 /// of available API operations, including their paths, HTTP methods, and descriptions.
-static API_ENDPOINTS: &[EndpointInfo] = &[
+pub(crate) static API_ENDPOINTS: &[EndpointInfo] = &[
     // Routes from former get.rs
     EndpointInfo {
         path: "/",
@@ -158,13 +461,13 @@ static API_ENDPOINTS: &[EndpointInfo] = &[
     EndpointInfo {
         path: "/options",
         method: "OPTIONS",
-        description: "Responds with allowed HTTP methods.",
+        description: "Responds with the server-wide allowed HTTP methods.",
     },
     // Routes from former status.rs
     EndpointInfo {
         path: "/status/:code",
         method: "ANY",
-        description: "Returns the specified HTTP status code.",
+        description: "Returns the specified HTTP status code, or one at random from a comma-separated list, optionally weighted as code:weight (?body=, ?delay=, ?header=).",
     },
     // Routes from former anything.rs
     EndpointInfo {
@@ -183,6 +486,16 @@ static API_ENDPOINTS: &[EndpointInfo] = &[
         method: "GET",
         description: "Performs a health check.",
     },
+    EndpointInfo {
+        path: "/livez",
+        method: "GET",
+        description: "Liveness probe: 200 while the process is up.",
+    },
+    EndpointInfo {
+        path: "/readyz",
+        method: "GET",
+        description: "Readiness probe: 503 until every listener is bound, then 200.",
+    },
     // Delay endpoint
     EndpointInfo {
         path: "/delay/:n",
@@ -227,7 +540,37 @@ static API_ENDPOINTS: &[EndpointInfo] = &[
     EndpointInfo {
         path: "/uuid",
         method: "GET",
-        description: "Returns a randomly generated UUID v4.",
+        description: "Returns a randomly generated UUID (v4, or v7 with ?version=7).",
+    },
+    EndpointInfo {
+        path: "/uuid/:count",
+        method: "GET",
+        description: "Returns an array of count UUIDs (max 1000; v4, or v7 with ?version=7).",
+    },
+    EndpointInfo {
+        path: "/time",
+        method: "GET",
+        description: "Returns the server time as unix, unix_ms, rfc3339 and iso8601 (or one via ?format=).",
+    },
+    EndpointInfo {
+        path: "/env",
+        method: "GET",
+        description: "Returns the environment variables whose names start with an allowed prefix (env_allow_prefixes, default RUCHO_).",
+    },
+    EndpointInfo {
+        path: "/hostname",
+        method: "GET",
+        description: "Returns the host name of the serving machine or container.",
+    },
+    EndpointInfo {
+        path: "/version",
+        method: "GET",
+        description: "Returns the name, version, git commit, build timestamp and rustc version of the running build.",
+    },
+    EndpointInfo {
+        path: "/ws",
+        method: "GET",
+        description: "WebSocket echo: upgrades and sends every text/binary message back, answers pings.",
     },
     // IP endpoint
     EndpointInfo {
@@ -244,8 +587,8 @@ static API_ENDPOINTS: &[EndpointInfo] = &[
     // Headers endpoint
     EndpointInfo {
         path: "/headers",
-        method: "GET",
-        description: "Returns all request headers.",
+        method: "ANY",
+        description: "Returns all request headers (lowercased names, repeated headers as arrays).",
     },
     // /endpoints itself
     EndpointInfo {
@@ -270,12 +613,22 @@ static API_ENDPOINTS: &[EndpointInfo] = &[
         method: "GET",
         description: "Echoes each query parameter as a response header and in the JSON body.",
     },
+    EndpointInfo {
+        path: "/stream-bytes/:n",
+        method: "GET",
+        description: "Streams n random bytes in chunk_size chunks (max 10 MiB; ?seed= for reproducible bytes).",
+    },
     EndpointInfo {
         path: "/drip",
         method: "GET",
         description:
             "Streams numbytes bytes of '*' over duration seconds via chunked transfer encoding.",
     },
+    EndpointInfo {
+        path: "/events",
+        method: "GET",
+        description: "Server-Sent Events: count counter events (id/data 1..count), interval_ms apart.",
+    },
     EndpointInfo {
         path: "/xml",
         method: "GET",
@@ -324,6 +677,62 @@ static API_ENDPOINTS: &[EndpointInfo] = &[
         method: "GET",
         description: "Returns Cache-Control: public, max-age=n.",
     },
+    EndpointInfo {
+        path: "/countdown/:n",
+        method: "GET",
+        description:
+            "Poll-until-ready: 202 with X-Countdown per X-Client-Id poll, 200 on the n-th poll.",
+    },
+    EndpointInfo {
+        path: "/long-poll",
+        method: "GET",
+        description: "Waits for POST /long-poll/trigger (200 + event) or ?timeout_ms= (204).",
+    },
+    EndpointInfo {
+        path: "/long-poll/trigger",
+        method: "POST",
+        description: "Releases every waiting /long-poll with the JSON body as its event.",
+    },
+    EndpointInfo {
+        path: "/resource/:id",
+        method: "DELETE",
+        description: "200 on the first delete of an id, 404 on every later one.",
+    },
+    EndpointInfo {
+        path: "/admin/resource/reset",
+        method: "POST",
+        description: "Forgets every /resource/:id deletion.",
+    },
+    EndpointInfo {
+        path: "/kv/:key",
+        method: "PUT",
+        description: "Stores the body under a key (?ttl= seconds to expire); 201 new, 200 replaced.",
+    },
+    EndpointInfo {
+        path: "/kv/:key",
+        method: "GET",
+        description: "Returns the stored body with its Content-Type, or 404.",
+    },
+    EndpointInfo {
+        path: "/kv/:key",
+        method: "DELETE",
+        description: "Deletes a key: 200, or 404 if it wasn't there.",
+    },
+    EndpointInfo {
+        path: "/echo",
+        method: "ANY",
+        description: "Reflects the exact request body and Content-Type with ?status= (default 200).",
+    },
+    EndpointInfo {
+        path: "/dump",
+        method: "ANY",
+        description: "Returns the raw request (request line, headers, body) as text/plain.",
+    },
+    EndpointInfo {
+        path: "/batch",
+        method: "POST",
+        description: "Executes a JSON array of sub-requests in-process; returns their responses.",
+    },
 ];
 
 /// Creates and returns the Axum router for the core API endpoints.
@@ -333,7 +742,7 @@ static API_ENDPOINTS: &[EndpointInfo] = &[
 pub fn router() -> Router {
     Router::new()
         // Routes from get.rs
-        .route("/", get(root_handler))
+        .route("/", get(root_handler).fallback(root_method_not_allowed))
         .route("/get", get(get_handler))
         .route("/get", head(head_handler))
         // Routes from post.rs
@@ -353,12 +762,13 @@ pub fn router() -> Router {
         .route("/anything/*path", any(anything_handler))
         // Route for /uuid
         .route("/uuid", get(uuid_handler))
+        .route("/uuid/:count", get(uuid_bulk_handler))
         // Route for /ip
         .route("/ip", get(ip_handler))
         // Route for /user-agent
         .route("/user-agent", get(user_agent_handler))
         // Route for /headers
-        .route("/headers", get(headers_handler))
+        .route("/headers", any(headers_handler))
         // Route for /endpoints
         .route("/endpoints", get(endpoints_handler))
 }
@@ -372,7 +782,18 @@ pub fn router() -> Router {
 /// It accepts any HTTP method.
 ///
 /// # Path Parameters:
-/// - `code`: The HTTP status code to return (e.g., 200, 404, 500).
+/// - `code`: The HTTP status code to return (e.g., 200, 404, 500), or a
+///   comma-separated list (`200,500,503`) to return one of them at random.
+///   Entries may carry a weight from 1 to `MAX_STATUS_WEIGHT` (`200:3,500:1`
+///   returns 200 three times as often as 500).
+///
+/// # Query Parameters:
+/// - `body`: Send this text (`text/plain`) instead of the JSON body. Ignored,
+///   like the JSON body, for 1xx, 204 and 304, which carry none.
+/// - `delay`: Wait this many seconds (max `MAX_DELAY_SECONDS`) before answering.
+/// - `header`: Repeatable `Name:Value` pairs added to the response.
+/// - `retry_after`: `Retry-After` seconds for a `429` or `503` (default
+///   `DEFAULT_STATUS_RETRY_AFTER_SECONDS`); other statuses get none.
 ///
 /// # Responses:
 /// - Returns the status code specified by the `code` path parameter.
@@ -382,23 +803,110 @@ pub fn router() -> Router {
     get, post, put, patch, delete, options, head, // Indicates this path works for all these methods
     path = "/status/{code}",
     params(
-        ("code" = u16, Path, description = "HTTP status code to return")
+        ("code" = String, Path, description = "HTTP status code to return, or a comma-separated list to pick one at random; `code:weight` entries (e.g. `200:3,500:1`) are picked in proportion to their integer weights (1 to 1000000)"),
+        ("body" = Option<String>, Query, description = "Text body to send (`text/plain`) instead of the JSON status/reason; 1xx, 204 and 304 responses carry no body"),
+        ("delay" = Option<u64>, Query, description = "Seconds to wait before responding (max 300)"),
+        ("header" = Option<String>, Query, description = "Repeatable. A `Name:Value` header to add to the response"),
+        ("retry_after" = Option<u64>, Query, description = "`Retry-After` seconds sent with a 429 or 503 (default 1)")
     ),
     responses(
         (status = 200, description = "Returns the specified status code"),
-        (status = 400, description = "Invalid status code provided")
+        (status = 400, description = "Invalid status code, weight, delay, header, or retry_after provided")
         // Other status codes are returned directly as specified by `code`
     )
 )]
 pub async fn status_handler(
-    axum::extract::Path(code): axum::extract::Path<u16>,
+    axum::extract::Path(codes): axum::extract::Path<String>,
+    Query(query): Query<Vec<(String, String)>>,
+    clock: Option<Extension<Clock>>,
     _method: axum::http::Method,
 ) -> Response {
-    let status = StatusCode::from_u16(code).unwrap_or(StatusCode::BAD_REQUEST);
+    let Some(status) = status_choices(&codes).and_then(|choices| pick_status(&choices)) else {
+        return status_reason_response(StatusCode::BAD_REQUEST);
+    };
+
+    let mut body = None;
+    let mut delay = None;
+    let mut retry_after = DEFAULT_STATUS_RETRY_AFTER_SECONDS;
+    let mut extra_headers = HeaderMap::new();
+    for (key, value) in &query {
+        match key.as_str() {
+            "body" => body = Some(value.clone()),
+            "delay" => match value.parse::<u64>() {
+                Ok(seconds) if seconds <= MAX_DELAY_SECONDS => delay = Some(seconds),
+                _ => {
+                    return format_error_response(
+                        StatusCode::BAD_REQUEST,
+                        &format!("delay must be 0 to {MAX_DELAY_SECONDS} seconds"),
+                    )
+                }
+            },
+            "retry_after" => match value.parse::<u64>() {
+                Ok(seconds) => retry_after = seconds,
+                Err(_) => {
+                    return format_error_response(
+                        StatusCode::BAD_REQUEST,
+                        "retry_after must be a whole number of seconds",
+                    )
+                }
+            },
+            "header" => match parse_header_pair(value) {
+                Some((name, value)) => {
+                    extra_headers.append(name, value);
+                }
+                None => {
+                    return format_error_response(
+                        StatusCode::BAD_REQUEST,
+                        &format!("header must be Name:Value, got '{value}'"),
+                    )
+                }
+            },
+            _ => {}
+        }
+    }
+
+    if let Some(seconds) = delay {
+        let Extension(clock) = clock.unwrap_or_default();
+        clock.sleep(std::time::Duration::from_secs(seconds)).await;
+    }
+
+    // 1xx, 204 and 304 responses can't carry a body, so they get none.
+    let mut response = match body {
+        _ if forbids_body(status) => status.into_response(),
+        Some(body) => (
+            status,
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            body,
+        )
+            .into_response(),
+        None => status_reason_response(status),
+    };
+    if matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    ) {
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+    // Replaces, rather than duplicates, headers the response already has
+    // (including `Retry-After`).
+    response.headers_mut().extend(extra_headers);
+    response
+}
+
+/// Whether responses with `status` must not carry a body (RFC 9110 §6.4.1).
+fn forbids_body(status: StatusCode) -> bool {
+    status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+}
+
+/// Echoes the canonical reason phrase in the body (an inspection-fidelity win
+/// over httpbin, which returns an empty body) while the HTTP status line still
+/// carries the requested code.
+fn status_reason_response(status: StatusCode) -> Response {
     let reason = status.canonical_reason().unwrap_or("Unknown Status");
-    // Echo the canonical reason phrase in the body (an inspection-fidelity win
-    // over httpbin, which returns an empty body) while the HTTP status line
-    // still carries the requested code.
     (
         status,
         Json(json!({ "status": status.as_u16(), "reason": reason })),
@@ -406,6 +914,48 @@ pub async fn status_handler(
         .into_response()
 }
 
+/// Parses the `/status/:code` path segment: one code or a comma-separated
+/// list, each optionally weighted as `code:weight` (default weight 1).
+/// `None` if any code isn't a valid status, any weight isn't an integer from 1
+/// to `MAX_STATUS_WEIGHT`, or the weights together overflow a `u32`.
+fn status_choices(codes: &str) -> Option<Vec<(StatusCode, u32)>> {
+    let choices: Vec<_> = codes
+        .split(',')
+        .map(|entry| {
+            let (code, weight) = match entry.split_once(':') {
+                Some((code, weight)) => (code, weight.trim().parse::<u32>().ok()?),
+                None => (entry, 1),
+            };
+            let code = StatusCode::from_u16(code.trim().parse::<u16>().ok()?).ok()?;
+            (1..=MAX_STATUS_WEIGHT)
+                .contains(&weight)
+                .then_some((code, weight))
+        })
+        .collect::<Option<_>>()?;
+    // `pick_status` sums the weights as a `u32`.
+    choices
+        .iter()
+        .try_fold(0u32, |total, (_, weight)| total.checked_add(*weight))?;
+    Some(choices)
+}
+
+/// Picks one of `choices` at random, in proportion to its weight.
+fn pick_status(choices: &[(StatusCode, u32)]) -> Option<StatusCode> {
+    use rand::distributions::{Distribution, WeightedIndex};
+    let weights = WeightedIndex::new(choices.iter().map(|(_, weight)| *weight)).ok()?;
+    Some(choices[weights.sample(&mut rand::thread_rng())].0)
+}
+
+/// Splits a `Name:Value` query value into a header, or `None` when the name
+/// or value isn't valid.
+fn parse_header_pair(pair: &str) -> Option<(HeaderName, HeaderValue)> {
+    let (name, value) = pair.split_once(':')?;
+    Some((
+        HeaderName::from_bytes(name.trim().as_bytes()).ok()?,
+        HeaderValue::from_str(value.trim()).ok()?,
+    ))
+}
+
 // From anything.rs
 /// Echoes back details of the incoming request for any HTTP method.
 ///
@@ -413,7 +963,9 @@ pub async fn status_handler(
 /// It reflects the method, path, query parameters, headers, and body of the request.
 ///
 /// # Responses:
-/// - `200 OK`: Successfully echoed the request details as a JSON object.
+/// - `200 OK`: Successfully echoed the request details as a JSON object, or,
+///   with `?echo=stream`, a metadata line followed by the body streamed back
+///   unbuffered.
 ///
 /// Note: While this handler is registered for `/anything` and `/anything/*path`,
 /// the OpenAPI documentation for `/anything/*path` is handled by `anything_path_handler`
@@ -422,32 +974,65 @@ pub async fn status_handler(
     get, post, put, patch, delete, options, head, // Indicates this path works for all these methods
     path = "/anything",
     params(
-        ("connection" = Option<String>, Query, description = "Set to `close` to force a `Connection: close` response and hang up the connection afterward (HTTP/1.1 only; ignored over HTTP/2)")
+        ("connection" = Option<String>, Query, description = "Set to `close` to force a `Connection: close` response and hang up the connection afterward (HTTP/1.1 only; ignored over HTTP/2)"),
+        ("header_delay_ms" = Option<u64>, Query, description = "Delay sending the response headers by this many milliseconds (clamped to 300000), to exercise client header-read timeouts"),
+        ("preload" = Option<String>, Query, description = "Repeatable. Adds a `Link: <target>; rel=preload` header per target (max 16) to the final response. HTTP/2 server push and `103 Early Hints` are not supported; the body's `preload` object says so"),
+        ("sign" = Option<bool>, Query, description = "Set to `true` to add an `X-Signature: sha256=<hex>` header, the HMAC-SHA256 of the (uncompressed) response body under the configured `signing_secret`"),
+        ("fail_midstream" = Option<bool>, Query, description = "Set to `true` to send the first half of the JSON body and then abort the response, for testing truncated-JSON handling. Requires `fail_midstream_enabled`"),
+        ("fragment" = Option<bool>, Query, description = "Set to `true` to stream the body in tiny chunks (1 byte each, larger once the body exceeds 4096 bytes), each flushed separately; the chunk size is reported in `X-Fragment-Bytes`. Ignored with `fail_midstream`"),
+        ("limits" = Option<bool>, Query, description = "Set to `true` to add a `limits` object with the server's effective request limits: `max_body_size_bytes`, `max_headers` (HTTP/1.1), `max_query_params` (`null`: not limited), `max_delay_seconds`, `max_redirect_hops`"),
+        ("echo" = Option<String>, Query, description = "Set to `stream` to pipe the request body back unbuffered as `application/octet-stream`, after one line of JSON metadata (the echo without `body`). Not held to `max_body_size_bytes`; the other knobs except `header_delay_ms` are ignored")
     ),
     responses(
-        (status = 200, description = "Echoes request details (includes a `tls` object over HTTPS; a `connection` field when `?connection=close` is set)", body = serde_json::Value)
+        (status = 200, description = "Echoes request details, including `path` (still percent-encoded), `decoded_path` (percent-decoded, invalid UTF-8 replaced with U+FFFD), and `raw_path`: the request target exactly as received, percent-encoding and trailing slash intact (also includes a `tls` object over HTTPS; a `connection` field when `?connection=close` is set; a `header_delay_ms` field when a header delay was applied; a `preload` object when `?preload=` is set; a `signature_error` field when `?sign=true` is set but no `signing_secret` is configured; a `fail_midstream_error` field when `?fail_midstream=true` is set but `fail_midstream_enabled` is off; a `limits` object when `?limits=true` is set). With `?fail_midstream=true` enabled, the body is cut off halfway", body = serde_json::Value),
+        (status = 503, description = "`?echo=stream` while `max_streaming_responses` streams are already open")
     )
 )]
+#[allow(clippy::too_many_arguments)] // one extractor per echoed request facet
 pub async fn anything_handler(
     version: axum::http::Version,
     method: axum::http::Method,
     axum::extract::OriginalUri(uri): axum::extract::OriginalUri,
     headers: HeaderMap,
     timing: Option<Extension<RequestTiming>>,
+    request_id: Option<Extension<RequestId>>,
     tls: Option<Extension<std::sync::Arc<TlsConnectionInfo>>>,
-    body: axum::body::Bytes,
+    signing_key: Option<Extension<SigningKey>>,
+    fail_midstream_enabled: Option<Extension<FailMidstream>>,
+    limits: Option<Extension<RequestLimits>>,
+    streaming: Option<Extension<StreamingShutdown>>,
+    request: axum::extract::Request,
 ) -> impl IntoResponse {
     let query = uri.query().unwrap_or("");
+    let sign_requested = query_flag(query, "sign");
+    let fail_midstream_requested = query_flag(query, "fail_midstream");
+    let fragment_requested = query_flag(query, "fragment");
+    let limits_requested = query_flag(query, "limits");
     let close_requested = wants_connection_close(query);
     let http1 = is_http1(version);
+    let header_delay = header_delay_ms(query);
+    let preload = preload_targets(query);
+
+    // Slow-headers knob: `?header_delay_ms=N` holds the whole response (status
+    // line and headers included) for N ms, so a client's header-read timeout can
+    // be exercised separately from body/streaming timeouts (`/drip`).
+    if let Some(ms) = header_delay.filter(|&ms| ms > 0) {
+        tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+    }
 
     let mut resp = json!({
         "method": method.to_string(),
         "http_version": http_version_str(version),
+        // `path` is never percent-decoded, so an encoded `%2F` stays distinct
+        // from a real separator; `decoded_path` shows what it decodes to.
         "path": uri.path(),
+        "decoded_path": percent_encoding::percent_decode_str(uri.path()).decode_utf8_lossy(),
+        // `OriginalUri` is captured before any middleware rewrites it, so this
+        // is the target as received: `%2F` and friends stay encoded. HTTP/2
+        // requests carry the scheme and authority in the URI too; leave them out.
+        "raw_path": uri.path_and_query().map_or(uri.path(), |target| target.as_str()),
         "query": query,
-        "headers": serialize_headers(&headers),
-        "body": String::from_utf8_lossy(&body),
+        "headers": headers_to_json(&headers),
     });
 
     // Over HTTPS the TlsInfoAcceptor injects negotiated TLS parameters; echo
@@ -458,6 +1043,23 @@ pub async fn anything_handler(
         }
     }
 
+    // Streaming knob: `?echo=stream` pipes the body straight back instead of
+    // collecting it, so multi-gigabyte round-trips don't sit in memory.
+    if wants_stream_echo(query) {
+        let metadata = with_request_id(resp, request_id);
+        return stream_echo(&metadata, request.into_body(), streaming);
+    }
+
+    // Extracted here rather than as a handler argument so stream mode can take
+    // the body unread; `DefaultBodyLimit` still applies (413 past it).
+    let body = match axum::body::Bytes::from_request(request, &()).await {
+        Ok(body) => body,
+        Err(rejection) => return rejection.into_response(),
+    };
+    if let Some(obj) = resp.as_object_mut() {
+        obj.insert("body".to_string(), json!(String::from_utf8_lossy(&body)));
+    }
+
     // Connection-control knob: `?connection=close` asks the upstream to hang up
     // after this response, so a gateway's connection-pool / keep-alive reuse can
     // be observed against an upstream that voluntarily tears down. Reflect the
@@ -476,8 +1078,62 @@ pub async fn anything_handler(
         }
     }
 
+    if let Some(ms) = header_delay {
+        if let Some(obj) = resp.as_object_mut() {
+            obj.insert("header_delay_ms".to_string(), json!(ms));
+        }
+    }
+
+    // Preload knob: `?preload=/style.css` stands in for HTTP/2 server push.
+    // Hyper removed push and can't emit a `103 Early Hints` interim response,
+    // so the hints ride on the final response as `Link` headers; the body
+    // states that plainly so an HTTP/2 test doesn't wait for a push.
+    if !preload.is_empty() {
+        if let Some(obj) = resp.as_object_mut() {
+            obj.insert(
+                "preload".to_string(),
+                json!({
+                    "links": preload,
+                    "delivered_via": "Link header on the final response",
+                    "server_push": false,
+                    "early_hints_103": false,
+                }),
+            );
+        }
+    }
+
+    // Limits knob: `?limits=true` lets a client discover the server's caps
+    // before probing them.
+    if limits_requested {
+        if let Some(obj) = resp.as_object_mut() {
+            let limits = limits.map(|Extension(l)| l).unwrap_or_default();
+            obj.insert("limits".to_string(), limits.to_json());
+        }
+    }
+
+    // Asked to sign without a configured secret: say so rather than silently
+    // returning an unsigned response a verifier would reject for no clear reason.
+    if sign_requested && signing_key.is_none() {
+        if let Some(obj) = resp.as_object_mut() {
+            obj.insert(
+                "signature_error".to_string(),
+                json!("no signing_secret is configured"),
+            );
+        }
+    }
+
+    // Likewise for a truncation the server isn't configured to perform.
+    if fail_midstream_requested && fail_midstream_enabled.is_none() {
+        if let Some(obj) = resp.as_object_mut() {
+            obj.insert(
+                "fail_midstream_error".to_string(),
+                json!("fail_midstream_enabled is off"),
+            );
+        }
+    }
+
     let duration_ms = timing.map(|t| t.elapsed_ms());
-    let mut response = format_json_response_with_timing(resp, duration_ms);
+    let mut response = format_response(with_request_id(resp, request_id), duration_ms, &headers);
 
     // Hyper honors a per-response `Connection: close` by closing the socket
     // after writing the response (overriding the listener's keep-alive default).
@@ -489,24 +1145,49 @@ pub async fn anything_handler(
         );
     }
 
-    response
+    for target in preload {
+        if let Ok(link) = header::HeaderValue::from_str(&format!("<{target}>; rel=preload")) {
+            response.headers_mut().append(header::LINK, link);
+        }
+    }
+
+    // Signing knob: `?sign=true` adds an `X-Signature` HMAC of the body, so a
+    // client's signature-verification path has a known-good signer to test
+    // against. Signed over exactly the bytes the handler emits.
+    let response = match signing_key {
+        Some(Extension(key)) if sign_requested => sign_response(response, &key).await,
+        _ => response,
+    };
+
+    // Truncation knob: `?fail_midstream=true` starts a valid JSON body and
+    // aborts halfway, so a client's truncated-JSON handling can be tested
+    // deterministically (chaos truncation is random).
+    if fail_midstream_requested && fail_midstream_enabled.is_some() {
+        fail_midstream(response).await
+    } else if fragment_requested {
+        // Fragmentation knob: `?fragment=true` dribbles the same body out in
+        // tiny, separately flushed chunks, to test client reassembly/buffering.
+        fragment(response).await
+    } else {
+        response
+    }
 }
 
 #[utoipa::path(
     get, post, put, patch, delete, options, head,
-    path = "/anything/{path:.*}",
+    path = "/anything/{path}",
     params(
-        ("path" = String, Path, description = "Subpath for anything endpoint")
+        ("path" = String, Path, description = "Subpath for anything endpoint; may contain further `/` segments")
     ),
     responses(
-        (status = 200, description = "Echoes request details for subpath", body = serde_json::Value)
+        (status = 200, description = "Echoes request details for subpath. `path` keeps the subpath percent-encoded (`a%2Fb` stays one segment); `decoded_path` is the decoded form", body = serde_json::Value)
     )
 )]
 #[allow(dead_code)] // To suppress warnings as it's not called directly by our code
 /// **OpenAPI Documentation Handler for `/anything/*path`**.
 ///
 /// This function exists *solely* to generate the correct OpenAPI documentation
-/// for requests to `/anything/{path:.*}` (e.g., `/anything/foo/bar`).
+/// for requests to `/anything/{path}` (e.g., `/anything/foo/bar`).
 /// The actual requests to these wildcard paths are handled by `anything_handler`.
 ///
 /// This separation is necessary due to current limitations in `utoipa` regarding
@@ -520,7 +1201,7 @@ pub async fn anything_handler(
 /// - **Note**: This handler, if ever called directly, returns `501 Not Implemented`.
 pub async fn anything_path_handler(
     // Signature can mirror anything_handler but must include the Path extractor for "path"
-    // utoipa needs to see axum::extract::Path here for the {path:.*} parameter.
+    // utoipa needs to see axum::extract::Path here for the {path} parameter.
     #[allow(unused_variables)] method: axum::http::Method,
     #[allow(unused_variables)] uri: axum::extract::OriginalUri,
     #[allow(unused_variables)] headers: axum::http::HeaderMap,
@@ -535,51 +1216,108 @@ pub async fn anything_path_handler(
 }
 
 // From get.rs
-/// Serves a welcome message at the root path (`/`).
+/// Serves the welcome message at the root path (`/`).
+///
+/// The message comes from `welcome_message` (see [`WelcomeMessage`]).
 ///
 /// # HTTP Method:
 /// - `GET`
 ///
 /// # Responses:
-/// - `200 OK`: Returns a plain text welcome message.
+/// - `200 OK`: The message as plain text with a trailing newline, or as
+///   `{"message": ...}` when `application/json` is the preferred `Accept` type.
 #[utoipa::path(
     get,
     path = "/",
     responses(
-        (status = 200, description = "Welcome message", body = String)
+        (status = 200, description = "Welcome message (plain text)", body = String),
+        (status = 200, description = "Welcome message as `{\"message\": ...}` when Accept prefers application/json", content_type = "application/json", body = serde_json::Value),
+        (status = 405, description = "Any method other than GET/HEAD; JSON error with an `Allow: GET, HEAD` header")
     )
 )]
-pub async fn root_handler() -> &'static str {
-    "Welcome to Echo Server!
-"
+pub async fn root_handler(
+    welcome: Option<Extension<WelcomeMessage>>,
+    headers: HeaderMap,
+) -> Response {
+    let Extension(WelcomeMessage(message)) = welcome.unwrap_or_default();
+    let mut response = if preferred_media_type(&headers).as_deref() == Some("application/json") {
+        format_json_response(json!({ "message": &*message }))
+    } else {
+        format!("{message}\n").into_response()
+    };
+    response
+        .headers_mut()
+        .insert(header::VARY, HeaderValue::from_static("Accept"));
+    response
+}
+
+/// Answers any method other than GET/HEAD on `/` with a JSON 405.
+///
+/// Tools that probe the root with POST, PUT, OPTIONS, etc. get an explanation
+/// and an `Allow` header instead of axum's empty-bodied 405.
+async fn root_method_not_allowed(method: axum::http::Method) -> Response {
+    let mut response = format_error_response(
+        StatusCode::METHOD_NOT_ALLOWED,
+        &format!("Method {method} is not allowed on /; use GET (see /endpoints for the API)"),
+    );
+    response
+        .headers_mut()
+        .insert(header::ALLOW, header::HeaderValue::from_static("GET, HEAD"));
+    response
+}
+
+/// Fallback for requests that match no route: a JSON 404 echoing the path.
+///
+/// API clients get the same `{"error": ...}` shape as every other error
+/// instead of axum's empty-bodied 404.
+pub async fn not_found_handler(
+    axum::extract::OriginalUri(uri): axum::extract::OriginalUri,
+) -> Response {
+    format_error_response_with_path(StatusCode::NOT_FOUND, "Not Found", uri.path())
 }
 
 /// Handles GET requests to `/get`.
 ///
-/// Echoes back the request's method and headers as a JSON object.
+/// Echoes back the request's method, query parameters, headers, client
+/// address and URL as a JSON object.
+///
+/// Like httpbin, `args` maps each query parameter to its value, or to an array
+/// of values when the parameter repeats; `origin` is the client IP (see
+/// `/ip`) and `url` is the requested URL reconstructed from scheme, host, path
+/// and query.
 ///
 /// # HTTP Method:
 /// - `GET`
 ///
 /// # Responses:
-/// - `200 OK`: Returns a JSON object containing the method and headers.
+/// - `200 OK`: Returns a JSON object containing the method, args, headers,
+///   origin and url.
 #[utoipa::path(
     get,
     path = "/get",
     responses(
-        (status = 200, description = "Echoes request details (includes a `tls` object over HTTPS)", body = serde_json::Value)
+        (status = 200, description = "Echoes request details: `args`, `headers`, `origin`, `url` (includes a `tls` object over HTTPS)", body = serde_json::Value)
     )
 )]
+#[allow(clippy::too_many_arguments)] // one extractor per echoed request facet
 pub async fn get_handler(
     version: axum::http::Version,
+    axum::extract::OriginalUri(uri): axum::extract::OriginalUri,
     headers: HeaderMap,
+    connect_info: Option<axum::extract::ConnectInfo<std::net::SocketAddr>>,
     timing: Option<Extension<RequestTiming>>,
+    request_id: Option<Extension<RequestId>>,
     tls: Option<Extension<std::sync::Arc<TlsConnectionInfo>>>,
+    trust_proxy: Option<Extension<TrustProxy>>,
 ) -> Response {
+    let Extension(TrustProxy(trust_proxy)) = trust_proxy.unwrap_or_default();
     let mut payload = json!({
         "method": "GET",
         "http_version": http_version_str(version),
-        "headers": serialize_headers(&headers),
+        "args": query_args(uri.query().unwrap_or("")),
+        "headers": headers_to_json(&headers),
+        "origin": client_address(&headers, connect_info, trust_proxy).origin,
+        "url": request_url(&uri, &headers, tls.is_some()),
     });
     // Over HTTPS the TlsInfoAcceptor injects negotiated TLS parameters; echo
     // them under `tls`. Absent (and so omitted) on plain HTTP.
@@ -589,7 +1327,7 @@ pub async fn get_handler(
         }
     }
     let duration_ms = timing.map(|t| t.elapsed_ms());
-    format_json_response_with_timing(payload, duration_ms)
+    format_response(with_request_id(payload, request_id), duration_ms, &headers)
 }
 
 /// Handles HEAD requests to `/get`.
@@ -611,124 +1349,316 @@ pub async fn get_handler(
     )
 )]
 pub async fn head_handler() -> impl IntoResponse {
-    Response::builder()
-        .status(axum::http::StatusCode::OK)
-        .body(axum::body::Body::empty())
-        .expect("infallible: OK status with an empty body")
+    StatusCode::OK
+}
+
+/// Returns true when the `Accept` header prefers `text/html` over
+/// `application/json`.
+///
+/// Compares the q-values of the two explicitly listed media types (an unlisted
+/// type counts as q=0); on a tie, whichever is listed first wins. Wildcards are
+/// ignored, so `*/*` or no `Accept` header at all yields JSON.
+fn prefers_html(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    // (q, position) of each media type, if listed.
+    let mut html: Option<(f32, usize)> = None;
+    let mut json: Option<(f32, usize)> = None;
+    for (pos, range) in accept.split(',').enumerate() {
+        let mut parts = range.split(';');
+        let media = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let q = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        match media.as_str() {
+            "text/html" => html = html.or(Some((q, pos))),
+            "application/json" => json = json.or(Some((q, pos))),
+            _ => {}
+        }
+    }
+    match (html, json) {
+        (Some((hq, _)), None) => hq > 0.0,
+        (Some((hq, hpos)), Some((jq, jpos))) => hq > jq || (hq == jq && hq > 0.0 && hpos < jpos),
+        _ => false,
+    }
+}
+
+/// Escapes the HTML-significant characters in `s`.
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Renders `API_ENDPOINTS` as a standalone HTML page with one table row per
+/// endpoint.
+fn render_endpoints_html() -> String {
+    let rows: String = API_ENDPOINTS
+        .iter()
+        .map(|e| {
+            format!(
+                "      <tr><td>{}</td><td><code>{}</code></td><td>{}</td></tr>\n",
+                escape_html(e.method),
+                escape_html(e.path),
+                escape_html(e.description)
+            )
+        })
+        .collect();
+    format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n  <meta charset=\"utf-8\">\n  <title>rucho endpoints</title>\n</head>\n\
+         <body>\n  <h1>rucho endpoints</h1>\n  <table>\n    \
+         <thead><tr><th>Method</th><th>Path</th><th>Description</th></tr></thead>\n    \
+         <tbody>\n{rows}    </tbody>\n  </table>\n</body>\n</html>\n"
+    )
 }
 
 // Handler for /endpoints
 /// Lists all available API endpoints provided by this server.
 ///
-/// Returns a JSON array of `EndpointInfo` objects, each describing an endpoint's
-/// path, HTTP method, and a brief description.
+/// Content-negotiated: returns a JSON array of `EndpointInfo` objects (path,
+/// HTTP method, and a brief description) by default, or an HTML table of the
+/// same list when the `Accept` header prefers `text/html` — so a browser gets
+/// a readable index.
+///
+/// Both representations carry a content-hash `ETag`; a matching
+/// `If-None-Match` gets `304 Not Modified`, so polling clients skip the body.
 ///
 /// # HTTP Method:
 /// - `GET`
 ///
 /// # Responses:
-/// - `200 OK`: Successfully returns the list of endpoints.
-/// - `500 Internal Server Error`: If there's an issue serializing the endpoint data.
+/// - `200 OK`: Successfully returns the list of endpoints (JSON or HTML).
+/// - `304 Not Modified`: `If-None-Match` matched the current `ETag`.
 #[utoipa::path(
     get,
     path = "/endpoints",
     responses(
-        (status = 200, description = "Lists all available API endpoints", body = Vec<EndpointInfo>)
+        (status = 200, description = "Lists all available API endpoints", body = Vec<EndpointInfo>),
+        (status = 200, description = "HTML table of the endpoints (when Accept prefers text/html)", content_type = "text/html", body = String),
+        (status = 304, description = "Not Modified — If-None-Match matched the ETag")
     )
 )]
-pub async fn endpoints_handler(timing: Option<Extension<RequestTiming>>) -> Response {
-    // Serializing a `&'static [EndpointInfo]` (plain serializable structs) cannot
-    // fail, so there is no error path to handle.
-    let json_value = serde_json::to_value(API_ENDPOINTS)
-        .expect("infallible: API_ENDPOINTS is a static slice of plain serializable structs");
-    let duration_ms = timing.map(|t| t.elapsed_ms());
-    format_json_response_with_timing(json!({ "endpoints": json_value }), duration_ms)
+pub async fn endpoints_handler(
+    headers: HeaderMap,
+    timing: Option<Extension<RequestTiming>>,
+) -> Response {
+    let mut response = if prefers_html(&headers) {
+        let html = render_endpoints_html();
+        let page = (
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            html.clone(),
+        );
+        with_content_etag(&headers, html.as_bytes(), page.into_response())
+    } else {
+        match serde_json::to_value(API_ENDPOINTS) {
+            Ok(json_value) => {
+                let duration_ms = timing.map(|t| t.elapsed_ms());
+                format_json_response_with_etag(
+                    json!({ "endpoints": json_value }),
+                    duration_ms,
+                    &headers,
+                )
+            }
+            Err(_) => format_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to serialize endpoint list",
+            ),
+        }
+    };
+    // The representation (and so the ETag) depends on `Accept`.
+    response
+        .headers_mut()
+        .insert(header::VARY, header::HeaderValue::from_static("accept"));
+    response
 }
 
-// Handler for /uuid
-/// Returns a randomly generated UUID v4.
-///
-/// Generates a new random UUID (Universally Unique Identifier) using the v4 algorithm.
-/// Useful for generating unique identifiers for testing purposes.
-///
-/// # HTTP Method:
-/// - `GET`
-///
-/// # Responses:
+/// UUID versions `/uuid` and `/uuid/:count` can generate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UuidVersion {
+    /// Random.
+    V4,
+    /// Unix-timestamp ordered; successive UUIDs from this process sort in
+    /// generation order.
+    V7,
+}
+
+impl UuidVersion {
+    /// Parses `?version=` (`4`/`v4`, `7`/`v7`; v4 when absent).
+    fn parse(version: Option<&str>) -> Result<Self, String> {
+        match version.map(|v| v.trim().trim_start_matches(['v', 'V'])) {
+            None | Some("4") => Ok(Self::V4),
+            Some("7") => Ok(Self::V7),
+            Some(_) => Err(format!(
+                "Unsupported UUID version '{}' (supported: 4, 7)",
+                version.unwrap_or_default()
+            )),
+        }
+    }
+
+    fn generate(self) -> Uuid {
+        match self {
+            Self::V4 => Uuid::new_v4(),
+            Self::V7 => Uuid::now_v7(),
+        }
+    }
+}
+
+/// Query parameters for `/uuid` and `/uuid/:count`.
+#[derive(Debug, Deserialize)]
+pub struct UuidParams {
+    version: Option<String>,
+}
+
+// Handler for /uuid
+/// Returns a randomly generated UUID.
+///
+/// Generates a new UUID (Universally Unique Identifier): v4 (random) by
+/// default, or v7 (time-ordered) with `?version=7`. Useful for generating
+/// unique identifiers for testing purposes.
+///
+/// # HTTP Method:
+/// - `GET`
+///
+/// # Responses:
 /// - `200 OK`: Returns a JSON object containing the generated UUID.
+/// - `400 Bad Request`: Unsupported `version`.
 #[utoipa::path(
     get,
     path = "/uuid",
+    params(
+        ("version" = Option<String>, Query, description = "UUID version: `4` (random, default) or `7` (time-ordered)")
+    ),
     responses(
-        (status = 200, description = "Returns a randomly generated UUID", body = serde_json::Value)
+        (status = 200, description = "Returns a generated UUID", body = serde_json::Value),
+        (status = 400, description = "Unsupported version")
     )
 )]
-pub async fn uuid_handler(timing: Option<Extension<RequestTiming>>) -> Response {
-    let uuid = Uuid::new_v4();
+pub async fn uuid_handler(
+    Query(params): Query<UuidParams>,
+    timing: Option<Extension<RequestTiming>>,
+) -> Response {
+    let version = match UuidVersion::parse(params.version.as_deref()) {
+        Ok(version) => version,
+        Err(message) => return format_error_response(StatusCode::BAD_REQUEST, &message),
+    };
+    let uuid = version.generate();
     let duration_ms = timing.map(|t| t.elapsed_ms());
     format_json_response_with_timing(json!({"uuid": uuid.to_string()}), duration_ms)
 }
 
+// Handler for /uuid/:count
+/// Returns `count` generated UUIDs as a JSON array.
+///
+/// v4 by default, or v7 with `?version=7`; v7 UUIDs come back in generation
+/// order, so the array is already sorted. `count` is capped at
+/// `MAX_UUID_COUNT` (1000).
+///
+/// # HTTP Method:
+/// - `GET`
+///
+/// # Responses:
+/// - `200 OK`: Returns `{ "uuids": [...] }`.
+/// - `400 Bad Request`: `count` over the cap or unsupported `version`.
+#[utoipa::path(
+    get,
+    path = "/uuid/{count}",
+    params(
+        ("count" = usize, Path, description = "Number of UUIDs to generate (max 1000)"),
+        ("version" = Option<String>, Query, description = "UUID version: `4` (random, default) or `7` (time-ordered)")
+    ),
+    responses(
+        (status = 200, description = "Returns `count` generated UUIDs as `uuids`", body = serde_json::Value),
+        (status = 400, description = "count exceeds the maximum or unsupported version")
+    )
+)]
+pub async fn uuid_bulk_handler(
+    Path(count): Path<usize>,
+    Query(params): Query<UuidParams>,
+    timing: Option<Extension<RequestTiming>>,
+) -> Response {
+    if count > MAX_UUID_COUNT {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("count={count} exceeds maximum of {MAX_UUID_COUNT}"),
+        );
+    }
+    let version = match UuidVersion::parse(params.version.as_deref()) {
+        Ok(version) => version,
+        Err(message) => return format_error_response(StatusCode::BAD_REQUEST, &message),
+    };
+    let uuids: Vec<String> = (0..count).map(|_| version.generate().to_string()).collect();
+    let duration_ms = timing.map(|t| t.elapsed_ms());
+    format_json_response_with_timing(json!({ "uuids": uuids }), duration_ms)
+}
+
 // Handler for /ip
-/// Returns the client's IP address.
+/// Returns the client's IP address and the proxies the request came through.
 ///
-/// Extracts the client IP from request headers. Checks X-Forwarded-For first
-/// (for proxied requests), then X-Real-IP as a fallback.
+/// With `trust_proxy` (the default), the client is the left-most
+/// X-Forwarded-For entry, or X-Real-IP when there is no X-Forwarded-For;
+/// otherwise it is the socket peer address. `via` lists the hops after the
+/// client, ending with the socket peer.
 ///
 /// # HTTP Method:
 /// - `GET`
 ///
 /// # Responses:
-/// - `200 OK`: Returns a JSON object containing the client's origin IP.
+/// - `200 OK`: Returns a JSON object containing the client's `origin` IP and
+///   the `via` list.
 #[utoipa::path(
     get,
     path = "/ip",
     responses(
-        (status = 200, description = "Returns the client's IP address", body = serde_json::Value)
+        (status = 200, description = "Returns the client's IP address as `origin` and the proxy hops after it as `via` (forwarding headers are only believed with `trust_proxy`)", body = serde_json::Value)
     )
 )]
 pub async fn ip_handler(
     headers: HeaderMap,
     connect_info: Option<axum::extract::ConnectInfo<std::net::SocketAddr>>,
     timing: Option<Extension<RequestTiming>>,
+    trust_proxy: Option<Extension<TrustProxy>>,
 ) -> Response {
-    // Try X-Forwarded-For first (common for proxied requests)
-    let origin = headers
-        .get("x-forwarded-for")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.split(',').next().unwrap_or(s).trim().to_string())
-        // Fall back to X-Real-IP
-        .or_else(|| {
-            headers
-                .get("x-real-ip")
-                .and_then(|v| v.to_str().ok())
-                .map(|s| s.to_string())
-        })
-        // Fall back to the actual peer address from the TCP connection
-        .or_else(|| connect_info.map(|ci| ci.0.ip().to_string()))
-        // Last resort if the server wasn't bound with ConnectInfo
-        .unwrap_or_else(|| "unknown".to_string());
-
+    let Extension(TrustProxy(trust_proxy)) = trust_proxy.unwrap_or_default();
+    let address = client_address(&headers, connect_info, trust_proxy);
     let duration_ms = timing.map(|t| t.elapsed_ms());
-    format_json_response_with_timing(json!({"origin": origin}), duration_ms)
+    format_response(
+        json!({"origin": address.origin, "via": address.via}),
+        duration_ms,
+        &headers,
+    )
 }
 
 // Handler for /user-agent
 /// Returns the User-Agent header from the request.
 ///
 /// Extracts and returns the User-Agent header value. Useful for testing
-/// what user agent string your client is sending.
+/// what user agent string your client is sending. A request without the
+/// header gets JSON `null`, so "no User-Agent" and an empty one differ; a
+/// non-UTF-8 value is echoed as `{"base64": ...}` like in `/headers`.
 ///
 /// # HTTP Method:
 /// - `GET`
 ///
 /// # Responses:
-/// - `200 OK`: Returns a JSON object containing the User-Agent string.
+/// - `200 OK`: Returns a JSON object containing the User-Agent string, or `null`.
 #[utoipa::path(
     get,
     path = "/user-agent",
     responses(
-        (status = 200, description = "Returns the User-Agent header", body = serde_json::Value)
+        (status = 200, description = "Returns the User-Agent header (`null` when absent)", body = serde_json::Value)
     )
 )]
 pub async fn user_agent_handler(
@@ -737,27 +1667,27 @@ pub async fn user_agent_handler(
 ) -> Response {
     let user_agent = headers
         .get(axum::http::header::USER_AGENT)
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("")
-        .to_string();
+        .map_or(serde_json::Value::Null, header_value_to_json);
 
     let duration_ms = timing.map(|t| t.elapsed_ms());
-    format_json_response_with_timing(json!({"user-agent": user_agent}), duration_ms)
+    format_response(json!({"user-agent": user_agent}), duration_ms, &headers)
 }
 
 // Handler for /headers
 /// Returns all request headers as a JSON object.
 ///
 /// Useful for debugging what headers are being sent by the client,
-/// including auth tokens, proxy headers, and custom headers.
+/// including auth tokens, proxy headers, and custom headers. Names are
+/// lowercase (as HTTP/2 sends them); a header sent more than once is an array
+/// (see [`headers_to_json`]). The request body, if any, is ignored.
 ///
 /// # HTTP Method:
-/// - `GET`
+/// - Any (`GET`, `POST`, `PUT`, `PATCH`, `DELETE`, `OPTIONS`, `HEAD`)
 ///
 /// # Responses:
 /// - `200 OK`: Returns a JSON object containing all request headers.
 #[utoipa::path(
-    get,
+    get, post, put, patch, delete, options, head,
     path = "/headers",
     responses(
         (status = 200, description = "Returns all request headers", body = serde_json::Value)
@@ -768,7 +1698,11 @@ pub async fn headers_handler(
     timing: Option<Extension<RequestTiming>>,
 ) -> Response {
     let duration_ms = timing.map(|t| t.elapsed_ms());
-    format_json_response_with_timing(json!({"headers": serialize_headers(&headers)}), duration_ms)
+    format_response(
+        json!({"headers": headers_to_json(&headers)}),
+        duration_ms,
+        &headers,
+    )
 }
 
 // From post.rs
@@ -799,6 +1733,7 @@ pub async fn post_handler(
     version: axum::http::Version,
     headers: HeaderMap,
     timing: Option<Extension<RequestTiming>>,
+    request_id: Option<Extension<RequestId>>,
     body: Result<Json<serde_json::Value>, axum::extract::rejection::JsonRejection>,
 ) -> impl IntoResponse {
     match body {
@@ -806,11 +1741,15 @@ pub async fn post_handler(
             let response_payload = json!({
                 "method": "POST",
                 "http_version": http_version_str(version),
-                "headers": serialize_headers(&headers),
+                "headers": headers_to_json(&headers),
                 "body": payload_value,
             });
             let duration_ms = timing.map(|t| t.elapsed_ms());
-            format_json_response_with_timing(response_payload, duration_ms)
+            format_response(
+                with_request_id(response_payload, request_id),
+                duration_ms,
+                &headers,
+            )
         }
         Err(_) => format_error_response(StatusCode::BAD_REQUEST, "Invalid JSON payload"),
     }
@@ -844,6 +1783,7 @@ pub async fn put_handler(
     version: axum::http::Version,
     headers: HeaderMap,
     timing: Option<Extension<RequestTiming>>,
+    request_id: Option<Extension<RequestId>>,
     body: Result<Json<Payload>, axum::extract::rejection::JsonRejection>,
 ) -> impl IntoResponse {
     match body {
@@ -851,11 +1791,11 @@ pub async fn put_handler(
             let payload = json!({
                 "method": "PUT",
                 "http_version": http_version_str(version),
-                "headers": serialize_headers(&headers),
+                "headers": headers_to_json(&headers),
                 "body": body_json,
             });
             let duration_ms = timing.map(|t| t.elapsed_ms());
-            format_json_response_with_timing(payload, duration_ms)
+            format_response(with_request_id(payload, request_id), duration_ms, &headers)
         }
         Err(_) => format_error_response(StatusCode::BAD_REQUEST, "Invalid JSON payload"),
     }
@@ -889,6 +1829,7 @@ pub async fn patch_handler(
     version: axum::http::Version,
     headers: HeaderMap,
     timing: Option<Extension<RequestTiming>>,
+    request_id: Option<Extension<RequestId>>,
     body: Result<Json<Payload>, axum::extract::rejection::JsonRejection>,
 ) -> impl IntoResponse {
     match body {
@@ -896,11 +1837,11 @@ pub async fn patch_handler(
             let payload = json!({
                 "method": "PATCH",
                 "http_version": http_version_str(version),
-                "headers": serialize_headers(&headers),
+                "headers": headers_to_json(&headers),
                 "body": body_json,
             });
             let duration_ms = timing.map(|t| t.elapsed_ms());
-            format_json_response_with_timing(payload, duration_ms)
+            format_response(with_request_id(payload, request_id), duration_ms, &headers)
         }
         Err(_) => format_error_response(StatusCode::BAD_REQUEST, "Invalid JSON payload"),
     }
@@ -932,6 +1873,7 @@ pub async fn delete_handler(
     version: axum::http::Version,
     headers: HeaderMap,
     timing: Option<Extension<RequestTiming>>,
+    request_id: Option<Extension<RequestId>>,
     // Axum's Json extractor requires the body to be valid JSON if Content-Type: application/json is sent.
     // To make the body truly optional even with Content-Type, we'd need a custom extractor or to read the body manually.
     // For now, if Content-Type: application/json is sent, a valid JSON body (e.g. "{}") is expected or it's a rejection.
@@ -944,19 +1886,19 @@ pub async fn delete_handler(
             let payload = json!({
                 "method": "DELETE",
                 "http_version": http_version_str(version),
-                "headers": serialize_headers(&headers),
+                "headers": headers_to_json(&headers),
                 "body": body_json,
             });
-            format_json_response_with_timing(payload, duration_ms)
+            format_response(with_request_id(payload, request_id), duration_ms, &headers)
         }
         Err(_) => {
             let payload = json!({
                 "method": "DELETE",
                 "http_version": http_version_str(version),
-                "headers": serialize_headers(&headers),
+                "headers": headers_to_json(&headers),
                 "body": serde_json::Value::Null,
             });
-            format_json_response_with_timing(payload, duration_ms)
+            format_response(with_request_id(payload, request_id), duration_ms, &headers)
         }
     }
 }
@@ -965,7 +1907,8 @@ pub async fn delete_handler(
 /// Handles OPTIONS requests to `/options`.
 ///
 /// Responds with the allowed HTTP methods for this server in the `Allow` header.
-/// The body of the response is empty.
+/// The body of the response is empty. An `OPTIONS` request to any other route
+/// reports that route's own methods instead (see `server::allow_layer`).
 ///
 /// # HTTP Method:
 /// - `OPTIONS`
@@ -981,23 +1924,570 @@ pub async fn delete_handler(
     )
 )]
 pub async fn options_handler() -> impl IntoResponse {
-    Response::builder()
-        .status(StatusCode::NO_CONTENT)
-        .header(
-            axum::http::header::ALLOW,
+    (
+        StatusCode::NO_CONTENT,
+        [(
+            header::ALLOW,
             "GET, POST, PUT, PATCH, DELETE, OPTIONS, HEAD",
-        )
-        .body(axum::body::Body::empty())
-        .expect("infallible: valid status, Allow header, and empty body")
+        )],
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{http_version_str, router, wants_connection_close};
+    use super::{
+        client_address, escape_html, header_delay_ms, http_version_str, not_found_handler,
+        prefers_html, preload_targets, query_args, query_flag, router, wants_connection_close,
+        wants_stream_echo, FailMidstream, RequestLimits, SigningKey, WelcomeMessage,
+        HTTP1_MAX_HEADERS, MAX_DELAY_SECONDS, MAX_FRAGMENTS, MAX_PRELOAD_LINKS, MAX_REDIRECT_HOPS,
+        MAX_STATUS_WEIGHT, MAX_UUID_COUNT,
+    };
     use axum::body::Body;
-    use axum::http::{header::CONNECTION, Request, StatusCode, Version};
+    use axum::http::{header, header::CONNECTION, Request, StatusCode, Version};
+    use axum::Extension;
     use tower::ServiceExt;
 
+    #[test]
+    fn client_address_follows_trust_proxy() {
+        use axum::extract::ConnectInfo;
+        use axum::http::HeaderMap;
+
+        let peer = || Some(ConnectInfo("192.0.2.9:5000".parse().unwrap()));
+        let mut headers = HeaderMap::new();
+        headers.append("x-forwarded-for", "203.0.113.1, 10.0.0.1".parse().unwrap());
+        headers.append("x-forwarded-for", "10.0.0.2".parse().unwrap());
+        headers.insert("x-real-ip", "198.51.100.7".parse().unwrap());
+
+        let trusted = client_address(&headers, peer(), true);
+        assert_eq!(trusted.origin, "203.0.113.1");
+        assert_eq!(trusted.via, ["10.0.0.1", "10.0.0.2", "192.0.2.9"]);
+
+        let untrusted = client_address(&headers, peer(), false);
+        assert_eq!(untrusted.origin, "192.0.2.9");
+        assert!(untrusted.via.is_empty());
+
+        // X-Real-IP only counts when there is no X-Forwarded-For.
+        headers.remove("x-forwarded-for");
+        let real_ip = client_address(&headers, peer(), true);
+        assert_eq!(real_ip.origin, "198.51.100.7");
+        assert_eq!(real_ip.via, ["192.0.2.9"]);
+
+        assert_eq!(
+            client_address(&HeaderMap::new(), None, true).origin,
+            "unknown"
+        );
+    }
+
+    #[tokio::test]
+    async fn status_single_code_keeps_the_json_reason() {
+        let (status, json) = get_json("/status/418").await;
+        assert_eq!(status, StatusCode::IM_A_TEAPOT);
+        assert_eq!(
+            json,
+            serde_json::json!({"status": 418, "reason": "I'm a teapot"})
+        );
+        for bad in ["/status/abc", "/status/42", "/status/200,x"] {
+            let (status, json) = get_json(bad).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{bad}");
+            assert_eq!(json["reason"], "Bad Request", "{bad}");
+        }
+    }
+
+    #[tokio::test]
+    async fn status_custom_body_and_headers() {
+        let response = router()
+            .oneshot(
+                Request::get("/status/503?body=try%20later&header=Retry-After:5&header=X-A:1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(response.headers()["retry-after"], "5");
+        assert_eq!(response.headers()["x-a"], "1");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"try later");
+
+        for uri in [
+            "/status/204?body=hello",
+            "/status/304?body=hello",
+            "/status/204",
+        ] {
+            let response = router()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert!(response.headers().get("content-type").is_none(), "{uri}");
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert!(body.is_empty(), "{uri}");
+        }
+
+        let (status, _) = get_json("/status/200?header=no-colon").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = get_json("/status/200?delay=301").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn status_delay_waits_on_the_clock() {
+        use crate::utils::clock::{Clock, ManualClock};
+        use std::sync::Arc;
+
+        let manual = Arc::new(ManualClock::new());
+        let response = router()
+            .layer(Extension(Clock::new(manual.clone())))
+            .oneshot(
+                Request::get("/status/201?delay=3")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(manual.elapsed(), std::time::Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn status_list_picks_each_listed_code() {
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..200 {
+            let (status, _) = get_json("/status/200,500,503").await;
+            assert!(
+                [200, 500, 503].contains(&status.as_u16()),
+                "{status} is not in the list"
+            );
+            seen.insert(status);
+        }
+        assert_eq!(seen.len(), 3, "all three codes should come up in 200 tries");
+    }
+
+    #[test]
+    fn status_choices_parses_weights() {
+        use super::status_choices;
+        assert_eq!(
+            status_choices("200:3, 500"),
+            Some(vec![
+                (StatusCode::OK, 3),
+                (StatusCode::INTERNAL_SERVER_ERROR, 1)
+            ])
+        );
+        for bad in ["200:0", "200:-1", "200:1.5", "200:", "500:x", ":3"] {
+            assert_eq!(status_choices(bad), None, "{bad}");
+        }
+    }
+
+    #[tokio::test]
+    async fn status_weights_are_capped_and_cannot_overflow() {
+        use super::status_choices;
+        let max = format!("200:{MAX_STATUS_WEIGHT},500:{MAX_STATUS_WEIGHT}");
+        assert!(status_choices(&max).is_some());
+        assert_eq!(
+            status_choices(&format!("200:{}", MAX_STATUS_WEIGHT + 1)),
+            None
+        );
+        // Enough maximal weights to overflow the u32 sum.
+        let many = vec![format!("200:{MAX_STATUS_WEIGHT}"); 4_295].join(",");
+        assert_eq!(status_choices(&many), None);
+
+        for uri in [
+            "/status/200:4294967295,500:4294967295",
+            "/status/200:2147483648,500:2147483648",
+        ] {
+            assert_eq!(get_json(uri).await.0, StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn weighted_status_follows_the_weights() {
+        let (mut ok, mut errors) = (0u32, 0u32);
+        for _ in 0..2000 {
+            match get_json("/status/200:3,500:1").await.0 {
+                StatusCode::OK => ok += 1,
+                StatusCode::INTERNAL_SERVER_ERROR => errors += 1,
+                other => panic!("unexpected {other}"),
+            }
+        }
+        // Expected 1500:500. The bounds sit over four standard deviations
+        // (about 19 requests) from that on either side.
+        let ratio = f64::from(ok) / f64::from(errors);
+        assert!((2.4..3.8).contains(&ratio), "200:500 ratio was {ratio}");
+
+        let (status, json) = get_json("/status/200:0,500:1").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["reason"], "Bad Request");
+    }
+
+    #[tokio::test]
+    async fn root_serves_the_welcome_message_as_text_or_json() {
+        let send = |app: axum::Router, accept: Option<&'static str>| async move {
+            let mut request = Request::get("/");
+            if let Some(accept) = accept {
+                request = request.header(header::ACCEPT, accept);
+            }
+            let response = app
+                .oneshot(request.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let content_type = response.headers()[header::CONTENT_TYPE].clone();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (content_type, String::from_utf8(body.to_vec()).unwrap())
+        };
+
+        let (content_type, body) = send(router(), None).await;
+        assert!(content_type.to_str().unwrap().starts_with("text/plain"));
+        assert_eq!(body, "Welcome to Echo Server!\n");
+
+        let custom = || router().layer(Extension(WelcomeMessage("Staging upstream".into())));
+        let (_, body) = send(custom(), Some("text/plain")).await;
+        assert_eq!(body, "Staging upstream\n");
+
+        let (content_type, body) = send(custom(), Some("application/json")).await;
+        assert_eq!(content_type, "application/json");
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json, serde_json::json!({"message": "Staging upstream"}));
+    }
+
+    #[tokio::test]
+    async fn status_429_and_503_carry_retry_after() {
+        let retry_after = |uri: &'static str| async move {
+            let response = router()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            (
+                response.status(),
+                response
+                    .headers()
+                    .get(header::RETRY_AFTER)
+                    .map(|v| v.to_str().unwrap().to_string()),
+            )
+        };
+        assert_eq!(
+            retry_after("/status/503?retry_after=7").await,
+            (StatusCode::SERVICE_UNAVAILABLE, Some("7".into()))
+        );
+        assert_eq!(
+            retry_after("/status/429").await,
+            (StatusCode::TOO_MANY_REQUESTS, Some("1".into()))
+        );
+        assert_eq!(
+            retry_after("/status/500?retry_after=7").await,
+            (StatusCode::INTERNAL_SERVER_ERROR, None)
+        );
+        assert_eq!(
+            retry_after("/status/503?retry_after=soon").await.0,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    async fn get_json(uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = router()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    /// Reads `xml` to the end, failing on any well-formedness error, and
+    /// returns the element names in document order.
+    fn xml_element_names(xml: &str) -> Vec<String> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        reader.config_mut().check_end_names = true;
+        let mut names = Vec::new();
+        loop {
+            match reader.read_event().expect("well-formed XML") {
+                quick_xml::events::Event::Start(e) | quick_xml::events::Event::Empty(e) => {
+                    names.push(String::from_utf8(e.name().as_ref().to_vec()).unwrap())
+                }
+                quick_xml::events::Event::Eof => return names,
+                _ => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn echo_endpoints_serve_xml_when_accept_prefers_it() {
+        for uri in ["/get?tag=a&tag=b&1st=x", "/anything/sub", "/headers", "/ip"] {
+            let response = router()
+                .oneshot(
+                    Request::get(uri)
+                        .header("Accept", "application/xml")
+                        .header("X-Custom", "<&>")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{uri}");
+            assert_eq!(
+                response.headers()[axum::http::header::CONTENT_TYPE],
+                "application/xml",
+                "{uri}"
+            );
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let xml = String::from_utf8(body.to_vec()).unwrap();
+            let names = xml_element_names(&xml);
+            assert_eq!(names[0], "response", "{uri}");
+            if uri.starts_with("/get") {
+                assert!(xml.contains("<method>GET</method>"));
+                assert!(xml.contains("<x-custom>&lt;&amp;&gt;</x-custom>"));
+                assert!(xml.contains("<item>a</item>") && xml.contains("<item>b</item>"));
+                assert!(xml.contains(r#"<field name="1st">x</field>"#));
+            }
+        }
+
+        // Default stays JSON.
+        let (status, json) = get_json("/get").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["method"], "GET");
+    }
+
+    #[tokio::test]
+    async fn echo_endpoints_serve_msgpack_and_cbor_when_accepted() {
+        for (accept, content_type) in [
+            ("application/msgpack", "application/msgpack"),
+            ("application/cbor", "application/cbor"),
+        ] {
+            let response = router()
+                .oneshot(
+                    Request::post("/post")
+                        .header("Accept", accept)
+                        .header("Content-Type", "application/json")
+                        .body(Body::from(r#"{"n":1,"nested":{"ok":true}}"#))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{accept}");
+            assert_eq!(
+                response.headers()[axum::http::header::CONTENT_TYPE],
+                content_type
+            );
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let decoded: serde_json::Value = if accept == "application/msgpack" {
+                rmp_serde::from_slice(&body).unwrap()
+            } else {
+                ciborium::from_reader(&body[..]).unwrap()
+            };
+            assert_eq!(decoded["method"], "POST", "{accept}");
+            assert_eq!(decoded["headers"]["accept"], accept, "{accept}");
+            assert_eq!(
+                decoded["body"],
+                serde_json::json!({ "n": 1, "nested": { "ok": true } }),
+                "{accept}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn user_agent_is_echoed_or_null() {
+        let (status, json) = get_json("/user-agent").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(json["user-agent"].is_null());
+        assert!(json.as_object().unwrap().contains_key("user-agent"));
+
+        let response = router()
+            .oneshot(
+                Request::get("/user-agent")
+                    .header("User-Agent", "probe/1.0")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["user-agent"], "probe/1.0");
+    }
+
+    #[tokio::test]
+    async fn headers_answers_every_method_with_lowercased_names() {
+        for method in ["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"] {
+            let response = router()
+                .oneshot(
+                    Request::builder()
+                        .method(method)
+                        .uri("/headers")
+                        .header("X-Custom-Test", "hello")
+                        .header("Accept", "text/plain")
+                        .header("Accept", "application/json")
+                        .body(Body::from("ignored"))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{method}");
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["headers"]["x-custom-test"], "hello", "{method}");
+            assert_eq!(
+                json["headers"]["accept"],
+                serde_json::json!(["text/plain", "application/json"])
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn uuid_bulk_returns_count_uuids_of_the_requested_version() {
+        let (status, json) = get_json("/uuid/25").await;
+        assert_eq!(status, StatusCode::OK);
+        let uuids = json["uuids"].as_array().unwrap();
+        assert_eq!(uuids.len(), 25);
+        for uuid in uuids {
+            let uuid = uuid::Uuid::parse_str(uuid.as_str().unwrap()).unwrap();
+            assert_eq!(uuid.get_version_num(), 4);
+        }
+
+        let (_, json) = get_json("/uuid/3?version=v7").await;
+        for uuid in json["uuids"].as_array().unwrap() {
+            let uuid = uuid::Uuid::parse_str(uuid.as_str().unwrap()).unwrap();
+            assert_eq!(uuid.get_version_num(), 7);
+        }
+
+        let (status, json) = get_json("/uuid?version=7").await;
+        assert_eq!(status, StatusCode::OK);
+        let uuid = uuid::Uuid::parse_str(json["uuid"].as_str().unwrap()).unwrap();
+        assert_eq!(uuid.get_version_num(), 7);
+
+        assert_eq!(get_json("/uuid/0").await.1["uuids"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn uuid_v7_are_in_generation_order() {
+        let (_, json) = get_json(&format!("/uuid/{MAX_UUID_COUNT}?version=7")).await;
+        let uuids: Vec<uuid::Uuid> = json["uuids"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| uuid::Uuid::parse_str(u.as_str().unwrap()).unwrap())
+            .collect();
+        assert_eq!(uuids.len(), MAX_UUID_COUNT);
+        assert!(
+            uuids.windows(2).all(|pair| pair[0] < pair[1]),
+            "v7 UUIDs must strictly increase in generation order"
+        );
+    }
+
+    #[tokio::test]
+    async fn uuid_rejects_oversized_count_and_unknown_version() {
+        let (status, _) = get_json(&format!("/uuid/{}", MAX_UUID_COUNT + 1)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, json) = get_json("/uuid/2?version=1").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("version"));
+        let (status, _) = get_json("/uuid?version=5").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn query_args_collects_repeated_keys_into_arrays() {
+        let args = query_args("foo=bar&foo=baz&foo=qux&one=1&flag&q=a+b%2Fc&&");
+        assert_eq!(args["foo"], serde_json::json!(["bar", "baz", "qux"]));
+        assert_eq!(args["one"], "1");
+        assert_eq!(args["flag"], "");
+        assert_eq!(args["q"], "a b/c");
+        assert_eq!(query_args(""), serde_json::json!({}));
+    }
+
+    #[tokio::test]
+    async fn get_echoes_args_origin_and_url() {
+        let response = router()
+            .oneshot(
+                Request::get("/get?foo=bar&foo=baz")
+                    .header("host", "example.test:8080")
+                    .header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["args"]["foo"], serde_json::json!(["bar", "baz"]));
+        assert_eq!(json["origin"], "203.0.113.7");
+        assert_eq!(json["url"], "http://example.test:8080/get?foo=bar&foo=baz");
+    }
+
+    #[tokio::test]
+    async fn root_answers_get_and_rejects_other_methods_with_allow() {
+        let response = router()
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        for method in ["POST", "PUT", "DELETE", "OPTIONS"] {
+            let response = router()
+                .oneshot(
+                    Request::builder()
+                        .method(method)
+                        .uri("/")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{method}"
+            );
+            assert_eq!(response.headers()["allow"], "GET, HEAD");
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert!(body["error"].as_str().unwrap().contains(method), "{body}");
+        }
+    }
+
+    #[tokio::test]
+    async fn unmatched_paths_get_a_json_404_with_the_path() {
+        let response = router()
+            .fallback(not_found_handler)
+            .oneshot(
+                Request::builder()
+                    .uri("/does-not-exist")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "Not Found");
+        assert_eq!(body["path"], "/does-not-exist");
+    }
+
     #[test]
     fn http_version_str_maps_known_versions() {
         assert_eq!(http_version_str(Version::HTTP_09), "HTTP/0.9");
@@ -1022,6 +2512,410 @@ mod tests {
         assert!(!wants_connection_close("connection="));
     }
 
+    #[test]
+    fn header_delay_ms_parses_and_clamps() {
+        assert_eq!(header_delay_ms("header_delay_ms=250"), Some(250));
+        assert_eq!(header_delay_ms("a=1&header_delay_ms=0"), Some(0));
+        assert_eq!(
+            header_delay_ms("header_delay_ms=999999999"),
+            Some(MAX_DELAY_SECONDS * 1000),
+            "clamped to the delay cap"
+        );
+        // First valid occurrence wins; invalid values are skipped.
+        assert_eq!(
+            header_delay_ms("header_delay_ms=x&header_delay_ms=5"),
+            Some(5)
+        );
+        assert_eq!(header_delay_ms(""), None);
+        assert_eq!(header_delay_ms("header_delay_ms=-1"), None);
+        assert_eq!(header_delay_ms("header_delay_msx=5"), None);
+    }
+
+    #[test]
+    fn preload_targets_collects_safe_values_in_order() {
+        assert_eq!(
+            preload_targets("preload=/a.css&x=1&preload=https://cdn.example/b.js"),
+            vec!["/a.css", "https://cdn.example/b.js"]
+        );
+        // Values that could break out of `<...>` are dropped.
+        assert_eq!(
+            preload_targets("preload=&preload=/a>b&preload=/a,b&preload=/ok"),
+            vec!["/ok"]
+        );
+        assert!(preload_targets("preloadx=/a.css").is_empty());
+        let many = vec!["preload=/x"; MAX_PRELOAD_LINKS + 5].join("&");
+        assert_eq!(preload_targets(&many).len(), MAX_PRELOAD_LINKS);
+    }
+
+    #[test]
+    fn query_flag_detects_true_or_1() {
+        let cases = [
+            ("sign=true", "sign", true),
+            ("a=1&sign=TRUE", "sign", true),
+            ("fail_midstream=1", "fail_midstream", true),
+            ("limits=false&limits=1", "limits", true),
+            ("fragment=true&x=1", "fragment", true),
+            ("", "sign", false),
+            ("sign", "sign", false),
+            ("sign=", "sign", false),
+            ("sign=false", "sign", false),
+            ("sign=yes", "sign", false),
+            ("signx=true", "sign", false),
+            ("xsign=true", "sign", false),
+            ("fragments=true", "fragment", false),
+        ];
+        for (query, name, expected) in cases {
+            assert_eq!(query_flag(query, name), expected, "{name} in {query:?}");
+        }
+    }
+
+    #[test]
+    fn wants_stream_echo_detects_directive() {
+        assert!(wants_stream_echo("echo=stream"));
+        assert!(wants_stream_echo("a=1&echo=STREAM"));
+        assert!(!wants_stream_echo("echo=json"));
+        assert!(!wants_stream_echo("stream=echo"));
+    }
+
+    #[tokio::test]
+    async fn anything_stream_echo_answers_before_the_body_ends() {
+        use futures_util::StreamExt;
+
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<&'static [u8], std::io::Error>>(4);
+        let request_body = Body::from_stream(futures_util::stream::unfold(rx, |mut rx| async {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        }));
+        tx.send(Ok(b"first\n")).await.unwrap();
+
+        // The response arrives while the request body is still open.
+        let response = router()
+            .oneshot(
+                Request::post("/anything/up?echo=stream")
+                    .body(request_body)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            "application/octet-stream"
+        );
+        let mut chunks = response.into_body().into_data_stream();
+        let metadata = chunks.next().await.unwrap().unwrap();
+        assert_eq!(metadata.last(), Some(&b'\n'));
+        let metadata: serde_json::Value = serde_json::from_slice(&metadata).unwrap();
+        assert_eq!(metadata["method"], "POST");
+        assert_eq!(metadata["path"], "/anything/up");
+        assert!(metadata.get("body").is_none());
+        assert_eq!(chunks.next().await.unwrap().unwrap(), "first\n");
+
+        tx.send(Ok(b"\x00\xffsecond")).await.unwrap();
+        drop(tx);
+        assert_eq!(
+            chunks.next().await.unwrap().unwrap(),
+            &b"\x00\xffsecond"[..]
+        );
+        assert!(chunks.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn anything_stream_echo_is_not_held_to_the_body_limit() {
+        let app = router().layer(axum::extract::DefaultBodyLimit::max(4));
+        let send = |uri: &str| {
+            app.clone()
+                .oneshot(Request::post(uri).body(Body::from("0123456789")).unwrap())
+        };
+        let buffered = send("/anything").await.unwrap();
+        assert_eq!(buffered.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let streamed = send("/anything?echo=stream").await.unwrap();
+        assert_eq!(streamed.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(streamed.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.ends_with(b"}\n0123456789"));
+    }
+
+    #[tokio::test]
+    async fn anything_limits_reports_configured_values() {
+        let response = router()
+            .layer(Extension(RequestLimits {
+                max_body_size_bytes: 1234,
+            }))
+            .oneshot(
+                Request::get("/anything?limits=true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json["limits"],
+            serde_json::json!({
+                "max_body_size_bytes": 1234,
+                "max_headers": HTTP1_MAX_HEADERS,
+                "max_query_params": null,
+                "max_delay_seconds": MAX_DELAY_SECONDS,
+                "max_redirect_hops": MAX_REDIRECT_HOPS,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn anything_fragment_reassembles_into_the_full_body() {
+        use futures_util::StreamExt;
+
+        let response = router()
+            .oneshot(
+                Request::post("/anything?fragment=true")
+                    .body(Body::from("hello"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.headers().get("content-length").is_none());
+        assert_eq!(response.headers()["x-fragment-bytes"], "1");
+        let chunks: Vec<_> = response
+            .into_body()
+            .into_data_stream()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert!(chunks.iter().all(|chunk| chunk.len() == 1));
+
+        let body: Vec<u8> = chunks.concat();
+        assert_eq!(chunks.len(), body.len());
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["body"], "hello");
+        assert_eq!(json["query"], "fragment=true");
+    }
+
+    #[tokio::test]
+    async fn anything_fragment_caps_the_number_of_chunks() {
+        use futures_util::StreamExt;
+
+        let payload = "x".repeat(3 * MAX_FRAGMENTS);
+        let response = router()
+            .oneshot(
+                Request::post("/anything?fragment=true")
+                    .body(Body::from(payload.clone()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let chunks: Vec<_> = response
+            .into_body()
+            .into_data_stream()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert!(chunks.len() <= MAX_FRAGMENTS);
+        let json: serde_json::Value = serde_json::from_slice(&chunks.concat()).unwrap();
+        assert_eq!(json["body"], payload);
+    }
+
+    #[tokio::test]
+    async fn anything_fail_midstream_sends_half_the_body_then_errors() {
+        use futures_util::StreamExt;
+
+        let response = router()
+            .layer(Extension(FailMidstream))
+            .oneshot(
+                Request::get("/anything?fail_midstream=true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.headers().get("content-length").is_none());
+        let mut stream = response.into_body().into_data_stream();
+        let first = stream.next().await.unwrap().unwrap();
+        assert!(first.starts_with(b"{"));
+        assert!(serde_json::from_slice::<serde_json::Value>(&first).is_err());
+        assert!(stream.next().await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn anything_sign_signs_body_with_configured_key() {
+        let key = SigningKey::new("test-secret").unwrap();
+        let app = router().layer(Extension(key.clone()));
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/anything?sign=true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let signature = response.headers()["x-signature"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(signature, key.sign(&body));
+
+        // Not asked to sign: no header.
+        let response = app
+            .oneshot(Request::get("/anything").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(response.headers().get("x-signature").is_none());
+    }
+
+    #[tokio::test]
+    async fn anything_sign_without_secret_reports_error() {
+        let response = router()
+            .oneshot(
+                Request::get("/anything?sign=true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.headers().get("x-signature").is_none());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["signature_error"], "no signing_secret is configured");
+    }
+
+    #[tokio::test]
+    async fn anything_reports_encoded_and_decoded_path() {
+        let response = router()
+            .oneshot(
+                Request::get("/anything/a%2Fb/c%20d/%FF")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        // The encoded slash is not merged into a separator.
+        assert_eq!(json["path"], "/anything/a%2Fb/c%20d/%FF");
+        assert_eq!(json["decoded_path"], "/anything/a/b/c d/\u{FFFD}");
+    }
+
+    #[tokio::test]
+    async fn anything_raw_path_omits_scheme_and_authority() {
+        // HTTP/2 requests arrive with an absolute URI.
+        let (_, json) = get_json("http://example.com/anything/a%2Fb?q=1").await;
+        assert_eq!(json["raw_path"], "/anything/a%2Fb?q=1");
+    }
+
+    #[tokio::test]
+    async fn anything_sends_link_preload_hints() {
+        let response = router()
+            .oneshot(
+                Request::get("/anything?preload=/style.css&preload=/app.js")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let links: Vec<_> = response
+            .headers()
+            .get_all(axum::http::header::LINK)
+            .iter()
+            .map(|v| v.to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            links,
+            ["</style.css>; rel=preload", "</app.js>; rel=preload"]
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["preload"]["links"][0], "/style.css");
+        assert_eq!(body["preload"]["server_push"], false);
+        assert_eq!(body["preload"]["early_hints_103"], false);
+    }
+
+    fn accept(value: &str) -> axum::http::HeaderMap {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(axum::http::header::ACCEPT, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn prefers_html_follows_accept() {
+        // Typical browser Accept header.
+        assert!(prefers_html(&accept(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+        )));
+        assert!(prefers_html(&accept(
+            "text/html;q=0.9, application/json;q=0.5"
+        )));
+        assert!(prefers_html(&accept("text/html, application/json")));
+        assert!(!prefers_html(&accept("application/json, text/html")));
+        assert!(!prefers_html(&accept("application/json")));
+        assert!(!prefers_html(&accept("text/html;q=0.5, application/json")));
+        assert!(!prefers_html(&accept("text/html;q=0")));
+        assert!(!prefers_html(&accept("*/*")));
+        assert!(!prefers_html(&axum::http::HeaderMap::new()));
+    }
+
+    #[test]
+    fn escape_html_escapes_markup() {
+        assert_eq!(
+            escape_html(r#"<a href="x">&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[tokio::test]
+    async fn endpoints_negotiates_html_and_json() {
+        let html = router()
+            .oneshot(
+                Request::get("/endpoints")
+                    .header("accept", "text/html,application/xhtml+xml,*/*;q=0.8")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(html.status(), StatusCode::OK);
+        assert_eq!(
+            html.headers().get("content-type").unwrap(),
+            "text/html; charset=utf-8"
+        );
+        let body = axum::body::to_bytes(html.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.starts_with("<!DOCTYPE html>"));
+        assert!(text.contains("<td><code>/get</code></td>"));
+
+        for accept in [None, Some("application/json"), Some("*/*")] {
+            let mut req = Request::get("/endpoints");
+            if let Some(a) = accept {
+                req = req.header("accept", a);
+            }
+            let json = router()
+                .oneshot(req.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(
+                json.headers().get("content-type").unwrap(),
+                "application/json",
+                "{accept:?}"
+            );
+        }
+    }
+
     #[tokio::test]
     async fn anything_close_sets_connection_header_on_http1() {
         // oneshot requests default to HTTP/1.1, so the version guard passes.
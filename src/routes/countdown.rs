@@ -0,0 +1,293 @@
+//! Countdown endpoint — a resource that becomes available after `n` polls.
+//!
+//! `/countdown/:n` simulates a job a client has to poll: each request carrying
+//! the same `X-Client-Id` counts down by one. While polls remain the response is
+//! `202 Accepted` with `X-Countdown: <polls remaining>` and `Retry-After: 1`;
+//! the `n`-th poll returns `200 OK` with `X-Countdown: 0` and the final payload,
+//! and forgets the countdown so the next poll starts over. Useful for testing
+//! client polling and backoff loops.
+//!
+//! Countdowns are keyed on client id *and* `n`, so one client can run several
+//! at once. The store is bounded: at most `MAX_COUNTDOWN_CLIENTS` countdowns are
+//! tracked, and one left idle for `COUNTDOWN_IDLE_SECS` is forgotten.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use serde_json::json;
+
+use crate::utils::constants::{
+    COUNTDOWN_IDLE_SECS, MAX_COUNTDOWN_CLIENTS, MAX_COUNTDOWN_CLIENT_ID_BYTES, MAX_COUNTDOWN_POLLS,
+};
+use crate::utils::error_response::format_error_response;
+use crate::utils::json_response::format_json_response;
+
+/// Request header identifying the polling client.
+const CLIENT_ID_HEADER: &str = "x-client-id";
+
+/// Response header carrying the number of polls still to go.
+const COUNTDOWN_HEADER: &str = "x-countdown";
+
+/// One in-progress countdown.
+#[derive(Debug, Clone, Copy)]
+struct Countdown {
+    /// Polls still to go before the resource is ready.
+    remaining: u64,
+    last_poll: Instant,
+}
+
+/// Bounded store of in-progress countdowns, keyed on `(client id, n)`.
+#[derive(Debug, Default)]
+pub struct CountdownStore {
+    countdowns: Mutex<HashMap<(String, u64), Countdown>>,
+}
+
+impl CountdownStore {
+    /// Records a poll by `client_id` of `/countdown/n` and returns the number
+    /// of polls remaining after it; 0 means the resource is ready, and the
+    /// countdown is forgotten.
+    pub fn poll(&self, client_id: &str, n: u64) -> u64 {
+        self.poll_at(client_id, n, Instant::now())
+    }
+
+    /// [`poll`](Self::poll) with an injectable clock, for tests.
+    fn poll_at(&self, client_id: &str, n: u64, now: Instant) -> u64 {
+        let mut countdowns = self
+            .countdowns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let key = (client_id.to_string(), n);
+        let idle = Duration::from_secs(COUNTDOWN_IDLE_SECS);
+
+        // A countdown idle for too long starts over, as if it had been evicted.
+        let remaining = match countdowns.get(&key) {
+            Some(c) if now.saturating_duration_since(c.last_poll) < idle => c.remaining,
+            _ => n,
+        }
+        .saturating_sub(1);
+
+        if remaining == 0 {
+            countdowns.remove(&key);
+            return 0;
+        }
+        if countdowns.len() >= MAX_COUNTDOWN_CLIENTS && !countdowns.contains_key(&key) {
+            Self::evict(&mut countdowns, now, idle);
+        }
+        countdowns.insert(
+            key,
+            Countdown {
+                remaining,
+                last_poll: now,
+            },
+        );
+        remaining
+    }
+
+    /// Drops idle countdowns; if none were idle, drops the least recently
+    /// polled one, so there is room for a new entry.
+    fn evict(countdowns: &mut HashMap<(String, u64), Countdown>, now: Instant, idle: Duration) {
+        countdowns.retain(|_, c| now.saturating_duration_since(c.last_poll) < idle);
+        if countdowns.len() >= MAX_COUNTDOWN_CLIENTS {
+            let oldest = countdowns
+                .iter()
+                .min_by_key(|(_, c)| c.last_poll)
+                .map(|(key, _)| key.clone());
+            if let Some(key) = oldest {
+                countdowns.remove(&key);
+            }
+        }
+    }
+
+    /// Number of countdowns currently tracked.
+    pub fn len(&self) -> usize {
+        self.countdowns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Whether no countdowns are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Counts down one poll for the client named by `X-Client-Id`.
+///
+/// Returns `202 Accepted` with `X-Countdown: <remaining>` and `Retry-After: 1`
+/// while polls remain, then `200 OK` with `X-Countdown: 0` on the `n`-th poll.
+/// `n = 0` is ready immediately. A missing, blank, or overlong `X-Client-Id`,
+/// or `n` above `MAX_COUNTDOWN_POLLS` (1000), returns 400.
+#[utoipa::path(
+    get,
+    path = "/countdown/{n}",
+    params(
+        ("n" = u64, Path, description = "Number of polls until the resource is ready (max 1000)"),
+        ("X-Client-Id" = String, Header, description = "Identifies the polling client")
+    ),
+    responses(
+        (status = 200, description = "Resource ready (X-Countdown: 0)"),
+        (status = 202, description = "Not ready yet; X-Countdown holds the polls remaining"),
+        (status = 400, description = "Missing X-Client-Id or n exceeds the maximum")
+    )
+)]
+pub async fn countdown_handler(
+    State(store): State<Arc<CountdownStore>>,
+    Path(n): Path<u64>,
+    headers: HeaderMap,
+) -> Response {
+    if n > MAX_COUNTDOWN_POLLS {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("Countdown of {n} polls exceeds maximum of {MAX_COUNTDOWN_POLLS}"),
+        );
+    }
+    let client_id = headers
+        .get(CLIENT_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty());
+    let Some(client_id) = client_id else {
+        return format_error_response(StatusCode::BAD_REQUEST, "X-Client-Id header is required");
+    };
+    if client_id.len() > MAX_COUNTDOWN_CLIENT_ID_BYTES {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("X-Client-Id exceeds {MAX_COUNTDOWN_CLIENT_ID_BYTES} bytes"),
+        );
+    }
+
+    let remaining = if n == 0 { 0 } else { store.poll(client_id, n) };
+    let complete = remaining == 0;
+    let body = format_json_response(json!({
+        "client_id": client_id,
+        "n": n,
+        "countdown": remaining,
+        "complete": complete,
+    }));
+
+    if complete {
+        ([(COUNTDOWN_HEADER, HeaderValue::from(0u64))], body).into_response()
+    } else {
+        (
+            StatusCode::ACCEPTED,
+            [
+                (COUNTDOWN_HEADER, HeaderValue::from(remaining)),
+                (header::RETRY_AFTER.as_str(), HeaderValue::from_static("1")),
+            ],
+            body,
+        )
+            .into_response()
+    }
+}
+
+/// Creates and returns the Axum router for the countdown endpoint, with its
+/// own countdown store.
+pub fn router() -> Router {
+    Router::new().route(
+        "/countdown/:n",
+        get(countdown_handler).with_state(Arc::new(CountdownStore::default())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[test]
+    fn store_counts_down_then_forgets() {
+        let store = CountdownStore::default();
+        let now = Instant::now();
+        assert_eq!(store.poll_at("a", 3, now), 2);
+        assert_eq!(store.poll_at("a", 3, now), 1);
+        // Countdowns are independent per client and per n.
+        assert_eq!(store.poll_at("b", 3, now), 2);
+        assert_eq!(store.poll_at("a", 5, now), 4);
+        assert_eq!(store.poll_at("a", 3, now), 0);
+        assert_eq!(store.len(), 2);
+        // Completed: the next poll starts over.
+        assert_eq!(store.poll_at("a", 3, now), 2);
+    }
+
+    #[test]
+    fn idle_countdown_starts_over() {
+        let store = CountdownStore::default();
+        let start = Instant::now();
+        assert_eq!(store.poll_at("a", 3, start), 2);
+        let later = start + Duration::from_secs(COUNTDOWN_IDLE_SECS);
+        assert_eq!(store.poll_at("a", 3, later), 2);
+    }
+
+    #[test]
+    fn store_is_bounded() {
+        let store = CountdownStore::default();
+        let start = Instant::now();
+        for i in 0..MAX_COUNTDOWN_CLIENTS {
+            store.poll_at(&i.to_string(), 10, start + Duration::from_millis(i as u64));
+        }
+        assert_eq!(store.len(), MAX_COUNTDOWN_CLIENTS);
+
+        let now = start + Duration::from_secs(1);
+        assert_eq!(store.poll_at("new", 10, now), 9);
+        assert_eq!(store.len(), MAX_COUNTDOWN_CLIENTS);
+        // Only the least recently polled countdown ("0") was evicted: "1" is
+        // still counting, "0" restarts.
+        assert_eq!(store.poll_at("1", 10, now), 8);
+        assert_eq!(store.poll_at("0", 10, now), 9);
+    }
+
+    async fn poll(app: &Router, path: &str, client_id: Option<&str>) -> Response {
+        let mut req = Request::get(path);
+        if let Some(id) = client_id {
+            req = req.header(CLIENT_ID_HEADER, id);
+        }
+        app.clone()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn ready_after_n_polls() {
+        let app = router();
+        for expected in ["2", "1"] {
+            let resp = poll(&app, "/countdown/3", Some("client")).await;
+            assert_eq!(resp.status(), StatusCode::ACCEPTED);
+            assert_eq!(resp.headers()[COUNTDOWN_HEADER], expected);
+            assert_eq!(resp.headers()[header::RETRY_AFTER], "1");
+        }
+        let resp = poll(&app, "/countdown/3", Some("client")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[COUNTDOWN_HEADER], "0");
+        assert!(resp.headers().get(header::RETRY_AFTER).is_none());
+
+        let resp = poll(&app, "/countdown/0", Some("client")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_client_id_and_oversized_n() {
+        let app = router();
+        let resp = poll(&app, "/countdown/3", None).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let resp = poll(&app, "/countdown/3", Some("  ")).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let long_id = "x".repeat(MAX_COUNTDOWN_CLIENT_ID_BYTES + 1);
+        let resp = poll(&app, "/countdown/3", Some(&long_id)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let path = format!("/countdown/{}", MAX_COUNTDOWN_POLLS + 1);
+        let resp = poll(&app, &path, Some("client")).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,12 +1,13 @@
 //! Delay endpoint for testing timeout handling and slow responses.
 
-use crate::utils::constants::MAX_DELAY_SECONDS;
-use axum::{http::StatusCode, response::IntoResponse, routing::any, Router};
+use crate::utils::{clock::Clock, constants::MAX_DELAY_SECONDS};
+use axum::{http::StatusCode, response::IntoResponse, routing::any, Extension, Router};
 
 /// Handles requests to the `/delay/:n` endpoint.
 ///
 /// Introduces a delay of `n` seconds before sending a response.
-/// The delay duration `n` is extracted from the path.
+/// The delay duration `n` is extracted from the path and waited out on the
+/// app's [`Clock`] (the system clock when none is layered).
 ///
 /// # Security
 ///
@@ -25,6 +26,7 @@ use axum::{http::StatusCode, response::IntoResponse, routing::any, Router};
 )]
 pub async fn delay_handler(
     axum::extract::Path(n): axum::extract::Path<u64>,
+    clock: Option<Extension<Clock>>,
     _method: axum::http::Method,
     _body: axum::body::Body,
 ) -> impl IntoResponse {
@@ -39,7 +41,8 @@ pub async fn delay_handler(
             .into_response();
     }
 
-    tokio::time::sleep(std::time::Duration::from_secs(n)).await;
+    let Extension(clock) = clock.unwrap_or_default();
+    clock.sleep(std::time::Duration::from_secs(n)).await;
     (StatusCode::OK, format!("Response delayed by {} seconds", n)).into_response()
 }
 
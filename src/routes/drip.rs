@@ -16,7 +16,7 @@
 
 use axum::{
     body::Body,
-    extract::Query,
+    extract::{Extension, Query},
     http::{header, StatusCode},
     response::Response,
     routing::get,
@@ -26,6 +26,7 @@ use futures_util::stream::{self, Stream};
 use serde::Deserialize;
 use std::time::Duration;
 
+use crate::server::shutdown::StreamingShutdown;
 use crate::utils::{
     constants::{MAX_DELAY_SECONDS, MAX_DRIP_NUMBYTES},
     error_response::format_error_response,
@@ -55,6 +56,10 @@ fn default_code() -> u16 {
 }
 
 /// Streams `numbytes` bytes of `*` over `duration` seconds.
+///
+/// When the app provides a [`StreamingShutdown`], the stream is cut once its
+/// shutdown grace runs out, and the request gets 503 if
+/// `max_streaming_responses` streams are already open.
 #[utoipa::path(
     get,
     path = "/drip",
@@ -66,10 +71,14 @@ fn default_code() -> u16 {
     ),
     responses(
         (status = 200, description = "Bytes streamed slowly", body = Vec<u8>, content_type = "application/octet-stream"),
-        (status = 400, description = "Parameter exceeds cap or invalid status code")
+        (status = 400, description = "Parameter exceeds cap or invalid status code"),
+        (status = 503, description = "Too many streaming responses in flight")
     )
 )]
-pub async fn drip_handler(Query(params): Query<DripParams>) -> Response {
+pub async fn drip_handler(
+    streaming: Option<Extension<StreamingShutdown>>,
+    Query(params): Query<DripParams>,
+) -> Response {
     if params.numbytes > MAX_DRIP_NUMBYTES {
         return format_error_response(
             StatusCode::BAD_REQUEST,
@@ -107,11 +116,20 @@ pub async fn drip_handler(Query(params): Query<DripParams>) -> Response {
         }
     };
 
-    let body = Body::from_stream(build_drip_stream(
+    let stream = build_drip_stream(
         params.numbytes,
         params.duration.saturating_mul(1000),
         Duration::from_secs(params.delay),
-    ));
+    );
+    let body = match streaming {
+        Some(Extension(streaming)) => match streaming.guard(stream) {
+            Ok(guarded) => Body::from_stream(guarded),
+            Err(e) => {
+                return format_error_response(StatusCode::SERVICE_UNAVAILABLE, &e.to_string())
+            }
+        },
+        None => Body::from_stream(stream),
+    };
 
     match Response::builder()
         .status(status)
@@ -0,0 +1,137 @@
+//! Raw request dump endpoint.
+//!
+//! `/dump` answers any method with the request reconstructed as HTTP/1.x
+//! text, `text/plain`: the request line, one `name: value` line per header in
+//! the order received, a blank line, then the body:
+//!
+//! ```text
+//! POST /dump?x=1 HTTP/1.1
+//! host: localhost:8080
+//! content-type: application/json
+//!
+//! {"a":1}
+//! ```
+//!
+//! Header names appear lowercased, as the HTTP stack normalizes them. A body
+//! that isn't valid UTF-8 is replaced by a note giving its size, so the dump
+//! stays printable.
+
+use axum::{
+    body::Bytes,
+    extract::OriginalUri,
+    http::{header, HeaderMap, HeaderValue, Method, Version},
+    response::{IntoResponse, Response},
+    routing::any,
+    Router,
+};
+
+use crate::routes::core_routes::http_version_str;
+
+/// Reconstructs the request as HTTP/1.x text.
+fn dump_request(
+    method: &Method,
+    uri: &axum::http::Uri,
+    version: Version,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> String {
+    let target = uri.path_and_query().map_or("/", |pq| pq.as_str());
+    let mut dump = format!("{method} {target} {}\r\n", http_version_str(version));
+    for (name, value) in headers {
+        dump.push_str(name.as_str());
+        dump.push_str(": ");
+        dump.push_str(&String::from_utf8_lossy(value.as_bytes()));
+        dump.push_str("\r\n");
+    }
+    dump.push_str("\r\n");
+    match std::str::from_utf8(body) {
+        Ok(text) => dump.push_str(text),
+        Err(_) => dump.push_str(&format!("[binary body omitted: {} bytes]", body.len())),
+    }
+    dump
+}
+
+/// Returns the raw request — request line, headers and body — as plain text.
+#[utoipa::path(
+    post,
+    path = "/dump",
+    request_body(content = String, description = "Any body; included verbatim unless it isn't UTF-8", content_type = "*/*"),
+    responses(
+        (status = 200, description = "The request as HTTP/1.x text: request line, headers, blank line, body", content_type = "text/plain", body = String)
+    )
+)]
+pub async fn dump_handler(
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    version: Version,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let dump = dump_request(&method, &uri, version, &headers, &body);
+    (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; charset=utf-8"),
+        )],
+        dump,
+    )
+        .into_response()
+}
+
+/// Creates and returns the Axum router for the dump endpoint.
+pub fn router() -> Router {
+    Router::new().route("/dump", any(dump_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    async fn dump(request: Request<Body>) -> (StatusCode, String) {
+        let resp = router().oneshot(request).await.unwrap();
+        let status = resp.status();
+        assert_eq!(
+            resp.headers()[header::CONTENT_TYPE],
+            "text/plain; charset=utf-8"
+        );
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn dump_has_request_line_headers_and_body() {
+        let request = Request::post("/dump?x=1")
+            .header("X-Custom", "hello")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"a":1}"#))
+            .unwrap();
+        let (status, dump) = dump(request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            dump,
+            "POST /dump?x=1 HTTP/1.1\r\n\
+             x-custom: hello\r\n\
+             content-type: application/json\r\n\
+             \r\n\
+             {\"a\":1}"
+        );
+    }
+
+    #[tokio::test]
+    async fn dump_omits_binary_bodies() {
+        let request = Request::put("/dump")
+            .body(Body::from(vec![0xff, 0xfe, 0x00]))
+            .unwrap();
+        let (_, dump) = dump(request).await;
+        assert!(dump.starts_with("PUT /dump HTTP/1.1\r\n"), "{dump}");
+        assert!(
+            dump.ends_with("\r\n\r\n[binary body omitted: 3 bytes]"),
+            "{dump}"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Pure body-reflection endpoint.
+//!
+//! `/echo` returns the request body byte-for-byte as the response body, with
+//! the request's `Content-Type` and a caller-chosen status. Unlike `/anything`,
+//! nothing is wrapped in JSON — this is the shape most mock/test setups want
+//! when they need an upstream that answers with exactly what it was sent.
+//!
+//! Query parameters (optional):
+//! - `status` — HTTP status code on the response (default 200, range 200-599)
+
+use axum::{
+    body::Bytes,
+    extract::Query,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::any,
+    Router,
+};
+use serde::Deserialize;
+
+use crate::utils::error_response::format_error_response;
+
+/// Query parameters for `/echo`.
+#[derive(Debug, Deserialize)]
+pub struct EchoParams {
+    #[serde(default = "default_status")]
+    status: u16,
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+/// Reflects the request body as the response body with a chosen status.
+///
+/// The response carries the request's `Content-Type` unchanged (omitted when
+/// the request had none). `status` must be a final status code (200-599);
+/// 1xx codes aren't valid final responses and return 400. Statuses that forbid
+/// a body (`204`, `304`) are honored, and the body is dropped by the HTTP layer.
+#[utoipa::path(
+    post,
+    path = "/echo",
+    params(
+        ("status" = Option<u16>, Query, description = "Status code on the response (default 200, range 200-599)")
+    ),
+    request_body(content = Vec<u8>, description = "Any body; reflected verbatim", content_type = "*/*"),
+    responses(
+        (status = 200, description = "The exact request body, with the request's Content-Type"),
+        (status = 400, description = "Invalid status code")
+    )
+)]
+pub async fn echo_handler(
+    Query(params): Query<EchoParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let status = match StatusCode::from_u16(params.status) {
+        Ok(s) if (200..=599).contains(&s.as_u16()) => s,
+        _ => {
+            return format_error_response(
+                StatusCode::BAD_REQUEST,
+                &format!("status={} is not between 200 and 599", params.status),
+            );
+        }
+    };
+
+    let mut response = (status, body).into_response();
+    // `Bytes` responses default to application/octet-stream; reflect the
+    // request's content type instead, or none at all if it didn't send one.
+    match headers.get(header::CONTENT_TYPE) {
+        Some(content_type) => {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, content_type.clone());
+        }
+        None => {
+            response.headers_mut().remove(header::CONTENT_TYPE);
+        }
+    }
+    response
+}
+
+/// Creates and returns the Axum router for the echo endpoint.
+pub fn router() -> Router {
+    Router::new().route("/echo", any(echo_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn send(uri: &str, content_type: Option<&str>, body: Vec<u8>) -> Response {
+        let mut req = Request::post(uri);
+        if let Some(ct) = content_type {
+            req = req.header(header::CONTENT_TYPE, ct);
+        }
+        router()
+            .oneshot(req.body(Body::from(body)).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_reflects_json_with_status() {
+        let payload = br#"{"hello": "world",  "n": 1}"#.to_vec();
+        let response = send(
+            "/echo?status=202",
+            Some("application/json"),
+            payload.clone(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        assert_eq!(body_bytes(response).await, payload);
+    }
+
+    #[tokio::test]
+    async fn test_reflects_binary_exactly() {
+        let payload: Vec<u8> = (0..=255u8).chain([0, 0xff, 0xfe]).collect();
+        let response = send(
+            "/echo?status=500",
+            Some("application/octet-stream"),
+            payload.clone(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body_bytes(response).await, payload);
+    }
+
+    #[tokio::test]
+    async fn test_defaults_to_200() {
+        let response = send("/echo", Some("text/plain"), b"plain".to_vec()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_bytes(response).await, b"plain");
+    }
+
+    #[tokio::test]
+    async fn test_no_content_type_when_request_has_none() {
+        let response = send("/echo", None, b"raw".to_vec()).await;
+        assert!(response.headers().get(header::CONTENT_TYPE).is_none());
+        assert_eq!(body_bytes(response).await, b"raw");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_status_returns_400() {
+        for uri in [
+            "/echo?status=99",
+            "/echo?status=100",
+            "/echo?status=600",
+            "/echo?status=999",
+            "/echo?status=1000",
+        ] {
+            let response = send(uri, None, Vec::new()).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+}
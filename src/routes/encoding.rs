@@ -14,14 +14,15 @@
 use std::io::Write;
 
 use axum::{
-    http::{header, HeaderMap, Method},
+    http::{header, HeaderMap, Method, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
     Router,
 };
 use flate2::{write::GzEncoder, write::ZlibEncoder, Compression};
 
-use crate::routes::core_routes::serialize_headers;
+use crate::utils::error_response::format_error_response;
+use crate::utils::headers::headers_to_json;
 
 /// Serializes the request-echo JSON (`{ "<flag>": true, "method", "headers" }`)
 /// to bytes, ready to be compressed.
@@ -32,22 +33,28 @@ fn echo_json(codec_flag: &str, method: &Method, headers: &HeaderMap) -> Vec<u8>
         "method".to_owned(),
         serde_json::Value::String(method.as_str().to_owned()),
     );
-    obj.insert("headers".to_owned(), serialize_headers(headers));
-    serde_json::to_vec(&serde_json::Value::Object(obj))
-        .expect("infallible: serializing encoding echo body")
+    obj.insert("headers".to_owned(), headers_to_json(headers));
+    serde_json::Value::Object(obj).to_string().into_bytes()
 }
 
 /// Builds the response: raw compressed `body`, `Content-Type: application/json`,
-/// and the forced `Content-Encoding`.
-fn encoded(content_encoding: &'static str, body: Vec<u8>) -> Response {
-    (
-        [
-            (header::CONTENT_TYPE, "application/json"),
-            (header::CONTENT_ENCODING, content_encoding),
-        ],
-        body,
-    )
-        .into_response()
+/// and the forced `Content-Encoding`. A compression error (not expected when
+/// writing to memory) becomes a 500.
+fn encoded(content_encoding: &'static str, body: std::io::Result<Vec<u8>>) -> Response {
+    match body {
+        Ok(body) => (
+            [
+                (header::CONTENT_TYPE, "application/json"),
+                (header::CONTENT_ENCODING, content_encoding),
+            ],
+            body,
+        )
+            .into_response(),
+        Err(e) => format_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Failed to {content_encoding}-encode response: {e}"),
+        ),
+    }
 }
 
 /// Returns a gzip-encoded JSON echo of the request (`Content-Encoding: gzip`).
@@ -59,8 +66,7 @@ fn encoded(content_encoding: &'static str, body: Vec<u8>) -> Response {
 pub async fn gzip_handler(method: Method, headers: HeaderMap) -> Response {
     let json = echo_json("gzipped", &method, &headers);
     let mut enc = GzEncoder::new(Vec::new(), Compression::default());
-    enc.write_all(&json).expect("infallible: gzip write to Vec");
-    encoded("gzip", enc.finish().expect("infallible: gzip finish"))
+    encoded("gzip", enc.write_all(&json).and_then(|()| enc.finish()))
 }
 
 /// Returns a deflate-encoded JSON echo (`Content-Encoding: deflate`).
@@ -75,9 +81,7 @@ pub async fn gzip_handler(method: Method, headers: HeaderMap) -> Response {
 pub async fn deflate_handler(method: Method, headers: HeaderMap) -> Response {
     let json = echo_json("deflated", &method, &headers);
     let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
-    enc.write_all(&json)
-        .expect("infallible: deflate write to Vec");
-    encoded("deflate", enc.finish().expect("infallible: deflate finish"))
+    encoded("deflate", enc.write_all(&json).and_then(|()| enc.finish()))
 }
 
 /// Returns a brotli-encoded JSON echo (`Content-Encoding: br`).
@@ -91,9 +95,8 @@ pub async fn brotli_handler(method: Method, headers: HeaderMap) -> Response {
     let mut compressed = Vec::new();
     let mut input = json.as_slice();
     let params = brotli::enc::BrotliEncoderParams::default();
-    brotli::BrotliCompress(&mut input, &mut compressed, &params)
-        .expect("infallible: brotli compress to Vec");
-    encoded("br", compressed)
+    let result = brotli::BrotliCompress(&mut input, &mut compressed, &params);
+    encoded("br", result.map(|_| compressed))
 }
 
 /// Creates and returns the Axum router for the forced-encoding endpoints.
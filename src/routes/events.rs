@@ -0,0 +1,208 @@
+//! Server-Sent Events endpoint — a finite counter stream.
+//!
+//! `GET /events` answers `text/event-stream` and emits `count` events, one
+//! every `interval_ms`, then ends the stream. Event `n` (1-based) carries
+//! `id: n` and `data: n`, so a client can check ordering and resume points
+//! (`Last-Event-ID`) through a gateway. Keep-alive comments are sent while
+//! the stream is idle, which matters for long intervals behind proxies with
+//! idle timeouts.
+//!
+//! Query parameters (all optional):
+//! - `count`       — events to emit (default 10, max [`MAX_SSE_EVENTS`])
+//! - `interval_ms` — milliseconds between events (default 1000, max [`MAX_SSE_INTERVAL_MS`])
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    extract::{Extension, Query},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Router,
+};
+use futures_util::stream::{self, Stream};
+use serde::Deserialize;
+
+use crate::server::shutdown::StreamingShutdown;
+use crate::utils::{
+    constants::{DEFAULT_SSE_EVENTS, DEFAULT_SSE_INTERVAL_MS, MAX_SSE_EVENTS, MAX_SSE_INTERVAL_MS},
+    error_response::format_error_response,
+};
+
+/// Query parameters for `/events`. All fields default if missing.
+#[derive(Debug, Deserialize)]
+pub struct EventsParams {
+    #[serde(default = "default_count")]
+    count: u64,
+    #[serde(default = "default_interval_ms")]
+    interval_ms: u64,
+}
+
+fn default_count() -> u64 {
+    DEFAULT_SSE_EVENTS
+}
+fn default_interval_ms() -> u64 {
+    DEFAULT_SSE_INTERVAL_MS
+}
+
+/// Streams `count` counter events, `interval_ms` apart, as Server-Sent Events.
+///
+/// The first event is sent immediately. When the app provides a
+/// [`StreamingShutdown`], the stream is cut once its shutdown grace runs out,
+/// and the request gets 503 if `max_streaming_responses` streams are already
+/// open.
+#[utoipa::path(
+    get,
+    path = "/events",
+    params(
+        ("count" = Option<u64>, Query, description = "Events to emit (default 10, max 1000)"),
+        ("interval_ms" = Option<u64>, Query, description = "Milliseconds between events (default 1000, max 300000)")
+    ),
+    responses(
+        (status = 200, description = "Counter events (`id: n`, `data: n`)", body = String, content_type = "text/event-stream"),
+        (status = 400, description = "Parameter exceeds cap"),
+        (status = 503, description = "Too many streaming responses in flight")
+    )
+)]
+pub async fn events_handler(
+    streaming: Option<Extension<StreamingShutdown>>,
+    Query(params): Query<EventsParams>,
+) -> Response {
+    if params.count > MAX_SSE_EVENTS {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "count={} exceeds maximum of {}",
+                params.count, MAX_SSE_EVENTS
+            ),
+        );
+    }
+    if params.interval_ms > MAX_SSE_INTERVAL_MS {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "interval_ms={} exceeds maximum of {}",
+                params.interval_ms, MAX_SSE_INTERVAL_MS
+            ),
+        );
+    }
+
+    let events = build_event_stream(params.count, Duration::from_millis(params.interval_ms));
+    let response = Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response();
+    let Some(Extension(streaming)) = streaming else {
+        return response;
+    };
+    // Guard the framed body, so a cut lands between events, never inside one.
+    let (parts, body) = response.into_parts();
+    match streaming.guard(body.into_data_stream()) {
+        Ok(guarded) => Response::from_parts(parts, Body::from_stream(guarded)),
+        Err(e) => format_error_response(StatusCode::SERVICE_UNAVAILABLE, &e.to_string()),
+    }
+}
+
+/// Builds the counter stream: events `1..=count`, the first immediately and
+/// each later one `interval` after the previous.
+fn build_event_stream(
+    count: u64,
+    interval: Duration,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(1u64, move |n| async move {
+        if n > count {
+            return None;
+        }
+        if n > 1 && !interval.is_zero() {
+            tokio::time::sleep(interval).await;
+        }
+        let event = Event::default().id(n.to_string()).data(n.to_string());
+        Some((Ok(event), n + 1))
+    })
+}
+
+/// Creates and returns the Axum router for the Server-Sent Events endpoint.
+pub fn router() -> Router {
+    Router::new().route("/events", get(events_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+    use axum::http::{header, Request};
+    use tower::ServiceExt;
+
+    async fn events(query: &str) -> Response {
+        router()
+            .oneshot(
+                Request::get(format!("/events?{query}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn emits_count_numbered_events_then_ends() {
+        let resp = events("count=3&interval_ms=0").await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            std::str::from_utf8(&body).unwrap(),
+            "id: 1\ndata: 1\n\nid: 2\ndata: 2\n\nid: 3\ndata: 3\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn zero_count_ends_immediately() {
+        let resp = events("count=0").await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn count_and_interval_over_cap_return_400() {
+        let resp = events(&format!("count={}", MAX_SSE_EVENTS + 1)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let resp = events(&format!("interval_ms={}", MAX_SSE_INTERVAL_MS + 1)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn events_are_spaced_by_interval() {
+        let start = tokio::time::Instant::now();
+        let resp = events("count=3&interval_ms=500").await;
+        to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_millis(1000));
+    }
+
+    #[tokio::test]
+    async fn streaming_guard_caps_open_streams() {
+        let streaming = StreamingShutdown::with_max_streams(Duration::from_secs(5), 1);
+        let app = router().layer(Extension(streaming.clone()));
+        let request = || {
+            Request::get("/events?count=2&interval_ms=60000")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let open = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(open.status(), StatusCode::OK);
+        let refused = app.oneshot(request()).await.unwrap();
+        assert_eq!(refused.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // Cancelling cuts the open stream with an error after the first event.
+        streaming.cancel();
+        assert!(to_bytes(open.into_body(), usize::MAX).await.is_err());
+    }
+}
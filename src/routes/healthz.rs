@@ -1,23 +1,227 @@
 // healthz.rs
-use axum::{http::StatusCode, response::IntoResponse, routing::get, Router};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-/// Creates and returns the Axum router for the health check endpoint.
+use axum::{
+    extract::Query,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::utils::clock::Clock;
+use crate::utils::json_response::{format_json_response, preferred_media_type};
+
+/// Whether the server is ready for traffic, as reported by `/readyz`.
 ///
-/// This router provides a simple `/healthz` endpoint that returns an HTTP 200 OK status.
+/// Starts out not ready; `run_server` calls [`set_ready`](Self::set_ready)
+/// once every listener is bound. Clones share the flag.
+#[derive(Clone, Debug, Default)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Readiness {
+    /// Creates a flag that is not ready yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the server ready.
+    pub fn set_ready(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Whether [`set_ready`](Self::set_ready) has been called.
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// When the app started, on its [`Clock`]; `/healthz` reports the uptime
+/// since.
+#[derive(Clone, Copy, Debug)]
+pub struct StartTime(pub Instant);
+
+/// Query parameters for `/healthz`.
+#[derive(Debug, Deserialize)]
+pub struct HealthzParams {
+    format: Option<String>,
+}
+
+/// Creates and returns the Axum router for the health check endpoints.
+///
+/// This router provides `/healthz` and `/livez`, which return an HTTP 200 OK
+/// status whenever the process is serving, and `/readyz`, which returns 503
+/// until the server is ready.
 pub fn router() -> Router {
-    Router::new().route("/healthz", get(healthz_handler))
+    Router::new()
+        .route("/healthz", get(healthz_handler))
+        .route("/livez", get(livez_handler))
+        .route("/readyz", get(readyz_handler))
 }
 
 /// Handles requests to the `/healthz` endpoint.
 ///
-/// Returns an HTTP 200 OK status and the plain text "OK".
+/// Returns an HTTP 200 OK status and the plain text "OK". With `?format=json`,
+/// or an `Accept` header preferring JSON, the body is
+/// `{"status": "ok", "uptime_seconds": N, "version": "..."}` instead, with the
+/// whole seconds since the [`StartTime`] (0 when mounted without one).
 #[utoipa::path(
     get,
     path = "/healthz",
+    params(
+        ("format" = Option<String>, Query, description = "`json` for a JSON body with uptime and version")
+    ),
+    responses(
+        (status = 200, description = "Health check successful", body = String),
+        (status = 200, description = "`{\"status\": \"ok\", \"uptime_seconds\": N, \"version\": ...}` with `?format=json` or Accept: application/json", content_type = "application/json", body = serde_json::Value)
+    )
+)]
+pub async fn healthz_handler(
+    Query(params): Query<HealthzParams>,
+    clock: Option<Extension<Clock>>,
+    start: Option<Extension<StartTime>>,
+    headers: HeaderMap,
+) -> Response {
+    let json = match params.format.as_deref() {
+        Some(format) => format.eq_ignore_ascii_case("json"),
+        None => preferred_media_type(&headers).as_deref() == Some("application/json"),
+    };
+    let mut response = if json {
+        let Extension(clock) = clock.unwrap_or_default();
+        let uptime = start.map_or(Duration::ZERO, |Extension(StartTime(start))| {
+            clock.elapsed_since(start)
+        });
+        format_json_response(json!({
+            "status": "ok",
+            "uptime_seconds": uptime.as_secs(),
+            "version": env!("CARGO_PKG_VERSION"),
+        }))
+    } else {
+        (StatusCode::OK, "OK").into_response()
+    };
+    response
+        .headers_mut()
+        .insert(header::VARY, HeaderValue::from_static("Accept"));
+    response
+}
+
+/// Handles requests to the `/livez` endpoint (liveness probe).
+///
+/// Returns an HTTP 200 OK status and the plain text "OK" for as long as the
+/// process can answer at all.
+#[utoipa::path(
+    get,
+    path = "/livez",
     responses(
-        (status = 200, description = "Health check successful", body = String)
+        (status = 200, description = "The process is alive", body = String)
     )
 )]
-pub async fn healthz_handler() -> impl IntoResponse {
+pub async fn livez_handler() -> impl IntoResponse {
     (StatusCode::OK, "OK")
 }
+
+/// Handles requests to the `/readyz` endpoint (readiness probe).
+///
+/// Returns 200 "OK" once the [`Readiness`] flag is set, and 503 "Not Ready"
+/// before. Mounted without a `Readiness` extension, there is nothing to wait
+/// for, so it always reports ready.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    responses(
+        (status = 200, description = "Ready for traffic", body = String),
+        (status = 503, description = "Listeners not bound yet", body = String)
+    )
+)]
+pub async fn readyz_handler(readiness: Option<Extension<Readiness>>) -> impl IntoResponse {
+    match readiness {
+        Some(Extension(readiness)) if !readiness.is_ready() => {
+            (StatusCode::SERVICE_UNAVAILABLE, "Not Ready")
+        }
+        _ => (StatusCode::OK, "OK"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn status(app: &Router, path: &str) -> StatusCode {
+        let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn readyz_is_503_until_ready() {
+        let readiness = Readiness::new();
+        let app = router().layer(Extension(readiness.clone()));
+
+        assert_eq!(
+            status(&app, "/readyz").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(status(&app, "/livez").await, StatusCode::OK);
+
+        readiness.set_ready();
+        assert_eq!(status(&app, "/readyz").await, StatusCode::OK);
+        assert_eq!(status(&app, "/livez").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn healthz_json_reports_uptime_and_version() {
+        let manual = Arc::new(crate::utils::clock::ManualClock::new());
+        let clock = Clock::new(manual.clone());
+        let app = router()
+            .layer(Extension(StartTime(clock.now())))
+            .layer(Extension(clock));
+        manual.advance(Duration::from_secs(90));
+
+        let request = Request::builder()
+            .uri("/healthz?format=json")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["uptime_seconds"], 90);
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+
+        let request = Request::builder()
+            .uri("/healthz")
+            .header(header::ACCEPT, "application/json")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["uptime_seconds"].is_u64());
+
+        let request = Request::builder()
+            .uri("/healthz")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"OK");
+    }
+
+    #[tokio::test]
+    async fn readyz_without_readiness_is_ready() {
+        assert_eq!(status(&router(), "/readyz").await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,211 @@
+//! Introspection endpoints — what the serving process sees about itself.
+//!
+//! `GET /env` returns the process environment variables whose names start
+//! with one of the allowed prefixes (`env_allow_prefixes`, `RUCHO_` by
+//! default), as `{"env": {"NAME": "value", ...}}`. Everything else is left
+//! out, so credentials passed to the container don't leak; the value of
+//! `RUCHO_SIGNING_SECRET` is masked even when its prefix is allowed.
+//!
+//! `GET /hostname` returns `{"hostname": "..."}`, the name of the host (or
+//! container) serving the request, to tell replicas apart behind a load
+//! balancer.
+//!
+//! `GET /version` returns the build: package name and version plus the git
+//! commit, build time and compiler that `build.rs` recorded.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::{response::Response, routing::get, Extension, Router};
+use serde_json::json;
+use sysinfo::System;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::utils::constants::DEFAULT_ENV_ALLOW_PREFIX;
+use crate::utils::json_response::format_json_response;
+
+/// Variables whose values `/env` masks even when their prefix is allowed.
+const MASKED_ENV_VARS: &[&str] = &["RUCHO_SIGNING_SECRET"];
+
+/// Environment variable name prefixes `/env` shows; layered on by `build_app`
+/// from `env_allow_prefixes`. [`DEFAULT_ENV_ALLOW_PREFIX`] when absent.
+#[derive(Debug, Clone)]
+pub struct EnvAllowList(pub Arc<[String]>);
+
+impl Default for EnvAllowList {
+    fn default() -> Self {
+        Self(Arc::from([DEFAULT_ENV_ALLOW_PREFIX.to_string()]))
+    }
+}
+
+/// The `vars` whose names start with one of `prefixes`, sorted by name, with
+/// [`MASKED_ENV_VARS`] values masked.
+fn allowed_env(
+    vars: impl Iterator<Item = (String, String)>,
+    prefixes: &[String],
+) -> BTreeMap<String, String> {
+    vars.filter(|(name, _)| prefixes.iter().any(|prefix| name.starts_with(prefix)))
+        .map(|(name, value)| {
+            if MASKED_ENV_VARS.contains(&name.as_str()) {
+                (name, "********".to_string())
+            } else {
+                (name, value)
+            }
+        })
+        .collect()
+}
+
+/// Returns the allow-listed process environment variables.
+///
+/// Variables whose name or value isn't valid UTF-8 are left out.
+#[utoipa::path(
+    get,
+    path = "/env",
+    responses(
+        (status = 200, description = "`{\"env\": {...}}`: environment variables whose names start with an allowed prefix (`env_allow_prefixes`, default `RUCHO_`)", body = serde_json::Value)
+    )
+)]
+pub async fn env_handler(allow: Option<Extension<EnvAllowList>>) -> Response {
+    let Extension(EnvAllowList(prefixes)) = allow.unwrap_or_default();
+    let vars = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+    format_json_response(json!({ "env": allowed_env(vars, &prefixes) }))
+}
+
+/// Returns the host name of the machine serving the request.
+///
+/// `hostname` is `null` if the OS doesn't report one.
+#[utoipa::path(
+    get,
+    path = "/hostname",
+    responses(
+        (status = 200, description = "`{\"hostname\": \"...\"}`: the serving host's name", body = serde_json::Value)
+    )
+)]
+pub async fn hostname_handler() -> Response {
+    format_json_response(json!({ "hostname": System::host_name() }))
+}
+
+/// The build time `build.rs` recorded, as RFC 3339 UTC.
+fn build_timestamp() -> Option<String> {
+    let secs = env!("RUCHO_BUILD_TIMESTAMP").parse::<i64>().ok()?;
+    OffsetDateTime::from_unix_timestamp(secs)
+        .ok()?
+        .format(&Rfc3339)
+        .ok()
+}
+
+/// Returns the name, version and build metadata of the running binary.
+///
+/// `git_commit` is `unknown` for builds made outside a git checkout without
+/// `RUCHO_GIT_COMMIT` set.
+#[utoipa::path(
+    get,
+    path = "/version",
+    responses(
+        (status = 200, description = "`name`, `version`, `git_commit`, `build_timestamp` (RFC 3339) and `rustc_version` of the running build", body = serde_json::Value)
+    )
+)]
+pub async fn version_handler() -> Response {
+    format_json_response(json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_commit": env!("RUCHO_GIT_COMMIT"),
+        "build_timestamp": build_timestamp(),
+        "rustc_version": env!("RUCHO_RUSTC_VERSION"),
+    }))
+}
+
+/// Creates and returns the Axum router for the introspection endpoints.
+pub fn router() -> Router {
+    Router::new()
+        .route("/env", get(env_handler))
+        .route("/hostname", get(hostname_handler))
+        .route("/version", get(version_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    async fn get_json(app: Router, uri: &str) -> (StatusCode, Value) {
+        let resp = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn env_shows_only_allowed_prefixes() {
+        // Names unique to this test, so parallel tests can't disturb them.
+        std::env::set_var("RUCHO_INTROSPECT_TEST_ALLOWED", "visible");
+        std::env::set_var("INTROSPECT_TEST_HIDDEN", "secret");
+
+        let (status, json) = get_json(router(), "/env").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["env"]["RUCHO_INTROSPECT_TEST_ALLOWED"], "visible");
+        assert!(json["env"].get("INTROSPECT_TEST_HIDDEN").is_none());
+        assert!(json["env"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .all(|name| name.starts_with("RUCHO_")));
+
+        let allow = EnvAllowList(Arc::from(["INTROSPECT_TEST_".to_string()]));
+        let (_, json) = get_json(router().layer(Extension(allow)), "/env").await;
+        assert_eq!(json["env"]["INTROSPECT_TEST_HIDDEN"], "secret");
+        assert!(json["env"].get("RUCHO_INTROSPECT_TEST_ALLOWED").is_none());
+    }
+
+    #[tokio::test]
+    async fn hostname_is_a_non_empty_string() {
+        let (status, json) = get_json(router(), "/hostname").await;
+        assert_eq!(status, StatusCode::OK);
+        let hostname = json["hostname"].as_str().unwrap();
+        assert!(!hostname.is_empty());
+    }
+
+    #[tokio::test]
+    async fn version_reports_the_build() {
+        let (status, json) = get_json(router(), "/version").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["name"], env!("CARGO_PKG_NAME"));
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert!(!json["git_commit"].as_str().unwrap().is_empty());
+        assert!(json["rustc_version"]
+            .as_str()
+            .unwrap()
+            .starts_with("rustc "));
+        let built = json["build_timestamp"].as_str().unwrap();
+        assert!(OffsetDateTime::parse(built, &Rfc3339).is_ok(), "{built}");
+    }
+
+    #[test]
+    fn allowed_env_masks_the_signing_secret() {
+        let vars = [
+            ("RUCHO_SIGNING_SECRET", "hunter2"),
+            ("RUCHO_LOG_LEVEL", "debug"),
+            ("AWS_SECRET_ACCESS_KEY", "abc"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+        let env = allowed_env(vars, &["RUCHO_".to_string()]);
+        assert_eq!(
+            env,
+            BTreeMap::from([
+                ("RUCHO_LOG_LEVEL".to_string(), "debug".to_string()),
+                ("RUCHO_SIGNING_SECRET".to_string(), "********".to_string()),
+            ])
+        );
+        assert!(allowed_env(std::iter::empty(), &[]).is_empty());
+    }
+}
@@ -0,0 +1,474 @@
+//! Key-value endpoint — a small in-memory store for stateful tests.
+//!
+//! `PUT /kv/:key` stores the request body (and its `Content-Type`),
+//! `GET /kv/:key` returns it byte-for-byte, and `DELETE /kv/:key` removes it.
+//! A missing key is `404`. Useful for checking what a gateway cache or proxy
+//! forwards, or for chaining requests through an upstream that remembers.
+//!
+//! `PUT /kv/:key?ttl=<seconds>` expires the entry after that long (at most
+//! `MAX_KV_TTL_SECONDS`), measured on the app's [`Clock`]. Without `ttl` an
+//! entry lives until it is deleted or evicted.
+//!
+//! The store is bounded: at most `MAX_KV_ENTRIES` keys and `MAX_KV_TOTAL_BYTES`
+//! of values are held; storing past either evicts the oldest entries. Each
+//! value is bounded by the request body limit (`max_body_size_bytes`), and one
+//! larger than `MAX_KV_TOTAL_BYTES` is rejected with `413`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::utils::clock::Clock;
+use crate::utils::constants::{
+    MAX_KV_ENTRIES, MAX_KV_KEY_BYTES, MAX_KV_TOTAL_BYTES, MAX_KV_TTL_SECONDS,
+};
+use crate::utils::error_response::format_error_response;
+use crate::utils::json_response::format_json_response;
+
+/// A stored value.
+#[derive(Debug, Clone)]
+struct KvEntry {
+    value: Bytes,
+    content_type: Option<HeaderValue>,
+    stored_at: Instant,
+    expires_at: Option<Instant>,
+}
+
+impl KvEntry {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|at| now < at)
+    }
+}
+
+/// Bounded in-memory store behind `/kv/:key`.
+#[derive(Debug, Default)]
+pub struct KvStore {
+    entries: RwLock<HashMap<String, KvEntry>>,
+}
+
+impl KvStore {
+    /// Stores `value` under `key`, expiring at `expires_at` if given, evicting
+    /// the oldest entries as needed to stay within `MAX_KV_ENTRIES` and
+    /// `MAX_KV_TOTAL_BYTES`. Returns `true` if the key was new (or had
+    /// expired), `false` if it replaced a live entry.
+    fn put(
+        &self,
+        key: &str,
+        value: Bytes,
+        content_type: Option<HeaderValue>,
+        now: Instant,
+        expires_at: Option<Instant>,
+    ) -> bool {
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        let created = !entries.remove(key).is_some_and(|entry| entry.is_live(now));
+        let full = |entries: &HashMap<String, KvEntry>| {
+            let bytes: usize = entries.values().map(|entry| entry.value.len()).sum();
+            entries.len() >= MAX_KV_ENTRIES || bytes + value.len() > MAX_KV_TOTAL_BYTES
+        };
+        if full(&entries) {
+            entries.retain(|_, entry| entry.is_live(now));
+        }
+        while full(&entries) {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+        entries.insert(
+            key.to_string(),
+            KvEntry {
+                value,
+                content_type,
+                stored_at: now,
+                expires_at,
+            },
+        );
+        created
+    }
+
+    /// The live entry under `key`, if any. An expired entry is dropped.
+    fn get(&self, key: &str, now: Instant) -> Option<KvEntry> {
+        let entry = self
+            .entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .cloned()?;
+        if entry.is_live(now) {
+            return Some(entry);
+        }
+        // Re-check under the write lock: a concurrent PUT may have replaced it.
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        if entries.get(key).is_some_and(|entry| !entry.is_live(now)) {
+            entries.remove(key);
+        }
+        None
+    }
+
+    /// Removes `key`. Returns `true` if a live entry was removed.
+    fn remove(&self, key: &str, now: Instant) -> bool {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key)
+            .is_some_and(|entry| entry.is_live(now))
+    }
+
+    /// Number of entries held, including expired ones not yet dropped.
+    pub fn len(&self) -> usize {
+        self.entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Whether the store holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Query parameters for `PUT /kv/:key`.
+#[derive(Debug, Deserialize)]
+pub struct KvPutParams {
+    /// Seconds until the entry expires.
+    ttl: Option<u64>,
+}
+
+/// A `400` for keys longer than `MAX_KV_KEY_BYTES`.
+fn key_too_long(key: &str) -> Option<Response> {
+    (key.len() > MAX_KV_KEY_BYTES).then(|| {
+        format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("Key exceeds {MAX_KV_KEY_BYTES} bytes"),
+        )
+    })
+}
+
+fn missing(key: &str) -> Response {
+    format_error_response(StatusCode::NOT_FOUND, &format!("Key {key} not found"))
+}
+
+/// Stores the request body under `key`.
+///
+/// Answers `201 Created` for a new key and `200 OK` when replacing one, with
+/// the key, stored size, and `ttl`. The request's `Content-Type` is kept and
+/// returned by `GET`. A value larger than `MAX_KV_TOTAL_BYTES` is `413`.
+#[utoipa::path(
+    put,
+    path = "/kv/{key}",
+    params(
+        ("key" = String, Path, description = "Key (max 128 bytes)"),
+        ("ttl" = Option<u64>, Query, description = "Seconds until the entry expires (max 86400)")
+    ),
+    request_body(content = String, description = "Value to store, any content type"),
+    responses(
+        (status = 201, description = "New key stored", body = serde_json::Value),
+        (status = 200, description = "Existing key replaced", body = serde_json::Value),
+        (status = 400, description = "Key too long, or ttl is 0 or over the maximum"),
+        (status = 413, description = "Value larger than the whole store (64 MiB)")
+    )
+)]
+pub async fn kv_put_handler(
+    State(store): State<Arc<KvStore>>,
+    clock: Option<Extension<Clock>>,
+    Path(key): Path<String>,
+    Query(params): Query<KvPutParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Some(response) = key_too_long(&key) {
+        return response;
+    }
+    if let Some(ttl) = params.ttl {
+        if ttl == 0 || ttl > MAX_KV_TTL_SECONDS {
+            return format_error_response(
+                StatusCode::BAD_REQUEST,
+                &format!("ttl must be between 1 and {MAX_KV_TTL_SECONDS} seconds"),
+            );
+        }
+    }
+    if body.len() > MAX_KV_TOTAL_BYTES {
+        return format_error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            &format!("Value exceeds {MAX_KV_TOTAL_BYTES} bytes"),
+        );
+    }
+    let Extension(clock) = clock.unwrap_or_default();
+    let now = clock.now();
+    let size = body.len();
+    let content_type = headers.get(header::CONTENT_TYPE).cloned();
+    let expires_at = params.ttl.map(|ttl| now + Duration::from_secs(ttl));
+    let created = store.put(&key, body, content_type, now, expires_at);
+
+    let mut response = format_json_response(json!({
+        "key": key,
+        "size": size,
+        "ttl": params.ttl,
+    }));
+    if created {
+        *response.status_mut() = StatusCode::CREATED;
+    }
+    response
+}
+
+/// Returns the value stored under `key`, or `404`.
+///
+/// The body is the stored bytes with the `Content-Type` they were stored
+/// with (`application/octet-stream` if none).
+#[utoipa::path(
+    get,
+    path = "/kv/{key}",
+    params(
+        ("key" = String, Path, description = "Key (max 128 bytes)")
+    ),
+    responses(
+        (status = 200, description = "The stored value, byte-for-byte"),
+        (status = 404, description = "No such key, or it expired"),
+        (status = 400, description = "Key too long")
+    )
+)]
+pub async fn kv_get_handler(
+    State(store): State<Arc<KvStore>>,
+    clock: Option<Extension<Clock>>,
+    Path(key): Path<String>,
+) -> Response {
+    if let Some(response) = key_too_long(&key) {
+        return response;
+    }
+    let Extension(clock) = clock.unwrap_or_default();
+    let Some(entry) = store.get(&key, clock.now()) else {
+        return missing(&key);
+    };
+    let content_type = entry
+        .content_type
+        .unwrap_or(HeaderValue::from_static("application/octet-stream"));
+    ([(header::CONTENT_TYPE, content_type)], entry.value).into_response()
+}
+
+/// Deletes the value stored under `key`: `200 OK`, or `404` if there was none.
+#[utoipa::path(
+    delete,
+    path = "/kv/{key}",
+    params(
+        ("key" = String, Path, description = "Key (max 128 bytes)")
+    ),
+    responses(
+        (status = 200, description = "Key deleted", body = serde_json::Value),
+        (status = 404, description = "No such key, or it expired"),
+        (status = 400, description = "Key too long")
+    )
+)]
+pub async fn kv_delete_handler(
+    State(store): State<Arc<KvStore>>,
+    clock: Option<Extension<Clock>>,
+    Path(key): Path<String>,
+) -> Response {
+    if let Some(response) = key_too_long(&key) {
+        return response;
+    }
+    let Extension(clock) = clock.unwrap_or_default();
+    if store.remove(&key, clock.now()) {
+        format_json_response(json!({ "key": key, "deleted": true }))
+    } else {
+        missing(&key)
+    }
+}
+
+/// Creates and returns the Axum router for the key-value endpoints, sharing
+/// one store.
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/kv/:key",
+            get(kv_get_handler)
+                .put(kv_put_handler)
+                .delete(kv_delete_handler),
+        )
+        .with_state(Arc::new(KvStore::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::ManualClock;
+    use axum::body::Body;
+    use axum::http::{Method, Request};
+    use tower::ServiceExt;
+
+    async fn send(app: &Router, method: Method, uri: &str, body: &'static str) -> Response {
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(header::CONTENT_TYPE, "text/plain")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn body_of(response: Response) -> Bytes {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn put_get_replace_delete() {
+        let app = router();
+        assert_eq!(
+            send(&app, Method::GET, "/kv/a", "").await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let response = send(&app, Method::PUT, "/kv/a", "one").await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let json: serde_json::Value = serde_json::from_slice(&body_of(response).await).unwrap();
+        assert_eq!(json, json!({"key": "a", "size": 3, "ttl": null}));
+
+        let response = send(&app, Method::GET, "/kv/a", "").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/plain");
+        assert_eq!(&body_of(response).await[..], b"one");
+
+        let response = send(&app, Method::PUT, "/kv/a", "two").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            &body_of(send(&app, Method::GET, "/kv/a", "").await).await[..],
+            b"two"
+        );
+
+        assert_eq!(
+            send(&app, Method::DELETE, "/kv/a", "").await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&app, Method::DELETE, "/kv/a", "").await.status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            send(&app, Method::GET, "/kv/a", "").await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn entries_expire_after_their_ttl() {
+        let manual = Arc::new(ManualClock::new());
+        let app = router().layer(Extension(Clock::new(manual.clone())));
+
+        let response = send(&app, Method::PUT, "/kv/t?ttl=10", "soon gone").await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        send(&app, Method::PUT, "/kv/forever", "kept").await;
+
+        manual.advance(Duration::from_secs(9));
+        assert_eq!(
+            send(&app, Method::GET, "/kv/t", "").await.status(),
+            StatusCode::OK
+        );
+        manual.advance(Duration::from_secs(1));
+        assert_eq!(
+            send(&app, Method::GET, "/kv/t", "").await.status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            send(&app, Method::DELETE, "/kv/t", "").await.status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            send(&app, Method::GET, "/kv/forever", "").await.status(),
+            StatusCode::OK
+        );
+        // Storing over an expired key counts as new.
+        assert_eq!(
+            send(&app, Method::PUT, "/kv/t", "back").await.status(),
+            StatusCode::CREATED
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_ttl_and_overlong_key() {
+        let app = router();
+        for ttl in ["0", "86401"] {
+            assert_eq!(
+                send(&app, Method::PUT, &format!("/kv/a?ttl={ttl}"), "x")
+                    .await
+                    .status(),
+                StatusCode::BAD_REQUEST
+            );
+        }
+        let key = "k".repeat(MAX_KV_KEY_BYTES + 1);
+        assert_eq!(
+            send(&app, Method::PUT, &format!("/kv/{key}"), "x")
+                .await
+                .status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn store_is_bounded() {
+        let store = KvStore::default();
+        let now = Instant::now();
+        for i in 0..MAX_KV_ENTRIES {
+            let at = now + Duration::from_millis(i as u64);
+            store.put(&i.to_string(), Bytes::new(), None, at, None);
+        }
+        let later = now + Duration::from_secs(10);
+        assert!(store.put("new", Bytes::new(), None, later, None));
+        assert_eq!(store.len(), MAX_KV_ENTRIES);
+        assert!(store.get("0", later).is_none(), "oldest entry was evicted");
+        assert!(store.get("1", later).is_some());
+    }
+
+    #[test]
+    fn store_is_bounded_in_bytes() {
+        let store = KvStore::default();
+        let now = Instant::now();
+        let quarter = Bytes::from(vec![0; MAX_KV_TOTAL_BYTES / 4]);
+        for i in 0..4 {
+            let at = now + Duration::from_millis(i);
+            store.put(&i.to_string(), quarter.clone(), None, at, None);
+        }
+        let later = now + Duration::from_secs(10);
+        assert!(store.put("new", Bytes::from_static(b"x"), None, later, None));
+        assert_eq!(store.len(), 4);
+        assert!(store.get("0", later).is_none(), "oldest entry was evicted");
+        assert!(store.get("1", later).is_some());
+        // Replacing a key frees its old value first.
+        assert!(!store.put("1", quarter.clone(), None, later, None));
+        assert_eq!(store.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_value_larger_than_the_store() {
+        let app = router().layer(axum::extract::DefaultBodyLimit::disable());
+        let response = app
+            .oneshot(
+                Request::put("/kv/big")
+                    .body(Body::from(vec![0; MAX_KV_TOTAL_BYTES + 1]))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
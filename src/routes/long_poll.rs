@@ -0,0 +1,242 @@
+//! Long-poll endpoints — hold a request open until an event fires or it times out.
+//!
+//! `GET /long-poll?timeout_ms=N` waits until someone calls
+//! `POST /long-poll/trigger`, then answers `200 OK` with the trigger's JSON
+//! body as `event`. If no trigger arrives within `timeout_ms` (default
+//! [`DEFAULT_LONG_POLL_TIMEOUT_MS`], max [`MAX_LONG_POLL_TIMEOUT_MS`]) it
+//! answers `204 No Content`. Useful for testing client long-polling loops and
+//! proxy idle timeouts on held-open requests.
+//!
+//! A trigger wakes every request waiting at that moment (a broadcast); later
+//! requests wait for the next trigger.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+
+use crate::utils::constants::{DEFAULT_LONG_POLL_TIMEOUT_MS, MAX_LONG_POLL_TIMEOUT_MS};
+use crate::utils::error_response::format_error_response;
+use crate::utils::json_response::format_json_response;
+
+/// Triggers buffered per waiter; a waiter only needs the first one.
+const TRIGGER_CHANNEL_CAPACITY: usize = 16;
+
+/// Broadcast channel connecting `/long-poll/trigger` to waiting `/long-poll`
+/// requests.
+#[derive(Debug)]
+pub struct LongPollHub {
+    events: broadcast::Sender<Value>,
+}
+
+impl Default for LongPollHub {
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(TRIGGER_CHANNEL_CAPACITY);
+        Self { events }
+    }
+}
+
+impl LongPollHub {
+    /// Wakes every waiting long-poll with `event` and returns how many there were.
+    pub fn trigger(&self, event: Value) -> usize {
+        // `send` only fails when nobody is waiting.
+        self.events.send(event).unwrap_or(0)
+    }
+}
+
+/// Query parameters for `/long-poll`.
+#[derive(Debug, Deserialize)]
+pub struct LongPollParams {
+    #[serde(default = "default_timeout_ms")]
+    timeout_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    DEFAULT_LONG_POLL_TIMEOUT_MS
+}
+
+/// Waits for a `POST /long-poll/trigger` or for `timeout_ms` to elapse.
+#[utoipa::path(
+    get,
+    path = "/long-poll",
+    params(
+        ("timeout_ms" = Option<u64>, Query, description = "How long to wait for a trigger in milliseconds (default 30000, max 300000)")
+    ),
+    responses(
+        (status = 200, description = "A trigger fired; its body is echoed as `event`", body = serde_json::Value),
+        (status = 204, description = "No trigger before the timeout"),
+        (status = 400, description = "timeout_ms exceeds the maximum")
+    )
+)]
+pub async fn long_poll_handler(
+    State(hub): State<Arc<LongPollHub>>,
+    Query(params): Query<LongPollParams>,
+) -> Response {
+    if params.timeout_ms > MAX_LONG_POLL_TIMEOUT_MS {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "timeout_ms={} exceeds maximum of {}",
+                params.timeout_ms, MAX_LONG_POLL_TIMEOUT_MS
+            ),
+        );
+    }
+
+    let started = Instant::now();
+    let mut events = hub.events.subscribe();
+    let timeout = Duration::from_millis(params.timeout_ms);
+    match tokio::time::timeout(timeout, events.recv()).await {
+        Ok(received) => {
+            // Lagging behind several triggers still means one fired.
+            let event = received.unwrap_or(Value::Null);
+            format_json_response(json!({
+                "triggered": true,
+                "event": event,
+                "waited_ms": started.elapsed().as_millis() as u64,
+            }))
+        }
+        Err(_) => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// Releases every waiting `/long-poll` request.
+///
+/// The optional JSON body is handed to the waiters as `event` (`null` when
+/// empty). Returns how many requests were released.
+#[utoipa::path(
+    post,
+    path = "/long-poll/trigger",
+    request_body(content = Option<serde_json::Value>, description = "Optional JSON event delivered to waiters"),
+    responses(
+        (status = 200, description = "Trigger sent; `waiters` is the number of requests released", body = serde_json::Value),
+        (status = 400, description = "Body is not valid JSON")
+    )
+)]
+pub async fn long_poll_trigger_handler(
+    State(hub): State<Arc<LongPollHub>>,
+    body: Bytes,
+) -> Response {
+    let event = if body.is_empty() {
+        Value::Null
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(event) => event,
+            Err(e) => {
+                return format_error_response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Trigger body must be JSON: {e}"),
+                )
+            }
+        }
+    };
+    let waiters = hub.trigger(event);
+    format_json_response(json!({ "waiters": waiters }))
+}
+
+/// Creates and returns the Axum router for the long-poll endpoints, sharing
+/// one trigger hub.
+pub fn router() -> Router {
+    let hub = Arc::new(LongPollHub::default());
+    Router::new()
+        .route("/long-poll", get(long_poll_handler))
+        .route("/long-poll/trigger", post(long_poll_trigger_handler))
+        .with_state(hub)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+        let resp = app.clone().oneshot(request).await.unwrap();
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json = serde_json::from_slice(&body).unwrap_or(Value::Null);
+        (status, json)
+    }
+
+    fn trigger(body: &'static str) -> Request<Body> {
+        Request::post("/long-poll/trigger")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn trigger_releases_waiting_request() {
+        let app = router();
+        let waiter = {
+            let app = app.clone();
+            tokio::spawn(async move {
+                let req = Request::get("/long-poll?timeout_ms=60000")
+                    .body(Body::empty())
+                    .unwrap();
+                send(&app, req).await
+            })
+        };
+
+        // Trigger until the spawned request has subscribed and is released.
+        let mut released = 0;
+        for _ in 0..100 {
+            tokio::task::yield_now().await;
+            let (status, json) = send(&app, trigger(r#"{"job":"done"}"#)).await;
+            assert_eq!(status, StatusCode::OK);
+            released = json["waiters"].as_u64().unwrap();
+            if released > 0 {
+                break;
+            }
+        }
+        assert_eq!(released, 1);
+
+        let (status, json) = waiter.await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["triggered"], true);
+        assert_eq!(json["event"], json!({"job": "done"}));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn times_out_with_no_content() {
+        let app = router();
+        let started = tokio::time::Instant::now();
+        let req = Request::get("/long-poll?timeout_ms=5000")
+            .body(Body::empty())
+            .unwrap();
+        let (status, _) = send(&app, req).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(started.elapsed(), Duration::from_millis(5000));
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_timeout_and_bad_trigger_body() {
+        let app = router();
+        let req = Request::get(format!(
+            "/long-poll?timeout_ms={}",
+            MAX_LONG_POLL_TIMEOUT_MS + 1
+        ))
+        .body(Body::empty())
+        .unwrap();
+        assert_eq!(send(&app, req).await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            send(&app, trigger("not json")).await.0,
+            StatusCode::BAD_REQUEST
+        );
+        // Nobody waiting: the trigger succeeds and releases no one.
+        let (status, json) = send(&app, trigger("")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["waiters"], 0);
+    }
+}
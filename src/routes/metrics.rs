@@ -11,13 +11,19 @@ use crate::utils::metrics::Metrics;
 /// Handler for the `/metrics` endpoint.
 ///
 /// Returns a JSON object containing:
+/// - `started_at`: Server start time as an RFC 3339 UTC timestamp
+/// - `uptime_seconds`: Whole seconds since server start
 /// - `all_time`: Total requests, successes, failures, and per-endpoint hits since server start
-/// - `last_hour`: Same metrics but only for the last 60 minutes (rolling window)
+/// - `last_hour`: Same metrics but only for the rolling window — the last 60
+///   minutes by default (`metrics_window_buckets` × `metrics_bucket_seconds`),
+///   with its span reported as `window_seconds`
 ///
 /// # Example Response
 ///
 /// ```json
 /// {
+///   "started_at": "2026-01-01T00:00:00Z",
+///   "uptime_seconds": 3600,
 ///   "all_time": {
 ///     "total_requests": 1000,
 ///     "successes": 950,
@@ -29,6 +35,7 @@ use crate::utils::metrics::Metrics;
 ///     }
 ///   },
 ///   "last_hour": {
+///     "window_seconds": 3600,
 ///     "total_requests": 100,
 ///     "successes": 95,
 ///     "failures": 5,
@@ -44,7 +51,7 @@ use crate::utils::metrics::Metrics;
     get,
     path = "/metrics",
     responses(
-        (status = 200, description = "Request statistics as JSON: `all_time` totals plus a rolling `last_hour` window, each with total/success/failure counts and per-endpoint hits. Only mounted when `metrics_enabled` is set — otherwise the route returns 404.", body = serde_json::Value)
+        (status = 200, description = "Request statistics as JSON: `started_at` / `uptime_seconds`, `all_time` totals plus a rolling `last_hour` window, each with total/success/failure counts and per-endpoint hits. Only mounted when `metrics_enabled` is set — otherwise the route returns 404.", body = serde_json::Value)
     )
 )]
 pub async fn get_metrics(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
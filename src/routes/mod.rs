@@ -3,24 +3,38 @@
 //! This module contains all the HTTP route handlers organized into submodules:
 //!
 //! - [`base64`] - Base64 decoding endpoint
-//! - [`bytes`] - Random bytes endpoint
+//! - [`batch`] - Batch endpoint executing sub-requests against the in-process router
+//! - [`bytes`] - Random bytes endpoints (buffered and streamed)
 //! - [`cache`] - Cache / conditional-request endpoints (/cache, /cache/:n)
 //! - [`content_types`] - XML and HTML document endpoints (non-JSON content types)
 //! - [`cookies`] - Cookie inspection and manipulation endpoints
+//! - [`countdown`] - Poll-until-ready countdown endpoint
 //! - [`core_routes`] - Main API endpoints (GET, POST, PUT, PATCH, DELETE, etc.)
 //! - [`delay`] - Delay endpoint for testing timeouts
+//! - [`dump`] - Raw request dump endpoint (request line, headers, body as text)
 //! - [`drip`] - Slow-streaming bytes endpoint for testing inter-byte timeouts
+//! - [`echo`] - Pure body-reflection endpoint (exact bytes, chosen status)
+//! - [`events`] - Server-Sent Events counter stream
 //! - [`encoding`] - Forced content-encoding endpoints (/gzip, /deflate, /brotli)
-//! - [`healthz`] - Health check endpoint
+//! - [`healthz`] - Health check, liveness and readiness endpoints
 //! - [`image`] - Sample image endpoint (png/jpeg/svg/webp)
+//! - [`introspect`] - Process introspection endpoints (allow-listed environment, hostname, build version)
+//! - [`kv`] - In-memory key-value store with optional TTL
+//! - [`long_poll`] - Long-poll endpoint released by a trigger or a timeout
 //! - [`metrics`] - Metrics endpoint (JSON)
 //! - [`range`] - Byte-range endpoint (partial content)
 //! - [`redirect`] - Chained redirect endpoint
+//! - [`resource`] - Deletable resource endpoint for idempotent-DELETE testing
 //! - [`response_headers`] - Echo query params as response headers
+//! - [`static_files`] - Static file serving from `static_dir` (off by default)
+//! - [`time`] - Current server time in several formats
+//! - [`ws`] - WebSocket echo endpoint
 
 /// Module for the base64 decoding endpoint (`/base64/:encoded`).
 pub mod base64;
-/// Module for the random-bytes endpoint (`/bytes/:n`).
+/// Module for the batch endpoint (`/batch`).
+pub mod batch;
+/// Module for the random-bytes endpoints (`/bytes/:n`, `/stream-bytes/:n`).
 pub mod bytes;
 /// Module for the cache / conditional-request endpoints (`/cache`, `/cache/:n`).
 pub mod cache;
@@ -30,21 +44,43 @@ pub mod content_types;
 pub mod cookies;
 /// Module for core API routes, including various HTTP method handlers and utility endpoints.
 pub mod core_routes;
+/// Module for the countdown endpoint (`/countdown/:n`).
+pub mod countdown;
 /// Module for the delay endpoint (`/delay/:n`).
 pub mod delay;
 /// Module for the slow-streaming drip endpoint (`/drip`).
 pub mod drip;
+/// Module for the raw request dump endpoint (`/dump`).
+pub mod dump;
+/// Module for the body-reflection endpoint (`/echo`).
+pub mod echo;
 /// Module for the forced content-encoding endpoints (`/gzip`, `/deflate`, `/brotli`).
 pub mod encoding;
-/// Module for the health check endpoint (`/healthz`).
+/// Module for the Server-Sent Events endpoint (`/events`).
+pub mod events;
+/// Module for the health check endpoints (`/healthz`, `/livez`, `/readyz`).
 pub mod healthz;
 /// Module for the sample-image endpoint (`/image/:format`).
 pub mod image;
+/// Module for the introspection endpoints (`/env`, `/hostname`, `/version`).
+pub mod introspect;
+/// Module for the key-value store endpoints (`/kv/:key`).
+pub mod kv;
+/// Module for the long-poll endpoints (`/long-poll`, `/long-poll/trigger`).
+pub mod long_poll;
 /// Module for the metrics endpoint (`/metrics`).
 pub mod metrics;
 /// Module for the byte-range endpoint (`/range/:n`).
 pub mod range;
 /// Module for the redirect endpoint (`/redirect/:n`).
 pub mod redirect;
+/// Module for the deletable resource endpoints (`/resource/:id`, `/admin/resource/reset`).
+pub mod resource;
 /// Module for the response-headers endpoint (`/response-headers`).
 pub mod response_headers;
+/// Module for static file serving (`static_prefix`, only when `static_dir` is set).
+pub mod static_files;
+/// Module for the server-time endpoint (`/time`).
+pub mod time;
+/// Module for the WebSocket echo endpoint (`/ws`).
+pub mod ws;
@@ -8,6 +8,8 @@
 //!
 //! Only a single range is supported (the first, if a comma-separated list is
 //! sent) — multipart/byteranges responses are out of scope for a test target.
+//! A `Range` in a unit other than `bytes` is ignored, as RFC 9110 requires for
+//! unknown range units, and the full body is served.
 
 use axum::{
     extract::Path,
@@ -27,6 +29,13 @@ fn gen_bytes(start: usize, end: usize) -> Vec<u8> {
     (start..end).map(|i| b'a' + (i % 26) as u8).collect()
 }
 
+/// The range set of a `Range` header value in the `bytes` unit, or `None` for
+/// another unit. Range units are case-insensitive (RFC 9110 §14.1).
+fn bytes_range_spec(header: &str) -> Option<&str> {
+    let (unit, spec) = header.split_once('=')?;
+    unit.trim().eq_ignore_ascii_case("bytes").then_some(spec)
+}
+
 /// Parses a `Range` header value against a resource of length `n`.
 ///
 /// Returns the satisfiable inclusive byte range `(start, end)`, or `None` if the
@@ -34,7 +43,7 @@ fn gen_bytes(start: usize, end: usize) -> Vec<u8> {
 /// `bytes=start-end`, `bytes=start-` (to end), and `bytes=-suffix` (last bytes).
 /// An end beyond the resource is clamped to `n - 1`.
 fn parse_range(header: &str, n: usize) -> Option<(usize, usize)> {
-    let spec = header.strip_prefix("bytes=")?;
+    let spec = bytes_range_spec(header)?;
     // Only the first range is honored if multiple are supplied.
     let first = spec.split(',').next()?.trim();
     let (start_s, end_s) = first.split_once('-')?;
@@ -67,7 +76,8 @@ fn parse_range(header: &str, n: usize) -> Option<(usize, usize)> {
 
 /// Returns `n` bytes of deterministic content with range-request support.
 ///
-/// Without a `Range` header: `200 OK`, full body, `Accept-Ranges: bytes`.
+/// Without a `Range` header (or with one in a unit other than `bytes`):
+/// `200 OK`, full body, `Accept-Ranges: bytes`.
 /// With a satisfiable `Range`: `206 Partial Content` + `Content-Range`.
 /// With an unsatisfiable `Range`: `416 Range Not Satisfiable` + `Content-Range: bytes */n`.
 /// `n` is capped at `MAX_BYTES_RESPONSE_SIZE` (10 MiB); larger values return 400.
@@ -95,6 +105,7 @@ pub async fn range_handler(Path(n): Path<usize>, headers: HeaderMap) -> Response
     let range = headers
         .get(header::RANGE)
         .and_then(|v| v.to_str().ok())
+        .filter(|r| bytes_range_spec(r).is_some())
         .map(str::to_owned);
 
     match range {
@@ -173,6 +184,12 @@ mod tests {
         assert_eq!(parse_range("bytes=0-9", 0), None); // empty resource
     }
 
+    #[test]
+    fn parse_accepts_the_unit_in_any_case() {
+        assert_eq!(parse_range("BYTES=0-1", 100), Some((0, 1)));
+        assert_eq!(parse_range("Bytes=-10", 100), Some((90, 99)));
+    }
+
     #[test]
     fn parse_takes_first_of_multiple_ranges() {
         assert_eq!(parse_range("bytes=0-9,20-29", 100), Some((0, 9)));
@@ -216,6 +233,16 @@ mod tests {
         assert_eq!(&body[..], b"abcde");
     }
 
+    #[tokio::test]
+    async fn uppercase_unit_gets_partial_content() {
+        let resp = get("/range/26", Some("BYTES=0-1")).await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 0-1/26"
+        );
+    }
+
     #[tokio::test]
     async fn suffix_range_returns_tail() {
         let resp = get("/range/26", Some("bytes=-3")).await;
@@ -240,6 +267,17 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn unknown_range_unit_is_ignored() {
+        let resp = get("/range/26", Some("items=0-4")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().get(header::CONTENT_RANGE).is_none());
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.len(), 26);
+    }
+
     #[tokio::test]
     async fn exceeding_max_returns_400() {
         let resp = get(&format!("/range/{}", MAX_BYTES_RESPONSE_SIZE + 1), None).await;
@@ -0,0 +1,206 @@
+//! Resource endpoint — a deletable resource for idempotent-DELETE testing.
+//!
+//! `DELETE /resource/:id` answers `200 OK` the first time an id is deleted and
+//! `404 Not Found` on every later delete of the same id, the way a real API
+//! behaves when a retried DELETE arrives after the first one succeeded. Useful
+//! for testing that clients treat the repeated 404 as success.
+//!
+//! `POST /admin/resource/reset` forgets every deletion, so each id can be
+//! deleted again. Under `/admin`, it can be guarded with
+//! `ssl_client_cert_paths`.
+//!
+//! The store is bounded: at most `MAX_DELETED_RESOURCES` deletions are
+//! remembered; past that the oldest is forgotten, and its id deletes with
+//! `200` again.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    routing::{delete, post},
+    Router,
+};
+use serde_json::json;
+
+use crate::utils::constants::{MAX_DELETED_RESOURCES, MAX_RESOURCE_ID_BYTES};
+use crate::utils::error_response::format_error_response;
+use crate::utils::json_response::format_json_response;
+
+/// Bounded record of deleted resource ids.
+#[derive(Debug, Default)]
+pub struct ResourceStore {
+    deleted: Mutex<HashMap<String, Instant>>,
+}
+
+impl ResourceStore {
+    /// Records the deletion of `id`. Returns `true` the first time, `false`
+    /// if `id` was already deleted.
+    pub fn delete(&self, id: &str) -> bool {
+        let mut deleted = self.deleted.lock().unwrap_or_else(PoisonError::into_inner);
+        if deleted.contains_key(id) {
+            return false;
+        }
+        if deleted.len() >= MAX_DELETED_RESOURCES {
+            let oldest = deleted
+                .iter()
+                .min_by_key(|(_, at)| **at)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                deleted.remove(&oldest);
+            }
+        }
+        deleted.insert(id.to_string(), Instant::now());
+        true
+    }
+
+    /// Forgets every deletion and returns how many there were.
+    pub fn reset(&self) -> usize {
+        let mut deleted = self.deleted.lock().unwrap_or_else(PoisonError::into_inner);
+        let cleared = deleted.len();
+        deleted.clear();
+        cleared
+    }
+
+    /// Number of deletions currently remembered.
+    pub fn len(&self) -> usize {
+        self.deleted
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Whether no deletions are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Deletes resource `id`: `200 OK` the first time, `404 Not Found` after.
+///
+/// An id longer than `MAX_RESOURCE_ID_BYTES` (128) returns 400.
+#[utoipa::path(
+    delete,
+    path = "/resource/{id}",
+    params(
+        ("id" = String, Path, description = "Resource id (max 128 bytes)")
+    ),
+    responses(
+        (status = 200, description = "First delete of this id", body = serde_json::Value),
+        (status = 404, description = "This id was already deleted"),
+        (status = 400, description = "id exceeds the maximum length")
+    )
+)]
+pub async fn delete_resource_handler(
+    State(store): State<Arc<ResourceStore>>,
+    Path(id): Path<String>,
+) -> Response {
+    if id.len() > MAX_RESOURCE_ID_BYTES {
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("Resource id exceeds {MAX_RESOURCE_ID_BYTES} bytes"),
+        );
+    }
+    if store.delete(&id) {
+        format_json_response(json!({ "id": id, "deleted": true }))
+    } else {
+        format_error_response(
+            StatusCode::NOT_FOUND,
+            &format!("Resource {id} was already deleted"),
+        )
+    }
+}
+
+/// Forgets every deleted resource, so each id deletes with `200` again.
+#[utoipa::path(
+    post,
+    path = "/admin/resource/reset",
+    responses(
+        (status = 200, description = "Deletions forgotten; `cleared` is how many", body = serde_json::Value)
+    )
+)]
+pub async fn reset_resources_handler(State(store): State<Arc<ResourceStore>>) -> Response {
+    format_json_response(json!({ "cleared": store.reset() }))
+}
+
+/// Creates and returns the Axum router for the resource endpoints, sharing one
+/// resource store.
+pub fn router() -> Router {
+    Router::new()
+        .route("/resource/:id", delete(delete_resource_handler))
+        .route("/admin/resource/reset", post(reset_resources_handler))
+        .with_state(Arc::new(ResourceStore::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn send(app: &Router, request: Request<Body>) -> StatusCode {
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    fn delete_req(id: &str) -> Request<Body> {
+        Request::delete(format!("/resource/{id}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn store_is_bounded() {
+        let store = ResourceStore::default();
+        for i in 0..MAX_DELETED_RESOURCES {
+            assert!(store.delete(&i.to_string()));
+        }
+        assert!(store.delete("new"));
+        assert_eq!(store.len(), MAX_DELETED_RESOURCES);
+        assert!(!store.delete("new"));
+    }
+
+    #[tokio::test]
+    async fn first_delete_is_200_then_404() {
+        let app = router();
+        assert_eq!(send(&app, delete_req("42")).await, StatusCode::OK);
+        assert_eq!(send(&app, delete_req("42")).await, StatusCode::NOT_FOUND);
+        assert_eq!(send(&app, delete_req("42")).await, StatusCode::NOT_FOUND);
+        // Other ids are independent.
+        assert_eq!(send(&app, delete_req("43")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn reset_makes_ids_deletable_again() {
+        let app = router();
+        assert_eq!(send(&app, delete_req("42")).await, StatusCode::OK);
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::post("/admin/resource/reset")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["cleared"], 1);
+
+        assert_eq!(send(&app, delete_req("42")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rejects_overlong_id() {
+        let app = router();
+        let id = "x".repeat(MAX_RESOURCE_ID_BYTES + 1);
+        assert_eq!(send(&app, delete_req(&id)).await, StatusCode::BAD_REQUEST);
+    }
+}
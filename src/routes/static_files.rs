@@ -0,0 +1,214 @@
+//! Static file serving from `static_dir`, mounted at `static_prefix`.
+//!
+//! Off unless `static_dir` is set. When it is, `GET /static/fixtures/a.json`
+//! serves `<static_dir>/fixtures/a.json` through tower-http's `ServeDir`, with
+//! a guessed `Content-Type`, `Last-Modified`, and range support.
+//!
+//! Directories aren't served, not even their `index.html`: `ServeDir` would
+//! redirect `/static/dir` to `/static/dir/`, and the trailing-slash
+//! normalization in `build_app` would strip it again, looping forever.
+//!
+//! `ServeDir` refuses paths that would leave the directory (`..`, absolute
+//! segments) without touching the filesystem. Those, and files that don't
+//! exist, get the same JSON 404 as an unknown route.
+
+use std::path::PathBuf;
+
+use axum::{handler::HandlerWithoutStateExt, Router};
+use tower_http::services::ServeDir;
+use utoipa::OpenApi;
+
+use crate::openapi::ApiDoc;
+use crate::routes::core_routes::not_found_handler;
+
+/// Where static files come from and where they're mounted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaticFiles {
+    /// Directory the files are served from (`static_dir`).
+    pub dir: PathBuf,
+    /// Path the directory is mounted at, e.g. `/static` (`static_prefix`).
+    pub prefix: String,
+}
+
+/// Routes `build_app` mounts outside the OpenAPI spec.
+const UNDOCUMENTED_ROUTES: &[&str] = &["/swagger-ui", "/api-docs/openapi.json"];
+
+/// Checks that `prefix` can be mounted: it starts with `/`, isn't the root,
+/// has no trailing `/`, holds no `:` or `*` route parameters, and doesn't
+/// overlap a built-in route (which would panic when the router is built).
+pub fn validate_prefix(prefix: &str) -> Result<(), String> {
+    if !prefix.starts_with('/') || prefix == "/" || prefix.ends_with('/') {
+        return Err(format!(
+            "static_prefix '{prefix}' must start with '/', not end with '/', and not be '/'"
+        ));
+    }
+    if prefix.contains([':', '*']) {
+        return Err(format!(
+            "static_prefix '{prefix}' must not contain ':' or '*'"
+        ));
+    }
+    let openapi = ApiDoc::openapi();
+    let routes = openapi
+        .paths
+        .paths
+        .keys()
+        .map(String::as_str)
+        .chain(UNDOCUMENTED_ROUTES.iter().copied());
+    for route in routes {
+        if overlaps(prefix, route) {
+            return Err(format!(
+                "static_prefix '{prefix}' overlaps the built-in route '{route}'"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether mounting under `prefix` would overlap `route`: one's segments
+/// start the other's, with a `{param}` segment matching anything. The root
+/// route `/` overlaps nothing.
+fn overlaps(prefix: &str, route: &str) -> bool {
+    let route = route.trim_start_matches('/');
+    if route.is_empty() {
+        return false;
+    }
+    prefix
+        .trim_start_matches('/')
+        .split('/')
+        .zip(route.split('/'))
+        .all(|(ours, theirs)| ours == theirs || theirs.starts_with('{'))
+}
+
+/// Creates the router serving `static_files.dir` under `static_files.prefix`.
+pub fn router(static_files: &StaticFiles) -> Router {
+    let serve_dir = ServeDir::new(&static_files.dir)
+        .append_index_html_on_directories(false)
+        .not_found_service(not_found_handler.into_service());
+    Router::new().nest_service(&static_files.prefix, serve_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        extract::Request,
+        http::{header, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    fn serve(dir: &tempfile::TempDir) -> Router {
+        router(&StaticFiles {
+            dir: dir.path().to_path_buf(),
+            prefix: "/static".to_string(),
+        })
+    }
+
+    async fn get(app: Router, uri: &str) -> (StatusCode, Option<String>, String) {
+        let response = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            content_type,
+            String::from_utf8_lossy(&body).into_owned(),
+        )
+    }
+
+    #[tokio::test]
+    async fn serves_files_under_the_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("fixtures")).unwrap();
+        std::fs::write(dir.path().join("fixtures/user.json"), r#"{"id":1}"#).unwrap();
+
+        let (status, content_type, body) = get(serve(&dir), "/static/fixtures/user.json").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("application/json"));
+        assert_eq!(body, r#"{"id":1}"#);
+    }
+
+    #[tokio::test]
+    async fn missing_files_and_traversal_get_the_json_404() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("secret.txt"), "top secret").unwrap();
+        let served = root.path().join("public");
+        std::fs::create_dir(&served).unwrap();
+        let app = router(&StaticFiles {
+            dir: served,
+            prefix: "/static".to_string(),
+        });
+
+        for uri in [
+            "/static/nope.txt",
+            "/static",
+            "/static/",
+            "/static/../secret.txt",
+            "/static/%2e%2e/secret.txt",
+            "/static/..%2fsecret.txt",
+        ] {
+            let (status, content_type, body) = get(app.clone(), uri).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{uri}");
+            assert_eq!(content_type.as_deref(), Some("application/json"), "{uri}");
+            assert!(!body.contains("top secret"), "{uri}");
+        }
+    }
+
+    #[test]
+    fn validate_prefix_rejects_unmountable_prefixes() {
+        assert!(validate_prefix("/static").is_ok());
+        assert!(validate_prefix("/fixtures/v1").is_ok());
+        for bad in ["", "/", "static", "/static/", "/:id", "/files/*rest"] {
+            assert!(validate_prefix(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn validate_prefix_rejects_builtin_routes() {
+        assert!(validate_prefix("/admin/static").is_ok());
+        assert!(validate_prefix("/statics").is_ok());
+        for taken in [
+            "/get",
+            "/anything",
+            "/anything/files",
+            "/kv",
+            "/kv/files",
+            "/status",
+            "/long-poll",
+            "/admin/resource",
+            "/swagger-ui",
+            "/api-docs",
+        ] {
+            let error = validate_prefix(taken).unwrap_err();
+            assert!(
+                error.contains("overlaps the built-in route"),
+                "{taken}: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_allowed_prefix_mounts_without_panicking() {
+        // A sample of prefixes next to built-in routes: whatever passes
+        // validation must merge into the real app.
+        for prefix in ["/admin/static", "/statics", "/gets", "/kvs", "/cookie"] {
+            validate_prefix(prefix).unwrap();
+            let static_files = StaticFiles {
+                dir: PathBuf::from("."),
+                prefix: prefix.to_string(),
+            };
+            let _ = crate::app::build_app(crate::app::AppOptions {
+                static_files: Some(static_files),
+                ..Default::default()
+            });
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Time endpoint — the server's current time, for testing clock-skew handling.
+//!
+//! `GET /time` returns the time in every representation at once:
+//! `unix` (whole seconds), `unix_ms`, `rfc3339` and `iso8601`, all in UTC.
+//! `?format=` narrows the response to one of them. The time is read from the
+//! app's [`Clock`], so a test on a `ManualClock` sees a fixed, known time.
+
+use std::time::UNIX_EPOCH;
+
+use axum::{extract::Query, http::StatusCode, response::Response, routing::get, Extension, Router};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use time::format_description::well_known::{Iso8601, Rfc3339};
+use time::OffsetDateTime;
+
+use crate::utils::clock::Clock;
+use crate::utils::error_response::format_error_response;
+use crate::utils::json_response::format_json_response;
+
+/// Representations `/time` can return (the `?format=` values).
+const TIME_FORMATS: [&str; 4] = ["unix", "unix_ms", "rfc3339", "iso8601"];
+
+/// Query parameters for `/time`.
+#[derive(Debug, Deserialize)]
+pub struct TimeParams {
+    format: Option<String>,
+}
+
+/// Returns the current server time as `unix`, `unix_ms`, `rfc3339` and
+/// `iso8601` (UTC), or only the one named by `?format=`.
+///
+/// An unknown `format` returns 400.
+#[utoipa::path(
+    get,
+    path = "/time",
+    params(
+        ("format" = Option<String>, Query, description = "Only this representation: `unix`, `unix_ms`, `rfc3339` or `iso8601` (default: all)")
+    ),
+    responses(
+        (status = 200, description = "Current server time", body = serde_json::Value),
+        (status = 400, description = "Unknown format")
+    )
+)]
+pub async fn time_handler(
+    Query(params): Query<TimeParams>,
+    clock: Option<Extension<Clock>>,
+) -> Response {
+    let Extension(clock) = clock.unwrap_or_default();
+    let now = time_json(OffsetDateTime::from(clock.system_time()));
+    match params.format.as_deref() {
+        None => format_json_response(now),
+        Some(format) => match now.get(format) {
+            Some(value) => format_json_response(json!({ format: value })),
+            None => format_error_response(
+                StatusCode::BAD_REQUEST,
+                &format!(
+                    "Unknown time format '{format}' (supported: {})",
+                    TIME_FORMATS.join(", ")
+                ),
+            ),
+        },
+    }
+}
+
+/// Every representation of `now`, keyed by its `TIME_FORMATS` name.
+///
+/// Formatting a UTC time with a four-digit year can't fail in practice; a
+/// representation that does is left out rather than failing the request.
+fn time_json(now: OffsetDateTime) -> Value {
+    let unix_ms = (now - OffsetDateTime::from(UNIX_EPOCH)).whole_milliseconds();
+    let mut map = Map::new();
+    map.insert("unix".into(), json!(now.unix_timestamp()));
+    map.insert("unix_ms".into(), json!(unix_ms as i64));
+    if let Ok(rfc3339) = now.format(&Rfc3339) {
+        map.insert("rfc3339".into(), json!(rfc3339));
+    }
+    if let Ok(iso8601) = now.format(&Iso8601::DEFAULT) {
+        map.insert("iso8601".into(), json!(iso8601));
+    }
+    Value::Object(map)
+}
+
+/// Creates and returns the Axum router for the time endpoint.
+pub fn router() -> Router {
+    Router::new().route("/time", get(time_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::ManualClock;
+    use axum::body::Body;
+    use axum::http::Request;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+    use tower::ServiceExt;
+
+    async fn get_time(app: Router, uri: &str) -> (StatusCode, Value) {
+        let resp = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn returns_every_format_close_to_now() {
+        let (status, json) = get_time(router(), "/time").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let unix = json["unix"].as_u64().unwrap();
+        assert!(now.as_secs().abs_diff(unix) <= 2, "unix {unix} vs {now:?}");
+        let unix_ms = json["unix_ms"].as_u64().unwrap();
+        assert!((now.as_millis() as u64).abs_diff(unix_ms) <= 2_000);
+        assert_eq!(unix_ms / 1000, unix);
+
+        let rfc3339 = json["rfc3339"].as_str().unwrap();
+        let parsed = OffsetDateTime::parse(rfc3339, &Rfc3339).unwrap();
+        assert_eq!(parsed.unix_timestamp() as u64, unix);
+        let iso8601 = json["iso8601"].as_str().unwrap();
+        let parsed = OffsetDateTime::parse(iso8601, &Iso8601::DEFAULT).unwrap();
+        assert_eq!(parsed.unix_timestamp() as u64, unix);
+    }
+
+    #[tokio::test]
+    async fn format_selects_one_representation() {
+        let (status, json) = get_time(router(), "/time?format=unix_ms").await;
+        assert_eq!(status, StatusCode::OK);
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["unix_ms"]);
+
+        let (status, json) = get_time(router(), "/time?format=epoch").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("epoch"));
+    }
+
+    #[tokio::test]
+    async fn reads_the_app_clock() {
+        let manual = Arc::new(ManualClock::new());
+        let clock = Clock::new(manual.clone());
+        let app = router().layer(Extension(clock));
+        let (_, before) = get_time(app.clone(), "/time?format=unix_ms").await;
+        manual.advance(Duration::from_secs(3600));
+        let (_, after) = get_time(app, "/time?format=unix_ms").await;
+        assert_eq!(
+            after["unix_ms"].as_u64().unwrap() - before["unix_ms"].as_u64().unwrap(),
+            3_600_000
+        );
+    }
+}
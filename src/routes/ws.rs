@@ -0,0 +1,96 @@
+//! WebSocket echo endpoint.
+//!
+//! `GET /ws` upgrades to a WebSocket and sends every text and binary message
+//! straight back, so a gateway's WebSocket proxying (upgrade handling, frame
+//! forwarding, idle timeouts) can be tested against a known peer. Pings are
+//! answered with a pong carrying the same payload, and a close frame from the
+//! client is acknowledged before the socket is closed.
+//!
+//! A request that isn't a valid upgrade (no `Upgrade: websocket`, wrong
+//! method or version, or a connection that can't be upgraded, such as an
+//! HTTP/2 stream) gets the usual JSON error body.
+
+use axum::{
+    extract::ws::{rejection::WebSocketUpgradeRejection, Message, WebSocket, WebSocketUpgrade},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+
+use crate::utils::error_response::format_error_response;
+
+/// Upgrades the connection to a WebSocket that echoes every message back.
+#[utoipa::path(
+    get,
+    path = "/ws",
+    responses(
+        (status = 101, description = "Switched to a WebSocket echoing text and binary messages"),
+        (status = 400, description = "Not a valid WebSocket upgrade request"),
+        (status = 426, description = "Connection can't be upgraded")
+    )
+)]
+pub async fn ws_handler(upgrade: Result<WebSocketUpgrade, WebSocketUpgradeRejection>) -> Response {
+    match upgrade {
+        Ok(upgrade) => upgrade.on_upgrade(echo_socket).into_response(),
+        Err(rejection) => format_error_response(rejection.status(), &rejection.body_text()),
+    }
+}
+
+/// Echoes `socket` until the client closes it or the connection drops.
+async fn echo_socket(mut socket: WebSocket) {
+    while let Some(Ok(message)) = socket.recv().await {
+        let reply = match message {
+            Message::Text(_) | Message::Binary(_) => message,
+            Message::Ping(payload) => Message::Pong(payload),
+            // The close acknowledgement is queued by tungstenite and written
+            // by the next `recv`, which then ends the loop.
+            Message::Pong(_) | Message::Close(_) => continue,
+        };
+        if socket.send(reply).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Creates and returns the Axum router for the WebSocket echo endpoint.
+pub fn router() -> Router {
+    Router::new().route("/ws", get(ws_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    async fn send(request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let resp = router().oneshot(request).await.unwrap();
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn plain_get_is_rejected_with_a_json_error() {
+        let (status, json) = send(Request::get("/ws").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("upgrade"));
+    }
+
+    #[tokio::test]
+    async fn upgrade_without_an_upgradable_connection_is_426() {
+        let request = Request::get("/ws")
+            .header("Connection", "upgrade")
+            .header("Upgrade", "websocket")
+            .header("Sec-WebSocket-Version", "13")
+            .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .body(Body::empty())
+            .unwrap();
+        let (status, json) = send(request).await;
+        assert_eq!(status, StatusCode::UPGRADE_REQUIRED);
+        assert!(json["error"].is_string());
+    }
+}
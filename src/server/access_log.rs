@@ -0,0 +1,667 @@
+//! Access log middleware.
+//!
+//! With `access_log` set, [`access_log_middleware`] writes one line per
+//! request to that file (or to stdout for `-`) once the response headers are
+//! ready, in the configured [`AccessLogFormat`]:
+//!
+//! - `common`: Apache Common Log Format,
+//!   `host - - [time] "request line" status bytes`.
+//! - `combined` (the default): Common plus `"referer" "user-agent"`.
+//! - `json`: one object per line with the same fields, plus `duration_ms`
+//!   and the `X-Request-Id`.
+//!
+//! `host` is the TCP peer address. `bytes` is the body size when it's known
+//! up front; streamed bodies are logged as `-` (`null` in JSON). The access
+//! log is separate from the tracing output, so `log_level` and `log_format`
+//! don't affect it.
+//!
+//! Requests whose path starts with one of the log's excluded prefixes
+//! (`/healthz` by default) aren't logged; they still reach the rest of the
+//! stack, so metrics count them. With a sample rate below 1.0, each
+//! remaining request is logged with that probability, to cut I/O under load.
+//!
+//! A file access log can roll over to a new file every minute, hour or day
+//! ([`AccessLogRotation`]); each file is named after the configured path plus
+//! the period it covers, e.g. `access.log.2026-10-15-20` for an hourly log.
+
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::{ConnectInfo, Request},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use rand::Rng;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::utils::clock::Clock;
+
+/// The line format of the access log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AccessLogFormat {
+    /// Apache Common Log Format
+    Common,
+    /// Apache Combined Log Format: Common plus referer and user agent
+    #[default]
+    Combined,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for AccessLogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "common" => Ok(AccessLogFormat::Common),
+            "combined" => Ok(AccessLogFormat::Combined),
+            "json" => Ok(AccessLogFormat::Json),
+            _ => Err(format!(
+                "unknown access log format '{s}' (expected combined, common, or json)"
+            )),
+        }
+    }
+}
+
+/// How often a file access log rolls over to a new file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AccessLogRotation {
+    /// A new file every minute
+    Minutely,
+    /// A new file every hour
+    Hourly,
+    /// A new file every day
+    Daily,
+    /// A single file that is never rolled over
+    #[default]
+    Never,
+}
+
+impl FromStr for AccessLogRotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "minutely" => Ok(AccessLogRotation::Minutely),
+            "hourly" => Ok(AccessLogRotation::Hourly),
+            "daily" => Ok(AccessLogRotation::Daily),
+            "never" => Ok(AccessLogRotation::Never),
+            _ => Err(format!(
+                "unknown access log rotation '{s}' (expected daily, hourly, minutely, or never)"
+            )),
+        }
+    }
+}
+
+impl AccessLogRotation {
+    /// The file that lines logged at `time` go to: `path` itself for `never`,
+    /// otherwise `path` plus the UTC period, e.g. `access.log.2026-10-15` for
+    /// `daily`, `-20` more for `hourly` and `-20-05` for `minutely`.
+    fn file_name(self, path: &str, time: OffsetDateTime) -> String {
+        let date = format!(
+            "{path}.{:04}-{:02}-{:02}",
+            time.year(),
+            u8::from(time.month()),
+            time.day()
+        );
+        match self {
+            AccessLogRotation::Never => path.to_string(),
+            AccessLogRotation::Daily => date,
+            AccessLogRotation::Hourly => format!("{date}-{:02}", time.hour()),
+            AccessLogRotation::Minutely => {
+                format!("{date}-{:02}-{:02}", time.hour(), time.minute())
+            }
+        }
+    }
+}
+
+/// Opens `path` for appending, creating it if needed.
+fn open_append(path: &str) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// A file that moves on to a new one whenever the rotation period changes.
+#[derive(Debug)]
+pub struct RollingFile {
+    path: String,
+    rotation: AccessLogRotation,
+    clock: Clock,
+    current_name: String,
+    current: File,
+}
+
+impl RollingFile {
+    /// Opens the file for the current period.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the file can't be opened.
+    pub fn open(path: &str, rotation: AccessLogRotation, clock: Clock) -> io::Result<Self> {
+        let current_name = rotation.file_name(path, OffsetDateTime::from(clock.system_time()));
+        let current = open_append(&current_name)?;
+        Ok(RollingFile {
+            path: path.to_string(),
+            rotation,
+            clock,
+            current_name,
+            current,
+        })
+    }
+
+    /// Switches to the current period's file if the period has changed.
+    fn roll(&mut self) -> io::Result<()> {
+        let name = self
+            .rotation
+            .file_name(&self.path, OffsetDateTime::from(self.clock.system_time()));
+        if name != self.current_name {
+            self.current = open_append(&name)?;
+            self.current_name = name;
+        }
+        Ok(())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.roll()?;
+        self.current.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.current.flush()
+    }
+}
+
+/// Where [`AccessLog::open`] sends lines.
+#[derive(Debug)]
+pub enum AccessLogWriter {
+    /// `-`: standard output
+    Stdout(io::Stdout),
+    /// Rotation `never`: one file, appended to
+    File(File),
+    /// Any other rotation
+    Rolling(RollingFile),
+}
+
+impl AccessLogWriter {
+    /// The writer for `path` and `rotation`; `-` is stdout whatever the
+    /// rotation.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the file can't be opened.
+    pub fn open(path: &str, rotation: AccessLogRotation, clock: Clock) -> io::Result<Self> {
+        Ok(match rotation {
+            _ if path == "-" => AccessLogWriter::Stdout(io::stdout()),
+            AccessLogRotation::Never => AccessLogWriter::File(open_append(path)?),
+            _ => AccessLogWriter::Rolling(RollingFile::open(path, rotation, clock)?),
+        })
+    }
+}
+
+impl Write for AccessLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            AccessLogWriter::Stdout(out) => out.write(buf),
+            AccessLogWriter::File(file) => file.write(buf),
+            AccessLogWriter::Rolling(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            AccessLogWriter::Stdout(out) => out.flush(),
+            AccessLogWriter::File(file) => file.flush(),
+            AccessLogWriter::Rolling(file) => file.flush(),
+        }
+    }
+}
+
+/// What the access log records about one request.
+struct AccessEntry {
+    host: Option<IpAddr>,
+    time: OffsetDateTime,
+    method: String,
+    target: String,
+    version: String,
+    status: u16,
+    bytes: Option<u64>,
+    duration: Duration,
+    referer: Option<String>,
+    user_agent: Option<String>,
+    request_id: Option<String>,
+}
+
+impl AccessEntry {
+    /// Formats the entry as one line (without the newline).
+    fn format(&self, format: AccessLogFormat) -> String {
+        if format == AccessLogFormat::Json {
+            return serde_json::json!({
+                "time": self.time.format(&Rfc3339).unwrap_or_default(),
+                "remote_addr": self.host.map(|ip| ip.to_string()),
+                "method": self.method,
+                "path": self.target,
+                "protocol": self.version,
+                "status": self.status,
+                "bytes": self.bytes,
+                "duration_ms": self.duration.as_secs_f64() * 1000.0,
+                "referer": self.referer,
+                "user_agent": self.user_agent,
+                "request_id": self.request_id,
+            })
+            .to_string();
+        }
+
+        // e.g. 10/Oct/2000:13:55:36 +0000; the clock's time is always UTC.
+        let month = self.time.month().to_string();
+        let time = format!(
+            "{:02}/{}/{:04}:{:02}:{:02}:{:02} +0000",
+            self.time.day(),
+            &month[..3],
+            self.time.year(),
+            self.time.hour(),
+            self.time.minute(),
+            self.time.second()
+        );
+        let mut line = format!(
+            "{} - - [{}] \"{} {} {}\" {} {}",
+            self.host
+                .map_or_else(|| "-".to_string(), |ip| ip.to_string()),
+            time,
+            self.method,
+            escape(&self.target),
+            self.version,
+            self.status,
+            self.bytes
+                .filter(|&bytes| bytes > 0)
+                .map_or_else(|| "-".to_string(), |bytes| bytes.to_string()),
+        );
+        if format == AccessLogFormat::Combined {
+            let _ = write!(
+                line,
+                " \"{}\" \"{}\"",
+                self.referer.as_deref().map_or("-".into(), escape),
+                self.user_agent.as_deref().map_or("-".into(), escape),
+            );
+        }
+        line
+    }
+}
+
+/// Escapes `"` and `\` so a value can't end its quoted field early.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// A header's value, if it's present and visible ASCII.
+fn header_value(headers: &HeaderMap, name: impl header::AsHeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Where access log lines go, and in which format.
+pub struct AccessLog {
+    format: AccessLogFormat,
+    clock: Clock,
+    exclude: Vec<String>,
+    sample_rate: f64,
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl AccessLog {
+    /// An access log writing `format` lines to `out`, timed on `clock`.
+    pub fn new(format: AccessLogFormat, clock: Clock, out: Box<dyn Write + Send>) -> Self {
+        AccessLog {
+            format,
+            clock,
+            exclude: Vec::new(),
+            sample_rate: 1.0,
+            out: Mutex::new(out),
+        }
+    }
+
+    /// Leaves requests whose path starts with any of `prefixes` out of the log.
+    pub fn with_exclude(mut self, prefixes: Vec<String>) -> Self {
+        self.exclude = prefixes;
+        self
+    }
+
+    /// Logs each request with probability `rate` (0.0–1.0) instead of always.
+    pub fn with_sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = rate;
+        self
+    }
+
+    /// Whether requests for `path` are left out of the log.
+    fn excludes(&self, path: &str) -> bool {
+        self.exclude.iter().any(|prefix| path.starts_with(prefix))
+    }
+
+    /// Rolls whether to log this request: always at a rate of 1.0, otherwise
+    /// with probability `sample_rate`.
+    fn sampled(&self) -> bool {
+        self.sample_rate >= 1.0 || rand::thread_rng().gen::<f64>() < self.sample_rate
+    }
+
+    /// Opens the access log at `path` for appending, creating it if needed
+    /// and rolling it over every `rotation` period; `-` writes to stdout
+    /// instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the file can't be opened.
+    pub fn open(
+        path: &str,
+        format: AccessLogFormat,
+        rotation: AccessLogRotation,
+        clock: Clock,
+    ) -> io::Result<Self> {
+        let out = AccessLogWriter::open(path, rotation, clock.clone())?;
+        Ok(AccessLog::new(format, clock, Box::new(out)))
+    }
+
+    /// Writes one line. A failed write is logged and otherwise ignored, so a
+    /// full disk doesn't fail requests.
+    fn write_line(&self, line: &str) {
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(out, "{line}").and_then(|()| out.flush()) {
+            tracing::warn!("Failed to write the access log: {}", e);
+        }
+    }
+}
+
+impl std::fmt::Debug for AccessLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessLog")
+            .field("format", &self.format)
+            .field("exclude", &self.exclude)
+            .field("sample_rate", &self.sample_rate)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Middleware that writes an access log line for every request to `log`,
+/// except those it excludes or doesn't sample.
+pub async fn access_log_middleware(
+    request: Request,
+    next: Next,
+    log: Arc<AccessLog>,
+) -> Response<Body> {
+    if log.excludes(request.uri().path()) || !log.sampled() {
+        return next.run(request).await;
+    }
+    let start = log.clock.now();
+    let time = OffsetDateTime::from(log.clock.system_time());
+    let host = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ci| ci.0.ip());
+    let method = request.method().to_string();
+    let target = request
+        .uri()
+        .path_and_query()
+        .map_or_else(|| request.uri().path().to_string(), ToString::to_string);
+    let version = format!("{:?}", request.version());
+    let referer = header_value(request.headers(), header::REFERER);
+    let user_agent = header_value(request.headers(), header::USER_AGENT);
+
+    let response = next.run(request).await;
+
+    let entry = AccessEntry {
+        host,
+        time,
+        method,
+        target,
+        version,
+        status: response.status().as_u16(),
+        bytes: response.body().size_hint().exact(),
+        duration: log.clock.elapsed_since(start),
+        referer,
+        user_agent,
+        request_id: header_value(response.headers(), "x-request-id"),
+    };
+    log.write_line(&entry.format(log.format));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tower::ServiceExt;
+
+    /// A writer whose output the test keeps a handle on.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuf {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    /// Sends one request through a router logging in `format`, returning the
+    /// access log output.
+    async fn log_request(format: AccessLogFormat, request: Request) -> String {
+        let buf = SharedBuf::default();
+        let log = Arc::new(AccessLog::new(
+            format,
+            Clock::default(),
+            Box::new(buf.clone()),
+        ));
+        let app = Router::new()
+            .route("/get", get(|| async { "hello" }))
+            .layer(axum::middleware::from_fn(move |req, next| {
+                access_log_middleware(req, next, log.clone())
+            }));
+        app.oneshot(request).await.unwrap();
+        buf.contents()
+    }
+
+    fn get_request() -> Request {
+        let mut request = Request::builder()
+            .uri("/get?a=1")
+            .header(header::REFERER, "https://example.com/")
+            .header(header::USER_AGENT, "curl/8.0 \"test\"")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 7], 5555))));
+        request
+    }
+
+    /// The Common Log Format part of a line, with the timestamp checked and
+    /// replaced by `[TIME]`.
+    fn without_time(line: &str) -> String {
+        let (before, rest) = line.split_once(" [").unwrap();
+        let (time, after) = rest.split_once("] ").unwrap();
+        // e.g. 10/Oct/2000:13:55:36 +0000
+        assert_eq!(time.len(), 26, "unexpected timestamp: {time}");
+        assert!(time.ends_with(" +0000"), "unexpected timestamp: {time}");
+        format!("{before} [TIME] {after}")
+    }
+
+    #[tokio::test]
+    async fn common_format() {
+        let out = log_request(AccessLogFormat::Common, get_request()).await;
+        assert_eq!(
+            without_time(out.trim_end()),
+            r#"10.0.0.7 - - [TIME] "GET /get?a=1 HTTP/1.1" 200 5"#
+        );
+        assert_eq!(out.lines().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn combined_format() {
+        let out = log_request(AccessLogFormat::Combined, get_request()).await;
+        assert_eq!(
+            without_time(out.trim_end()),
+            r#"10.0.0.7 - - [TIME] "GET /get?a=1 HTTP/1.1" 200 5 "https://example.com/" "curl/8.0 \"test\"""#
+        );
+
+        let request = Request::builder()
+            .uri("/missing")
+            .body(Body::empty())
+            .unwrap();
+        let out = log_request(AccessLogFormat::Combined, request).await;
+        assert_eq!(
+            without_time(out.trim_end()),
+            r#"- - - [TIME] "GET /missing HTTP/1.1" 404 - "-" "-""#
+        );
+    }
+
+    #[tokio::test]
+    async fn json_format() {
+        let out = log_request(AccessLogFormat::Json, get_request()).await;
+        let line: serde_json::Value = serde_json::from_str(out.trim_end()).unwrap();
+        assert_eq!(line["remote_addr"], "10.0.0.7");
+        assert_eq!(line["method"], "GET");
+        assert_eq!(line["path"], "/get?a=1");
+        assert_eq!(line["protocol"], "HTTP/1.1");
+        assert_eq!(line["status"], 200);
+        assert_eq!(line["bytes"], 5);
+        assert!(line["duration_ms"].as_f64().unwrap() >= 0.0);
+        assert_eq!(line["user_agent"], "curl/8.0 \"test\"");
+        assert!(line["request_id"].is_null());
+        assert!(OffsetDateTime::parse(line["time"].as_str().unwrap(), &Rfc3339).is_ok());
+    }
+
+    #[test]
+    fn excludes_matches_path_prefixes() {
+        let log = AccessLog::new(
+            AccessLogFormat::Common,
+            Clock::default(),
+            Box::new(io::sink()),
+        )
+        .with_exclude(vec!["/healthz".to_string(), "/static/".to_string()]);
+        assert!(log.excludes("/healthz"));
+        assert!(log.excludes("/static/app.js"));
+        assert!(!log.excludes("/get"));
+        assert!(!log.excludes("/static"));
+    }
+
+    #[tokio::test]
+    async fn sample_rate_logs_about_that_fraction() {
+        const REQUESTS: usize = 2000;
+        for (rate, expected) in [(0.0, 0..=0), (0.25, 400..=600), (1.0, 2000..=2000)] {
+            let buf = SharedBuf::default();
+            let log = Arc::new(
+                AccessLog::new(
+                    AccessLogFormat::Common,
+                    Clock::default(),
+                    Box::new(buf.clone()),
+                )
+                .with_sample_rate(rate),
+            );
+            let app = Router::new()
+                .route("/get", get(|| async { "hello" }))
+                .layer(axum::middleware::from_fn(move |req, next| {
+                    access_log_middleware(req, next, log.clone())
+                }));
+            for _ in 0..REQUESTS {
+                let request = Request::builder().uri("/get").body(Body::empty()).unwrap();
+                app.clone().oneshot(request).await.unwrap();
+            }
+            // 0.25 of 2000 is 500 ± 19 (one standard deviation).
+            let logged = buf.contents().lines().count();
+            assert!(
+                expected.contains(&logged),
+                "rate {rate}: logged {logged} of {REQUESTS}"
+            );
+        }
+    }
+
+    #[test]
+    fn format_parses_case_insensitively() {
+        assert_eq!("JSON".parse(), Ok(AccessLogFormat::Json));
+        assert_eq!("common".parse(), Ok(AccessLogFormat::Common));
+        assert_eq!("Combined".parse(), Ok(AccessLogFormat::Combined));
+        assert!("apache".parse::<AccessLogFormat>().is_err());
+    }
+
+    #[test]
+    fn rotation_selects_the_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("access.log");
+        let path = path.to_str().unwrap();
+        let open = |path, rotation| AccessLogWriter::open(path, rotation, Clock::default());
+
+        assert!(matches!(
+            open(path, AccessLogRotation::Never),
+            Ok(AccessLogWriter::File(_))
+        ));
+        for rotation in [
+            AccessLogRotation::Daily,
+            AccessLogRotation::Hourly,
+            AccessLogRotation::Minutely,
+        ] {
+            match open(path, rotation) {
+                Ok(AccessLogWriter::Rolling(file)) => assert_eq!(file.rotation, rotation),
+                other => panic!("{rotation:?} opened {other:?}"),
+            }
+        }
+        assert!(matches!(
+            open("-", AccessLogRotation::Daily),
+            Ok(AccessLogWriter::Stdout(_))
+        ));
+    }
+
+    #[test]
+    fn rotation_file_names() {
+        // 2025-10-15 20:05 UTC
+        let time = OffsetDateTime::from_unix_timestamp(1_760_558_700).unwrap();
+        let name = |rotation: AccessLogRotation| rotation.file_name("access.log", time);
+        assert_eq!(name(AccessLogRotation::Never), "access.log");
+        assert_eq!(name(AccessLogRotation::Daily), "access.log.2025-10-15");
+        assert_eq!(name(AccessLogRotation::Hourly), "access.log.2025-10-15-20");
+        assert_eq!(
+            name(AccessLogRotation::Minutely),
+            "access.log.2025-10-15-20-05"
+        );
+        assert_eq!("Hourly".parse(), Ok(AccessLogRotation::Hourly));
+        assert!("weekly".parse::<AccessLogRotation>().is_err());
+    }
+
+    #[test]
+    fn rolling_file_moves_on_when_the_period_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("access.log");
+        let manual = Arc::new(crate::utils::clock::ManualClock::new());
+        let clock = Clock::new(manual.clone());
+        let mut file =
+            RollingFile::open(path.to_str().unwrap(), AccessLogRotation::Minutely, clock).unwrap();
+
+        writeln!(file, "first").unwrap();
+        let first = file.current_name.clone();
+        manual.advance(Duration::from_secs(60));
+        writeln!(file, "second").unwrap();
+        assert_ne!(file.current_name, first);
+
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "first\n");
+        assert_eq!(
+            std::fs::read_to_string(&file.current_name).unwrap(),
+            "second\n"
+        );
+    }
+}
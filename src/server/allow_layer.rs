@@ -0,0 +1,356 @@
+//! Responses built from a route's `Allow` set.
+//!
+//! When a route doesn't register the request's method, axum answers `405` with
+//! an `Allow` header listing the methods it does register (a GET route also
+//! lists HEAD), but with an empty body. [`allow_middleware`] finishes that
+//! answer:
+//!
+//! - For `OPTIONS`, the `405` becomes `204 No Content` carrying the same
+//!   `Allow` set plus `OPTIONS`, so `OPTIONS /post` reports `POST, OPTIONS`
+//!   and `OPTIONS /get` reports `GET, HEAD, OPTIONS`, straight from the
+//!   router's registrations.
+//! - For any other method, axum's empty `405` gets the usual JSON error body
+//!   (`format_error_response`). A route's own `405` (e.g. `/`) keeps its body.
+//!   Either way `Allow` lists `OPTIONS` too, since it is now answered.
+//!
+//! Routes that handle `OPTIONS` themselves (`/options`, and `any` routes such
+//! as `/anything`, which echo it) and unknown paths (`404`) pass through
+//! untouched.
+//!
+//! axum adds `Allow` to a `405` only after the route's own layers have run,
+//! so `Router::layer` middleware never sees it. The middleware therefore
+//! wraps a whole finished router, via [`with_allow_responses`].
+//!
+//! tower-http's `CorsLayer` answers *every* `OPTIONS` as a preflight, which
+//! would hide all of the above; [`with_preflight_only_cors`] applies the
+//! configured CORS policy to real preflights only.
+
+use std::convert::Infallible;
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use tower::{Layer, ServiceExt};
+
+use crate::server::cors::CorsConfig;
+use crate::utils::error_response::format_error_response;
+
+/// Wraps `router` so its `405`s get [`allow_middleware`]'s answer.
+///
+/// The result is a router whose fallback is the wrapped one, so layers added
+/// to it afterwards still apply to every request.
+pub fn with_allow_responses(router: Router) -> Router {
+    Router::new().fallback_service(middleware::from_fn(allow_middleware).layer(router))
+}
+
+/// Wraps `router` in the `cors` policy, except for `OPTIONS` requests that
+/// aren't CORS preflights.
+///
+/// A preflight carries `Access-Control-Request-Method`. Any other `OPTIONS`
+/// skips the CORS layer and reaches the routes, and gets the headers the
+/// layer puts on an actual request (`Access-Control-Allow-Origin`,
+/// `Access-Control-Expose-Headers: *`) when its `Origin` is allowed.
+pub fn with_preflight_only_cors(router: Router, cors: &CorsConfig) -> Router {
+    let with_cors = Router::new().fallback_service(cors.layer().layer(router.clone()));
+    let cors = cors.clone();
+    Router::new().fallback_service(tower::service_fn(move |request: Request| {
+        let preflight_or_not_options = request.method() != Method::OPTIONS
+            || request
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+        let allow_origin = request
+            .headers()
+            .get(header::ORIGIN)
+            .and_then(|origin| cors.allow_origin_for(origin));
+        let target = if preflight_or_not_options {
+            with_cors.clone()
+        } else {
+            router.clone()
+        };
+        async move {
+            let mut response = target.oneshot(request).await?;
+            if let (false, Some(allow_origin)) = (preflight_or_not_options, allow_origin) {
+                let headers = response.headers_mut();
+                headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+                headers.insert(
+                    header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                    HeaderValue::from_static("*"),
+                );
+            }
+            Ok::<_, Infallible>(response)
+        }
+    }))
+}
+
+/// Middleware finishing a `405` that lists `Allow`.
+///
+/// For `OPTIONS` it becomes a bodiless `204` with `OPTIONS` added to `Allow`,
+/// keeping the response's other headers. For other methods, an empty-bodied
+/// `405` gets a JSON error naming the allowed methods. Must wrap a service that
+/// sets `Allow` itself (a `Router` or `MethodRouter`), not sit in a
+/// `Router::layer`.
+pub async fn allow_middleware(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_owned();
+    let response = next.run(request).await;
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+        return response;
+    }
+    let Some(allow) = response
+        .headers()
+        .get(header::ALLOW)
+        .and_then(|value| value.to_str().ok())
+        .map(allow_with_options)
+    else {
+        return response;
+    };
+    let Ok(allow_value) = HeaderValue::from_str(&allow) else {
+        return response;
+    };
+
+    if method == Method::OPTIONS {
+        let (mut parts, _) = response.into_parts();
+        parts.status = StatusCode::NO_CONTENT;
+        parts.headers.insert(header::ALLOW, allow_value);
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::CONTENT_TYPE);
+        return Response::from_parts(parts, Body::empty());
+    }
+
+    // axum's own 405 has no body; a route's hand-built one keeps its body.
+    let mut response = if response.headers().contains_key(header::CONTENT_TYPE) {
+        response
+    } else {
+        let mut error = format_error_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            &format!("Method {method} is not allowed on {path}; allowed: {allow}"),
+        );
+        error.headers_mut().extend(
+            response
+                .headers()
+                .iter()
+                .filter(|(name, _)| *name != header::CONTENT_LENGTH)
+                .map(|(name, value)| (name.clone(), value.clone())),
+        );
+        error
+    };
+    response.headers_mut().insert(header::ALLOW, allow_value);
+    response
+}
+
+/// Normalizes a comma-separated `Allow` value to `", "` separators, drops
+/// repeats (a route with both `get` and an explicit `head` lists HEAD twice),
+/// and appends `OPTIONS` when it's missing.
+fn allow_with_options(allow: &str) -> String {
+    let mut methods: Vec<&str> = Vec::new();
+    for method in allow.split(',').map(str::trim).chain(["OPTIONS"]) {
+        if !method.is_empty() && !methods.iter().any(|m| m.eq_ignore_ascii_case(method)) {
+            methods.push(method);
+        }
+    }
+    methods.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::{any, get, options, post};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        with_allow_responses(
+            Router::new()
+                .route("/get", get(|| async { "get" }))
+                .route("/post", post(|| async { "post" }))
+                .route("/both", get(|| async { "get" }).put(|| async { "put" }))
+                .route("/any", any(|| async { "any" }))
+                .route("/own", options(|| async { StatusCode::IM_A_TEAPOT })),
+        )
+        .layer(middleware::from_fn(|req, next: Next| async move {
+            let mut response = next.run(req).await;
+            response
+                .headers_mut()
+                .insert("x-outer", HeaderValue::from_static("1"));
+            response
+        }))
+    }
+
+    async fn send(method: Method, path: &str) -> Response {
+        app()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn allow_for(path: &str) -> String {
+        let response = send(Method::OPTIONS, path).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT, "{path}");
+        response.headers()[header::ALLOW]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn options_reports_each_routes_methods() {
+        let response = send(Method::OPTIONS, "/get").await;
+        assert_eq!(
+            response.headers()["x-outer"],
+            "1",
+            "outer layers still apply"
+        );
+        assert_eq!(allow_for("/get").await, "GET, HEAD, OPTIONS");
+        assert_eq!(allow_for("/post").await, "POST, OPTIONS");
+        assert_eq!(allow_for("/both").await, "GET, HEAD, PUT, OPTIONS");
+    }
+
+    #[tokio::test]
+    async fn routes_handling_options_and_unknown_paths_pass_through() {
+        assert_eq!(
+            send(Method::OPTIONS, "/own").await.status(),
+            StatusCode::IM_A_TEAPOT
+        );
+        assert_eq!(send(Method::OPTIONS, "/any").await.status(), StatusCode::OK);
+        assert_eq!(
+            send(Method::OPTIONS, "/missing").await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_methods_get_a_json_405_with_allow() {
+        let response = send(Method::DELETE, "/post").await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "POST, OPTIONS");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()["x-outer"], "1");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json["error"],
+            "Method DELETE is not allowed on /post; allowed: POST, OPTIONS"
+        );
+
+        let response = send(Method::POST, "/both").await;
+        assert_eq!(response.headers()[header::ALLOW], "GET, HEAD, PUT, OPTIONS");
+    }
+
+    #[tokio::test]
+    async fn a_routes_own_405_keeps_its_body() {
+        let app = with_allow_responses(Router::new().route(
+            "/",
+            get(|| async { "root" }).fallback(|| async {
+                (
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    [(header::ALLOW, "GET, HEAD")],
+                    "custom",
+                )
+            }),
+        ));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "GET, HEAD, OPTIONS");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"custom");
+    }
+
+    #[tokio::test]
+    async fn cors_answers_preflights_but_not_plain_options() {
+        let app = with_preflight_only_cors(app(), &CorsConfig::default());
+        let options = |preflight: bool| {
+            let mut request = Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/post")
+                .header(header::ORIGIN, "https://example.com");
+            if preflight {
+                request = request.header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST");
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let preflight = options(true).await.unwrap();
+        assert_eq!(preflight.status(), StatusCode::OK);
+        assert!(preflight
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_METHODS));
+
+        let plain = options(false).await.unwrap();
+        assert_eq!(plain.status(), StatusCode::NO_CONTENT);
+        assert_eq!(plain.headers()[header::ALLOW], "POST, OPTIONS");
+        assert_eq!(plain.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+
+    #[tokio::test]
+    async fn a_disallowed_origin_gets_no_allow_origin() {
+        let cors = CorsConfig {
+            allow_origins: vec!["https://app.example".to_string()],
+            ..CorsConfig::default()
+        };
+        let app = with_preflight_only_cors(app(), &cors);
+        let send = |method: Method, origin: &'static str, preflight: bool| {
+            let mut request = Request::builder()
+                .method(method)
+                .uri("/post")
+                .header(header::ORIGIN, origin);
+            if preflight {
+                request = request.header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST");
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        for (method, preflight) in [
+            (Method::POST, false),
+            (Method::OPTIONS, true),
+            (Method::OPTIONS, false),
+        ] {
+            let denied = send(method.clone(), "https://evil.example", preflight)
+                .await
+                .unwrap();
+            assert!(
+                !denied
+                    .headers()
+                    .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+                "{method} preflight={preflight}"
+            );
+            let allowed = send(method.clone(), "https://app.example", preflight)
+                .await
+                .unwrap();
+            assert_eq!(
+                allowed.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+                "https://app.example",
+                "{method} preflight={preflight}"
+            );
+        }
+    }
+
+    #[test]
+    fn allow_with_options_normalizes_and_dedupes() {
+        assert_eq!(allow_with_options("GET,HEAD"), "GET, HEAD, OPTIONS");
+        assert_eq!(allow_with_options("GET, OPTIONS"), "GET, OPTIONS");
+        assert_eq!(allow_with_options("GET,HEAD,HEAD"), "GET, HEAD, OPTIONS");
+    }
+}
@@ -3,16 +3,21 @@
 //! This module provides middleware that randomly injects failures, delays, and
 //! response corruption to help test application resilience. Each chaos type
 //! rolls independently against its configured probability rate per request.
+//!
+//! By default rolls come from a per-thread entropy-seeded RNG. When
+//! `chaos_seed` is configured, every roll is drawn from a single shared seeded
+//! RNG instead (see [`ChaosRng`]), making a run reproducible.
 
 use axum::body::Body;
 use axum::extract::Request;
 use axum::middleware::Next;
-use axum::response::Response;
+use axum::response::{IntoResponse, Response};
 use http::StatusCode;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Exp, Normal};
 use std::cell::RefCell;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 
 use crate::utils::config::ChaosConfig;
 
@@ -30,17 +35,92 @@ fn roll_probability() -> f64 {
     CHAOS_RNG.with(|rng| rng.borrow_mut().gen::<f64>())
 }
 
+/// Source of randomness for chaos decisions.
+///
+/// Unseeded, it defers to the per-thread `CHAOS_RNG` (no contention). Seeded,
+/// all rolls go through one `Mutex<StdRng>` so the sequence of draws is fixed
+/// by the seed. The sequence is only reproducible for a fixed *request order*:
+/// concurrent requests interleave their draws nondeterministically, so replay
+/// a seeded run with requests issued sequentially.
+#[derive(Debug)]
+pub struct ChaosRng {
+    seeded: Option<Mutex<StdRng>>,
+}
+
+impl ChaosRng {
+    /// Creates a chaos RNG, seeded when `seed` is `Some`.
+    pub fn new(seed: Option<u64>) -> Self {
+        Self {
+            seeded: seed.map(|s| Mutex::new(StdRng::seed_from_u64(s))),
+        }
+    }
+
+    /// Runs `f` with exclusive access to the active RNG. The lock (if any) is
+    /// released before returning, so it is never held across an `.await`.
+    fn with<R>(&self, f: impl FnOnce(&mut StdRng) -> R) -> R {
+        match &self.seeded {
+            Some(rng) => f(&mut rng.lock().unwrap_or_else(PoisonError::into_inner)),
+            None => CHAOS_RNG.with(|rng| f(&mut rng.borrow_mut())),
+        }
+    }
+
+    /// Draws a uniform probability in `[0, 1)`.
+    fn roll(&self) -> f64 {
+        match self.seeded {
+            Some(_) => self.with(|rng| rng.gen::<f64>()),
+            None => roll_probability(),
+        }
+    }
+}
+
+/// Draws a random delay (ms) for `chaos_delay_ms = random` from the configured
+/// `delay_distribution`, clamped to `0..=delay_max_ms`.
+///
+/// `uniform` keeps the original `0..delay_max_ms` range; `normal` and
+/// `exponential` model tail latency around `delay_mean_ms` / `1 / delay_lambda`.
+/// Parameters are validated at startup, so a distribution that still fails to
+/// construct falls back to no delay rather than panicking.
+fn sample_random_delay(chaos: &ChaosConfig, rng: &mut StdRng) -> u64 {
+    let max = chaos.delay_max_ms;
+    let sample = match chaos.delay_distribution.as_str() {
+        "normal" => Normal::new(chaos.delay_mean_ms, chaos.delay_stddev_ms)
+            .map(|d| d.sample(rng))
+            .unwrap_or(0.0),
+        "exponential" => Exp::new(chaos.delay_lambda)
+            .map(|d| d.sample(rng))
+            .unwrap_or(0.0),
+        _ => return rng.gen_range(0..max),
+    };
+    // `as` saturates: NaN/negative → 0, overly large → u64::MAX, then capped.
+    (sample.round() as u64).min(max)
+}
+
+/// Flips `count` distinct, randomly chosen bits of `bytes` in place.
+///
+/// Bit positions are sampled without replacement, so exactly `count` bits
+/// change (capped at the body's bit length); two flips may land in the same
+/// byte, so the number of differing bytes is at most `count`.
+fn flip_bits(bytes: &mut [u8], count: usize, rng: &mut StdRng) {
+    let total_bits = bytes.len() * 8;
+    for bit in rand::seq::index::sample(rng, total_bits, count.min(total_bits)) {
+        bytes[bit / 8] ^= 1 << (bit % 8);
+    }
+}
+
 /// Builds the `X-Chaos` response header value from the applied-effects list.
-/// Infallible: the value is a comma-joined list of static ASCII tokens.
+///
+/// The value is a comma-joined list of static ASCII tokens, so the fallback
+/// is never taken; it only keeps this path free of panics.
 fn chaos_header(applied: &[&str]) -> http::HeaderValue {
-    applied
-        .join(",")
-        .parse()
-        .expect("infallible: x-chaos header value is ASCII")
+    http::HeaderValue::from_str(&applied.join(","))
+        .unwrap_or_else(|_| http::HeaderValue::from_static("chaos"))
 }
 
 /// Middleware that injects chaos behaviors based on configuration.
 ///
+/// All random draws come from `rng`; build it once per app with
+/// `ChaosRng::new(chaos.seed)` and share it across requests.
+///
 /// Evaluation order: failure → delay → corruption.
 /// Failure short-circuits (skips handler). Delay and corruption can stack.
 /// When `inform_header` is true, affected responses include an `X-Chaos` header
@@ -49,13 +129,13 @@ pub async fn chaos_middleware(
     request: Request,
     next: Next,
     chaos: Arc<ChaosConfig>,
+    rng: Arc<ChaosRng>,
 ) -> Response<Body> {
     let mut applied: Vec<&str> = Vec::new();
 
     // 1. Roll for failure — short-circuit with error response
-    if chaos.has_failure() && roll_probability() < chaos.failure_rate {
-        let code_idx =
-            CHAOS_RNG.with(|rng| rng.borrow_mut().gen_range(0..chaos.failure_codes.len()));
+    if chaos.has_failure() && rng.roll() < chaos.failure_rate {
+        let code_idx = rng.with(|rng| rng.gen_range(0..chaos.failure_codes.len()));
         let status_code = chaos.failure_codes[code_idx];
         applied.push("failure");
 
@@ -67,14 +147,13 @@ pub async fn chaos_middleware(
             }
         });
 
-        let mut response = Response::builder()
-            .status(StatusCode::from_u16(status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR))
-            .header("content-type", "application/json")
-            .body(Body::from(
-                serde_json::to_vec_pretty(&body)
-                    .expect("infallible: serializing static chaos body"),
-            ))
-            .expect("infallible: chaos failure response builder");
+        // `{:#}` pretty-prints a `Value` without a fallible serializer call.
+        let mut response = (
+            StatusCode::from_u16(status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            [(http::header::CONTENT_TYPE, "application/json")],
+            format!("{body:#}"),
+        )
+            .into_response();
 
         if chaos.inform_header {
             response
@@ -86,9 +165,9 @@ pub async fn chaos_middleware(
     }
 
     // 2. Roll for delay — sleep before passing to handler
-    if chaos.has_delay() && roll_probability() < chaos.delay_rate {
+    if chaos.has_delay() && rng.roll() < chaos.delay_rate {
         let delay_ms = if chaos.delay_ms == "random" {
-            CHAOS_RNG.with(|rng| rng.borrow_mut().gen_range(0..chaos.delay_max_ms))
+            rng.with(|rng| sample_random_delay(&chaos, rng))
         } else {
             chaos.delay_ms.parse::<u64>().unwrap_or(0)
         };
@@ -103,7 +182,7 @@ pub async fn chaos_middleware(
     let response = next.run(request).await;
 
     // 4. Roll for corruption — modify response body
-    if chaos.has_corruption() && roll_probability() < chaos.corruption_rate {
+    if chaos.has_corruption() && rng.roll() < chaos.corruption_rate {
         applied.push("corruption");
         let (mut parts, body) = response.into_parts();
 
@@ -121,12 +200,18 @@ pub async fn chaos_middleware(
                     .await
                     .unwrap_or_default();
                 let len = bytes.len();
-                let garbage: Vec<u8> = CHAOS_RNG.with(|rng| {
-                    let mut rng = rng.borrow_mut();
-                    (0..len).map(|_| rng.gen_range(0x21u8..0x7F)).collect()
-                });
+                let garbage: Vec<u8> =
+                    rng.with(|rng| (0..len).map(|_| rng.gen_range(0x21u8..0x7F)).collect());
                 Body::from(garbage)
             }
+            "bitflip" => {
+                let mut bytes = axum::body::to_bytes(body, usize::MAX)
+                    .await
+                    .unwrap_or_default()
+                    .to_vec();
+                rng.with(|rng| flip_bits(&mut bytes, chaos.corruption_bitflip_count, rng));
+                Body::from(bytes)
+            }
             _ => body, // Shouldn't happen after validation
         };
 
@@ -163,6 +248,189 @@ mod tests {
         }
     }
 
+    /// Runs `n` sequential requests through a seeded failure-only chaos app and
+    /// returns which ones were failed.
+    async fn failure_pattern(seed: u64, n: usize) -> Vec<bool> {
+        use axum::{middleware, routing::get, Router};
+        use tower::ServiceExt;
+
+        let chaos = Arc::new(ChaosConfig {
+            modes: vec!["failure".to_string()],
+            failure_rate: 0.5,
+            failure_codes: vec![503],
+            seed: Some(seed),
+            ..ChaosConfig::default()
+        });
+        let rng = Arc::new(ChaosRng::new(chaos.seed));
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(middleware::from_fn(move |req, next| {
+                let (chaos, rng) = (chaos.clone(), rng.clone());
+                async move { chaos_middleware(req, next, chaos, rng).await }
+            }));
+
+        let mut pattern = Vec::with_capacity(n);
+        for _ in 0..n {
+            let req = Request::builder().uri("/").body(Body::empty()).unwrap();
+            let resp = app.clone().oneshot(req).await.unwrap();
+            pattern.push(resp.status() == StatusCode::SERVICE_UNAVAILABLE);
+        }
+        pattern
+    }
+
+    #[tokio::test]
+    async fn seeded_chaos_is_reproducible() {
+        let first = failure_pattern(42, 32).await;
+        let second = failure_pattern(42, 32).await;
+        assert_eq!(first, second, "same seed must replay the same failures");
+        // At a 50% rate over 32 requests the pattern is mixed, not degenerate.
+        assert!(first.contains(&true) && first.contains(&false));
+
+        let other = failure_pattern(43, 32).await;
+        assert_ne!(first, other, "a different seed gives a different pattern");
+    }
+
+    #[test]
+    fn seeded_rng_matches_seeded_stdrng() {
+        use rand::Rng;
+
+        let rng = ChaosRng::new(Some(7));
+        let mut reference = StdRng::seed_from_u64(7);
+        for _ in 0..10 {
+            assert_eq!(rng.roll(), reference.gen::<f64>());
+        }
+    }
+
+    /// Empirical mean of `n` delays drawn with `chaos`'s distribution.
+    fn mean_delay(chaos: &ChaosConfig, n: usize) -> f64 {
+        let mut rng = StdRng::seed_from_u64(3);
+        let total: u64 = (0..n).map(|_| sample_random_delay(chaos, &mut rng)).sum();
+        total as f64 / n as f64
+    }
+
+    #[test]
+    fn delay_distributions_hit_configured_mean() {
+        let base = ChaosConfig {
+            delay_ms: "random".to_string(),
+            delay_max_ms: 100_000,
+            ..ChaosConfig::default()
+        };
+
+        let normal = ChaosConfig {
+            delay_distribution: "normal".to_string(),
+            delay_mean_ms: 200.0,
+            delay_stddev_ms: 40.0,
+            ..base.clone()
+        };
+        let mean = mean_delay(&normal, 20_000);
+        assert!((mean - 200.0).abs() < 5.0, "normal mean {mean}");
+
+        let exponential = ChaosConfig {
+            delay_distribution: "exponential".to_string(),
+            delay_lambda: 0.01,
+            ..base.clone()
+        };
+        let mean = mean_delay(&exponential, 20_000);
+        assert!((mean - 100.0).abs() < 5.0, "exponential mean {mean}");
+
+        let uniform = ChaosConfig {
+            delay_max_ms: 1000,
+            ..base
+        };
+        let mean = mean_delay(&uniform, 20_000);
+        assert!((mean - 500.0).abs() < 15.0, "uniform mean {mean}");
+    }
+
+    #[test]
+    fn random_delay_is_clamped_to_max() {
+        let chaos = ChaosConfig {
+            delay_ms: "random".to_string(),
+            delay_max_ms: 50,
+            delay_distribution: "normal".to_string(),
+            delay_mean_ms: 40.0,
+            delay_stddev_ms: 100.0,
+            ..ChaosConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..1000 {
+            assert!(sample_random_delay(&chaos, &mut rng) <= 50);
+        }
+    }
+
+    #[test]
+    fn flip_bits_preserves_length_and_flips_requested_bits() {
+        let original: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+        let mut rng = StdRng::seed_from_u64(1);
+        for count in [1usize, 4, 16] {
+            let mut corrupted = original.clone();
+            flip_bits(&mut corrupted, count, &mut rng);
+
+            assert_eq!(corrupted.len(), original.len());
+            let differing_bytes = original
+                .iter()
+                .zip(&corrupted)
+                .filter(|(a, b)| a != b)
+                .count();
+            let differing_bits: u32 = original
+                .iter()
+                .zip(&corrupted)
+                .map(|(a, b)| (a ^ b).count_ones())
+                .sum();
+            assert_eq!(differing_bits as usize, count);
+            assert!((1..=count).contains(&differing_bytes), "{differing_bytes}");
+        }
+    }
+
+    #[test]
+    fn flip_bits_caps_at_body_size() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut tiny = vec![0u8; 1];
+        flip_bits(&mut tiny, 100, &mut rng);
+        assert_eq!(tiny, vec![0xff]);
+
+        let mut empty: Vec<u8> = Vec::new();
+        flip_bits(&mut empty, 3, &mut rng);
+        assert!(empty.is_empty());
+    }
+
+    #[tokio::test]
+    async fn bitflip_corruption_keeps_length() {
+        use axum::{middleware, routing::get, Router};
+        use tower::ServiceExt;
+
+        const BODY: &str = "the quick brown fox jumps over the lazy dog";
+        let chaos = Arc::new(ChaosConfig {
+            modes: vec!["corruption".to_string()],
+            corruption_rate: 1.0,
+            corruption_type: "bitflip".to_string(),
+            corruption_bitflip_count: 3,
+            seed: Some(9),
+            ..ChaosConfig::default()
+        });
+        let rng = Arc::new(ChaosRng::new(chaos.seed));
+        let app = Router::new()
+            .route("/", get(|| async { BODY }))
+            .layer(middleware::from_fn(move |req, next| {
+                let (chaos, rng) = (chaos.clone(), rng.clone());
+                async move { chaos_middleware(req, next, chaos, rng).await }
+            }));
+
+        let req = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.headers()["x-chaos"], "corruption");
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        assert_eq!(body.len(), BODY.len());
+        let differing = body
+            .iter()
+            .zip(BODY.as_bytes())
+            .filter(|(a, b)| a != b)
+            .count();
+        assert!((1..=3).contains(&differing), "{differing} bytes differ");
+    }
+
     proptest! {
         /// Whatever rate is configured, the chaos roll stays in `[0, 1)` (so the
         /// `roll < rate` gate is well-defined), and a `0.0` rate never trips it.
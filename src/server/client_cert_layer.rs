@@ -0,0 +1,130 @@
+//! Per-path client certificate enforcement.
+//!
+//! With `ssl_client_ca` alone, every HTTPS handshake must present a client
+//! certificate. Setting `ssl_client_cert_paths` relaxes the handshake so
+//! anonymous clients can connect, and this middleware instead requires a
+//! verified certificate only for requests under the listed path prefixes
+//! (e.g. `/admin`), answering `403 Forbidden` otherwise. Requests over plain
+//! HTTP never carry a certificate, so guarded paths are refused there too.
+//!
+//! The certificate itself was already verified against the CA during the
+//! handshake; this layer only checks that one was presented, via the
+//! per-connection [`TlsConnectionInfo`] extension.
+
+use std::sync::Arc;
+
+use axum::{body::Body, extract::Request, http::StatusCode, middleware::Next, response::Response};
+
+use crate::server::tls::TlsConnectionInfo;
+use crate::utils::error_response::format_error_response;
+
+/// Whether `path` falls under any of the `guarded` prefixes.
+///
+/// Prefixes match on segment boundaries: `/admin` guards `/admin` and
+/// `/admin/users`, but not `/administrator`.
+fn is_guarded(path: &str, guarded: &[String]) -> bool {
+    guarded.iter().any(|prefix| {
+        let prefix = prefix.trim_end_matches('/');
+        prefix.is_empty()
+            || path == prefix
+            || path
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// Middleware that rejects requests to `guarded` path prefixes unless the
+/// connection presented a verified client certificate.
+pub async fn client_cert_middleware(
+    request: Request,
+    next: Next,
+    guarded: Arc<Vec<String>>,
+) -> Response<Body> {
+    if !is_guarded(request.uri().path(), &guarded) {
+        return next.run(request).await;
+    }
+
+    let has_cert = request
+        .extensions()
+        .get::<Arc<TlsConnectionInfo>>()
+        .is_some_and(|tls| tls.client_cert_present);
+    if has_cert {
+        next.run(request).await
+    } else {
+        format_error_response(StatusCode::FORBIDDEN, "Client certificate required")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Extension, Router};
+    use tower::ServiceExt;
+
+    fn guarded() -> Vec<String> {
+        vec!["/admin".to_string(), "/ops/".to_string()]
+    }
+
+    #[test]
+    fn prefixes_match_on_segment_boundaries() {
+        let guarded = guarded();
+        assert!(is_guarded("/admin", &guarded));
+        assert!(is_guarded("/admin/users", &guarded));
+        assert!(is_guarded("/ops", &guarded));
+        assert!(is_guarded("/ops/restart", &guarded));
+        assert!(!is_guarded("/administrator", &guarded));
+        assert!(!is_guarded("/get", &guarded));
+        assert!(is_guarded("/anything", &["/".to_string()]));
+    }
+
+    fn app(tls: Option<TlsConnectionInfo>) -> Router {
+        let guarded = Arc::new(guarded());
+        let app = Router::new()
+            .route("/admin/stats", get(|| async { "secret" }))
+            .route("/get", get(|| async { "public" }))
+            .layer(middleware::from_fn(move |req, next| {
+                let guarded = guarded.clone();
+                async move { client_cert_middleware(req, next, guarded).await }
+            }));
+        match tls {
+            Some(tls) => app.layer(Extension(Arc::new(tls))),
+            None => app,
+        }
+    }
+
+    async fn status(app: Router, path: &str) -> StatusCode {
+        let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn guarded_path_requires_client_cert() {
+        let anonymous = TlsConnectionInfo::default();
+        assert_eq!(
+            status(app(Some(anonymous.clone())), "/admin/stats").await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(app(None), "/admin/stats").await,
+            StatusCode::FORBIDDEN
+        );
+
+        let authenticated = TlsConnectionInfo {
+            client_cert_present: true,
+            ..anonymous
+        };
+        assert_eq!(
+            status(app(Some(authenticated)), "/admin/stats").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn public_path_needs_no_client_cert() {
+        assert_eq!(status(app(None), "/get").await, StatusCode::OK);
+        assert_eq!(
+            status(app(Some(TlsConnectionInfo::default())), "/get").await,
+            StatusCode::OK
+        );
+    }
+}
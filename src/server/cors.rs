@@ -0,0 +1,178 @@
+//! CORS policy built from the `cors_allow_*` config fields.
+//!
+//! Each list is either `*` (any value, the default) or explicit entries:
+//! origins like `https://app.example.com`, method names, and request header
+//! names. With every list at `*` the layer matches tower-http's
+//! `CorsLayer::permissive()`, which rucho always used before these settings
+//! existed. A request from an origin that isn't listed gets no
+//! `Access-Control-Allow-Origin` header, so the browser blocks it.
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer, ExposeHeaders};
+
+/// The configured CORS allow-lists; `*` in a list allows anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsConfig {
+    /// Origins allowed to read responses (`cors_allow_origins`).
+    pub allow_origins: Vec<String>,
+    /// Methods allowed in a preflight (`cors_allow_methods`).
+    pub allow_methods: Vec<String>,
+    /// Request headers allowed in a preflight (`cors_allow_headers`).
+    pub allow_headers: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        let any = vec!["*".to_string()];
+        Self {
+            allow_origins: any.clone(),
+            allow_methods: any.clone(),
+            allow_headers: any,
+        }
+    }
+}
+
+/// Whether a config list means "anything": empty, or containing `*`.
+pub fn is_wildcard(list: &[String]) -> bool {
+    list.is_empty() || list.iter().any(|entry| entry == "*")
+}
+
+impl CorsConfig {
+    /// Builds the `CorsLayer` for this policy.
+    ///
+    /// Entries that don't parse as a header value, method, or header name are
+    /// skipped; `Config::validate` rejects them before the server starts.
+    pub fn layer(&self) -> CorsLayer {
+        let origins = if is_wildcard(&self.allow_origins) {
+            AllowOrigin::from(Any)
+        } else {
+            AllowOrigin::list(
+                self.allow_origins
+                    .iter()
+                    .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+            )
+        };
+        let methods = if is_wildcard(&self.allow_methods) {
+            AllowMethods::from(Any)
+        } else {
+            AllowMethods::list(
+                self.allow_methods
+                    .iter()
+                    .filter_map(|method| Method::from_bytes(method.as_bytes()).ok()),
+            )
+        };
+        let headers = if is_wildcard(&self.allow_headers) {
+            AllowHeaders::from(Any)
+        } else {
+            AllowHeaders::list(
+                self.allow_headers
+                    .iter()
+                    .filter_map(|name| HeaderName::from_bytes(name.as_bytes()).ok()),
+            )
+        };
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .expose_headers(ExposeHeaders::from(Any))
+    }
+
+    /// The `Access-Control-Allow-Origin` value for a request from `origin`,
+    /// or `None` when the origin isn't allowed.
+    ///
+    /// `*` under a wildcard policy, otherwise the origin itself.
+    pub fn allow_origin_for(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        if is_wildcard(&self.allow_origins) {
+            return Some(HeaderValue::from_static("*"));
+        }
+        self.allow_origins
+            .iter()
+            .any(|allowed| allowed.as_bytes() == origin.as_bytes())
+            .then(|| origin.clone())
+    }
+
+    /// Checks that every entry parses, naming the first one that doesn't.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(origin) = self
+            .allow_origins
+            .iter()
+            .find(|o| *o != "*" && HeaderValue::from_str(o).is_err())
+        {
+            return Err(format!(
+                "cors_allow_origins entry '{origin}' is not a valid origin"
+            ));
+        }
+        if let Some(method) = self
+            .allow_methods
+            .iter()
+            .find(|m| *m != "*" && Method::from_bytes(m.as_bytes()).is_err())
+        {
+            return Err(format!(
+                "cors_allow_methods entry '{method}' is not a valid method"
+            ));
+        }
+        if let Some(name) = self
+            .allow_headers
+            .iter()
+            .find(|h| *h != "*" && HeaderName::from_bytes(h.as_bytes()).is_err())
+        {
+            return Err(format!(
+                "cors_allow_headers entry '{name}' is not a valid header name"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn allow_origin_for_follows_the_list() {
+        let any = CorsConfig::default();
+        let origin = HeaderValue::from_static("https://evil.example");
+        assert_eq!(any.allow_origin_for(&origin).unwrap(), "*");
+
+        let listed = CorsConfig {
+            allow_origins: strings(&["https://app.example"]),
+            ..CorsConfig::default()
+        };
+        assert_eq!(listed.allow_origin_for(&origin), None);
+        let app = HeaderValue::from_static("https://app.example");
+        assert_eq!(
+            listed.allow_origin_for(&app).unwrap(),
+            "https://app.example"
+        );
+    }
+
+    #[test]
+    fn validate_names_the_bad_entry() {
+        assert!(CorsConfig::default().validate().is_ok());
+        let bad_method = CorsConfig {
+            allow_methods: strings(&["GET", "NOT A METHOD"]),
+            ..CorsConfig::default()
+        };
+        assert!(bad_method.validate().unwrap_err().contains("NOT A METHOD"));
+        let bad_header = CorsConfig {
+            allow_headers: strings(&["x ok"]),
+            ..CorsConfig::default()
+        };
+        assert!(bad_header
+            .validate()
+            .unwrap_err()
+            .contains("cors_allow_headers"));
+        let bad_origin = CorsConfig {
+            allow_origins: strings(&["https://a\nb"]),
+            ..CorsConfig::default()
+        };
+        assert!(bad_origin
+            .validate()
+            .unwrap_err()
+            .contains("cors_allow_origins"));
+    }
+}
@@ -0,0 +1,101 @@
+//! `Date` response header middleware.
+//!
+//! hyper writes a `Date` header when it serializes a response onto the wire,
+//! but responses seen in-process (the `/batch` sub-responses, tower
+//! `oneshot` tests, outer middleware) have none. [`date_middleware`] sets it
+//! on every response in RFC 1123 form (`Sun, 06 Nov 1994 08:49:37 GMT`),
+//! taking the time from the app's [`Clock`]. A `Date` that a handler already
+//! set is left alone, and hyper doesn't overwrite one either.
+
+use std::time::SystemTime;
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use time::OffsetDateTime;
+
+use crate::utils::clock::Clock;
+
+/// Middleware that adds a `Date` header to responses that lack one.
+pub async fn date_middleware(request: Request, next: Next, clock: Clock) -> Response<Body> {
+    let mut response = next.run(request).await;
+    if !response.headers().contains_key(header::DATE) {
+        if let Ok(value) = HeaderValue::from_str(&http_date(clock.system_time())) {
+            response.headers_mut().insert(header::DATE, value);
+        }
+    }
+    response
+}
+
+/// Formats `at` as an IMF-fixdate (RFC 1123), e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+pub fn http_date(at: SystemTime) -> String {
+    let at = OffsetDateTime::from(at);
+    let weekday = at.weekday().to_string();
+    let month = at.month().to_string();
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        &weekday[..3],
+        at.day(),
+        &month[..3],
+        at.year(),
+        at.hour(),
+        at.minute(),
+        at.second()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    #[test]
+    fn http_date_matches_rfc_1123() {
+        let at = SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777);
+        assert_eq!(http_date(at), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(
+            http_date(SystemTime::UNIX_EPOCH),
+            "Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+    }
+
+    #[tokio::test]
+    async fn sets_date_unless_the_handler_did() {
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route(
+                "/dated",
+                get(|| async { ([(header::DATE, "Mon, 01 Jan 2024 00:00:00 GMT")], "ok") }),
+            )
+            .layer(axum::middleware::from_fn(|req, next| {
+                date_middleware(req, next, Clock::default())
+            }));
+        let send = |path: &str| {
+            app.clone()
+                .oneshot(Request::builder().uri(path).body(Body::empty()).unwrap())
+        };
+
+        let response = send("/").await.unwrap();
+        let date = response.headers()[header::DATE].to_str().unwrap();
+        let format = time::format_description::parse(
+            "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT",
+        )
+        .unwrap();
+        assert!(
+            time::PrimitiveDateTime::parse(date, &format).is_ok(),
+            "{date}"
+        );
+
+        let response = send("/dated").await.unwrap();
+        assert_eq!(
+            response.headers()[header::DATE],
+            "Mon, 01 Jan 2024 00:00:00 GMT"
+        );
+    }
+}
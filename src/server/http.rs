@@ -1,14 +1,18 @@
 //! HTTP and HTTPS server setup.
 
+use std::fmt;
+use std::net::SocketAddr;
 use std::time::Duration;
 
 use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
 use axum_server::Handle;
 use hyper_util::rt::TokioTimer;
 use socket2::{SockRef, TcpKeepalive};
 use tokio::task::JoinHandle;
 
 use crate::utils::config::Config;
+use crate::utils::constants::HTTP1_MAX_HEADERS;
 use crate::utils::server_config;
 
 /// Configures TCP socket options (keep-alive, nodelay) on a standard TCP listener.
@@ -37,8 +41,8 @@ fn configure_tcp_socket(listener: &std::net::TcpListener, config: &Config) {
 
 /// Configures HTTP-level settings on the axum_server builder.
 ///
-/// Sets HTTP/1.1 keep-alive, header read timeout (with timer), and HTTP/2
-/// keep-alive interval and timeout.
+/// Sets HTTP/1.1 keep-alive, header read timeout (with timer), and header
+/// count limit, and HTTP/2 keep-alive interval and timeout.
 fn configure_http_builder<A>(server: &mut axum_server::Server<A>, config: &Config) {
     let http_timeout = Duration::from_secs(config.http_keep_alive_timeout);
     let header_timeout = Duration::from_secs(config.header_read_timeout);
@@ -48,7 +52,8 @@ fn configure_http_builder<A>(server: &mut axum_server::Server<A>, config: &Confi
         .http1()
         .keep_alive(true)
         .timer(TokioTimer::new())
-        .header_read_timeout(header_timeout);
+        .header_read_timeout(header_timeout)
+        .max_headers(HTTP1_MAX_HEADERS);
 
     server
         .http_builder()
@@ -57,163 +62,318 @@ fn configure_http_builder<A>(server: &mut axum_server::Server<A>, config: &Confi
         .keep_alive_timeout(Duration::from_secs(20));
 }
 
+/// Where a listener's socket comes from.
+enum ListenerSource {
+    /// Bind a fresh socket on this address.
+    Bind(SocketAddr),
+    /// Serve on a socket inherited from a supervisor (see `socket_activation`).
+    Inherited(std::net::TcpListener),
+}
+
+impl ListenerSource {
+    /// Produces the listening socket, logging and returning `None` if binding
+    /// fails. `kind` ("HTTP"/"HTTPS") names the listener in log messages.
+    async fn open(self, kind: &str) -> Option<std::net::TcpListener> {
+        let sock_addr = match self {
+            Self::Inherited(listener) => return Some(listener),
+            Self::Bind(sock_addr) => sock_addr,
+        };
+        match tokio::net::TcpListener::bind(sock_addr).await {
+            Ok(listener) => match listener.into_std() {
+                Ok(std_listener) => Some(std_listener),
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to convert tokio listener to std for {}: {}. \
+                        Skipping this {} listener.",
+                        sock_addr,
+                        e,
+                        kind
+                    );
+                    None
+                }
+            },
+            Err(e) => {
+                tracing::error!(
+                    "Failed to bind {} listener for {}: {}. Skipping this listener.",
+                    kind,
+                    sock_addr,
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Display for ListenerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bind(sock_addr) => write!(f, "{sock_addr}"),
+            Self::Inherited(listener) => match listener.local_addr() {
+                Ok(sock_addr) => write!(f, "{sock_addr} (inherited)"),
+                Err(_) => f.write_str("inherited socket"),
+            },
+        }
+    }
+}
+
 /// Sets up HTTP and HTTPS listeners based on configuration.
 ///
-/// Parses the primary and secondary listen addresses from config,
-/// determines if SSL should be used, and spawns the appropriate server tasks.
+/// Starts a listener per configured address
+/// ([`listen_addresses`](server_config::listen_addresses): the primary and
+/// secondary addresses plus the `server_listen` list), using HTTPS for those
+/// marked `ssl`, and spawns the server tasks.
+///
+/// If the process inherited listening sockets (`LISTEN_FDS`, see
+/// [`socket_activation`](crate::server::socket_activation)), those are served
+/// instead and nothing is bound: the i-th inherited socket takes the role of
+/// the i-th configured listen address, so an `ssl` suffix still selects HTTPS,
+/// and any extra sockets serve plain HTTP.
+///
+/// Returns the TLS configs of the HTTPS listeners serving `ssl_cert`/`ssl_key`
+/// files, for reloading when the certificate is rotated.
 pub async fn setup_http_listeners(
     config: &Config,
     app: Router,
     handle: Handle,
     server_handles: &mut Vec<JoinHandle<Result<(), std::io::Error>>>,
-) {
-    let mut listeners_to_start: Vec<(String, bool)> = Vec::new();
+) -> Vec<RustlsConfig> {
+    let inherited = crate::server::socket_activation::take_inherited_listeners();
+    start_listeners(config, app, handle, server_handles, inherited).await
+}
 
-    if let Some(parsed) = server_config::parse_listen_address(&config.server_listen_primary) {
-        listeners_to_start.push(parsed);
-    }
-    if let Some(parsed) = server_config::parse_listen_address(&config.server_listen_secondary) {
-        listeners_to_start.push(parsed);
-    }
+/// [`setup_http_listeners`] with the inherited sockets passed in.
+async fn start_listeners(
+    config: &Config,
+    app: Router,
+    handle: Handle,
+    server_handles: &mut Vec<JoinHandle<Result<(), std::io::Error>>>,
+    inherited: Vec<std::net::TcpListener>,
+) -> Vec<RustlsConfig> {
+    let listeners_to_start = server_config::listen_addresses(config);
 
-    for (address_str, is_ssl) in listeners_to_start {
+    let sources: Vec<(ListenerSource, bool)> = if inherited.is_empty() {
+        listeners_to_start
+            .into_iter()
+            .filter_map(|(address_str, is_ssl)| {
+                match server_config::parse_socket_addr(&address_str) {
+                    Ok(sock_addr) => Some((ListenerSource::Bind(sock_addr), is_ssl)),
+                    Err(e) => {
+                        tracing::error!(
+                            "Failed to parse listen address '{}': {}. Skipping this listener.",
+                            address_str,
+                            e
+                        );
+                        None
+                    }
+                }
+            })
+            .collect()
+    } else {
+        tracing::info!(
+            "Using {} inherited listening socket(s) instead of binding configured addresses",
+            inherited.len()
+        );
+        let roles = listeners_to_start
+            .into_iter()
+            .map(|(_, is_ssl)| is_ssl)
+            .chain(std::iter::repeat(false));
+        inherited
+            .into_iter()
+            .map(ListenerSource::Inherited)
+            .zip(roles)
+            .collect()
+    };
+
+    let mut reloadable = Vec::new();
+    for (source, is_ssl) in sources {
         let app_clone = app.clone();
         let handle_clone = handle.clone();
 
-        let sock_addr: std::net::SocketAddr = match address_str.parse() {
-            Ok(addr) => addr,
-            Err(e) => {
-                tracing::error!(
-                    "Failed to parse address '{}': {}. Skipping this listener.",
-                    address_str,
-                    e
-                );
-                continue;
-            }
-        };
-
         if is_ssl {
-            setup_https_listener(config, sock_addr, app_clone, handle_clone, server_handles).await;
+            reloadable.extend(
+                setup_https_listener(config, source, app_clone, handle_clone, server_handles).await,
+            );
         } else {
-            setup_http_listener(config, sock_addr, app_clone, handle_clone, server_handles).await;
+            setup_http_listener(config, source, app_clone, handle_clone, server_handles).await;
         }
     }
 
     if server_handles.is_empty() {
         tracing::warn!("No HTTP/HTTPS server instances were configured or able to start.");
     }
+    reloadable
 }
 
-/// Sets up an HTTP listener on the given address.
+/// Sets up an HTTP listener on the given socket source.
 async fn setup_http_listener(
     config: &Config,
-    sock_addr: std::net::SocketAddr,
+    source: ListenerSource,
     app: Router,
     handle: Handle,
     server_handles: &mut Vec<JoinHandle<Result<(), std::io::Error>>>,
 ) {
-    match tokio::net::TcpListener::bind(sock_addr).await {
-        Ok(listener) => match listener.into_std() {
-            Ok(std_listener) => {
-                configure_tcp_socket(&std_listener, config);
-
-                tracing::info!("Starting HTTP server on http://{}", sock_addr);
-                let mut server = axum_server::Server::from_tcp(std_listener);
-                configure_http_builder(&mut server, config);
-                let server_future = server
-                    .handle(handle)
-                    .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>());
-                server_handles.push(tokio::spawn(server_future));
-            }
-            Err(e) => {
-                tracing::error!(
-                    "Failed to convert tokio listener to std for {}: {}. Skipping this listener.",
-                    sock_addr,
-                    e
-                );
-            }
-        },
-        Err(e) => {
-            tracing::error!(
-                "Failed to bind HTTP listener for {}: {}. Skipping this listener.",
-                sock_addr,
-                e
-            );
-        }
-    }
+    let description = source.to_string();
+    let Some(std_listener) = source.open("HTTP").await else {
+        return;
+    };
+    configure_tcp_socket(&std_listener, config);
+
+    tracing::info!("Starting HTTP server on http://{}", description);
+    let mut server = axum_server::Server::from_tcp(std_listener);
+    configure_http_builder(&mut server, config);
+    let server_future = server
+        .handle(handle)
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+    server_handles.push(tokio::spawn(server_future));
 }
 
-/// Sets up an HTTPS listener on the given address.
+/// Sets up an HTTPS listener on the given socket source.
+///
+/// Returns the listener's TLS config when it was started from `ssl_cert`/
+/// `ssl_key` files (and so can be reloaded from them); `None` otherwise.
 async fn setup_https_listener(
     config: &Config,
-    sock_addr: std::net::SocketAddr,
+    source: ListenerSource,
     app: Router,
     handle: Handle,
     server_handles: &mut Vec<JoinHandle<Result<(), std::io::Error>>>,
-) {
+) -> Option<RustlsConfig> {
     // Pick the TLS cert source: explicit ssl_cert/ssl_key files take precedence;
     // if none are usable and ssl_auto_cert is enabled, generate an ephemeral
     // in-memory self-signed certificate for zero-setup HTTPS.
-    let rustls_config = match server_config::try_load_rustls_config(
+    let tls_options = server_config::TlsOptions::from_config(config);
+    let (rustls_config, from_files) = match server_config::try_load_rustls_config(
         config.ssl_cert.as_deref(),
         config.ssl_key.as_deref(),
+        &tls_options,
     )
     .await
     {
-        Some(cfg) => Some(cfg),
-        None if config.ssl_auto_cert => server_config::generate_self_signed_rustls_config().await,
-        None => None,
+        Some(cfg) => (Some(cfg), true),
+        None if config.ssl_auto_cert => (
+            server_config::generate_self_signed_rustls_config(&tls_options).await,
+            false,
+        ),
+        None => (None, false),
     };
 
+    let description = source.to_string();
     match rustls_config {
         Some(rustls_config) => {
             // Bind and tune the TCP socket ourselves (mirroring the HTTP path) so
             // the HTTPS listener gets the same keep-alive / TCP_NODELAY settings,
             // then attach the TLS-info acceptor via `from_tcp`. `Server::bind`
             // would bind internally and skip `configure_tcp_socket`.
-            let std_listener = match tokio::net::TcpListener::bind(sock_addr).await {
-                Ok(listener) => match listener.into_std() {
-                    Ok(std_listener) => std_listener,
-                    Err(e) => {
-                        tracing::error!(
-                            "Failed to convert tokio listener to std for {}: {}. \
-                            Skipping this HTTPS listener.",
-                            sock_addr,
-                            e
-                        );
-                        return;
-                    }
-                },
-                Err(e) => {
-                    tracing::error!(
-                        "Failed to bind HTTPS listener for {}: {}. Skipping this listener.",
-                        sock_addr,
-                        e
-                    );
-                    return;
-                }
-            };
+            let std_listener = source.open("HTTPS").await?;
             configure_tcp_socket(&std_listener, config);
 
-            tracing::info!("Starting HTTPS server on https://{}", sock_addr);
+            tracing::info!("Starting HTTPS server on https://{}", description);
             // Use a TLS-info-injecting acceptor (instead of `bind_rustls`) so the
             // negotiated TLS parameters reach the `/get` and `/anything` handlers
             // as a request extension. ALPN/HTTP-2 and graceful shutdown are
             // unaffected — the wrapper delegates the handshake to `RustlsAcceptor`.
-            let acceptor = crate::server::tls::TlsInfoAcceptor::new(rustls_config);
+            let acceptor = crate::server::tls::TlsInfoAcceptor::new(rustls_config.clone());
             let mut server = axum_server::Server::from_tcp(std_listener).acceptor(acceptor);
             configure_http_builder(&mut server, config);
             let server_future = server
                 .handle(handle)
-                .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>());
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>());
             server_handles.push(tokio::spawn(server_future));
+            from_files.then_some(rustls_config)
         }
         None => {
             tracing::error!(
                 "No TLS configuration for {}: provide ssl_cert + ssl_key files, or set \
                 ssl_auto_cert = true. HTTPS server not started.",
-                sock_addr
+                description
             );
+            None
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    #[tokio::test]
+    async fn serves_on_inherited_socket_without_binding() {
+        use std::os::fd::IntoRawFd;
+
+        // Stand-in for a socket passed by a supervisor: bound elsewhere, handed
+        // over as a bare descriptor.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let fd = listener.into_raw_fd();
+        let inherited = unsafe { crate::server::socket_activation::listener_from_fd(fd) }
+            .expect("fd is a TCP listener");
+
+        // A configured address that is never bound while sockets are inherited.
+        let config = Config {
+            server_listen_primary: "127.0.0.1:1".to_string(),
+            server_listen_secondary: String::new(),
+            ..Config::default()
+        };
+        let app = Router::new().route("/", get(|| async { "inherited" }));
+        let handle = Handle::new();
+        let mut server_handles = Vec::new();
+        start_listeners(
+            &config,
+            app,
+            handle.clone(),
+            &mut server_handles,
+            vec![inherited],
+        )
+        .await;
+        assert_eq!(server_handles.len(), 1);
+
+        let body = reqwest::get(format!("http://{addr}/"))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "inherited");
+        handle.shutdown();
+    }
+
+    #[tokio::test]
+    async fn starts_a_listener_per_listed_address() {
+        // Reserve three free ports, then release them for the server to bind.
+        let ports: Vec<u16> = (0..3)
+            .map(|_| std::net::TcpListener::bind("127.0.0.1:0").unwrap())
+            .collect::<Vec<_>>()
+            .iter()
+            .map(|l| l.local_addr().unwrap().port())
+            .collect();
+        let config = Config {
+            server_listen: ports.iter().map(|p| format!("127.0.0.1:{p}")).collect(),
+            ..Config::default()
+        };
+        let app = Router::new().route("/", get(|| async { "listed" }));
+        let handle = Handle::new();
+        let mut server_handles = Vec::new();
+        start_listeners(
+            &config,
+            app,
+            handle.clone(),
+            &mut server_handles,
+            Vec::new(),
+        )
+        .await;
+        assert_eq!(server_handles.len(), 3);
+
+        for port in ports {
+            let body = reqwest::get(format!("http://127.0.0.1:{port}/"))
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap();
+            assert_eq!(body, "listed");
         }
+        handle.shutdown();
     }
 }
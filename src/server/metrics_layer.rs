@@ -47,8 +47,15 @@ const KNOWN_STATIC_PATHS: &[&str] = &[
     "/delete",
     "/options",
     "/healthz",
+    "/livez",
+    "/readyz",
     "/endpoints",
     "/uuid",
+    "/time",
+    "/env",
+    "/hostname",
+    "/version",
+    "/ws",
     "/ip",
     "/user-agent",
     "/headers",
@@ -59,6 +66,13 @@ const KNOWN_STATIC_PATHS: &[&str] = &[
     "/xml",
     "/html",
     "/drip",
+    "/events",
+    "/echo",
+    "/dump",
+    "/batch",
+    "/long-poll",
+    "/long-poll/trigger",
+    "/admin/resource/reset",
     "/gzip",
     "/deflate",
     "/brotli",
@@ -84,10 +98,15 @@ fn normalize_path(path: &str) -> Cow<'static, str> {
             Some(&"delay") => return Cow::Borrowed("/delay/:n"),
             Some(&"redirect") => return Cow::Borrowed("/redirect/:n"),
             Some(&"bytes") => return Cow::Borrowed("/bytes/:n"),
+            Some(&"stream-bytes") => return Cow::Borrowed("/stream-bytes/:n"),
             Some(&"cache") => return Cow::Borrowed("/cache/:n"),
             Some(&"base64") => return Cow::Borrowed("/base64/:encoded"),
             Some(&"image") => return Cow::Borrowed("/image/:format"),
             Some(&"range") => return Cow::Borrowed("/range/:n"),
+            Some(&"countdown") => return Cow::Borrowed("/countdown/:n"),
+            Some(&"resource") => return Cow::Borrowed("/resource/:id"),
+            Some(&"kv") => return Cow::Borrowed("/kv/:key"),
+            Some(&"uuid") => return Cow::Borrowed("/uuid/:count"),
             Some(&"anything") => return Cow::Borrowed("/anything/*path"),
             Some(&"cookies") => {
                 // Only set/delete are real sub-routes; bucket anything else.
@@ -121,6 +140,17 @@ mod tests {
         assert_eq!(normalize_path("/status/500"), "/status/:code");
     }
 
+    #[test]
+    fn test_normalize_stream_bytes_path() {
+        assert_eq!(normalize_path("/stream-bytes/4096"), "/stream-bytes/:n");
+    }
+
+    #[test]
+    fn test_normalize_uuid_count_path() {
+        assert_eq!(normalize_path("/uuid/10"), "/uuid/:count");
+        assert_eq!(normalize_path("/uuid"), "/uuid");
+    }
+
     #[test]
     fn test_normalize_delay_path() {
         assert_eq!(normalize_path("/delay/5"), "/delay/:n");
@@ -154,6 +184,25 @@ mod tests {
         assert_eq!(normalize_path("/range/0"), "/range/:n");
     }
 
+    #[test]
+    fn test_normalize_countdown_path() {
+        assert_eq!(normalize_path("/countdown/5"), "/countdown/:n");
+    }
+
+    #[test]
+    fn test_normalize_resource_path() {
+        assert_eq!(normalize_path("/resource/42"), "/resource/:id");
+        assert_eq!(
+            normalize_path("/admin/resource/reset"),
+            "/admin/resource/reset"
+        );
+    }
+
+    #[test]
+    fn test_normalize_kv_path() {
+        assert_eq!(normalize_path("/kv/session-1"), "/kv/:key");
+    }
+
     #[test]
     fn test_normalize_cookies_path() {
         assert_eq!(normalize_path("/cookies"), "/cookies");
@@ -2,39 +2,267 @@
 //!
 //! This module provides functionality for setting up and running the various
 //! server listeners (HTTP, HTTPS, TCP, UDP) and handling graceful shutdown.
+//! On Linux it also reports readiness and shutdown to systemd. On SIGHUP the
+//! settings that can change live (log level, chaos) are reloaded from config.
 
+pub mod access_log;
+pub mod allow_layer;
 pub mod chaos_layer;
+pub mod client_cert_layer;
+pub mod cors;
+pub mod date_layer;
 pub mod http;
 pub mod metrics_layer;
+pub mod panic_layer;
+pub mod rate_limit_layer;
 pub mod request_id;
+pub mod scenario_layer;
 pub mod shutdown;
+pub mod socket_activation;
+#[cfg(target_os = "linux")]
+pub mod systemd;
 pub mod tcp;
+pub mod timeout_layer;
 pub mod timing_layer;
 pub mod tls;
+pub mod trace_context;
+pub mod transform_layer;
 pub mod udp;
 
+use arc_swap::ArcSwap;
 use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
 use axum_server::Handle;
+use std::str::FromStr;
 use std::sync::Arc;
+use tracing::Level;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::{reload, Registry};
 
-use crate::utils::config::Config;
+use crate::routes::healthz::Readiness;
+use crate::server::shutdown::StreamingShutdown;
+use crate::utils::config::{ChaosConfig, Config, ConfigError};
+use crate::utils::server_config;
+
+/// Handle to the global subscriber's log filter (see [`Config::log_targets`]),
+/// for changing the log level while running.
+pub type LogLevelHandle = reload::Handle<Targets, Registry>;
+
+/// The parts of a running server a SIGHUP can change without a restart.
+#[derive(Clone)]
+pub struct ReloadHandles {
+    /// The log filter `main` installed on the global subscriber.
+    pub log_level: LogLevelHandle,
+    /// The `--log-level`/`-v` level from the command line, which a reload
+    /// keeps in place of the reloaded `log_level`.
+    pub log_level_override: Option<String>,
+    /// The chaos settings the app's chaos middleware reads on every request.
+    pub chaos: Arc<ArcSwap<ChaosConfig>>,
+}
+
+/// Applies the live-reloadable settings of `reloaded` that differ from
+/// `current`: the log level and filter, and the chaos settings. Anything else
+/// that changed (listeners included) is logged as needing a restart and left
+/// alone.
+pub fn apply_config_reload(current: &Config, reloaded: &Config, handles: &ReloadHandles) {
+    if reloaded.log_level != current.log_level || reloaded.log_filter != current.log_filter {
+        match Level::from_str(&reloaded.log_level.to_uppercase()) {
+            Ok(level) => {
+                let targets = reloaded.log_targets(LevelFilter::from_level(level));
+                match handles.log_level.reload(targets) {
+                    Ok(()) => match &reloaded.log_filter {
+                        Some(filter) => {
+                            tracing::info!("Log level changed to {} (filter: {})", level, filter)
+                        }
+                        None => tracing::info!("Log level changed to {}", level),
+                    },
+                    Err(e) => tracing::warn!("Failed to change log level: {}", e),
+                }
+            }
+            Err(_) => tracing::warn!(
+                "Invalid log level '{}' in reloaded config, keeping the current one",
+                reloaded.log_level
+            ),
+        }
+    }
+
+    if reloaded.chaos != current.chaos {
+        handles.chaos.store(Arc::new(reloaded.chaos.clone()));
+        if reloaded.chaos.is_enabled() {
+            tracing::info!("Chaos mode reloaded: {}", reloaded.chaos.modes.join(", "));
+        } else {
+            tracing::info!("Chaos mode disabled by reload");
+        }
+    }
+
+    let listeners_changed = server_config::listen_addresses(reloaded)
+        != server_config::listen_addresses(current)
+        || reloaded.server_listen_tcp != current.server_listen_tcp
+        || reloaded.server_listen_udp != current.server_listen_udp;
+    if listeners_changed {
+        tracing::warn!("Listener addresses changed; restart rucho to apply them");
+    }
+
+    // Everything but the live settings and listeners: compare the rest.
+    let mut rest = reloaded.clone();
+    rest.log_level.clone_from(&current.log_level);
+    rest.log_filter.clone_from(&current.log_filter);
+    rest.chaos.clone_from(&current.chaos);
+    rest.server_listen_primary
+        .clone_from(&current.server_listen_primary);
+    rest.server_listen_secondary
+        .clone_from(&current.server_listen_secondary);
+    rest.server_listen.clone_from(&current.server_listen);
+    rest.server_listen_tcp
+        .clone_from(&current.server_listen_tcp);
+    rest.server_listen_udp
+        .clone_from(&current.server_listen_udp);
+    if rest != *current {
+        tracing::warn!(
+            "Only log_level, log_filter and chaos settings reload live; restart rucho to apply the other changes"
+        );
+    }
+}
+
+/// Re-reads the config with `load` on every SIGHUP and applies what can change
+/// live (see [`apply_config_reload`]).
+///
+/// A reload with fatal load errors or a config that fails validation is
+/// logged and skipped, keeping the running settings.
+#[cfg(unix)]
+fn spawn_config_reload_on_sighup<L>(config: Config, handles: ReloadHandles, load: L)
+where
+    L: Fn() -> (Config, Vec<ConfigError>) + Send + 'static,
+{
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(signal) => signal,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to install SIGHUP handler; config reload disabled: {}",
+                e
+            );
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        let mut current = config;
+        while hangup.recv().await.is_some() {
+            tracing::info!("SIGHUP received, reloading configuration");
+            let (mut reloaded, errors) = load();
+            for error in &errors {
+                if error.is_fatal() {
+                    tracing::error!("Configuration error: {}", error);
+                } else {
+                    tracing::warn!("Configuration warning: {}", error);
+                }
+            }
+            if errors.iter().any(ConfigError::is_fatal) {
+                tracing::error!("Config reload aborted, keeping the current settings");
+                continue;
+            }
+            if let Err(e) = reloaded.validate() {
+                tracing::error!("Config reload aborted, keeping the current settings: {}", e);
+                continue;
+            }
+            if let Some(level) = &handles.log_level_override {
+                reloaded.log_level.clone_from(level);
+            }
+            apply_config_reload(&current, &reloaded, &handles);
+            // Track only what was applied, so unapplied changes keep warning.
+            current.log_level = reloaded.log_level;
+            current.log_filter = reloaded.log_filter;
+            current.chaos = reloaded.chaos;
+        }
+    });
+}
+
+/// Reloads the HTTPS certificate from `ssl_cert`/`ssl_key` on every SIGHUP, so
+/// operators can rotate certificates without a restart.
+///
+/// Each listener's `RustlsConfig` is swapped in place: new handshakes get the
+/// new certificate, existing connections are untouched. A failed reload is
+/// logged and the previous certificate keeps being served. Does nothing when
+/// no HTTPS listener is serving certificate files.
+#[cfg(unix)]
+fn spawn_tls_reload_on_sighup(config: &Config, tls_configs: Vec<RustlsConfig>) {
+    let (Some(cert), Some(key)) = (config.ssl_cert.clone(), config.ssl_key.clone()) else {
+        return;
+    };
+    if tls_configs.is_empty() {
+        return;
+    }
+    let options = server_config::TlsOptions::from_config(config);
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(signal) => signal,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to install SIGHUP handler; TLS reload disabled: {}",
+                e
+            );
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            tracing::info!("SIGHUP received, reloading TLS certificate from {}", cert);
+            for tls_config in &tls_configs {
+                match server_config::reload_rustls_config(tls_config, &cert, &key, &options).await {
+                    Ok(()) => tracing::info!("TLS certificate reloaded from {} and {}", cert, key),
+                    Err(e) => tracing::error!(
+                        "TLS certificate reload failed, keeping the current certificate: {}",
+                        e
+                    ),
+                }
+            }
+        }
+    });
+}
 
 /// Runs all configured server listeners.
 ///
 /// Sets up and starts HTTP/HTTPS, TCP, and UDP listeners based on the
-/// provided configuration, then waits for a shutdown signal.
-pub async fn run_server(config: &Config, app: Router) {
+/// provided configuration, then waits for a shutdown signal, draining
+/// in-flight requests for `shutdown_timeout_secs`. `streaming` must
+/// be the tracker `app` was built with, so shutdown can cut its streams, and
+/// `reload` must hold the chaos settings `app` was built with, so a SIGHUP can
+/// swap them. `readiness`, which `app`'s `/readyz` reports, is set once every
+/// listener is bound.
+pub async fn run_server(
+    config: &Config,
+    app: Router,
+    streaming: StreamingShutdown,
+    reload: ReloadHandles,
+    readiness: Readiness,
+) {
     let handle = Handle::new();
-    let shutdown = shutdown::shutdown_signal(handle.clone());
+    let shutdown = shutdown::shutdown_signal(
+        handle.clone(),
+        shutdown::shutdown_timeout(config.shutdown_timeout_secs),
+        streaming,
+    );
 
     let mut server_handles: Vec<tokio::task::JoinHandle<Result<(), std::io::Error>>> = Vec::new();
 
     // Setup HTTP/HTTPS listeners
-    http::setup_http_listeners(config, app.clone(), handle.clone(), &mut server_handles).await;
+    let tls_configs =
+        http::setup_http_listeners(config, app.clone(), handle.clone(), &mut server_handles).await;
+    let http_servers = server_handles.len();
+    #[cfg(unix)]
+    {
+        spawn_tls_reload_on_sighup(config, tls_configs);
+        spawn_config_reload_on_sighup(config.clone(), reload, Config::load_with_errors);
+    }
+    #[cfg(not(unix))]
+    drop((tls_configs, reload));
 
     // Setup TCP listener
     if let Some(tcp_addr_str) = &config.server_listen_tcp {
-        tcp::setup_tcp_listener(tcp_addr_str, &mut server_handles).await;
+        tcp::setup_tcp_listener(
+            tcp_addr_str,
+            config.tcp_accept_log_sample,
+            &mut server_handles,
+        )
+        .await;
     }
 
     // Setup UDP listener
@@ -50,9 +278,133 @@ pub async fn run_server(config: &Config, app: Router) {
             "{} server(s)/listener(s) started. Waiting for shutdown signal...",
             server_handles.len()
         );
+        // Every listener is bound by now, so `/readyz` and systemd may
+        // consider us started.
+        readiness.set_ready();
+        #[cfg(target_os = "linux")]
+        {
+            systemd::notify_ready();
+            systemd::spawn_watchdog();
+        }
         shutdown.await;
+        #[cfg(target_os = "linux")]
+        systemd::notify_stopping();
         tracing::info!("Shutdown signal received, all servers and listeners are stopping.");
+        // Let in-flight HTTP requests drain; each server returns once its
+        // connections close or the shutdown grace force-closes them. TCP and
+        // UDP listeners are simply dropped.
+        for server in server_handles.drain(..http_servers) {
+            let _ = server.await;
+        }
     } else {
         tracing::warn!("No server or listener instances were configured or able to start.");
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[tokio::test]
+    async fn sighup_reloads_log_level_and_chaos() {
+        let (filter, log_level) = reload::Layer::new(Targets::new().with_default(Level::INFO));
+        // The handle only works while the layer is alive.
+        let _subscriber = Registry::default().with(filter);
+
+        let current = Config::default();
+        let reloaded = Config {
+            log_level: "debug".to_string(),
+            chaos: ChaosConfig {
+                modes: vec!["failure".to_string()],
+                failure_rate: 0.5,
+                failure_codes: vec![503],
+                ..ChaosConfig::default()
+            },
+            server_listen_primary: "127.0.0.1:9999".to_string(),
+            ..Config::default()
+        };
+
+        let handles = ReloadHandles {
+            log_level,
+            log_level_override: None,
+            chaos: Arc::new(ArcSwap::from_pointee(current.chaos.clone())),
+        };
+        let expected = reloaded.clone();
+        spawn_config_reload_on_sighup(current, handles.clone(), move || {
+            (reloaded.clone(), Vec::new())
+        });
+
+        let status = std::process::Command::new("kill")
+            .args(["-HUP", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let level = || {
+            handles
+                .log_level
+                .with_current(Targets::default_level)
+                .unwrap()
+        };
+        for _ in 0..200 {
+            if level() == Some(LevelFilter::DEBUG) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(level(), Some(LevelFilter::DEBUG));
+        assert_eq!(**handles.chaos.load(), expected.chaos);
+    }
+
+    #[test]
+    fn invalid_reloaded_log_level_keeps_current() {
+        let (filter, log_level) = reload::Layer::new(Targets::new().with_default(Level::WARN));
+        let _subscriber = Registry::default().with(filter);
+        let handles = ReloadHandles {
+            log_level,
+            log_level_override: None,
+            chaos: Arc::new(ArcSwap::from_pointee(ChaosConfig::default())),
+        };
+        let reloaded = Config {
+            log_level: "loud".to_string(),
+            ..Config::default()
+        };
+
+        apply_config_reload(&Config::default(), &reloaded, &handles);
+        assert_eq!(
+            handles
+                .log_level
+                .with_current(Targets::default_level)
+                .unwrap(),
+            Some(LevelFilter::WARN)
+        );
+    }
+
+    #[test]
+    fn reload_applies_a_changed_log_filter() {
+        let (filter, log_level) = reload::Layer::new(Targets::new().with_default(Level::INFO));
+        let _subscriber = Registry::default().with(filter);
+        let handles = ReloadHandles {
+            log_level,
+            log_level_override: None,
+            chaos: Arc::new(ArcSwap::from_pointee(ChaosConfig::default())),
+        };
+        let reloaded = Config {
+            log_filter: Some("rucho=trace".to_string()),
+            ..Config::default()
+        };
+
+        apply_config_reload(&Config::default(), &reloaded, &handles);
+        let enabled = |target: &str, level: Level| {
+            handles
+                .log_level
+                .with_current(|f| f.would_enable(target, &level))
+                .unwrap()
+        };
+        assert!(enabled("rucho::server", Level::TRACE));
+        assert!(enabled("hyper", Level::INFO));
+        assert!(!enabled("hyper", Level::DEBUG));
+    }
+}
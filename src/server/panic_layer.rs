@@ -0,0 +1,85 @@
+//! Turns a panicking handler into a JSON `500` instead of a dropped
+//! connection.
+//!
+//! Without this, a panic inside a route aborts the connection's task and the
+//! client sees a reset with no response at all. [`catch_panic_layer`] wraps
+//! tower-http's `CatchPanicLayer` so the panic is logged and answered with
+//! `{"error": "Internal Server Error"}`. `build_app` applies it inside the
+//! metrics middleware, so the `500` is counted as a failure like any other.
+
+use std::any::Any;
+
+use axum::{http::StatusCode, response::Response};
+use tower_http::catch_panic::CatchPanicLayer;
+
+use crate::utils::error_response::format_error_response;
+
+/// Signature of [`panic_response`], as `CatchPanicLayer` needs a named type.
+pub type PanicHandler = fn(Box<dyn Any + Send + 'static>) -> Response;
+
+/// Returns a layer that answers a panic in any inner service with
+/// [`panic_response`].
+pub fn catch_panic_layer() -> CatchPanicLayer<PanicHandler> {
+    CatchPanicLayer::custom(panic_response as PanicHandler)
+}
+
+/// Logs the panic's message and builds the JSON `500` sent in its place.
+pub fn panic_response(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("<non-string panic payload>");
+    tracing::error!("Handler panicked: {}", message);
+    format_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::metrics_layer::metrics_middleware;
+    use crate::utils::metrics::Metrics;
+    use axum::{body::Body, extract::Request, middleware, routing::get, Router};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    async fn panics() -> &'static str {
+        panic!("boom")
+    }
+
+    #[tokio::test]
+    async fn a_panicking_handler_gets_a_json_500_counted_as_a_failure() {
+        let metrics = Arc::new(Metrics::new());
+        let recorder = metrics.clone();
+        let app = Router::new()
+            .route("/panic", get(panics))
+            .route("/ok", get(|| async { "fine" }))
+            .layer(catch_panic_layer())
+            .layer(middleware::from_fn(move |req, next| {
+                let metrics = recorder.clone();
+                async move { metrics_middleware(req, next, metrics).await }
+            }));
+
+        let request = |path: &str| Request::builder().uri(path).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request("/panic")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers()["content-type"], "application/json");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "Internal Server Error");
+        assert_eq!(metrics.get_total_failures(), 1);
+
+        // The router keeps serving after a panic.
+        let response = app.oneshot(request("/ok")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(metrics.get_total_failures(), 1);
+    }
+
+    #[test]
+    fn panic_response_accepts_formatted_payloads() {
+        let response = panic_response(Box::new(format!("index {} out of range", 3)));
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
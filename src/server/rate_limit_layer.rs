@@ -0,0 +1,359 @@
+//! Per-client-IP rate limiting middleware.
+//!
+//! Each client IP gets its own token bucket: it holds up to `burst` tokens,
+//! refills at `rps` tokens per second, and every request spends one. A request
+//! that finds its bucket empty is answered `429 Too Many Requests` with a
+//! `Retry-After` header (whole seconds until a token is available) and never
+//! reaches the handler.
+//!
+//! By default the client IP is the TCP peer address: forwarded headers are
+//! client-controlled, so a client could otherwise dodge its limit by sending a
+//! new one each time. With `rate_limit_trust_proxy` (off by default, and
+//! separate from the `trust_proxy` that `/ip` honours), it is the first
+//! `X-Forwarded-For` entry, then `X-Real-IP`, then the TCP peer — the same
+//! precedence `/ip` reports. Turn it on only behind a gateway that overwrites
+//! those headers.
+//!
+//! Opt-in via `rate_limit_rps` (0, the default, disables it).
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::utils::constants::{RATE_LIMIT_KEEP_AFTER_EVICTION, RATE_LIMIT_MAX_TRACKED_IPS};
+use crate::utils::error_response::format_error_response;
+
+/// A single client's token bucket.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token-bucket rate limiter keyed on client IP.
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Tokens added per second.
+    rate: f64,
+    /// Bucket capacity (maximum burst).
+    burst: f64,
+    /// Key on forwarding headers rather than the TCP peer.
+    trust_proxy: bool,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `rps` requests per second per IP with bursts
+    /// of up to `burst` requests. A `burst` of 0 defaults to `rps`.
+    pub fn new(rps: u32, burst: u32) -> Self {
+        let rps = rps.max(1);
+        let burst = if burst == 0 { rps } else { burst };
+        Self {
+            rate: f64::from(rps),
+            burst: f64::from(burst),
+            trust_proxy: false,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keys buckets on `X-Forwarded-For` / `X-Real-IP` when `trust_proxy` is
+    /// set, instead of the TCP peer address.
+    pub fn with_trust_proxy(mut self, trust_proxy: bool) -> Self {
+        self.trust_proxy = trust_proxy;
+        self
+    }
+
+    /// Maximum burst size (bucket capacity).
+    pub fn burst(&self) -> u32 {
+        self.burst as u32
+    }
+
+    /// Spends one token from `ip`'s bucket. Returns `Err(retry_after)` with the
+    /// time until the next token when the bucket is empty.
+    pub fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        self.check_at(ip, Instant::now())
+    }
+
+    /// [`check`](Self::check) with an injectable clock, for tests.
+    fn check_at(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if buckets.len() >= RATE_LIMIT_MAX_TRACKED_IPS && !buckets.contains_key(&ip) {
+            self.make_room(&mut buckets, now);
+        }
+
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: self.burst,
+            last_refill: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.rate).min(self.burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+
+    /// Shrinks a full map to at most [`RATE_LIMIT_KEEP_AFTER_EVICTION`]
+    /// buckets, so the map never exceeds [`RATE_LIMIT_MAX_TRACKED_IPS`].
+    ///
+    /// Buckets that have refilled to capacity go first — forgetting them is
+    /// indistinguishable from keeping them. If that isn't enough, the least
+    /// recently seen clients are forgotten too, and start over with a full
+    /// bucket. Shrinking by a margin keeps the O(n) pass rare even when every
+    /// request comes from a new address.
+    fn make_room(&self, buckets: &mut HashMap<IpAddr, Bucket>, now: Instant) {
+        let full_after = Duration::from_secs_f64(self.burst / self.rate);
+        buckets.retain(|_, b| now.saturating_duration_since(b.last_refill) < full_after);
+
+        let excess = buckets.len().saturating_sub(RATE_LIMIT_KEEP_AFTER_EVICTION);
+        if excess == 0 {
+            return;
+        }
+        let mut by_age: Vec<(Instant, IpAddr)> =
+            buckets.iter().map(|(ip, b)| (b.last_refill, *ip)).collect();
+        by_age.select_nth_unstable(excess - 1);
+        for (_, ip) in &by_age[..excess] {
+            buckets.remove(ip);
+        }
+    }
+}
+
+/// Resolves the client IP. With `trust_proxy`: the first `X-Forwarded-For`
+/// entry, then `X-Real-IP`, then the TCP peer, with unparseable header values
+/// falling through to the next source. Without it: the TCP peer.
+fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trust_proxy: bool) -> Option<IpAddr> {
+    let peer_ip = peer.map(|addr| addr.ip());
+    if !trust_proxy {
+        return peer_ip;
+    }
+    let header_ip = |name: &str, first_entry: bool| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| {
+                if first_entry {
+                    s.split(',').next().unwrap_or(s)
+                } else {
+                    s
+                }
+            })
+            .and_then(|s| s.trim().parse::<IpAddr>().ok())
+    };
+    header_ip("x-forwarded-for", true)
+        .or_else(|| header_ip("x-real-ip", false))
+        .or(peer_ip)
+}
+
+/// Middleware that enforces `limiter` per client IP, taking the IP from
+/// forwarding headers only if the limiter trusts them.
+///
+/// Requests over the limit get `429 Too Many Requests` with a `Retry-After`
+/// header (seconds, rounded up, at least 1). Requests whose client IP can't be
+/// determined are let through.
+pub async fn rate_limit_middleware(
+    request: Request,
+    next: Next,
+    limiter: Arc<RateLimiter>,
+) -> Response<Body> {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ci| ci.0);
+    let Some(ip) = client_ip(request.headers(), peer, limiter.trust_proxy) else {
+        return next.run(request).await;
+    };
+
+    match limiter.check(ip) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            let mut response =
+                format_error_response(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded");
+            let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from(seconds));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    const IP: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 1));
+
+    #[test]
+    fn bucket_allows_burst_then_refills() {
+        let limiter = RateLimiter::new(2, 3);
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check_at(IP, start).is_ok());
+        }
+        let retry = limiter.check_at(IP, start).unwrap_err();
+        assert!((retry.as_secs_f64() - 0.5).abs() < 1e-9, "{retry:?}");
+
+        // Half a second refills one token at 2 rps.
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.check_at(IP, later).is_ok());
+        assert!(limiter.check_at(IP, later).is_err());
+    }
+
+    #[test]
+    fn buckets_are_per_ip() {
+        let limiter = RateLimiter::new(1, 1);
+        let now = Instant::now();
+        let other = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 2));
+        assert!(limiter.check_at(IP, now).is_ok());
+        assert!(limiter.check_at(IP, now).is_err());
+        assert!(limiter.check_at(other, now).is_ok());
+    }
+
+    #[test]
+    fn burst_defaults_to_rps() {
+        let limiter = RateLimiter::new(4, 0);
+        let now = Instant::now();
+        assert_eq!(
+            (0..10)
+                .filter(|_| limiter.check_at(IP, now).is_ok())
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn evicts_refilled_buckets_when_full() {
+        let limiter = RateLimiter::new(10, 1);
+        let start = Instant::now();
+        for i in 0..RATE_LIMIT_MAX_TRACKED_IPS as u32 {
+            let ip = IpAddr::V4(std::net::Ipv4Addr::from(i));
+            assert!(limiter.check_at(ip, start).is_ok());
+        }
+        // Long after every bucket refilled, a new IP triggers eviction.
+        limiter
+            .check_at(IP, start + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn flood_of_new_ips_stays_within_the_cap() {
+        let limiter = RateLimiter::new(1, 5);
+        let start = Instant::now();
+        // Every bucket is still draining, so none can be evicted as refilled.
+        let flood = RATE_LIMIT_MAX_TRACKED_IPS as u32 * 3;
+        for i in 0..flood {
+            let ip = IpAddr::V4(std::net::Ipv4Addr::from(i));
+            let now = start + Duration::from_micros(u64::from(i));
+            assert!(limiter.check_at(ip, now).is_ok());
+            assert!(limiter.buckets.lock().unwrap().len() <= RATE_LIMIT_MAX_TRACKED_IPS);
+        }
+        // The most recent clients are the ones still tracked.
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(buckets.contains_key(&IpAddr::V4(std::net::Ipv4Addr::from(flood - 1))));
+        assert!(!buckets.contains_key(&IpAddr::V4(std::net::Ipv4Addr::from(0))));
+    }
+
+    #[test]
+    fn client_ip_precedence() {
+        let peer: SocketAddr = "192.0.2.9:4000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_ip(&headers, Some(peer), true), Some(peer.ip()));
+        assert_eq!(client_ip(&headers, None, true), None);
+
+        headers.insert("x-real-ip", "198.51.100.2".parse().unwrap());
+        assert_eq!(
+            client_ip(&headers, Some(peer), true),
+            "198.51.100.2".parse().ok()
+        );
+
+        headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        assert_eq!(
+            client_ip(&headers, Some(peer), true),
+            "203.0.113.7".parse().ok()
+        );
+
+        headers.insert("x-forwarded-for", "not-an-ip".parse().unwrap());
+        assert_eq!(
+            client_ip(&headers, Some(peer), true),
+            "198.51.100.2".parse().ok()
+        );
+    }
+
+    #[test]
+    fn client_ip_ignores_forwarding_headers_unless_trusted() {
+        let peer: SocketAddr = "192.0.2.9:4000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.7".parse().unwrap());
+        headers.insert("x-real-ip", "198.51.100.2".parse().unwrap());
+        assert_eq!(client_ip(&headers, Some(peer), false), Some(peer.ip()));
+        assert_eq!(client_ip(&headers, None, false), None);
+    }
+
+    /// A router behind the rate limiter, served with a fixed peer address.
+    fn limited_app(limiter: RateLimiter) -> Router {
+        let limiter = Arc::new(limiter);
+        let peer: SocketAddr = "192.0.2.9:4000".parse().unwrap();
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(middleware::from_fn(move |req, next| {
+                let limiter = limiter.clone();
+                async move { rate_limit_middleware(req, next, limiter).await }
+            }))
+            .layer(axum::Extension(ConnectInfo(peer)))
+    }
+
+    async fn send(app: &Router, xff: &str) -> Response {
+        let req = Request::builder()
+            .uri("/")
+            .header("x-forwarded-for", xff)
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(req).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn burst_over_limit_gets_429_with_retry_after() {
+        let app = limited_app(RateLimiter::new(1, 3).with_trust_proxy(true));
+
+        for _ in 0..3 {
+            assert_eq!(send(&app, "203.0.113.1").await.status(), StatusCode::OK);
+        }
+        for _ in 0..2 {
+            let resp = send(&app, "203.0.113.1").await;
+            assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(resp.headers()[header::RETRY_AFTER], "1");
+        }
+        // A different client is unaffected.
+        assert_eq!(send(&app, "203.0.113.2").await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn untrusted_forwarding_headers_cannot_dodge_the_limit() {
+        // Not trusting forwarding headers is the default.
+        let app = limited_app(RateLimiter::new(1, 2));
+
+        assert_eq!(send(&app, "203.0.113.1").await.status(), StatusCode::OK);
+        assert_eq!(send(&app, "203.0.113.2").await.status(), StatusCode::OK);
+        // A fresh X-Forwarded-For doesn't buy a fresh bucket: it's the peer's.
+        assert_eq!(
+            send(&app, "203.0.113.3").await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+}
@@ -6,27 +6,63 @@
 //! natively, or an upstream client may set it), that value is propagated to the
 //! response unchanged; otherwise a fresh UUID v4 is minted.
 //!
-//! The request is forwarded untouched, so echo endpoints (`/get`, `/headers`)
-//! reflect exactly what the client sent. The response header is set only when a
-//! handler has not already set one (e.g. `/response-headers`), so a handler's
-//! deliberate value wins. When the header appears multiple times inbound, the
-//! first value is used (`HeaderMap::get` semantics).
+//! The request's headers are forwarded untouched, so echo endpoints (`/get`,
+//! `/headers`) reflect exactly what the client sent; the id travels as a
+//! [`RequestId`] request extension instead. `TraceLayer` records it on each
+//! request's span (see [`make_request_span`]) and the JSON echo handlers report
+//! it as `request_id`, so a response can be matched to its log lines. The
+//! response header is set only when a handler has not already set one (e.g.
+//! `/response-headers`), so a handler's deliberate value wins. When the header
+//! appears multiple times inbound, the first value is used (`HeaderMap::get`
+//! semantics).
 //!
 //! Gated by the `request_id_enabled` config toggle (default on).
 
 use axum::{body::Body, extract::Request, middleware::Next, response::Response};
 use http::header::HeaderValue;
+use tracing::Span;
 use uuid::Uuid;
 
 /// Canonical correlation-ID header name (lowercase, HTTP/2-safe).
 const HEADER: &str = "x-request-id";
 
+/// The request's correlation id, stored as a request extension by
+/// [`request_id_middleware`].
+#[derive(Debug, Clone)]
+pub struct RequestId(HeaderValue);
+
+impl RequestId {
+    /// The id as a string. Inbound ids are opaque header bytes; one that isn't
+    /// visible ASCII is rendered lossily.
+    pub fn as_str(&self) -> std::borrow::Cow<'_, str> {
+        String::from_utf8_lossy(self.0.as_bytes())
+    }
+}
+
+/// Builds the per-request `TraceLayer` span: the fields of tower-http's
+/// `DefaultMakeSpan` (`method`, `uri`, `version`) plus `request_id` when the
+/// request-id middleware ran, so every request/response log line carries it.
+pub fn make_request_span<B>(request: &http::Request<B>) -> Span {
+    let span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+        request_id = tracing::field::Empty,
+    );
+    if let Some(id) = request.extensions().get::<RequestId>() {
+        span.record("request_id", tracing::field::display(id.as_str()));
+    }
+    span
+}
+
 /// Middleware that ensures every response carries an `X-Request-Id` header.
 ///
 /// Propagates a non-blank inbound `X-Request-Id` when present, otherwise mints a
-/// UUID v4. A value a handler already set is left untouched; the request itself
-/// is never modified.
-pub async fn request_id_middleware(request: Request, next: Next) -> Response<Body> {
+/// UUID v4, and exposes it to inner layers as a [`RequestId`] extension. A value
+/// a handler already set is left untouched; the request's headers are never
+/// modified.
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response<Body> {
     // Reuse a non-blank inbound id (mesh/client correlation), else mint one.
     let request_id = request
         .headers()
@@ -34,6 +70,9 @@ pub async fn request_id_middleware(request: Request, next: Next) -> Response<Bod
         .filter(|value| value.as_bytes().iter().any(|b| !b.is_ascii_whitespace()))
         .cloned()
         .unwrap_or_else(new_request_id);
+    request
+        .extensions_mut()
+        .insert(RequestId(request_id.clone()));
 
     let mut response = next.run(request).await;
 
@@ -49,11 +88,10 @@ pub async fn request_id_middleware(request: Request, next: Next) -> Response<Bod
 
 /// Generates a fresh request ID as a UUID v4 header value.
 fn new_request_id() -> HeaderValue {
-    // A UUID v4 string is ASCII hex + hyphens, so this never fails. (Inbound
-    // CR/LF can't reach here either — httparse/http reject control bytes at
-    // parse time — but this path only ever sees a freshly-minted UUID anyway.)
+    // A UUID v4 string is ASCII hex + hyphens, so the nil-UUID fallback is
+    // never taken; it only keeps this path free of panics.
     HeaderValue::from_str(&Uuid::new_v4().to_string())
-        .expect("UUID v4 string is always a valid header value")
+        .unwrap_or_else(|_| HeaderValue::from_static("00000000-0000-0000-0000-000000000000"))
 }
 
 #[cfg(test)]
@@ -174,6 +212,35 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn id_is_exposed_as_request_extension() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(
+                    |axum::Extension(id): axum::Extension<RequestId>| async move {
+                        id.as_str().into_owned()
+                    },
+                ),
+            )
+            .layer(axum::middleware::from_fn(request_id_middleware));
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(HEADER, "abc-123")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"abc-123");
+    }
+
     #[tokio::test]
     async fn ids_are_unique_per_request() {
         let first = app()
@@ -0,0 +1,197 @@
+//! Per-request deterministic behaviors selected by the `X-Rucho-Scenario`
+//! header.
+//!
+//! Scenarios are defined in config (`scenario.<name>.<field>`, see
+//! [`Scenario`]) so a test suite can drive a specific server behavior per
+//! request (a fixed status, delay, or body) without query-parameter noise in
+//! the URL under test. A request naming a scenario first waits out its
+//! `delay_ms`; then, if the scenario sets a `status` or `body`, the scenario
+//! answers in place of the route, otherwise the route answers as usual.
+//! Requests without the header are untouched; an unknown scenario name is a
+//! `400 Bad Request`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::utils::config::Scenario;
+use crate::utils::error_response::format_error_response;
+use crate::utils::json_response::format_json_response;
+
+/// Request header naming the scenario to run.
+pub const SCENARIO_HEADER: &str = "x-rucho-scenario";
+
+/// Builds the response for a scenario that answers the request itself.
+///
+/// The body is sent verbatim, as `application/json` if it parses as JSON and
+/// `text/plain` otherwise. A scenario with a status but no body gets a small
+/// JSON body naming the scenario.
+fn scenario_response(name: &str, scenario: &Scenario) -> Response {
+    let status = scenario
+        .status
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .unwrap_or(StatusCode::OK);
+    let mut response = match &scenario.body {
+        Some(body) => {
+            let content_type = if serde_json::from_str::<serde_json::Value>(body).is_ok() {
+                "application/json"
+            } else {
+                "text/plain; charset=utf-8"
+            };
+            ([(header::CONTENT_TYPE, content_type)], body.clone()).into_response()
+        }
+        None => format_json_response(json!({
+            "scenario": name,
+            "status": status.as_u16(),
+        })),
+    };
+    *response.status_mut() = status;
+    if let Ok(value) = HeaderValue::from_str(name) {
+        response.headers_mut().insert(SCENARIO_HEADER, value);
+    }
+    response
+}
+
+/// Middleware that applies the scenario named by `X-Rucho-Scenario`.
+pub async fn scenario_middleware(
+    request: Request,
+    next: Next,
+    scenarios: Arc<BTreeMap<String, Scenario>>,
+) -> Response {
+    let Some(value) = request.headers().get(SCENARIO_HEADER) else {
+        return next.run(request).await;
+    };
+    let name = value.to_str().map(str::trim).unwrap_or_default();
+    let Some(scenario) = scenarios.get(name) else {
+        let known: Vec<&str> = scenarios.keys().map(String::as_str).collect();
+        let known = if known.is_empty() {
+            "none are configured".to_string()
+        } else {
+            format!("known scenarios: {}", known.join(", "))
+        };
+        return format_error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("Unknown scenario '{name}' ({known})"),
+        );
+    };
+
+    if let Some(ms) = scenario.delay_ms.filter(|&ms| ms > 0) {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+    }
+    if scenario.responds() {
+        scenario_response(name, scenario)
+    } else {
+        next.run(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn scenarios() -> BTreeMap<String, Scenario> {
+        BTreeMap::from([
+            (
+                "outage".to_string(),
+                Scenario {
+                    status: Some(503),
+                    body: Some(r#"{"error":"down for maintenance"}"#.to_string()),
+                    ..Scenario::default()
+                },
+            ),
+            (
+                "slow".to_string(),
+                Scenario {
+                    delay_ms: Some(1500),
+                    ..Scenario::default()
+                },
+            ),
+            (
+                "teapot".to_string(),
+                Scenario {
+                    status: Some(418),
+                    ..Scenario::default()
+                },
+            ),
+        ])
+    }
+
+    fn app() -> Router {
+        let scenarios = Arc::new(scenarios());
+        Router::new()
+            .route("/get", get(|| async { "from the route" }))
+            .layer(middleware::from_fn(move |req, next| {
+                let scenarios = scenarios.clone();
+                async move { scenario_middleware(req, next, scenarios).await }
+            }))
+    }
+
+    async fn send(scenario: Option<&str>) -> Response {
+        let mut req = Request::get("/get");
+        if let Some(name) = scenario {
+            req = req.header(SCENARIO_HEADER, name);
+        }
+        app()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn fixed_status_and_body_scenario_answers_in_place_of_route() {
+        let response = send(Some("outage")).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()[SCENARIO_HEADER], "outage");
+        assert_eq!(
+            body_text(response).await,
+            r#"{"error":"down for maintenance"}"#
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn delay_only_scenario_waits_then_reaches_route() {
+        let start = tokio::time::Instant::now();
+        let response = send(Some("slow")).await;
+        assert_eq!(start.elapsed(), Duration::from_millis(1500));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_text(response).await, "from the route");
+    }
+
+    #[tokio::test]
+    async fn status_only_scenario_gets_json_body() {
+        let response = send(Some("teapot")).await;
+        assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+        let body: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+        assert_eq!(body["scenario"], "teapot");
+        assert_eq!(body["status"], 418);
+    }
+
+    #[tokio::test]
+    async fn no_header_reaches_route_and_unknown_name_is_rejected() {
+        let response = send(None).await;
+        assert_eq!(body_text(response).await, "from the route");
+
+        let response = send(Some("nope")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_text(response).await;
+        assert!(body.contains("outage, slow, teapot"), "{body}");
+    }
+}
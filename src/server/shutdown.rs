@@ -1,24 +1,247 @@
 //! Graceful shutdown handling.
+//!
+//! On SIGINT/SIGTERM the HTTP listeners stop accepting and in-flight requests
+//! get `shutdown_timeout_secs` to finish (or forever, when it is 0). Streaming
+//! responses (`/drip`, `/stream-bytes/:n`, `/events`, `/anything?echo=stream`)
+//! have their own grace, `streaming_shutdown_grace_secs`: each streaming body
+//! is wrapped by
+//! [`StreamingShutdown::guard`], and once that grace runs out every guarded
+//! stream still open is cut with an error, so the client sees a truncated
+//! response rather than a clean end.
+//!
+//! The same tracker caps how many guarded streams may be open at once
+//! (`max_streaming_responses`): past the cap, [`StreamingShutdown::guard`]
+//! refuses and the endpoint answers 503.
 
+use axum::body::Bytes;
+use axum::BoxError;
 use axum_server::Handle;
+use futures_util::{Stream, StreamExt};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::signal;
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
 
-/// Grace period for in-flight requests to complete before forced shutdown.
-const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+use crate::utils::constants::{
+    DEFAULT_MAX_STREAMING_RESPONSES, DEFAULT_STREAMING_SHUTDOWN_GRACE_SECS,
+};
+
+/// Tracks streaming responses so shutdown can cut them after their own grace.
+///
+/// Cheap to clone; clones share the same set of streams.
+#[derive(Debug, Clone)]
+pub struct StreamingShutdown {
+    inner: Arc<StreamingInner>,
+}
+
+#[derive(Debug)]
+struct StreamingInner {
+    grace: Duration,
+    cancelled: watch::Sender<bool>,
+    active: AtomicUsize,
+    /// One permit per stream allowed open at once; `None` means unlimited.
+    slots: Option<Arc<Semaphore>>,
+    max_streams: usize,
+}
+
+/// Returned by [`StreamingShutdown::guard`] when `max_streams` streams are
+/// already open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamLimitReached {
+    /// The configured cap.
+    pub max_streams: usize,
+}
+
+impl std::fmt::Display for StreamLimitReached {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "too many streaming responses in flight (max {})",
+            self.max_streams
+        )
+    }
+}
+
+impl std::error::Error for StreamLimitReached {}
+
+/// Counts one open guarded stream, and holds its slot, for as long as it lives.
+struct ActiveStream {
+    inner: Arc<StreamingInner>,
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+impl ActiveStream {
+    fn new(inner: Arc<StreamingInner>) -> Result<Self, StreamLimitReached> {
+        let slot = match &inner.slots {
+            Some(slots) => {
+                Some(
+                    slots
+                        .clone()
+                        .try_acquire_owned()
+                        .map_err(|_| StreamLimitReached {
+                            max_streams: inner.max_streams,
+                        })?,
+                )
+            }
+            None => None,
+        };
+        inner.active.fetch_add(1, Ordering::Relaxed);
+        Ok(Self { inner, _slot: slot })
+    }
+}
+
+impl Drop for ActiveStream {
+    fn drop(&mut self) {
+        self.inner.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for StreamingShutdown {
+    fn default() -> Self {
+        Self::with_max_streams(
+            Duration::from_secs(DEFAULT_STREAMING_SHUTDOWN_GRACE_SECS),
+            DEFAULT_MAX_STREAMING_RESPONSES,
+        )
+    }
+}
+
+impl StreamingShutdown {
+    /// Creates a tracker whose streams are cut `grace` after shutdown starts,
+    /// with no cap on how many may be open.
+    pub fn new(grace: Duration) -> Self {
+        Self::with_max_streams(grace, 0)
+    }
+
+    /// Like [`new`](Self::new), but at most `max_streams` guarded streams may
+    /// be open at once (`0` means unlimited).
+    pub fn with_max_streams(grace: Duration, max_streams: usize) -> Self {
+        Self {
+            inner: Arc::new(StreamingInner {
+                grace,
+                cancelled: watch::channel(false).0,
+                active: AtomicUsize::new(0),
+                slots: (max_streams > 0).then(|| Arc::new(Semaphore::new(max_streams))),
+                max_streams,
+            }),
+        }
+    }
+
+    /// How long streams may run once shutdown starts.
+    pub fn grace(&self) -> Duration {
+        self.inner.grace
+    }
+
+    /// Number of guarded streams currently open.
+    pub fn active(&self) -> usize {
+        self.inner.active.load(Ordering::Relaxed)
+    }
+
+    /// Cuts every guarded stream now, and any guarded later.
+    pub fn cancel(&self) {
+        self.inner.cancelled.send_replace(true);
+    }
+
+    /// Wraps a response body stream so [`cancel`](Self::cancel) ends it with
+    /// an error. Items pass through unchanged until then.
+    ///
+    /// Fails with [`StreamLimitReached`] when `max_streams` guarded streams
+    /// are already open; the slot frees when the returned stream is dropped.
+    pub fn guard<S, T, E>(
+        &self,
+        stream: S,
+    ) -> Result<impl Stream<Item = Result<Bytes, BoxError>> + Send + 'static, StreamLimitReached>
+    where
+        S: Stream<Item = Result<T, E>> + Send + 'static,
+        T: Into<Bytes> + 'static,
+        E: Into<BoxError> + 'static,
+    {
+        let state = (
+            stream.boxed(),
+            self.inner.cancelled.subscribe(),
+            ActiveStream::new(self.inner.clone())?,
+        );
+        Ok(futures_util::stream::unfold(
+            Some(state),
+            |state| async move {
+                let (mut stream, mut cancelled, active) = state?;
+                let next = tokio::select! {
+                    biased;
+                    // The sender lives as long as `active`, so this only fires on cancel.
+                    _ = cancelled.wait_for(|cut| *cut) => None,
+                    item = stream.next() => Some(item?),
+                };
+                match next {
+                    Some(item) => {
+                        let item = item.map(Into::into).map_err(Into::into);
+                        Some((item, Some((stream, cancelled, active))))
+                    }
+                    None => Some((Err(BoxError::from("server shutting down")), None)),
+                }
+            },
+        ))
+    }
+}
+
+/// Converts `shutdown_timeout_secs` into a drain timeout: `0` means wait
+/// indefinitely (`None`).
+pub fn shutdown_timeout(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// The timeout handed to [`Handle::graceful_shutdown`]: the longer of `drain`
+/// and the streaming grace, so a streaming grace longer than the drain is
+/// honored. `None` (wait indefinitely) stays `None`.
+pub fn graceful_shutdown_timeout(
+    drain: Option<Duration>,
+    streaming_grace: Duration,
+) -> Option<Duration> {
+    drain.map(|drain| drain.max(streaming_grace))
+}
+
+/// Starts graceful shutdown on `handle` and schedules the streaming cut.
+///
+/// Connections are force-closed once [`graceful_shutdown_timeout`] runs out,
+/// or never when `drain` is `None`; guarded streams are cut once the streaming
+/// grace runs out.
+pub fn begin_graceful_shutdown(
+    handle: &Handle,
+    drain: Option<Duration>,
+    streaming: &StreamingShutdown,
+) {
+    let grace = streaming.grace();
+    handle.graceful_shutdown(graceful_shutdown_timeout(drain, grace));
+    let active = streaming.active();
+    if active > 0 {
+        tracing::info!(
+            "{active} streaming response(s) in flight; cutting them in {}s",
+            grace.as_secs()
+        );
+    }
+    let streaming = streaming.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(grace).await;
+        streaming.cancel();
+    });
+}
 
 /// Listens for a shutdown signal and initiates graceful shutdown.
 ///
 /// Resolves when either **SIGINT** (Ctrl+C) or, on Unix, **SIGTERM** is
-/// received, then triggers graceful shutdown on the provided `Handle` with a
-/// 5-second timeout for in-flight requests.
+/// received, then triggers graceful shutdown on the provided `Handle` with the
+/// `drain` timeout for in-flight requests (`None` waits indefinitely), and the
+/// `streaming` grace for streaming responses (see [`begin_graceful_shutdown`]).
 ///
 /// SIGTERM handling matters because container runtimes (Docker, Kubernetes,
 /// Kong Mesh / Kuma sidecars) stop a process by sending SIGTERM, *not* SIGINT.
 /// Without it, the default SIGTERM disposition hard-kills the process and drops
 /// in-flight requests instead of draining them. On non-Unix targets only SIGINT
 /// is available, so the SIGTERM branch is compiled out.
-pub async fn shutdown_signal(handle: Handle) {
+pub async fn shutdown_signal(
+    handle: Handle,
+    drain: Option<Duration>,
+    streaming: StreamingShutdown,
+) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -43,12 +266,98 @@ pub async fn shutdown_signal(handle: Handle) {
     };
 
     tracing::info!("{signal} received, starting graceful shutdown");
-    handle.graceful_shutdown(Some(SHUTDOWN_GRACE));
+    begin_graceful_shutdown(&handle, drain, &streaming);
 }
 
-#[cfg(all(test, unix))]
+#[cfg(test)]
 mod tests {
     use super::*;
+    use futures_util::stream;
+
+    fn ticking(n: u8) -> impl Stream<Item = Result<Vec<u8>, std::io::Error>> {
+        stream::unfold(0u8, move |i| async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            (i < n).then(|| (Ok(vec![i]), i + 1))
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn guarded_stream_passes_items_through() {
+        let streaming = StreamingShutdown::default();
+        let items: Vec<_> = streaming.guard(ticking(3)).unwrap().collect().await;
+        let items: Vec<Bytes> = items.into_iter().map(Result::unwrap).collect();
+        assert_eq!(items, [vec![0], vec![1], vec![2]]);
+        assert_eq!(streaming.active(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancel_cuts_open_streams_with_an_error() {
+        let streaming = StreamingShutdown::new(Duration::from_millis(2500));
+        let mut guarded = Box::pin(streaming.guard(ticking(10)).unwrap());
+        assert_eq!(streaming.active(), 1);
+
+        let start = tokio::time::Instant::now();
+        let handle = Handle::new();
+        begin_graceful_shutdown(&handle, Some(Duration::from_secs(5)), &streaming);
+
+        assert!(guarded.next().await.unwrap().is_ok());
+        assert!(guarded.next().await.unwrap().is_ok());
+        // The third item is due at 3s, after the 2.5s grace.
+        let cut = guarded.next().await.unwrap();
+        assert!(cut.is_err());
+        assert_eq!(start.elapsed(), Duration::from_millis(2500));
+        assert!(guarded.next().await.is_none());
+        drop(guarded);
+        assert_eq!(streaming.active(), 0);
+    }
+
+    #[test]
+    fn shutdown_timeout_zero_waits_indefinitely() {
+        assert_eq!(shutdown_timeout(0), None);
+        assert_eq!(shutdown_timeout(30), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn graceful_shutdown_timeout_uses_configured_drain() {
+        let secs = Duration::from_secs;
+        // The configured drain is passed through when it covers the streams...
+        assert_eq!(
+            graceful_shutdown_timeout(shutdown_timeout(30), secs(5)),
+            Some(secs(30))
+        );
+        // ...stretched to a longer streaming grace...
+        assert_eq!(
+            graceful_shutdown_timeout(shutdown_timeout(2), secs(10)),
+            Some(secs(10))
+        );
+        // ...and left unbounded when it is 0.
+        assert_eq!(
+            graceful_shutdown_timeout(shutdown_timeout(0), secs(10)),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn guard_refuses_streams_past_the_limit() {
+        let streaming = StreamingShutdown::with_max_streams(Duration::from_secs(1), 2);
+        let first = streaming.guard(ticking(1)).unwrap();
+        let second = streaming.guard(ticking(1)).unwrap();
+        let refused = streaming.guard(ticking(1)).err();
+        assert_eq!(refused, Some(StreamLimitReached { max_streams: 2 }));
+        assert_eq!(streaming.active(), 2);
+
+        // Finishing a stream frees its slot.
+        drop(first);
+        assert!(streaming.guard(ticking(1)).is_ok());
+        drop(second);
+        assert_eq!(streaming.active(), 0);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod signal_tests {
+    use super::*;
+    use crate::utils::constants::DEFAULT_SHUTDOWN_TIMEOUT_SECS;
 
     /// Sending SIGTERM must resolve `shutdown_signal` and initiate graceful
     /// shutdown — the regression this module exists to prevent (the handler
@@ -56,7 +365,11 @@ mod tests {
     #[tokio::test]
     async fn sigterm_triggers_graceful_shutdown() {
         let handle = Handle::new();
-        let task = tokio::spawn(shutdown_signal(handle.clone()));
+        let task = tokio::spawn(shutdown_signal(
+            handle.clone(),
+            shutdown_timeout(DEFAULT_SHUTDOWN_TIMEOUT_SECS),
+            StreamingShutdown::default(),
+        ));
 
         // Let the spawned task be polled so the SIGTERM handler is installed
         // before we raise the signal (otherwise the default disposition would
@@ -0,0 +1,140 @@
+//! Inherited listening sockets (systemd socket activation protocol).
+//!
+//! A supervisor that already holds rucho's listening sockets — systemd with a
+//! `.socket` unit, or a restart helper handing sockets from an old rucho process
+//! to a new one — passes them as open file descriptors starting at fd 3, with
+//! `LISTEN_FDS` set to their count and `LISTEN_PID` to the receiving process's
+//! pid. Serving on those descriptors instead of binding means no connection is
+//! refused while the process is swapped out: the kernel keeps queueing on the
+//! socket in between.
+//!
+//! [`take_inherited_listeners`] adopts such descriptors; `setup_http_listeners`
+//! serves on them in place of binding its configured addresses. Only TCP stream
+//! sockets are adopted; anything else is left open and skipped with a warning.
+//! Unix only — elsewhere no sockets are ever inherited.
+
+use std::net::TcpListener;
+
+/// First inherited descriptor (`SD_LISTEN_FDS_START`); 0-2 are stdio.
+#[cfg(unix)]
+const LISTEN_FDS_START: std::os::fd::RawFd = 3;
+
+/// Number of descriptors the environment says were passed to process `pid`.
+///
+/// Zero unless `LISTEN_FDS` is a positive integer and `LISTEN_PID`, when set,
+/// names `pid` — the variables are inherited by child processes too, and a
+/// child must not claim its parent's sockets.
+fn listen_fd_count(listen_pid: Option<&str>, listen_fds: Option<&str>, pid: u32) -> usize {
+    if let Some(listen_pid) = listen_pid {
+        if listen_pid.trim().parse::<u32>().ok() != Some(pid) {
+            return 0;
+        }
+    }
+    listen_fds
+        .and_then(|n| n.trim().parse::<usize>().ok())
+        .unwrap_or(0)
+}
+
+/// Adopts the listening sockets passed to this process, if any, in descriptor
+/// order.
+///
+/// Consumes the `LISTEN_PID`/`LISTEN_FDS`/`LISTEN_FDNAMES` variables so they
+/// aren't passed on to child processes. Call at most once: the descriptors are
+/// owned by the returned listeners.
+pub fn take_inherited_listeners() -> Vec<TcpListener> {
+    let count = listen_fd_count(
+        std::env::var("LISTEN_PID").ok().as_deref(),
+        std::env::var("LISTEN_FDS").ok().as_deref(),
+        std::process::id(),
+    );
+    for name in ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"] {
+        std::env::remove_var(name);
+    }
+    if count == 0 {
+        return Vec::new();
+    }
+    adopt(count)
+}
+
+#[cfg(unix)]
+fn adopt(count: usize) -> Vec<TcpListener> {
+    let Ok(count) = std::os::fd::RawFd::try_from(count) else {
+        tracing::warn!("LISTEN_FDS={} is out of range; ignoring", count);
+        return Vec::new();
+    };
+    (LISTEN_FDS_START..LISTEN_FDS_START.saturating_add(count))
+        // SAFETY: the socket activation protocol hands these descriptors to this
+        // process, and `take_inherited_listeners` claims them only once.
+        .filter_map(|fd| unsafe { listener_from_fd(fd) })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn adopt(count: usize) -> Vec<TcpListener> {
+    tracing::warn!(
+        "LISTEN_FDS={} set, but inherited sockets are only supported on Unix; ignoring",
+        count
+    );
+    Vec::new()
+}
+
+/// Wraps `fd` as a TCP listener if it is a TCP stream socket.
+///
+/// # Safety
+///
+/// `fd` must be an open descriptor owned by nothing else in this process; on
+/// success, ownership moves to the returned listener.
+#[cfg(unix)]
+pub(crate) unsafe fn listener_from_fd(fd: std::os::fd::RawFd) -> Option<TcpListener> {
+    use std::os::fd::{FromRawFd, IntoRawFd};
+
+    let socket = socket2::Socket::from_raw_fd(fd);
+    let is_tcp_stream = socket.r#type().ok() == Some(socket2::Type::STREAM)
+        && socket
+            .local_addr()
+            .is_ok_and(|addr| addr.as_socket().is_some());
+    if !is_tcp_stream {
+        tracing::warn!("Inherited fd {} is not a TCP stream socket; skipping", fd);
+        // Leave the descriptor open: it isn't ours to interpret or close.
+        let _ = socket.into_raw_fd();
+        return None;
+    }
+    Some(socket.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_requires_matching_pid() {
+        assert_eq!(listen_fd_count(Some("42"), Some("2"), 42), 2);
+        assert_eq!(listen_fd_count(None, Some("1"), 42), 1);
+        assert_eq!(listen_fd_count(Some("41"), Some("2"), 42), 0);
+        assert_eq!(listen_fd_count(Some("junk"), Some("2"), 42), 0);
+    }
+
+    #[test]
+    fn count_is_zero_without_valid_listen_fds() {
+        assert_eq!(listen_fd_count(Some("42"), None, 42), 0);
+        assert_eq!(listen_fd_count(Some("42"), Some("-1"), 42), 0);
+        assert_eq!(listen_fd_count(None, Some(""), 42), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn adopts_tcp_listener_and_skips_other_fds() {
+        use std::os::fd::{AsRawFd, IntoRawFd};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let fd = listener.into_raw_fd();
+        let adopted = unsafe { listener_from_fd(fd) }.expect("TCP listener is adopted");
+        assert_eq!(adopted.local_addr().unwrap(), addr);
+
+        let udp = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        assert!(unsafe { listener_from_fd(udp.as_raw_fd()) }.is_none());
+        // The skipped descriptor was left open (still usable, closed by `udp`).
+        assert!(udp.local_addr().is_ok());
+    }
+}
@@ -0,0 +1,98 @@
+//! systemd service notifications (`sd_notify`).
+//!
+//! Under a `Type=notify` unit, systemd waits for `READY=1` before considering
+//! rucho started, so units ordered after it (and `systemctl start` itself) only
+//! proceed once the listeners are actually bound. With `WatchdogSec=` set,
+//! systemd also expects periodic `WATCHDOG=1` pings and restarts a process that
+//! stops sending them.
+//!
+//! Every function is a no-op when `NOTIFY_SOCKET` is unset, i.e. when not
+//! started by systemd. Linux only.
+
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::path::Path;
+use std::time::Duration;
+
+use sd_notify::NotifyState;
+
+/// Sends `states` to systemd over `NOTIFY_SOCKET`; a no-op when it's unset.
+fn notify(states: &[NotifyState]) -> io::Result<()> {
+    match std::env::var_os("NOTIFY_SOCKET") {
+        Some(socket) => notify_socket(Path::new(&socket), states),
+        None => Ok(()),
+    }
+}
+
+/// Sends `states`, one `KEY=value` line each, as a single datagram to the
+/// notification socket at `socket`.
+fn notify_socket(socket: &Path, states: &[NotifyState]) -> io::Result<()> {
+    let message: String = states.iter().map(|state| format!("{state}\n")).collect();
+    UnixDatagram::unbound()?.send_to(message.as_bytes(), socket)?;
+    Ok(())
+}
+
+/// Tells systemd the server is ready to accept connections (`READY=1`).
+///
+/// Call once the listeners are bound. Failures are logged and otherwise
+/// ignored: a missing notification only delays systemd's view of startup.
+pub fn notify_ready() {
+    if let Err(e) = notify(&[NotifyState::Ready]) {
+        tracing::warn!("Failed to send systemd readiness notification: {}", e);
+    }
+}
+
+/// Tells systemd the server is shutting down (`STOPPING=1`).
+pub fn notify_stopping() {
+    if let Err(e) = notify(&[NotifyState::Stopping]) {
+        tracing::warn!("Failed to send systemd stopping notification: {}", e);
+    }
+}
+
+/// Starts pinging the systemd watchdog if the unit enables one (`WatchdogSec=`).
+///
+/// Pings every half watchdog interval, as `sd_watchdog_enabled(3)` recommends.
+/// The task runs on the Tokio runtime, so a wedged runtime stops the pings and
+/// lets systemd restart the service.
+pub fn spawn_watchdog() {
+    let mut usec = 0;
+    if !sd_notify::watchdog_enabled(false, &mut usec) || usec == 0 {
+        return;
+    }
+    let period = Duration::from_micros(usec) / 2;
+    tracing::info!("systemd watchdog enabled, pinging every {:?}", period);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = notify(&[NotifyState::Watchdog]) {
+                tracing::warn!("Failed to ping systemd watchdog: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn states_are_sent_to_the_notify_socket() {
+        // Stand-in for systemd's notification socket.
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let socket = UnixDatagram::bind(&path).unwrap();
+        socket
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+
+        notify_socket(&path, &[NotifyState::Ready]).unwrap();
+        notify_socket(&path, &[NotifyState::Stopping]).unwrap();
+
+        let mut buf = [0u8; 64];
+        let n = socket.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1\n");
+        let n = socket.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"STOPPING=1\n");
+    }
+}
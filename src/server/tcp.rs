@@ -1,19 +1,63 @@
 //! TCP echo server setup.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use tokio::net::TcpListener;
 use tokio::task::JoinHandle;
 
 use crate::tcp_udp_handlers::handle_tcp_connection;
 
+/// Samples the per-connection accept log so it doesn't flood under load.
+///
+/// Every accepted connection is counted; only every `every`-th one is logged
+/// (the first accept is always logged). `every = 1` logs all accepts and
+/// `every = 0` disables the accept log entirely — the counter still runs.
+#[derive(Debug)]
+pub struct AcceptLogSampler {
+    every: u64,
+    accepted: AtomicU64,
+}
+
+impl AcceptLogSampler {
+    /// Creates a sampler that logs one in `every` accepted connections.
+    pub fn new(every: u64) -> Self {
+        Self {
+            every,
+            accepted: AtomicU64::new(0),
+        }
+    }
+
+    /// Counts one accepted connection.
+    ///
+    /// Returns `Some(total)` — the running accept count, including this one —
+    /// when this connection should be logged, or `None` when it is sampled out.
+    pub fn record(&self) -> Option<u64> {
+        let total = self.accepted.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.every != 0 && (total - 1) % self.every == 0 {
+            Some(total)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the total number of accepted connections counted so far.
+    pub fn accepted(&self) -> u64 {
+        self.accepted.load(Ordering::Relaxed)
+    }
+}
+
 /// Sets up a TCP echo listener on the given address.
 ///
 /// Parses the address string and binds a TCP listener. Incoming connections
 /// are handled by `handle_tcp_connection` which echoes data back to clients.
+/// The accept log is sampled to one in `accept_log_sample` connections (see
+/// [`AcceptLogSampler`]).
 pub async fn setup_tcp_listener(
     tcp_addr_str: &str,
+    accept_log_sample: u64,
     server_handles: &mut Vec<JoinHandle<Result<(), std::io::Error>>>,
 ) {
-    let addr: std::net::SocketAddr = match tcp_addr_str.parse() {
+    let addr = match crate::utils::server_config::parse_socket_addr(tcp_addr_str) {
         Ok(addr) => addr,
         Err(e) => {
             tracing::error!("Failed to parse TCP address '{}': {}", tcp_addr_str, e);
@@ -24,11 +68,18 @@ pub async fn setup_tcp_listener(
     match TcpListener::bind(addr).await {
         Ok(listener) => {
             tracing::info!("Starting TCP echo listener on {}", addr);
+            let sampler = AcceptLogSampler::new(accept_log_sample);
             let tcp_listener_handle = tokio::spawn(async move {
                 loop {
                     match listener.accept().await {
                         Ok((socket, client_addr)) => {
-                            tracing::info!("Accepted new TCP connection from {}", client_addr);
+                            if let Some(total) = sampler.record() {
+                                tracing::info!(
+                                    "Accepted new TCP connection from {} ({} accepted total)",
+                                    client_addr,
+                                    total
+                                );
+                            }
                             tokio::spawn(handle_tcp_connection(socket));
                         }
                         Err(e) => {
@@ -49,3 +100,43 @@ pub async fn setup_tcp_listener(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logged(sampler: &AcceptLogSampler, accepts: u64) -> u64 {
+        (0..accepts).filter(|_| sampler.record().is_some()).count() as u64
+    }
+
+    #[test]
+    fn test_sample_one_logs_every_accept() {
+        let sampler = AcceptLogSampler::new(1);
+        assert_eq!(logged(&sampler, 50), 50);
+        assert_eq!(sampler.accepted(), 50);
+    }
+
+    #[test]
+    fn test_sampling_reduces_log_volume() {
+        let sampler = AcceptLogSampler::new(10);
+        assert_eq!(logged(&sampler, 100), 10);
+        assert_eq!(sampler.accepted(), 100, "every accept is still counted");
+    }
+
+    #[test]
+    fn test_first_accept_always_logged_with_running_total() {
+        let sampler = AcceptLogSampler::new(3);
+        let totals: Vec<Option<u64>> = (0..7).map(|_| sampler.record()).collect();
+        assert_eq!(
+            totals,
+            vec![Some(1), None, None, Some(4), None, None, Some(7)]
+        );
+    }
+
+    #[test]
+    fn test_zero_disables_accept_log() {
+        let sampler = AcceptLogSampler::new(0);
+        assert_eq!(logged(&sampler, 20), 0);
+        assert_eq!(sampler.accepted(), 20);
+    }
+}
@@ -0,0 +1,78 @@
+//! Request timeout middleware.
+//!
+//! With `request_timeout_secs` set, a request whose handler hasn't produced a
+//! response within that many seconds is abandoned — the handler's future is
+//! dropped, releasing whatever it held — and answered `504 Gateway Timeout`
+//! with a JSON error body. A `/delay/:n` longer than the timeout therefore
+//! 504s, which is handy for exercising a client's own timeout handling.
+//!
+//! The deadline covers producing the response head only: a streaming body
+//! (`/drip`, `/events`, ...) that has started may run past it.
+//!
+//! Opt-in via `request_timeout_secs` (0, the default, disables it).
+
+use std::time::Duration;
+
+use axum::{body::Body, extract::Request, http::StatusCode, middleware::Next, response::Response};
+
+use crate::utils::error_response::format_error_response;
+
+/// Middleware that answers 504 when the inner service takes longer than
+/// `timeout` to respond.
+pub async fn timeout_middleware(request: Request, next: Next, timeout: Duration) -> Response<Body> {
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!("Request timed out after {:?}", timeout);
+            format_error_response(StatusCode::GATEWAY_TIMEOUT, "Request timed out")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, Router};
+    use tower::ServiceExt;
+
+    fn app(timeout: Duration) -> Router {
+        crate::routes::delay::router()
+            .merge(crate::routes::healthz::router())
+            .layer(middleware::from_fn(move |req, next| {
+                timeout_middleware(req, next, timeout)
+            }))
+    }
+
+    async fn send(app: Router, uri: &str) -> Response {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fast_request_passes_through() {
+        let response = send(app(Duration::from_secs(1)), "/healthz").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"OK");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_delay_gets_504_at_the_deadline() {
+        let start = tokio::time::Instant::now();
+        let response = send(app(Duration::from_secs(1)), "/delay/3").await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], br#"{"error":"Request timed out"}"#);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn delay_within_the_timeout_succeeds() {
+        let response = send(app(Duration::from_secs(5)), "/delay/2").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
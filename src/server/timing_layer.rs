@@ -8,6 +8,7 @@
 
 use axum::{body::Body, extract::Request, http::HeaderValue, middleware::Next, response::Response};
 
+use crate::utils::clock::Clock;
 use crate::utils::timing::RequestTiming;
 
 /// Header name carrying the measured response time (e.g. `1.234ms`).
@@ -19,10 +20,11 @@ const RESPONSE_TIME_HEADER: &str = "x-response-time";
 /// the elapsed time), then, after the inner handler returns, sets an
 /// `X-Response-Time: <ms>ms` header on the response. This measures the inner
 /// processing time (handler plus any inner middleware such as chaos), matching
-/// the `duration_ms` value echo handlers report in their JSON body.
-pub async fn timing_middleware(mut request: Request, next: Next) -> Response<Body> {
-    let timing = RequestTiming::now();
-    request.extensions_mut().insert(timing);
+/// the `duration_ms` value echo handlers report in their JSON body. Both are
+/// measured on `clock`.
+pub async fn timing_middleware(mut request: Request, next: Next, clock: Clock) -> Response<Body> {
+    let timing = RequestTiming::new(&clock);
+    request.extensions_mut().insert(timing.clone());
 
     let mut response = next.run(request).await;
 
@@ -42,9 +44,12 @@ mod tests {
 
     #[tokio::test]
     async fn sets_x_response_time_header() {
-        let app = Router::new()
-            .route("/", get(|| async { "ok" }))
-            .layer(axum::middleware::from_fn(timing_middleware));
+        let app =
+            Router::new()
+                .route("/", get(|| async { "ok" }))
+                .layer(axum::middleware::from_fn(|req, next| {
+                    timing_middleware(req, next, Clock::default())
+                }));
 
         let resp = app
             .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
@@ -67,4 +72,32 @@ mod tests {
             .expect("the numeric prefix must parse as f64 milliseconds");
         assert!(ms >= 0.0, "elapsed time cannot be negative");
     }
+
+    #[tokio::test]
+    async fn measures_on_the_given_clock() {
+        use crate::utils::clock::ManualClock;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let manual = Arc::new(ManualClock::new());
+        let clock = Clock::new(manual.clone());
+        let handler_clock = clock.clone();
+        let app = Router::new()
+            .route(
+                "/",
+                get(move || async move {
+                    handler_clock.sleep(Duration::from_millis(1500)).await;
+                    "ok"
+                }),
+            )
+            .layer(axum::middleware::from_fn(move |req, next| {
+                timing_middleware(req, next, clock.clone())
+            }));
+
+        let resp = app
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.headers()[RESPONSE_TIME_HEADER], "1500.000ms");
+    }
 }
@@ -37,7 +37,7 @@ use tower_http::add_extension::AddExtension;
 /// Surfaced as a request extension by [`TlsInfoAcceptor`] and echoed under the
 /// `tls` key by the `/get` and `/anything` handlers. All fields are best-effort:
 /// any value rustls did not negotiate is `None`/empty.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TlsConnectionInfo {
     /// Negotiated protocol version, e.g. `"TLSv1.3"` / `"TLSv1.2"`.
     pub version: Option<String>,
@@ -50,6 +50,9 @@ pub struct TlsConnectionInfo {
     /// DER byte-length of each presented client certificate, leaf-first.
     /// Empty unless client-cert auth (mTLS) is configured.
     pub client_certs: Vec<usize>,
+    /// Subject distinguished name of the verified client (leaf) certificate,
+    /// e.g. `"CN=client.example"`. `None` without mTLS or if it can't be parsed.
+    pub client_cert_subject: Option<String>,
 }
 
 impl TlsConnectionInfo {
@@ -73,10 +76,14 @@ impl TlsConnectionInfo {
             .alpn_protocol()
             .map(|p| String::from_utf8_lossy(p).into_owned());
 
-        let client_certs: Vec<usize> = conn
-            .peer_certificates()
-            .map(|certs| certs.iter().map(|c| c.as_ref().len()).collect())
-            .unwrap_or_default();
+        let peer_certs = conn.peer_certificates().unwrap_or_default();
+        let client_certs: Vec<usize> = peer_certs.iter().map(|c| c.as_ref().len()).collect();
+        // rustls has already verified the chain; parse the leaf only to report it.
+        let client_cert_subject = peer_certs.first().and_then(|leaf| {
+            x509_parser::parse_x509_certificate(leaf.as_ref())
+                .ok()
+                .map(|(_, cert)| cert.subject().to_string())
+        });
 
         Self {
             version,
@@ -84,6 +91,7 @@ impl TlsConnectionInfo {
             alpn,
             client_cert_present: !client_certs.is_empty(),
             client_certs,
+            client_cert_subject,
         }
     }
 
@@ -94,6 +102,7 @@ impl TlsConnectionInfo {
             "cipher_suite": self.cipher_suite,
             "alpn": self.alpn,
             "client_cert_present": self.client_cert_present,
+            "client_cert_subject": self.client_cert_subject,
             "client_certs": self
                 .client_certs
                 .iter()
@@ -158,6 +167,7 @@ mod tests {
             alpn: Some("h2".to_string()),
             client_cert_present: false,
             client_certs: Vec::new(),
+            client_cert_subject: None,
         };
 
         let json = info.to_json();
@@ -165,6 +175,7 @@ mod tests {
         assert_eq!(json["cipher_suite"], "TLS13_AES_128_GCM_SHA256");
         assert_eq!(json["alpn"], "h2");
         assert_eq!(json["client_cert_present"], false);
+        assert!(json["client_cert_subject"].is_null());
         assert_eq!(json["client_certs"].as_array().unwrap().len(), 0);
     }
 
@@ -176,6 +187,7 @@ mod tests {
             alpn: None,
             client_cert_present: true,
             client_certs: vec![1200, 980],
+            client_cert_subject: Some("CN=client".to_string()),
         };
 
         let json = info.to_json();
@@ -183,6 +195,7 @@ mod tests {
         assert!(json["cipher_suite"].is_null());
         assert!(json["alpn"].is_null());
         assert_eq!(json["client_cert_present"], true);
+        assert_eq!(json["client_cert_subject"], "CN=client");
         let certs = json["client_certs"].as_array().unwrap();
         assert_eq!(certs.len(), 2);
         assert_eq!(certs[0]["der_length"], 1200);
@@ -0,0 +1,482 @@
+//! Distributed-tracing context propagation middleware.
+//!
+//! Recognizes the W3C Trace Context headers (`traceparent` / `tracestate`) and,
+//! as a fallback, Zipkin B3 propagation (the single `b3` header or the
+//! multi-header `X-B3-TraceId` / `X-B3-SpanId` / `X-B3-Sampled` form). A valid
+//! inbound context is:
+//!
+//! 1. parsed into a [`TraceContext`] and inserted as a request extension, so a
+//!    handler can read it with `Option<Extension<TraceContext>>`,
+//! 2. attached to a `trace_context` tracing span (fields `trace_id`,
+//!    `parent_id`, `sampled`, `format`) that wraps the rest of the stack — every
+//!    log line emitted while serving the request, including the `TraceLayer`
+//!    request/response events, carries the incoming trace id, and
+//! 3. echoed back on the response unchanged (only the recognized propagation
+//!    headers, and only when a handler has not already set them).
+//!
+//! W3C takes precedence when both formats are present. Malformed headers are
+//! ignored (no span fields, no echo) rather than rejected — an echo server must
+//! never fail a request because of a tracing header. rucho makes no outbound
+//! calls, so there is nothing further downstream to propagate to; the echoed
+//! headers are the propagation.
+
+use axum::{body::Body, extract::Request, middleware::Next, response::Response};
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use tracing::Instrument;
+
+/// W3C Trace Context parent header.
+const TRACEPARENT: &str = "traceparent";
+/// W3C Trace Context vendor-state header.
+const TRACESTATE: &str = "tracestate";
+/// B3 single-header propagation.
+const B3: &str = "b3";
+/// B3 multi-header propagation, in echo order.
+const B3_MULTI: &[&str] = &[
+    "x-b3-traceid",
+    "x-b3-spanid",
+    "x-b3-parentspanid",
+    "x-b3-sampled",
+    "x-b3-flags",
+];
+
+/// Which propagation format an inbound trace context arrived in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// W3C Trace Context (`traceparent` / `tracestate`).
+    W3c,
+    /// Zipkin B3, single `b3` header.
+    B3Single,
+    /// Zipkin B3, multiple `X-B3-*` headers.
+    B3Multi,
+}
+
+impl TraceFormat {
+    /// Short label recorded on the span (`"w3c"`, `"b3"`, `"b3-multi"`).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TraceFormat::W3c => "w3c",
+            TraceFormat::B3Single => "b3",
+            TraceFormat::B3Multi => "b3-multi",
+        }
+    }
+}
+
+/// A parsed inbound distributed-tracing context.
+///
+/// Inserted as a request extension by [`trace_context_middleware`]. Ids are
+/// normalized to lowercase hex; a 64-bit B3 trace id stays 16 chars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// Trace id: 32 hex chars (W3C, 128-bit B3) or 16 (64-bit B3).
+    pub trace_id: String,
+    /// The caller's span id (W3C `parent-id` / B3 `SpanId`): 16 hex chars.
+    pub parent_id: String,
+    /// Sampling decision, when the caller made one.
+    pub sampled: Option<bool>,
+    /// Opaque W3C `tracestate`, if sent alongside a valid `traceparent`.
+    pub tracestate: Option<String>,
+    /// Which propagation format the context arrived in.
+    pub format: TraceFormat,
+}
+
+/// True if `s` is exactly `len` ASCII hex digits (either case).
+fn is_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// True if `s` is all `'0'` (the W3C/B3 "invalid id" sentinel).
+fn is_all_zero(s: &str) -> bool {
+    s.bytes().all(|b| b == b'0')
+}
+
+/// Parses a W3C `traceparent` value (`version-traceid-parentid-flags`).
+///
+/// Accepts version `00` exactly, and any later version (other than the
+/// forbidden `ff`) by reading its first four fields, per the spec's
+/// forward-compatibility rule. All-zero trace or parent ids are invalid.
+fn parse_traceparent(value: &str) -> Option<TraceContext> {
+    let mut parts = value.trim().split('-');
+    let version = parts.next()?;
+    let trace_id = parts.next()?;
+    let parent_id = parts.next()?;
+    let flags = parts.next()?;
+
+    if !is_hex(version, 2) || version.eq_ignore_ascii_case("ff") {
+        return None;
+    }
+    // Version 00 has exactly four fields; future versions may append more.
+    if version == "00" && parts.next().is_some() {
+        return None;
+    }
+    if !is_hex(trace_id, 32) || is_all_zero(trace_id) {
+        return None;
+    }
+    if !is_hex(parent_id, 16) || is_all_zero(parent_id) {
+        return None;
+    }
+    if !is_hex(flags, 2) {
+        return None;
+    }
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+
+    Some(TraceContext {
+        trace_id: trace_id.to_ascii_lowercase(),
+        parent_id: parent_id.to_ascii_lowercase(),
+        sampled: Some(flags & 0x01 == 0x01),
+        tracestate: None,
+        format: TraceFormat::W3c,
+    })
+}
+
+/// Parses a B3 sampling-state token (`1`/`0`/`d`, or `true`/`false` for the
+/// legacy multi-header form). `d` (debug) implies sampled.
+fn parse_b3_sampled(value: &str) -> Option<bool> {
+    match value.trim() {
+        "1" | "d" => Some(true),
+        "0" => Some(false),
+        v if v.eq_ignore_ascii_case("true") => Some(true),
+        v if v.eq_ignore_ascii_case("false") => Some(false),
+        _ => None,
+    }
+}
+
+/// True for a valid B3 trace id: 16 or 32 hex chars, not all zero.
+fn is_b3_trace_id(s: &str) -> bool {
+    (is_hex(s, 16) || is_hex(s, 32)) && !is_all_zero(s)
+}
+
+/// Parses a single `b3` header (`traceid-spanid[-sampled[-parentspanid]]`).
+///
+/// A bare sampling decision (`b3: 0`) carries no ids and yields `None`.
+fn parse_b3_single(value: &str) -> Option<TraceContext> {
+    let mut parts = value.trim().split('-');
+    let trace_id = parts.next()?;
+    let span_id = parts.next()?;
+    let sampled = match parts.next() {
+        Some(s) => Some(parse_b3_sampled(s)?),
+        None => None,
+    };
+    if let Some(parent) = parts.next() {
+        if !is_hex(parent, 16) {
+            return None;
+        }
+    }
+    if parts.next().is_some() || !is_b3_trace_id(trace_id) || !is_hex(span_id, 16) {
+        return None;
+    }
+
+    Some(TraceContext {
+        trace_id: trace_id.to_ascii_lowercase(),
+        parent_id: span_id.to_ascii_lowercase(),
+        sampled,
+        tracestate: None,
+        format: TraceFormat::B3Single,
+    })
+}
+
+/// Parses the multi-header B3 form. Both `X-B3-TraceId` and `X-B3-SpanId` are
+/// required; `X-B3-Flags: 1` (debug) forces `sampled`.
+fn parse_b3_multi(headers: &HeaderMap) -> Option<TraceContext> {
+    let get = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let trace_id = get("x-b3-traceid")?.trim();
+    let span_id = get("x-b3-spanid")?.trim();
+    if !is_b3_trace_id(trace_id) || !is_hex(span_id, 16) {
+        return None;
+    }
+    let debug = get("x-b3-flags").is_some_and(|v| v.trim() == "1");
+    let sampled = if debug {
+        Some(true)
+    } else {
+        get("x-b3-sampled").and_then(parse_b3_sampled)
+    };
+
+    Some(TraceContext {
+        trace_id: trace_id.to_ascii_lowercase(),
+        parent_id: span_id.to_ascii_lowercase(),
+        sampled,
+        tracestate: None,
+        format: TraceFormat::B3Multi,
+    })
+}
+
+/// Extracts the inbound trace context, preferring W3C over B3.
+///
+/// Returns `None` when no recognized header is present or all are malformed.
+pub fn extract_trace_context(headers: &HeaderMap) -> Option<TraceContext> {
+    let header_str = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if let Some(mut ctx) = header_str(TRACEPARENT).and_then(parse_traceparent) {
+        // `tracestate` is only meaningful alongside a valid `traceparent`.
+        // Multiple tracestate header lines combine as a comma-joined list.
+        let states: Vec<&str> = headers
+            .get_all(TRACESTATE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect();
+        if !states.is_empty() {
+            ctx.tracestate = Some(states.join(","));
+        }
+        return Some(ctx);
+    }
+
+    header_str(B3)
+        .and_then(parse_b3_single)
+        .or_else(|| parse_b3_multi(headers))
+}
+
+/// Names of the inbound propagation headers to echo for a given format.
+fn echo_header_names(format: TraceFormat) -> &'static [&'static str] {
+    match format {
+        TraceFormat::W3c => &[TRACEPARENT, TRACESTATE],
+        TraceFormat::B3Single => &[B3],
+        TraceFormat::B3Multi => B3_MULTI,
+    }
+}
+
+/// Middleware that parses, spans, and echoes an inbound trace context.
+///
+/// Requests without a valid context pass through untouched (no span, no
+/// extension, no echoed headers).
+pub async fn trace_context_middleware(mut request: Request, next: Next) -> Response<Body> {
+    let Some(ctx) = extract_trace_context(request.headers()) else {
+        return next.run(request).await;
+    };
+
+    // Capture the exact inbound header lines to echo before the request moves.
+    let echoed: Vec<(HeaderName, HeaderValue)> = echo_header_names(ctx.format)
+        .iter()
+        .flat_map(|&name| {
+            request
+                .headers()
+                .get_all(name)
+                .iter()
+                .map(move |v| (HeaderName::from_static(name), v.clone()))
+        })
+        .collect();
+
+    let span = tracing::info_span!(
+        "trace_context",
+        trace_id = %ctx.trace_id,
+        parent_id = %ctx.parent_id,
+        sampled = ?ctx.sampled,
+        format = ctx.format.as_str(),
+    );
+    request.extensions_mut().insert(ctx);
+
+    let mut response = next.run(request).instrument(span).await;
+
+    // A handler's deliberate value (e.g. `/response-headers?traceparent=…`)
+    // wins, mirroring the request-id middleware.
+    let handler_set: Vec<HeaderName> = echoed
+        .iter()
+        .map(|(name, _)| name.clone())
+        .filter(|name| response.headers().contains_key(name))
+        .collect();
+    let headers = response.headers_mut();
+    for (name, value) in echoed {
+        if !handler_set.contains(&name) {
+            headers.append(name, value);
+        }
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Extension, Router};
+    use http::StatusCode;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tower::ServiceExt;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const PARENT_ID: &str = "00f067aa0ba902b7";
+
+    fn traceparent() -> String {
+        format!("00-{TRACE_ID}-{PARENT_ID}-01")
+    }
+
+    /// Echoes the parsed context's trace id (or `none`) and logs one event so
+    /// the span fields can be observed.
+    async fn echo_ctx(ctx: Option<Extension<TraceContext>>) -> String {
+        tracing::info!("handled");
+        ctx.map(|Extension(c)| c.trace_id)
+            .unwrap_or_else(|| "none".to_string())
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/", get(echo_ctx))
+            .layer(axum::middleware::from_fn(trace_context_middleware))
+    }
+
+    async fn send(headers: &[(&str, &str)]) -> Response {
+        let mut req = Request::builder().uri("/");
+        for (k, v) in headers {
+            req = req.header(*k, *v);
+        }
+        app()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body_string(resp: Response) -> String {
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn parses_valid_traceparent() {
+        let ctx = parse_traceparent(&traceparent()).unwrap();
+        assert_eq!(ctx.trace_id, TRACE_ID);
+        assert_eq!(ctx.parent_id, PARENT_ID);
+        assert_eq!(ctx.sampled, Some(true));
+        assert_eq!(ctx.format, TraceFormat::W3c);
+
+        let unsampled = parse_traceparent(&format!("00-{TRACE_ID}-{PARENT_ID}-00")).unwrap();
+        assert_eq!(unsampled.sampled, Some(false));
+    }
+
+    #[test]
+    fn rejects_malformed_traceparent() {
+        let zero_trace = format!("00-{}-{PARENT_ID}-01", "0".repeat(32));
+        let zero_parent = format!("00-{TRACE_ID}-{}-01", "0".repeat(16));
+        let bad_version = format!("ff-{TRACE_ID}-{PARENT_ID}-01");
+        let extra_field = format!("00-{TRACE_ID}-{PARENT_ID}-01-extra");
+        let short_trace = format!("00-{}-{PARENT_ID}-01", &TRACE_ID[..31]);
+        for bad in [
+            "",
+            "garbage",
+            zero_trace.as_str(),
+            zero_parent.as_str(),
+            bad_version.as_str(),
+            extra_field.as_str(),
+            short_trace.as_str(),
+        ] {
+            assert_eq!(parse_traceparent(bad), None, "should reject {bad:?}");
+        }
+    }
+
+    #[test]
+    fn future_version_reads_first_four_fields() {
+        let ctx = parse_traceparent(&format!("01-{TRACE_ID}-{PARENT_ID}-01-future")).unwrap();
+        assert_eq!(ctx.trace_id, TRACE_ID);
+    }
+
+    #[test]
+    fn parses_b3_single_and_multi() {
+        let single = parse_b3_single(&format!("{TRACE_ID}-{PARENT_ID}-1")).unwrap();
+        assert_eq!(single.format, TraceFormat::B3Single);
+        assert_eq!(single.sampled, Some(true));
+        assert_eq!(
+            parse_b3_single("0"),
+            None,
+            "bare sampling decision has no ids"
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-b3-traceid", HeaderValue::from_static("a3ce929d0e0e4736"));
+        headers.insert("x-b3-spanid", HeaderValue::from_static(PARENT_ID));
+        headers.insert("x-b3-flags", HeaderValue::from_static("1"));
+        let multi = parse_b3_multi(&headers).unwrap();
+        assert_eq!(multi.trace_id, "a3ce929d0e0e4736");
+        assert_eq!(multi.sampled, Some(true), "debug flag implies sampled");
+    }
+
+    #[test]
+    fn w3c_takes_precedence_over_b3() {
+        let mut headers = HeaderMap::new();
+        headers.insert(TRACEPARENT, traceparent().parse().unwrap());
+        headers.insert(
+            B3,
+            format!("{}-{PARENT_ID}", "a".repeat(32)).parse().unwrap(),
+        );
+        assert_eq!(
+            extract_trace_context(&headers).unwrap().format,
+            TraceFormat::W3c
+        );
+    }
+
+    #[tokio::test]
+    async fn traceparent_is_parsed_into_extension_and_echoed() {
+        let tp = traceparent();
+        let resp = send(&[(TRACEPARENT, &tp), (TRACESTATE, "congo=t61rcWkgMzE")]).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(TRACEPARENT).unwrap(), tp.as_str());
+        assert_eq!(resp.headers().get(TRACESTATE).unwrap(), "congo=t61rcWkgMzE");
+        assert_eq!(body_string(resp).await, TRACE_ID);
+    }
+
+    #[tokio::test]
+    async fn b3_headers_are_echoed() {
+        let b3 = format!("{TRACE_ID}-{PARENT_ID}-0");
+        let resp = send(&[(B3, &b3)]).await;
+        assert_eq!(resp.headers().get(B3).unwrap(), b3.as_str());
+        assert_eq!(body_string(resp).await, TRACE_ID);
+    }
+
+    #[tokio::test]
+    async fn malformed_context_passes_through_untouched() {
+        let resp = send(&[(TRACEPARENT, "not-a-traceparent")]).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().get(TRACEPARENT).is_none());
+        assert_eq!(body_string(resp).await, "none");
+    }
+
+    /// A `MakeWriter` that captures log output into a shared buffer.
+    #[derive(Clone)]
+    struct BufWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for BufWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().expect("buffer lock").extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for BufWriter {
+        type Writer = BufWriter;
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// Events logged while serving the request carry the inbound trace id via
+    /// the `trace_context` span. Uses a scoped subscriber on a current-thread
+    /// runtime so the handler runs on this thread under the default.
+    #[tokio::test(flavor = "current_thread")]
+    async fn handler_events_carry_trace_id_span_field() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(BufWriter(buf.clone()))
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let tp = traceparent();
+        send(&[(TRACEPARENT, &tp)]).await;
+
+        let out = String::from_utf8(buf.lock().expect("buffer lock").clone()).unwrap();
+        let line = out
+            .lines()
+            .find(|l| l.contains("handled"))
+            .expect("handler event must be logged");
+        let parsed: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(parsed["span"]["name"], "trace_context");
+        assert_eq!(parsed["span"]["trace_id"], TRACE_ID);
+        assert_eq!(parsed["span"]["parent_id"], PARENT_ID);
+        assert_eq!(parsed["span"]["format"], "w3c");
+    }
+}
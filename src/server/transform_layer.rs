@@ -0,0 +1,303 @@
+//! Response body transformation pipeline.
+//!
+//! Applies an ordered list of body transforms to each response, for composing
+//! test scenarios (e.g. a base64-wrapped gzip body, or an upper-cased echo) out
+//! of any endpoint. The pipeline comes from the `response_transforms` config
+//! field, and a request can choose its own with an `X-Transform` header
+//! (comma-separated, applied left to right); the header wins over the config.
+//! An unknown name in the header is answered with `400 Bad Request`.
+//!
+//! Transformed responses are buffered and carry `X-Transform-Applied` listing
+//! the transforms that ran. Only the body changes: `Content-Type` and
+//! `Content-Encoding` are left as the handler set them. HEAD, `204`, and `304`
+//! responses have no body and pass through untouched.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use base64::Engine;
+use flate2::{write::GzEncoder, write::ZlibEncoder, Compression};
+
+use crate::utils::error_response::format_error_response;
+
+/// Request header selecting a per-request pipeline.
+pub const TRANSFORM_HEADER: &str = "x-transform";
+
+/// Response header listing the transforms that were applied.
+pub const TRANSFORM_APPLIED_HEADER: &str = "x-transform-applied";
+
+/// Names accepted in `response_transforms` and `X-Transform`.
+pub const VALID_TRANSFORMS: &[&str] = &["gzip", "deflate", "base64", "uppercase", "lowercase"];
+
+/// One step of the pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// gzip-compress the body.
+    Gzip,
+    /// zlib-compress the body (what `Content-Encoding: deflate` means in practice).
+    Deflate,
+    /// Standard base64-encode the body (with padding).
+    Base64,
+    /// Upper-case ASCII letters; other bytes are left alone.
+    Uppercase,
+    /// Lower-case ASCII letters; other bytes are left alone.
+    Lowercase,
+}
+
+impl Transform {
+    /// Parses a transform name (ASCII case-insensitive).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "gzip" => Some(Self::Gzip),
+            "deflate" => Some(Self::Deflate),
+            "base64" => Some(Self::Base64),
+            "uppercase" => Some(Self::Uppercase),
+            "lowercase" => Some(Self::Lowercase),
+            _ => None,
+        }
+    }
+
+    /// The transform's canonical name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+            Self::Base64 => "base64",
+            Self::Uppercase => "uppercase",
+            Self::Lowercase => "lowercase",
+        }
+    }
+
+    /// Applies this transform to `body`.
+    pub fn apply(self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Gzip => {
+                let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+                enc.write_all(body)?;
+                enc.finish()
+            }
+            Self::Deflate => {
+                let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+                enc.write_all(body)?;
+                enc.finish()
+            }
+            Self::Base64 => Ok(base64::engine::general_purpose::STANDARD
+                .encode(body)
+                .into_bytes()),
+            Self::Uppercase => Ok(body.to_ascii_uppercase()),
+            Self::Lowercase => Ok(body.to_ascii_lowercase()),
+        }
+    }
+}
+
+/// Parses a pipeline from transform names, in order. Blank names are skipped.
+///
+/// # Errors
+///
+/// Returns a message naming the first unknown transform and listing the valid
+/// ones.
+pub fn parse_pipeline<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<Transform>, String> {
+    names
+        .into_iter()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            Transform::parse(name).ok_or_else(|| {
+                format!(
+                    "Unknown transform '{name}'. Valid transforms: {}",
+                    VALID_TRANSFORMS.join(", ")
+                )
+            })
+        })
+        .collect()
+}
+
+/// Applies every transform in `pipeline` to `body`, in order.
+pub fn apply_pipeline(pipeline: &[Transform], body: &[u8]) -> std::io::Result<Vec<u8>> {
+    pipeline
+        .iter()
+        .try_fold(body.to_vec(), |body, transform| transform.apply(&body))
+}
+
+/// Middleware that runs the response body through the request's `X-Transform`
+/// pipeline, or the configured `default_pipeline` when the header is absent.
+pub async fn transform_middleware(
+    request: Request,
+    next: Next,
+    default_pipeline: Arc<Vec<Transform>>,
+) -> Response {
+    let pipeline = match request.headers().get(TRANSFORM_HEADER) {
+        Some(value) => {
+            let parsed = value
+                .to_str()
+                .map_err(|_| "X-Transform header is not valid ASCII".to_string())
+                .and_then(|names| parse_pipeline(names.split(',')));
+            match parsed {
+                Ok(pipeline) => pipeline,
+                Err(message) => return format_error_response(StatusCode::BAD_REQUEST, &message),
+            }
+        }
+        None => default_pipeline.to_vec(),
+    };
+    let is_head = request.method() == Method::HEAD;
+
+    let response = next.run(request).await;
+    let status = response.status();
+    if pipeline.is_empty()
+        || is_head
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+    {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return format_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to read response body for transformation",
+        );
+    };
+    let transformed = match apply_pipeline(&pipeline, &bytes) {
+        Ok(transformed) => transformed,
+        Err(e) => {
+            return format_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Failed to transform response body: {e}"),
+            )
+        }
+    };
+
+    let applied: Vec<&str> = pipeline.iter().map(|t| t.name()).collect();
+    if let Ok(value) = HeaderValue::from_str(&applied.join(", ")) {
+        parts.headers.insert(TRANSFORM_APPLIED_HEADER, value);
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(transformed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use tower::ServiceExt;
+
+    const BODY: &str = "Hello, Transform!";
+
+    fn app(default_pipeline: Vec<Transform>) -> Router {
+        let pipeline = Arc::new(default_pipeline);
+        Router::new()
+            .route("/", get(|| async { BODY }))
+            .route("/empty", get(|| async { StatusCode::NO_CONTENT }))
+            .layer(middleware::from_fn(move |req, next| {
+                let pipeline = pipeline.clone();
+                async move { transform_middleware(req, next, pipeline).await }
+            }))
+    }
+
+    async fn send(app: Router, path: &str, transform: Option<&str>) -> Response {
+        let mut req = Request::get(path);
+        if let Some(names) = transform {
+            req = req.header(TRANSFORM_HEADER, names);
+        }
+        app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[test]
+    fn parse_pipeline_validates_names() {
+        assert_eq!(
+            parse_pipeline("GZIP, base64,".split(',')),
+            Ok(vec![Transform::Gzip, Transform::Base64])
+        );
+        let err = parse_pipeline(["gzip", "rot13"]).unwrap_err();
+        assert!(err.contains("'rot13'"), "{err}");
+        assert!(err.contains("uppercase"), "{err}");
+        for name in VALID_TRANSFORMS {
+            assert_eq!(Transform::parse(name).map(Transform::name), Some(*name));
+        }
+    }
+
+    #[test]
+    fn transforms_compose_in_order() {
+        let body = BODY.as_bytes();
+        let upper_then_b64 =
+            apply_pipeline(&[Transform::Uppercase, Transform::Base64], body).unwrap();
+        assert_eq!(upper_then_b64, b"SEVMTE8sIFRSQU5TRk9STSE=");
+        // Order matters: base64 first, then upper-casing, mangles the encoding.
+        let b64_then_upper =
+            apply_pipeline(&[Transform::Base64, Transform::Uppercase], body).unwrap();
+        assert_eq!(b64_then_upper, b"SGVSBG8SIFRYYW5ZZM9YBSE=");
+    }
+
+    #[tokio::test]
+    async fn header_pipeline_matches_applying_transforms_in_order() {
+        let response = send(app(Vec::new()), "/", Some("gzip, base64")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[TRANSFORM_APPLIED_HEADER], "gzip, base64");
+        let body = body_bytes(response).await;
+
+        let expected = Transform::Base64
+            .apply(&Transform::Gzip.apply(BODY.as_bytes()).unwrap())
+            .unwrap();
+        assert_eq!(body, expected);
+
+        // And it round-trips: base64-decode, then gunzip.
+        let gzipped = base64::engine::general_purpose::STANDARD
+            .decode(&body)
+            .unwrap();
+        let mut decoded = String::new();
+        GzDecoder::new(gzipped.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, BODY);
+    }
+
+    #[tokio::test]
+    async fn configured_pipeline_applies_and_header_overrides_it() {
+        let app = app(vec![Transform::Uppercase]);
+        let body = body_bytes(send(app.clone(), "/", None).await).await;
+        assert_eq!(body, BODY.to_ascii_uppercase().as_bytes());
+
+        let body = body_bytes(send(app, "/", Some("lowercase")).await).await;
+        assert_eq!(body, BODY.to_ascii_lowercase().as_bytes());
+    }
+
+    #[tokio::test]
+    async fn no_pipeline_leaves_response_alone() {
+        let response = send(app(Vec::new()), "/", None).await;
+        assert!(response.headers().get(TRANSFORM_APPLIED_HEADER).is_none());
+        assert_eq!(body_bytes(response).await, BODY.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn unknown_transform_is_rejected() {
+        let response = send(app(Vec::new()), "/", Some("base64, rot13")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn bodyless_responses_pass_through() {
+        let response = send(app(Vec::new()), "/empty", Some("gzip")).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(response.headers().get(TRANSFORM_APPLIED_HEADER).is_none());
+        assert!(body_bytes(response).await.is_empty());
+    }
+}
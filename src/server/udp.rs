@@ -16,7 +16,7 @@ use crate::tcp_udp_handlers::handle_udp_socket;
 ///
 /// `Some(UdpSocket)` if binding succeeds, `None` otherwise.
 pub async fn bind_udp_socket(udp_addr_str: &str) -> Option<UdpSocket> {
-    let addr: std::net::SocketAddr = match udp_addr_str.parse() {
+    let addr = match crate::utils::server_config::parse_socket_addr(udp_addr_str) {
         Ok(addr) => addr,
         Err(e) => {
             tracing::error!("Failed to parse UDP address '{}': {}", udp_addr_str, e);
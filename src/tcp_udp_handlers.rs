@@ -40,7 +40,9 @@ pub async fn handle_tcp_connection(mut stream: TcpStream) {
         Ok(addr) => addr.to_string(),
         Err(_) => "unknown peer".to_string(),
     };
-    tracing::info!("Accepted TCP connection from: {}", peer_addr);
+    // The (sampled) accept log lives in the listener loop; keep this at debug
+    // so per-connection logging doesn't flood INFO under load.
+    tracing::debug!("Handling TCP connection from: {}", peer_addr);
 
     // Use a fixed-size buffer capped at MAX_BUFFER_SIZE for security
     let mut buf = vec![0u8; MAX_BUFFER_SIZE.min(65536)];
@@ -0,0 +1,180 @@
+//! Time source abstraction.
+//!
+//! Code that reads the time — the metrics rolling window and uptime, request
+//! timing (`duration_ms`, `X-Response-Time`), and `/delay` — asks a [`Clock`]
+//! instead of calling `Instant::now()` or `tokio::time::sleep` directly. The
+//! server runs on [`SystemClock`]; tests swap in a [`ManualClock`] and move time
+//! forward explicitly, so windows, uptimes and delays can be checked exactly
+//! instead of with real sleeps and tolerances.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A source of monotonic and wall-clock time, plus a way to wait on it.
+pub trait TimeSource: Send + Sync + fmt::Debug {
+    /// The current monotonic instant.
+    fn now(&self) -> Instant;
+
+    /// The current wall-clock time.
+    fn system_time(&self) -> SystemTime;
+
+    /// Completes once `duration` has passed on this source.
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+/// The real clock: `Instant::now()`, `SystemTime::now()` and
+/// `tokio::time::sleep` (which honours tokio's paused test time).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Starts at the real time it was created and stays there until
+/// [`advance`](Self::advance) is called. `sleep` advances the clock by the
+/// requested duration and returns immediately, so a `/delay/3` served on a
+/// manual clock finishes at once but is measured as exactly three seconds.
+#[derive(Debug)]
+pub struct ManualClock {
+    origin: Instant,
+    origin_wall: SystemTime,
+    offset: Mutex<Duration>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManualClock {
+    /// Creates a manual clock frozen at the current real time.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            origin_wall: SystemTime::now(),
+            offset: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let mut offset = self.offset.lock().unwrap_or_else(PoisonError::into_inner);
+        *offset = offset.saturating_add(duration);
+    }
+
+    /// Total time the clock has been advanced since creation.
+    pub fn elapsed(&self) -> Duration {
+        *self.offset.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl TimeSource for ManualClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.origin_wall + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.advance(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+/// Cheaply clonable handle to the [`TimeSource`] in use.
+///
+/// Defaults to [`SystemClock`]. `build_app` layers it as an `Extension`, so
+/// handlers take `Option<Extension<Clock>>` and fall back to the default when
+/// mounted without it.
+#[derive(Clone, Debug)]
+pub struct Clock(Arc<dyn TimeSource>);
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self(Arc::new(SystemClock))
+    }
+}
+
+impl Clock {
+    /// Wraps a time source. Keep another `Arc` to a [`ManualClock`] to drive
+    /// it from a test.
+    pub fn new(source: Arc<dyn TimeSource>) -> Self {
+        Self(source)
+    }
+
+    /// The current monotonic instant.
+    pub fn now(&self) -> Instant {
+        self.0.now()
+    }
+
+    /// The current wall-clock time.
+    pub fn system_time(&self) -> SystemTime {
+        self.0.system_time()
+    }
+
+    /// Time elapsed since `earlier` on this clock (zero if `earlier` is later).
+    pub fn elapsed_since(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+
+    /// Completes once `duration` has passed on this clock.
+    pub async fn sleep(&self, duration: Duration) {
+        self.0.sleep(duration).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_only_moves_when_advanced() {
+        let manual = Arc::new(ManualClock::new());
+        let clock = Clock::new(manual.clone());
+        let (start, start_wall) = (clock.now(), clock.system_time());
+        assert_eq!(clock.now(), start);
+
+        manual.advance(Duration::from_secs(90));
+        assert_eq!(clock.elapsed_since(start), Duration::from_secs(90));
+        assert_eq!(
+            clock.system_time().duration_since(start_wall).unwrap(),
+            Duration::from_secs(90)
+        );
+    }
+
+    #[tokio::test]
+    async fn manual_sleep_advances_instead_of_waiting() {
+        let manual = Arc::new(ManualClock::new());
+        let clock = Clock::new(manual.clone());
+        let real_start = Instant::now();
+        clock.sleep(Duration::from_secs(300)).await;
+        assert_eq!(manual.elapsed(), Duration::from_secs(300));
+        assert!(real_start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn system_sleep_waits_on_tokio_time() {
+        let start = tokio::time::Instant::now();
+        Clock::default().sleep(Duration::from_secs(2)).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+}
@@ -1,20 +1,35 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use axum::http::HeaderValue;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+
+use crate::routes::static_files::{self, StaticFiles};
+use crate::server::access_log::{AccessLogFormat, AccessLogRotation};
+use crate::server::cors::CorsConfig;
+use crate::server::transform_layer::parse_pipeline;
 use crate::utils::constants::{
-    DEFAULT_HEADER_READ_TIMEOUT_SECS, DEFAULT_HTTP_KEEP_ALIVE_TIMEOUT_SECS, DEFAULT_LOG_FORMAT,
-    DEFAULT_LOG_LEVEL, DEFAULT_MAX_BODY_SIZE_BYTES, DEFAULT_PREFIX, DEFAULT_SERVER_LISTEN_PRIMARY,
-    DEFAULT_SERVER_LISTEN_SECONDARY, DEFAULT_TCP_KEEPALIVE_INTERVAL_SECS,
-    DEFAULT_TCP_KEEPALIVE_RETRIES, DEFAULT_TCP_KEEPALIVE_SECS, PID_FILE_PATH,
+    DEFAULT_ACCESS_LOG_EXCLUDE, DEFAULT_ACCESS_LOG_FORMAT, DEFAULT_ACCESS_LOG_ROTATION,
+    DEFAULT_ACCESS_LOG_SAMPLE_RATE, DEFAULT_ENV_ALLOW_PREFIX, DEFAULT_HEADER_READ_TIMEOUT_SECS,
+    DEFAULT_HTTP_KEEP_ALIVE_TIMEOUT_SECS, DEFAULT_LOG_FORMAT, DEFAULT_LOG_LEVEL,
+    DEFAULT_MAX_BODY_SIZE_BYTES, DEFAULT_MAX_STREAMING_RESPONSES, DEFAULT_METRICS_BUCKET_SECONDS,
+    DEFAULT_METRICS_WINDOW_BUCKETS, DEFAULT_PREFIX, DEFAULT_SERVER_LISTEN_PRIMARY,
+    DEFAULT_SERVER_LISTEN_SECONDARY, DEFAULT_SHUTDOWN_TIMEOUT_SECS, DEFAULT_STATIC_PREFIX,
+    DEFAULT_STREAMING_SHUTDOWN_GRACE_SECS, DEFAULT_TCP_ACCEPT_LOG_SAMPLE,
+    DEFAULT_TCP_KEEPALIVE_INTERVAL_SECS, DEFAULT_TCP_KEEPALIVE_RETRIES, DEFAULT_TCP_KEEPALIVE_SECS,
+    DEFAULT_WELCOME_MESSAGE, MAX_DELAY_SECONDS, MAX_METRICS_WINDOW_BUCKETS, PID_FILE_PATH,
 };
+use crate::utils::server_config::{parse_listen_address, parse_socket_addr, TlsMinVersion};
 
 /// Configuration for chaos engineering mode.
 ///
 /// Chaos mode enables random injection of failures, delays, and response corruption
 /// to help test application resilience. Each chaos type is configured independently
 /// and rolls against its own probability rate per request.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ChaosConfig {
     /// Active chaos types (e.g., "failure", "delay", "corruption").
     pub modes: Vec<String>,
@@ -28,12 +43,28 @@ pub struct ChaosConfig {
     pub delay_ms: String,
     /// Maximum delay in milliseconds when delay_ms is "random".
     pub delay_max_ms: u64,
+    /// Distribution random delays are drawn from: "uniform" (default),
+    /// "normal", or "exponential". Samples are clamped to `0..=delay_max_ms`.
+    pub delay_distribution: String,
+    /// Mean delay in milliseconds for the "normal" distribution.
+    pub delay_mean_ms: f64,
+    /// Standard deviation in milliseconds for the "normal" distribution.
+    pub delay_stddev_ms: f64,
+    /// Rate (per millisecond) for the "exponential" distribution; the mean
+    /// delay is `1 / delay_lambda` ms.
+    pub delay_lambda: f64,
     /// Probability of corrupting the response body (0.01-1.0).
     pub corruption_rate: f64,
-    /// How to corrupt the response body: "empty", "truncate", or "garbage".
+    /// How to corrupt the response body: "empty", "truncate", "garbage", or "bitflip".
     pub corruption_type: String,
+    /// Number of random bits to flip when corruption_type is "bitflip".
+    pub corruption_bitflip_count: usize,
     /// Whether to add X-Chaos header to affected responses (default: true).
     pub inform_header: bool,
+    /// Optional RNG seed. When set, every chaos decision is drawn from one
+    /// shared RNG seeded with this value, so a run's sequence of failures,
+    /// delays, and corruptions is reproducible (given the same request order).
+    pub seed: Option<u64>,
 }
 
 impl Default for ChaosConfig {
@@ -45,9 +76,15 @@ impl Default for ChaosConfig {
             delay_rate: 0.0,
             delay_ms: String::new(),
             delay_max_ms: 0,
+            delay_distribution: "uniform".to_string(),
+            delay_mean_ms: 0.0,
+            delay_stddev_ms: 0.0,
+            delay_lambda: 0.0,
             corruption_rate: 0.0,
             corruption_type: String::new(),
+            corruption_bitflip_count: 1,
             inform_header: true,
+            seed: None,
         }
     }
 }
@@ -74,48 +111,29 @@ impl ChaosConfig {
     }
 }
 
-/// Macro to load an environment variable into a config field.
+/// A named, deterministic behavior selected per request by the
+/// `X-Rucho-Scenario` header.
 ///
-/// Accepts an `$env_reader` callable (e.g. `env::var` or a test mock) so that
-/// tests can supply a pure HashMap-backed reader instead of mutating the process
-/// environment.
-macro_rules! load_env_var {
-    ($config:expr, $field:ident, $env_var:expr, $env_reader:expr) => {
-        if let Ok(value) = $env_reader($env_var) {
-            $config.$field = value;
-        }
-    };
-    ($config:expr, $field:ident, $env_var:expr, $env_reader:expr, option) => {
-        if let Ok(value) = $env_reader($env_var) {
-            $config.$field = Some(value);
-        }
-    };
-    ($config:expr, $field:ident, $env_var:expr, $env_reader:expr, bool) => {
-        if let Ok(value) = $env_reader($env_var) {
-            $config.$field = value.eq_ignore_ascii_case("true") || value == "1";
-        }
-    };
-    ($config:expr, $field:ident, $env_var:expr, $env_reader:expr, u64) => {
-        if let Ok(value) = $env_reader($env_var) {
-            if let Ok(v) = value.parse::<u64>() {
-                $config.$field = v;
-            }
-        }
-    };
-    ($config:expr, $field:ident, $env_var:expr, $env_reader:expr, u32) => {
-        if let Ok(value) = $env_reader($env_var) {
-            if let Ok(v) = value.parse::<u32>() {
-                $config.$field = v;
-            }
-        }
-    };
-    ($config:expr, $field:ident, $env_var:expr, $env_reader:expr, usize) => {
-        if let Ok(value) = $env_reader($env_var) {
-            if let Ok(v) = value.parse::<usize>() {
-                $config.$field = v;
-            }
-        }
-    };
+/// Defined in config files as `scenario.<name>.<field> = <value>` lines, where
+/// `<field>` is `status`, `delay_ms`, or `body`. A scenario with only a delay
+/// slows the request down and lets the route answer; one with a status or body
+/// answers itself, without reaching the route.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scenario {
+    /// Status code to answer with (default 200 when only `body` is set).
+    pub status: Option<u16>,
+    /// Milliseconds to wait before answering (max `MAX_DELAY_SECONDS` × 1000).
+    pub delay_ms: Option<u64>,
+    /// Response body to answer with, verbatim.
+    pub body: Option<String>,
+}
+
+impl Scenario {
+    /// Whether the scenario answers the request itself rather than passing it
+    /// on to the route.
+    pub fn responds(&self) -> bool {
+        self.status.is_some() || self.body.is_some()
+    }
 }
 
 /// Holds the application configuration.
@@ -129,23 +147,52 @@ macro_rules! load_env_var {
 /// A sample configuration file, `rucho.conf.default`, can be found in the `config_samples`
 /// directory of the source repository. This can be used as a template for creating
 /// `/etc/rucho/rucho.conf` or `./rucho.conf`.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Config {
     /// Prefix for certain operations, e.g., file paths (Not actively used by server logic yet).
     pub prefix: String,
     /// Logging level for the application (e.g., "info", "debug", "warn", "error").
     pub log_level: String,
+    /// Per-target log levels as comma-separated `target=level` directives,
+    /// e.g. `rucho=debug,tower_http=info`. Targets it doesn't name log at
+    /// `log_level`, unless it also has a bare level (`warn,rucho=debug`).
+    /// Unset (the default) logs everything at `log_level`.
+    pub log_filter: Option<String>,
     /// Log output format: `"text"` (human-readable, default) or `"json"`
     /// (structured, for mesh/aggregator deployments like Loki/Datadog/ELK).
     pub log_format: String,
+    /// File to write an access log line to for every request, or `-` for
+    /// stdout. Unset (the default) writes no access log.
+    pub access_log: Option<String>,
+    /// Access log line format: `"combined"` (Apache Combined, default),
+    /// `"common"` (Apache Common), or `"json"`.
+    pub access_log_format: String,
+    /// How often a file access log rolls over to a new file: `"daily"`,
+    /// `"hourly"`, `"minutely"`, or `"never"` (the default).
+    pub access_log_rotation: String,
+    /// Path prefixes whose requests get no access log line (metrics still
+    /// count them). Defaults to `/healthz`, which health checkers poll.
+    pub access_log_exclude: Vec<String>,
+    /// Fraction of requests (0.0–1.0) that get an access log line, picked at
+    /// random. `1.0` (the default) logs every request.
+    pub access_log_sample_rate: f64,
     /// Primary listen address and port for the server (e.g., "0.0.0.0:8080" or "ssl:0.0.0.0:8443").
     pub server_listen_primary: String,
     /// Secondary listen address and port for the server (e.g., "0.0.0.0:9090" or "ssl:0.0.0.0:9443"). Can be empty.
     pub server_listen_secondary: String,
+    /// Further HTTP(S) listen addresses, each in the same form as
+    /// `server_listen_primary`. Set as a comma-separated list, or by repeating
+    /// the `server_listen` line in one config file. See
+    /// [`listen_addresses`](crate::utils::server_config::listen_addresses) for
+    /// how it combines with the two fields above.
+    pub server_listen: Vec<String>,
     /// Optional TCP echo listener address (e.g., "0.0.0.0:7777").
     pub server_listen_tcp: Option<String>,
     /// Optional UDP echo listener address (e.g., "0.0.0.0:7778").
     pub server_listen_udp: Option<String>,
+    /// Log one in every N accepted TCP echo connections (default 1 = all;
+    /// 0 disables the accept log). Every accept is still counted.
+    pub tcp_accept_log_sample: u64,
     /// Optional path to an SSL certificate file for HTTPS. Required if any listen address uses "ssl:".
     pub ssl_cert: Option<String>,
     /// Optional path to an SSL private key file for HTTPS. Required if any listen address uses "ssl:".
@@ -155,17 +202,64 @@ pub struct Config {
     /// cert is self-signed (clients must skip verification) and regenerated on
     /// each start. Explicit `ssl_cert`/`ssl_key` files take precedence.
     pub ssl_auto_cert: bool,
+    /// Optional path to a PEM bundle of CA certificates. When set, HTTPS
+    /// listeners require mutual TLS: clients must present a certificate that
+    /// chains to one of these CAs, and its subject is echoed under `tls`.
+    pub ssl_client_ca: Option<String>,
+    /// Path prefixes (e.g. `/admin`) that require a verified client certificate.
+    /// Empty (the default) with `ssl_client_ca` set requires one on every HTTPS
+    /// connection; non-empty makes the certificate optional at the handshake and
+    /// enforces it only for these paths (403 otherwise). Requires `ssl_client_ca`.
+    pub ssl_client_cert_paths: Vec<String>,
+    /// Lowest TLS version HTTPS listeners accept: `"1.2"` (default) or `"1.3"`.
+    pub tls_min_version: String,
+    /// ALPN protocols advertised by HTTPS listeners, in preference order
+    /// (default `h2, http/1.1`). Empty disables ALPN.
+    pub tls_alpn: Vec<String>,
     /// Path to the PID file backing `rucho stop`/`status`. A write failure here
     /// is non-fatal — the server still starts (read-only filesystems, missing
     /// parent dir). Point it at a writable location (e.g. `/tmp`) if needed.
     pub pid_file: String,
     /// Enable the /metrics endpoint for request statistics.
     pub metrics_enabled: bool,
+    /// Number of buckets in the `/metrics` rolling window (1-10000).
+    pub metrics_window_buckets: usize,
+    /// Duration of each `/metrics` rolling-window bucket in seconds. The window
+    /// spans `metrics_window_buckets × metrics_bucket_seconds` (default 60 × 60s).
+    pub metrics_bucket_seconds: u64,
     /// Enable response compression (gzip, brotli) based on client Accept-Encoding.
     pub compression_enabled: bool,
     /// Set an `X-Request-Id` correlation header on every response (default on).
     /// Propagates a non-blank inbound `X-Request-Id`, otherwise mints a UUID v4.
     pub request_id_enabled: bool,
+    /// Trust `X-Forwarded-For` / `X-Real-IP` when reporting the client IP
+    /// (`origin` in `/ip` and `/get`). On by default, for running behind a
+    /// gateway; turn off when clients connect directly, so they can't spoof
+    /// their address, and the socket peer address is reported instead. The
+    /// rate limiter has its own `rate_limit_trust_proxy`.
+    pub trust_proxy: bool,
+    /// Origins allowed to make CORS requests, e.g. `https://app.example.com`;
+    /// `*` (the default) allows any. A disallowed origin gets no
+    /// `Access-Control-Allow-Origin` header.
+    pub cors_allow_origins: Vec<String>,
+    /// Methods a CORS preflight may ask for; `*` (the default) allows any.
+    pub cors_allow_methods: Vec<String>,
+    /// Request headers a CORS preflight may ask for; `*` (the default) allows any.
+    pub cors_allow_headers: Vec<String>,
+    /// Value of the `Server` header on every response (default
+    /// `rucho/<version>`). Empty sends no `Server` header.
+    pub server_header: String,
+    /// Banner served at `/` (default `Welcome to Echo Server!`): plain text
+    /// with a trailing newline, or `{"message": ...}` for `Accept:
+    /// application/json`.
+    pub welcome_message: String,
+    /// Directory to serve static files from; unset (the default) serves none.
+    pub static_dir: Option<String>,
+    /// Path `static_dir` is mounted at (default `/static`).
+    pub static_prefix: String,
+    /// Environment variable name prefixes `/env` shows. Defaults to `RUCHO_`;
+    /// empty shows nothing.
+    pub env_allow_prefixes: Vec<String>,
     /// HTTP keep-alive timeout in seconds. How long an idle connection stays open.
     pub http_keep_alive_timeout: u64,
     /// TCP keep-alive idle time in seconds. How long before probes start on idle connections.
@@ -178,9 +272,48 @@ pub struct Config {
     pub tcp_nodelay: bool,
     /// Maximum time in seconds to wait for request headers from a client.
     pub header_read_timeout: u64,
+    /// Seconds a handler may take to respond before the request is abandoned
+    /// with a 504 Gateway Timeout. `0` (the default) disables the timeout.
+    pub request_timeout_secs: u64,
+    /// Seconds in-flight requests get to finish once shutdown starts, before
+    /// connections are force-closed. `0` waits indefinitely.
+    pub shutdown_timeout_secs: u64,
+    /// Seconds streaming responses (`/drip`, `/stream-bytes/:n`, `/events`,
+    /// `/anything?echo=stream`) may keep running once shutdown starts,
+    /// independent of `shutdown_timeout_secs` for other requests.
+    pub streaming_shutdown_grace_secs: u64,
+    /// Maximum number of streaming responses (`/drip`, `/stream-bytes/:n`,
+    /// `/events`, `/anything?echo=stream`) open at once; further streaming
+    /// requests get 503 until one finishes. `0` means unlimited.
+    pub max_streaming_responses: usize,
     /// Maximum request body size in bytes. Enforced globally via `DefaultBodyLimit`.
     /// Requests with bodies larger than this receive a 413 Payload Too Large response.
     pub max_body_size_bytes: usize,
+    /// Per-client-IP rate limit in requests per second. `0` (default) disables
+    /// rate limiting; over-limit requests get 429 with `Retry-After`.
+    pub rate_limit_rps: u32,
+    /// Token-bucket burst size for the rate limiter. `0` (default) means the
+    /// same as `rate_limit_rps`.
+    pub rate_limit_burst: u32,
+    /// Key the rate limiter on `X-Forwarded-For` / `X-Real-IP` rather than the
+    /// socket peer. Off by default, since a client could otherwise dodge its
+    /// limit with a fresh header on every request.
+    pub rate_limit_trust_proxy: bool,
+    /// Secret for `/anything?sign=true`, which adds an `X-Signature` HMAC-SHA256
+    /// of the response body. Unset (the default) disables signing.
+    pub signing_secret: Option<String>,
+    /// Enables `/anything?fail_midstream=true`, which sends half of the JSON
+    /// body and then aborts the response. Off by default.
+    pub fail_midstream_enabled: bool,
+    /// Body transforms applied to every response, in order (e.g. `gzip,
+    /// base64`). Empty (the default) applies none. A request's `X-Transform`
+    /// header replaces this pipeline. Valid names: gzip, deflate, base64,
+    /// uppercase, lowercase.
+    pub response_transforms: Vec<String>,
+    /// Deterministic behaviors selectable with the `X-Rucho-Scenario` header,
+    /// by name. Config files only (`scenario.<name>.<field>` keys); there is no
+    /// environment variable form.
+    pub scenarios: BTreeMap<String, Scenario>,
     /// Chaos engineering configuration.
     pub chaos: ChaosConfig,
 }
@@ -192,25 +325,59 @@ impl Default for Config {
         Config {
             prefix: DEFAULT_PREFIX.to_string(),
             log_level: DEFAULT_LOG_LEVEL.to_string(),
+            log_filter: None,
             log_format: DEFAULT_LOG_FORMAT.to_string(),
+            access_log: None,
+            access_log_format: DEFAULT_ACCESS_LOG_FORMAT.to_string(),
+            access_log_rotation: DEFAULT_ACCESS_LOG_ROTATION.to_string(),
+            access_log_exclude: vec![DEFAULT_ACCESS_LOG_EXCLUDE.to_string()],
+            access_log_sample_rate: DEFAULT_ACCESS_LOG_SAMPLE_RATE,
             server_listen_primary: DEFAULT_SERVER_LISTEN_PRIMARY.to_string(),
             server_listen_secondary: DEFAULT_SERVER_LISTEN_SECONDARY.to_string(),
+            server_listen: Vec::new(),
             server_listen_tcp: None,
             server_listen_udp: None,
+            tcp_accept_log_sample: DEFAULT_TCP_ACCEPT_LOG_SAMPLE,
             ssl_cert: None,
             ssl_key: None,
             ssl_auto_cert: false,
+            ssl_client_ca: None,
+            ssl_client_cert_paths: Vec::new(),
+            tls_min_version: "1.2".to_string(),
+            tls_alpn: vec!["h2".to_string(), "http/1.1".to_string()],
             pid_file: PID_FILE_PATH.to_string(),
             metrics_enabled: false,
+            metrics_window_buckets: DEFAULT_METRICS_WINDOW_BUCKETS,
+            metrics_bucket_seconds: DEFAULT_METRICS_BUCKET_SECONDS,
             compression_enabled: false,
             request_id_enabled: true,
+            trust_proxy: true,
+            cors_allow_origins: vec!["*".to_string()],
+            cors_allow_methods: vec!["*".to_string()],
+            cors_allow_headers: vec!["*".to_string()],
+            server_header: format!("rucho/{}", env!("CARGO_PKG_VERSION")),
+            welcome_message: DEFAULT_WELCOME_MESSAGE.to_string(),
+            static_dir: None,
+            static_prefix: DEFAULT_STATIC_PREFIX.to_string(),
+            env_allow_prefixes: vec![DEFAULT_ENV_ALLOW_PREFIX.to_string()],
             http_keep_alive_timeout: DEFAULT_HTTP_KEEP_ALIVE_TIMEOUT_SECS,
             tcp_keepalive_time: DEFAULT_TCP_KEEPALIVE_SECS,
             tcp_keepalive_interval: DEFAULT_TCP_KEEPALIVE_INTERVAL_SECS,
             tcp_keepalive_retries: DEFAULT_TCP_KEEPALIVE_RETRIES,
             tcp_nodelay: true,
             header_read_timeout: DEFAULT_HEADER_READ_TIMEOUT_SECS,
+            request_timeout_secs: 0,
+            shutdown_timeout_secs: DEFAULT_SHUTDOWN_TIMEOUT_SECS,
+            streaming_shutdown_grace_secs: DEFAULT_STREAMING_SHUTDOWN_GRACE_SECS,
+            max_streaming_responses: DEFAULT_MAX_STREAMING_RESPONSES,
             max_body_size_bytes: DEFAULT_MAX_BODY_SIZE_BYTES,
+            rate_limit_rps: 0,
+            rate_limit_burst: 0,
+            rate_limit_trust_proxy: false,
+            signing_secret: None,
+            fail_midstream_enabled: false,
+            response_transforms: Vec::new(),
+            scenarios: BTreeMap::new(),
             chaos: ChaosConfig::default(),
         }
     }
@@ -227,6 +394,24 @@ pub enum ConfigValidationError {
     Connection(String),
     /// A chaos configuration requirement is not met
     Chaos(String),
+    /// A metrics configuration value is invalid
+    Metrics(String),
+    /// A TLS configuration requirement is not met
+    Tls(String),
+    /// A response transform name is unknown
+    Transform(String),
+    /// A scenario definition is invalid
+    Scenario(String),
+    /// A CORS allow-list entry doesn't parse
+    Cors(String),
+    /// A configured response header value is invalid
+    Header(String),
+    /// `static_dir` isn't a directory or `static_prefix` can't be mounted
+    StaticFiles(String),
+    /// `log_filter` has a directive that doesn't parse
+    LogFilter(String),
+    /// `access_log_format` or `access_log_rotation` has an unknown value
+    AccessLog(String),
 }
 
 impl std::fmt::Display for ConfigValidationError {
@@ -244,334 +429,643 @@ impl std::fmt::Display for ConfigValidationError {
             ConfigValidationError::Chaos(msg) => {
                 write!(f, "Chaos config error: {}", msg)
             }
+            ConfigValidationError::Metrics(msg) => {
+                write!(f, "Metrics config error: {}", msg)
+            }
+            ConfigValidationError::Tls(msg) => {
+                write!(f, "TLS config error: {}", msg)
+            }
+            ConfigValidationError::Transform(msg) => {
+                write!(f, "Transform config error: {}", msg)
+            }
+            ConfigValidationError::Scenario(msg) => {
+                write!(f, "Scenario config error: {}", msg)
+            }
+            ConfigValidationError::Cors(msg) => {
+                write!(f, "CORS config error: {}", msg)
+            }
+            ConfigValidationError::Header(msg) => {
+                write!(f, "Header config error: {}", msg)
+            }
+            ConfigValidationError::StaticFiles(msg) => {
+                write!(f, "Static files config error: {}", msg)
+            }
+            ConfigValidationError::LogFilter(msg) => {
+                write!(f, "Log filter config error: {}", msg)
+            }
+            ConfigValidationError::AccessLog(msg) => {
+                write!(f, "Access log config error: {}", msg)
+            }
         }
     }
 }
 
 impl std::error::Error for ConfigValidationError {}
 
+/// A problem found while loading configuration, before validation.
+///
+/// [`Config::load`] prints these as warnings and carries on;
+/// [`Config::load_checked`] returns them. Line numbers are 1-based, and `None`
+/// for entries from TOML files, whose keys are reported by their dotted path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// A config file exists but could not be read
+    UnreadableFile { path: PathBuf, reason: String },
+    /// A TOML config file failed to parse (the reason includes the position)
+    InvalidToml { path: PathBuf, reason: String },
+    /// A config file entry names a key rucho does not know
+    UnknownKey {
+        path: PathBuf,
+        line: Option<usize>,
+        key: String,
+    },
+    /// A config file line is not `key = value`
+    InvalidLine {
+        path: PathBuf,
+        line: usize,
+        content: String,
+    },
+    /// A config file value could not be parsed for its key
+    InvalidValue {
+        path: PathBuf,
+        line: Option<usize>,
+        key: String,
+        value: String,
+    },
+    /// A `RUCHO_*` environment variable's value could not be parsed for its key
+    InvalidEnvValue { var: String, value: String },
+    /// A listen address (from a file or the environment) has a bad port or host
+    InvalidListenAddress {
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl ConfigError {
+    /// Whether the error should stop the server from starting.
+    ///
+    /// Unknown keys are not fatal, so a config file written for a newer rucho
+    /// still loads; everything else means a setting was not applied as written.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, ConfigError::UnknownKey { .. })
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::UnreadableFile { path, reason } => {
+                write!(
+                    f,
+                    "Could not read config file {}: {}",
+                    path.display(),
+                    reason
+                )
+            }
+            ConfigError::InvalidToml { path, reason } => {
+                write!(f, "{}: invalid TOML: {}", path.display(), reason)
+            }
+            ConfigError::UnknownKey { path, line, key } => {
+                write!(f, "{}: unknown key '{}'", location(path, *line), key)
+            }
+            ConfigError::InvalidLine {
+                path,
+                line,
+                content,
+            } => write!(
+                f,
+                "{}:{}: invalid line '{}' (expected key = value)",
+                path.display(),
+                line,
+                content
+            ),
+            ConfigError::InvalidValue {
+                path,
+                line,
+                key,
+                value,
+            } => write!(
+                f,
+                "{}: invalid value '{}' for {}",
+                location(path, *line),
+                value,
+                key
+            ),
+            ConfigError::InvalidEnvValue { var, value } => {
+                write!(f, "invalid value '{}' for {}", value, var)
+            }
+            ConfigError::InvalidListenAddress { key, value, reason } => {
+                write!(
+                    f,
+                    "Invalid listen address '{}' for {}: {}",
+                    value, key, reason
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Where a setting's value came from, in increasing order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// The hardcoded default in `Config::default()`
+    Default,
+    /// The system-wide config file, `/etc/rucho/rucho.conf`
+    Etc,
+    /// The local config file, `./rucho.conf`
+    Cwd,
+    /// A `RUCHO_*` environment variable
+    Env,
+    /// A command-line flag, such as `--log-level`
+    Cli,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ConfigSource::Default => "default",
+            ConfigSource::Etc => "etc",
+            ConfigSource::Cwd => "cwd",
+            ConfigSource::Env => "env",
+            ConfigSource::Cli => "cli",
+        })
+    }
+}
+
+/// The [`ConfigSource`] that won for each setting of a loaded [`Config`],
+/// keyed by config-file key (as in [`Config::entries`]).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigSources(BTreeMap<String, ConfigSource>);
+
+impl ConfigSources {
+    /// The source of `key`; [`ConfigSource::Default`] if nothing set it.
+    pub fn get(&self, key: &str) -> ConfigSource {
+        self.0.get(key).copied().unwrap_or(ConfigSource::Default)
+    }
+
+    /// Records that `key` was last set by `source`.
+    pub fn set(&mut self, key: &str, source: ConfigSource) {
+        self.0.insert(key.to_string(), source);
+    }
+}
+
+/// Formats `path:line`, or just `path` when the line is unknown.
+fn location(path: &Path, line: Option<usize>) -> String {
+    match line {
+        Some(line) => format!("{}:{}", path.display(), line),
+        None => path.display().to_string(),
+    }
+}
+
+/// Whether a config file should be parsed as TOML: it has a `.toml` extension,
+/// or its first non-comment line is a `[section]` header.
+fn is_toml(path: &Path, contents: &str) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+        || contents
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .is_some_and(|line| line.starts_with('['))
+}
+
+/// Converts a scalar TOML value, or an array of them, to the flat format's
+/// string form; arrays become comma-separated lists. Returns `None` for tables
+/// and nested arrays.
+fn toml_value_to_flat(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Integer(i) => Some(i.to_string()),
+        toml::Value::Float(f) => Some(f.to_string()),
+        toml::Value::Boolean(b) => Some(b.to_string()),
+        toml::Value::Datetime(d) => Some(d.to_string()),
+        toml::Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                toml::Value::Array(_) | toml::Value::Table(_) => None,
+                scalar => toml_value_to_flat(scalar),
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|items| items.join(",")),
+        toml::Value::Table(_) => None,
+    }
+}
+
+/// One leaf of a TOML config: the equivalent flat key, the dotted TOML path
+/// (for error messages), and the value.
+struct TomlEntry<'a> {
+    key: String,
+    toml_path: String,
+    value: &'a toml::Value,
+}
+
+/// Flattens a TOML table into flat-format keys.
+///
+/// Tables join onto their parent key with `_`, so `[chaos] failure_rate` and
+/// `[chaos.failure] rate` both become `chaos_failure_rate`. `[scenario.<name>]`
+/// tables keep the flat format's dotted `scenario.<name>.<field>` keys.
+fn flatten_toml<'a>(table: &'a toml::Table, prefix: &str, toml_prefix: &str) -> Vec<TomlEntry<'a>> {
+    let mut entries = Vec::new();
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else if prefix == "scenario" || prefix.starts_with("scenario.") {
+            format!("{prefix}.{name}")
+        } else {
+            format!("{prefix}_{name}")
+        };
+        let toml_path = if toml_prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{toml_prefix}.{name}")
+        };
+        match value {
+            toml::Value::Table(inner) => entries.extend(flatten_toml(inner, &key, &toml_path)),
+            value => entries.push(TomlEntry {
+                key,
+                toml_path,
+                value,
+            }),
+        }
+    }
+    entries
+}
+
 impl Config {
-    // Internal helper function to parse lines from a configuration file.
+    /// Parses one `scenario.<name>.<field> = <value>` line into `config.scenarios`.
+    ///
+    /// Returns a description of the problem if the field is unknown or its value
+    /// does not parse; the caller attaches the file position.
+    fn parse_scenario_line(config: &mut Config, key: &str, value: &str) -> Result<(), EntryError> {
+        let (name, field) = key
+            .strip_prefix("scenario.")
+            .and_then(|rest| rest.rsplit_once('.'))
+            .ok_or(EntryError::UnknownKey)?;
+        let scenario = config.scenarios.entry(name.to_string()).or_default();
+        match field {
+            "status" => {
+                scenario.status = Some(value.parse().map_err(|_| EntryError::InvalidValue)?)
+            }
+            "delay_ms" => {
+                scenario.delay_ms = Some(value.parse().map_err(|_| EntryError::InvalidValue)?)
+            }
+            "body" => scenario.body = Some(value.to_string()),
+            _ => return Err(EntryError::UnknownKey),
+        }
+        Ok(())
+    }
+
+    // Internal helper function to parse a configuration file.
     // It updates the provided `Config` mutable instance with values found in the `contents`.
-    // Lines starting with '#' or empty lines are ignored.
-    // Expected format for lines is "key = value".
-    #[cfg_attr(not(test), allow(dead_code))] // Allow dead code for this helper when not in test builds
-    fn parse_file_contents(config: &mut Config, contents: String) {
-        for line in contents.lines() {
+    // TOML files (see `is_toml`) are flattened onto the same keys; otherwise
+    // lines starting with '#' or empty lines are ignored, and the expected
+    // format for lines is "key = value".
+    // Unknown keys and malformed lines are pushed onto `errors`, tagged with `path`.
+    // Every key applied is recorded in `sources` as coming from `source`.
+    fn parse_file_contents(
+        config: &mut Config,
+        path: &Path,
+        contents: String,
+        source: ConfigSource,
+        sources: &mut ConfigSources,
+        errors: &mut Vec<ConfigError>,
+    ) {
+        if is_toml(path, &contents) {
+            Self::parse_toml_contents(config, path, &contents, source, sources, errors);
+            return;
+        }
+        // Repeated `server_listen` lines accumulate within a file; the first one
+        // replaces whatever an earlier file set.
+        let mut server_listen_seen = false;
+        for (index, line) in contents.lines().enumerate() {
+            let line_number = index + 1;
             // Skip comments and empty lines
             if line.starts_with('#') || line.trim().is_empty() {
                 continue;
             }
-            let parts: Vec<&str> = line.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim();
-                let value = parts[1].trim();
+            let Some((key, value)) = line.split_once('=') else {
+                errors.push(ConfigError::InvalidLine {
+                    path: path.to_path_buf(),
+                    line: line_number,
+                    content: line.trim().to_string(),
+                });
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match Self::apply_entry(config, key, value, &mut server_listen_seen) {
+                Ok(()) => sources.set(key, source),
+                Err(EntryError::UnknownKey) => errors.push(ConfigError::UnknownKey {
+                    path: path.to_path_buf(),
+                    line: Some(line_number),
+                    key: key.to_string(),
+                }),
+                Err(EntryError::InvalidValue) => errors.push(ConfigError::InvalidValue {
+                    path: path.to_path_buf(),
+                    line: Some(line_number),
+                    key: key.to_string(),
+                    value: value.to_string(),
+                }),
+            }
+        }
+    }
+
+    /// Parses a TOML config file by flattening it onto the flat format's keys
+    /// (see [`flatten_toml`]) and applying each entry in turn.
+    fn parse_toml_contents(
+        config: &mut Config,
+        path: &Path,
+        contents: &str,
+        source: ConfigSource,
+        sources: &mut ConfigSources,
+        errors: &mut Vec<ConfigError>,
+    ) {
+        let table = match contents.parse::<toml::Table>() {
+            Ok(table) => table,
+            Err(e) => {
+                errors.push(ConfigError::InvalidToml {
+                    path: path.to_path_buf(),
+                    reason: e.message().to_string(),
+                });
+                return;
+            }
+        };
+        let mut server_listen_seen = false;
+        for entry in flatten_toml(&table, "", "") {
+            let result = match toml_value_to_flat(entry.value) {
+                Some(value) => {
+                    Self::apply_entry(config, &entry.key, &value, &mut server_listen_seen)
+                }
+                None => Err(EntryError::InvalidValue),
+            };
+            match result {
+                Ok(()) => sources.set(&entry.key, source),
+                Err(EntryError::UnknownKey) => errors.push(ConfigError::UnknownKey {
+                    path: path.to_path_buf(),
+                    line: None,
+                    key: entry.toml_path,
+                }),
+                Err(EntryError::InvalidValue) => errors.push(ConfigError::InvalidValue {
+                    path: path.to_path_buf(),
+                    line: None,
+                    key: entry.toml_path,
+                    value: entry.value.to_string(),
+                }),
+            }
+        }
+    }
+
+    /// Applies one `key = value` config entry.
+    ///
+    /// `server_listen_seen` tracks whether this file has set `server_listen`
+    /// yet, so repeated entries accumulate within a file.
+    fn apply_entry(
+        config: &mut Config,
+        key: &str,
+        value: &str,
+        server_listen_seen: &mut bool,
+    ) -> Result<(), EntryError> {
+        match key {
+            "prefix" => config.prefix = value.to_string(),
+            "log_level" => config.log_level = value.to_string(),
+            "log_filter" => config.log_filter = Some(value.to_string()),
+            "log_format" => config.log_format = value.to_string(),
+            "access_log" => config.access_log = Some(value.to_string()),
+            "access_log_format" => config.access_log_format = value.to_string(),
+            "access_log_rotation" => config.access_log_rotation = value.to_string(),
+            "access_log_sample_rate" => config.access_log_sample_rate = parse_value(value)?,
+            "env_allow_prefixes" => config.env_allow_prefixes = parse_list(value),
+            "access_log_exclude" => config.access_log_exclude = parse_list(value),
+            "server_listen_primary" => config.server_listen_primary = value.to_string(),
+            "server_listen_secondary" => config.server_listen_secondary = value.to_string(),
+            "server_listen" => {
+                if !*server_listen_seen {
+                    config.server_listen.clear();
+                    *server_listen_seen = true;
+                }
+                config.server_listen.extend(parse_list(value));
+            }
+            "server_listen_tcp" => config.server_listen_tcp = Some(value.to_string()),
+            "server_listen_udp" => config.server_listen_udp = Some(value.to_string()),
+            "tcp_accept_log_sample" => config.tcp_accept_log_sample = parse_value(value)?,
+            "ssl_cert" => config.ssl_cert = Some(value.to_string()),
+            "ssl_key" => config.ssl_key = Some(value.to_string()),
+            "ssl_auto_cert" => config.ssl_auto_cert = parse_bool(value)?,
+            "ssl_client_ca" => config.ssl_client_ca = Some(value.to_string()),
+            "ssl_client_cert_paths" => config.ssl_client_cert_paths = parse_list(value),
+            "tls_min_version" => config.tls_min_version = value.to_string(),
+            "tls_alpn" => config.tls_alpn = parse_list(value),
+            "pid_file" => config.pid_file = value.to_string(),
+            "server_header" => config.server_header = value.to_string(),
+            "welcome_message" => config.welcome_message = value.to_string(),
+            "static_dir" => config.static_dir = Some(value.to_string()),
+            "static_prefix" => config.static_prefix = value.to_string(),
+            "metrics_enabled" => config.metrics_enabled = parse_bool(value)?,
+            "metrics_window_buckets" => config.metrics_window_buckets = parse_value(value)?,
+            "metrics_bucket_seconds" => config.metrics_bucket_seconds = parse_value(value)?,
+            "compression_enabled" => config.compression_enabled = parse_bool(value)?,
+            "request_id_enabled" => config.request_id_enabled = parse_bool(value)?,
+            "trust_proxy" => config.trust_proxy = parse_bool(value)?,
+            "cors_allow_origins" | "cors_allow_methods" | "cors_allow_headers" => {
+                let list = parse_list(value);
                 match key {
-                    "prefix" => config.prefix = value.to_string(),
-                    "log_level" => config.log_level = value.to_string(),
-                    "log_format" => config.log_format = value.to_string(),
-                    "server_listen_primary" => config.server_listen_primary = value.to_string(),
-                    "server_listen_secondary" => config.server_listen_secondary = value.to_string(),
-                    "server_listen_tcp" => config.server_listen_tcp = Some(value.to_string()),
-                    "server_listen_udp" => config.server_listen_udp = Some(value.to_string()),
-                    "ssl_cert" => config.ssl_cert = Some(value.to_string()),
-                    "ssl_key" => config.ssl_key = Some(value.to_string()),
-                    "ssl_auto_cert" => {
-                        config.ssl_auto_cert = value.eq_ignore_ascii_case("true") || value == "1"
-                    }
-                    "pid_file" => config.pid_file = value.to_string(),
-                    "metrics_enabled" => {
-                        config.metrics_enabled = value.eq_ignore_ascii_case("true") || value == "1"
-                    }
-                    "compression_enabled" => {
-                        config.compression_enabled =
-                            value.eq_ignore_ascii_case("true") || value == "1"
-                    }
-                    "request_id_enabled" => {
-                        config.request_id_enabled =
-                            value.eq_ignore_ascii_case("true") || value == "1"
-                    }
-                    "http_keep_alive_timeout" => {
-                        if let Ok(v) = value.parse::<u64>() {
-                            config.http_keep_alive_timeout = v;
-                        }
-                    }
-                    "tcp_keepalive_time" => {
-                        if let Ok(v) = value.parse::<u64>() {
-                            config.tcp_keepalive_time = v;
-                        }
-                    }
-                    "tcp_keepalive_interval" => {
-                        if let Ok(v) = value.parse::<u64>() {
-                            config.tcp_keepalive_interval = v;
-                        }
-                    }
-                    "tcp_keepalive_retries" => {
-                        if let Ok(v) = value.parse::<u32>() {
-                            config.tcp_keepalive_retries = v;
-                        }
-                    }
-                    "tcp_nodelay" => {
-                        config.tcp_nodelay = value.eq_ignore_ascii_case("true") || value == "1"
-                    }
-                    "header_read_timeout" => {
-                        if let Ok(v) = value.parse::<u64>() {
-                            config.header_read_timeout = v;
-                        }
-                    }
-                    "max_body_size_bytes" => {
-                        if let Ok(v) = value.parse::<usize>() {
-                            config.max_body_size_bytes = v;
-                        }
-                    }
-                    "chaos_mode" => {
-                        config.chaos.modes = value
-                            .split(',')
-                            .map(|s| s.trim().to_string())
-                            .filter(|s| !s.is_empty())
-                            .collect();
-                    }
-                    "chaos_failure_rate" => {
-                        if let Ok(v) = value.parse::<f64>() {
-                            config.chaos.failure_rate = v;
-                        }
-                    }
-                    "chaos_failure_codes" => {
-                        config.chaos.failure_codes = value
-                            .split(',')
-                            .filter_map(|s| s.trim().parse::<u16>().ok())
-                            .collect();
-                    }
-                    "chaos_delay_rate" => {
-                        if let Ok(v) = value.parse::<f64>() {
-                            config.chaos.delay_rate = v;
-                        }
-                    }
-                    "chaos_delay_ms" => {
-                        config.chaos.delay_ms = value.to_string();
-                    }
-                    "chaos_delay_max_ms" => {
-                        if let Ok(v) = value.parse::<u64>() {
-                            config.chaos.delay_max_ms = v;
-                        }
-                    }
-                    "chaos_corruption_rate" => {
-                        if let Ok(v) = value.parse::<f64>() {
-                            config.chaos.corruption_rate = v;
-                        }
-                    }
-                    "chaos_corruption_type" => {
-                        config.chaos.corruption_type = value.to_string();
-                    }
-                    "chaos_inform_header" => {
-                        config.chaos.inform_header =
-                            value.eq_ignore_ascii_case("true") || value == "1"
-                    }
-                    _ => eprintln!("Warning: Unknown key in config file: {}", key),
+                    "cors_allow_origins" => config.cors_allow_origins = list,
+                    "cors_allow_methods" => config.cors_allow_methods = list,
+                    _ => config.cors_allow_headers = list,
                 }
-            } else {
-                eprintln!("Warning: Invalid line in config file: {}", line);
+            }
+            "http_keep_alive_timeout" => config.http_keep_alive_timeout = parse_value(value)?,
+            "tcp_keepalive_time" => config.tcp_keepalive_time = parse_value(value)?,
+            "tcp_keepalive_interval" => config.tcp_keepalive_interval = parse_value(value)?,
+            "tcp_keepalive_retries" => config.tcp_keepalive_retries = parse_value(value)?,
+            "tcp_nodelay" => config.tcp_nodelay = parse_bool(value)?,
+            "header_read_timeout" => config.header_read_timeout = parse_value(value)?,
+            "request_timeout_secs" => config.request_timeout_secs = parse_value(value)?,
+            "shutdown_timeout_secs" => config.shutdown_timeout_secs = parse_value(value)?,
+            "streaming_shutdown_grace_secs" => {
+                config.streaming_shutdown_grace_secs = parse_value(value)?
+            }
+            "max_streaming_responses" => config.max_streaming_responses = parse_value(value)?,
+            "max_body_size_bytes" => config.max_body_size_bytes = parse_value(value)?,
+            "rate_limit_rps" => config.rate_limit_rps = parse_value(value)?,
+            "rate_limit_burst" => config.rate_limit_burst = parse_value(value)?,
+            "rate_limit_trust_proxy" => config.rate_limit_trust_proxy = parse_bool(value)?,
+            "signing_secret" => config.signing_secret = Some(value.to_string()),
+            "fail_midstream_enabled" => config.fail_midstream_enabled = parse_bool(value)?,
+            "response_transforms" => config.response_transforms = parse_list(value),
+            "chaos_mode" => config.chaos.modes = parse_list(value),
+            "chaos_failure_rate" => config.chaos.failure_rate = parse_value(value)?,
+            "chaos_failure_codes" => {
+                config.chaos.failure_codes = parse_list(value)
+                    .iter()
+                    .map(|code| parse_value(code))
+                    .collect::<Result<_, _>>()?;
+            }
+            "chaos_delay_rate" => config.chaos.delay_rate = parse_value(value)?,
+            "chaos_delay_ms" => {
+                config.chaos.delay_ms = value.to_string();
+            }
+            "chaos_delay_max_ms" => config.chaos.delay_max_ms = parse_value(value)?,
+            "chaos_delay_distribution" => {
+                config.chaos.delay_distribution = value.to_string();
+            }
+            "chaos_delay_mean_ms" => config.chaos.delay_mean_ms = parse_value(value)?,
+            "chaos_delay_stddev_ms" => config.chaos.delay_stddev_ms = parse_value(value)?,
+            "chaos_delay_lambda" => config.chaos.delay_lambda = parse_value(value)?,
+            "chaos_corruption_rate" => config.chaos.corruption_rate = parse_value(value)?,
+            "chaos_corruption_type" => {
+                config.chaos.corruption_type = value.to_string();
+            }
+            "chaos_corruption_bitflip_count" => {
+                config.chaos.corruption_bitflip_count = parse_value(value)?
+            }
+            "chaos_inform_header" => config.chaos.inform_header = parse_bool(value)?,
+            "chaos_seed" => config.chaos.seed = Some(parse_value(value)?),
+            _ if key.starts_with("scenario.") => {
+                return Self::parse_scenario_line(config, key, value)
+            }
+            _ => return Err(EntryError::UnknownKey),
+        }
+        Ok(())
+    }
+
+    /// Checks every configured listen address parses, recording failures.
+    fn check_listen_addresses(&self, errors: &mut Vec<ConfigError>) {
+        let addresses = [
+            ("server_listen_primary", Some(&self.server_listen_primary)),
+            (
+                "server_listen_secondary",
+                Some(&self.server_listen_secondary),
+            ),
+            ("server_listen_tcp", self.server_listen_tcp.as_ref()),
+            ("server_listen_udp", self.server_listen_udp.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|v| (key, v)))
+        .chain(self.server_listen.iter().map(|v| ("server_listen", v)));
+        for (key, value) in addresses {
+            // Blank addresses are skipped by the listeners rather than rejected.
+            // HTTP(S) addresses may carry an SSL marker; strip it first.
+            let addr = match key {
+                "server_listen_tcp" | "server_listen_udp" => Some(value.clone()),
+                _ => parse_listen_address(value).map(|(addr, _)| addr),
+            };
+            let Some(addr) = addr.filter(|a| !a.trim().is_empty()) else {
+                continue;
+            };
+            if let Err(e) = parse_socket_addr(&addr) {
+                errors.push(ConfigError::InvalidListenAddress {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    reason: e.to_string(),
+                });
             }
         }
     }
 
-    /// Loads configuration from file paths with an injectable environment reader.
+    /// Loads configuration from file paths with an injectable environment reader,
+    /// printing any [`ConfigError`]s as warnings.
     ///
-    /// This is the core loading method. Tests inject a mock `env_reader` to avoid
-    /// mutating process-global environment variables; production code passes `env::var`.
+    /// Tests inject a mock `env_reader` to avoid mutating process-global
+    /// environment variables; production code passes `env::var`.
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn load_from_paths_with_env(
+        etc_path_override: Option<PathBuf>,
+        local_path_override: Option<PathBuf>,
+        env_reader: &dyn Fn(&str) -> Result<String, env::VarError>,
+    ) -> Self {
+        let (config, errors) =
+            Self::load_collecting_errors(etc_path_override, local_path_override, env_reader);
+        for error in &errors {
+            eprintln!("Warning: {}", error);
+        }
+        config
+    }
+
+    /// Loads configuration and returns it together with every problem found.
+    fn load_collecting_errors(
+        etc_path_override: Option<PathBuf>,
+        local_path_override: Option<PathBuf>,
+        env_reader: &dyn Fn(&str) -> Result<String, env::VarError>,
+    ) -> (Self, Vec<ConfigError>) {
+        let (config, _, errors) =
+            Self::load_tracking_sources(etc_path_override, local_path_override, env_reader);
+        (config, errors)
+    }
+
+    /// Loads configuration and returns it together with the source of each
+    /// setting and every problem found.
     ///
-    /// Loading order (later stages override earlier ones):
+    /// This is the core loading method. Loading order (later stages override
+    /// earlier ones):
     /// 1. Defaults from `Config::default()`.
     /// 2. Values from the ETC path (or `/etc/rucho/rucho.conf`).
     /// 3. Values from the local path (or `./rucho.conf`).
     /// 4. Environment variables via `env_reader`.
-    #[cfg_attr(not(test), allow(dead_code))]
-    fn load_from_paths_with_env(
+    ///
+    /// A setting's source is the last stage that set it, even to the value
+    /// it already had. `RUCHO_<KEY>` is recorded against `<key>` lowercased.
+    ///
+    /// Listen addresses are checked once all stages have applied.
+    fn load_tracking_sources(
         etc_path_override: Option<PathBuf>,
         local_path_override: Option<PathBuf>,
         env_reader: &dyn Fn(&str) -> Result<String, env::VarError>,
-    ) -> Self {
+    ) -> (Self, ConfigSources, Vec<ConfigError>) {
         let mut config = Config::default();
+        let mut sources = ConfigSources::default();
+        let mut errors = Vec::new();
 
         // Determine paths to use: override or default.
         let etc_config_path =
             etc_path_override.unwrap_or_else(|| PathBuf::from("/etc/rucho/rucho.conf"));
         let local_config_path = local_path_override.unwrap_or_else(|| PathBuf::from("rucho.conf"));
 
-        // Load from the system-wide config file (e.g., /etc/rucho/rucho.conf or override)
-        if etc_config_path.exists() {
-            if let Ok(contents) = fs::read_to_string(&etc_config_path) {
-                Self::parse_file_contents(&mut config, contents);
-            } else {
-                eprintln!(
-                    "Warning: Could not read system config file at {:?}, though it exists.",
-                    etc_config_path
-                );
+        // Load from the system-wide config file, then the local one, which
+        // overrides it. A missing file is fine; one that exists but can't be
+        // read is an error.
+        for (path, source) in [
+            (&etc_config_path, ConfigSource::Etc),
+            (&local_config_path, ConfigSource::Cwd),
+        ] {
+            if !path.exists() {
+                continue;
             }
-        }
-
-        // Load from the local config file (e.g., ./rucho.conf or override), overriding previous values
-        if local_config_path.exists() {
-            if let Ok(contents) = fs::read_to_string(&local_config_path) {
-                Self::parse_file_contents(&mut config, contents);
-            } else {
-                eprintln!(
-                    "Warning: Could not read local config file at {:?}, though it exists.",
-                    local_config_path
-                );
+            match fs::read_to_string(path) {
+                Ok(contents) => Self::parse_file_contents(
+                    &mut config,
+                    path,
+                    contents,
+                    source,
+                    &mut sources,
+                    &mut errors,
+                ),
+                Err(e) => errors.push(ConfigError::UnreadableFile {
+                    path: path.clone(),
+                    reason: e.to_string(),
+                }),
             }
         }
 
-        // 4. Override with environment variables
-        load_env_var!(config, prefix, "RUCHO_PREFIX", env_reader);
-        load_env_var!(config, log_level, "RUCHO_LOG_LEVEL", env_reader);
-        load_env_var!(config, log_format, "RUCHO_LOG_FORMAT", env_reader);
-        load_env_var!(
-            config,
-            server_listen_primary,
-            "RUCHO_SERVER_LISTEN_PRIMARY",
-            env_reader
-        );
-        load_env_var!(
-            config,
-            server_listen_secondary,
-            "RUCHO_SERVER_LISTEN_SECONDARY",
-            env_reader
-        );
-        load_env_var!(
-            config,
-            server_listen_tcp,
-            "RUCHO_SERVER_LISTEN_TCP",
-            env_reader,
-            option
-        );
-        load_env_var!(
-            config,
-            server_listen_udp,
-            "RUCHO_SERVER_LISTEN_UDP",
-            env_reader,
-            option
-        );
-        load_env_var!(config, ssl_cert, "RUCHO_SSL_CERT", env_reader, option);
-        load_env_var!(config, ssl_key, "RUCHO_SSL_KEY", env_reader, option);
-        load_env_var!(
-            config,
-            ssl_auto_cert,
-            "RUCHO_SSL_AUTO_CERT",
-            env_reader,
-            bool
-        );
-        load_env_var!(config, pid_file, "RUCHO_PID_FILE", env_reader);
-        load_env_var!(
-            config,
-            metrics_enabled,
-            "RUCHO_METRICS_ENABLED",
-            env_reader,
-            bool
-        );
-        load_env_var!(
-            config,
-            compression_enabled,
-            "RUCHO_COMPRESSION_ENABLED",
-            env_reader,
-            bool
-        );
-        load_env_var!(
-            config,
-            request_id_enabled,
-            "RUCHO_REQUEST_ID_ENABLED",
-            env_reader,
-            bool
-        );
-        load_env_var!(
-            config,
-            http_keep_alive_timeout,
-            "RUCHO_HTTP_KEEP_ALIVE_TIMEOUT",
-            env_reader,
-            u64
-        );
-        load_env_var!(
-            config,
-            tcp_keepalive_time,
-            "RUCHO_TCP_KEEPALIVE_TIME",
-            env_reader,
-            u64
-        );
-        load_env_var!(
-            config,
-            tcp_keepalive_interval,
-            "RUCHO_TCP_KEEPALIVE_INTERVAL",
-            env_reader,
-            u64
-        );
-        load_env_var!(
-            config,
-            tcp_keepalive_retries,
-            "RUCHO_TCP_KEEPALIVE_RETRIES",
-            env_reader,
-            u32
-        );
-        load_env_var!(config, tcp_nodelay, "RUCHO_TCP_NODELAY", env_reader, bool);
-        load_env_var!(
-            config,
-            header_read_timeout,
-            "RUCHO_HEADER_READ_TIMEOUT",
-            env_reader,
-            u64
-        );
-        load_env_var!(
-            config,
-            max_body_size_bytes,
-            "RUCHO_MAX_BODY_SIZE_BYTES",
-            env_reader,
-            usize
-        );
-
-        // Chaos mode env vars (manual parsing since macro doesn't support nested fields)
-        if let Ok(value) = env_reader("RUCHO_CHAOS_MODE") {
-            config.chaos.modes = value
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-        }
-        if let Ok(value) = env_reader("RUCHO_CHAOS_FAILURE_RATE") {
-            if let Ok(v) = value.parse::<f64>() {
-                config.chaos.failure_rate = v;
-            }
-        }
-        if let Ok(value) = env_reader("RUCHO_CHAOS_FAILURE_CODES") {
-            config.chaos.failure_codes = value
-                .split(',')
-                .filter_map(|s| s.trim().parse::<u16>().ok())
-                .collect();
-        }
-        if let Ok(value) = env_reader("RUCHO_CHAOS_DELAY_RATE") {
-            if let Ok(v) = value.parse::<f64>() {
-                config.chaos.delay_rate = v;
-            }
-        }
-        if let Ok(value) = env_reader("RUCHO_CHAOS_DELAY_MS") {
-            config.chaos.delay_ms = value;
-        }
-        if let Ok(value) = env_reader("RUCHO_CHAOS_DELAY_MAX_MS") {
-            if let Ok(v) = value.parse::<u64>() {
-                config.chaos.delay_max_ms = v;
-            }
-        }
-        if let Ok(value) = env_reader("RUCHO_CHAOS_CORRUPTION_RATE") {
-            if let Ok(v) = value.parse::<f64>() {
-                config.chaos.corruption_rate = v;
+        // 4. Override with environment variables: `RUCHO_<KEY>` for every
+        // key, parsed exactly like a config file entry. A list replaces the
+        // files' list rather than adding to it.
+        let mut server_listen_seen = false;
+        for (key, _) in Config::default().entries() {
+            let var = format!("RUCHO_{}", key.to_ascii_uppercase());
+            let Ok(value) = env_reader(&var) else {
+                continue;
+            };
+            match Self::apply_entry(&mut config, &key, &value, &mut server_listen_seen) {
+                Ok(()) => sources.set(&key, ConfigSource::Env),
+                Err(_) => errors.push(ConfigError::InvalidEnvValue { var, value }),
             }
         }
-        if let Ok(value) = env_reader("RUCHO_CHAOS_CORRUPTION_TYPE") {
-            config.chaos.corruption_type = value;
-        }
-        if let Ok(value) = env_reader("RUCHO_CHAOS_INFORM_HEADER") {
-            config.chaos.inform_header = value.eq_ignore_ascii_case("true") || value == "1";
-        }
 
-        config
+        config.check_listen_addresses(&mut errors);
+        (config, sources, errors)
     }
 
     /// Loads configuration from file paths using real environment variables.
@@ -593,6 +1087,18 @@ impl Config {
     ///
     /// - `SslCertWithoutKey`: SSL certificate is specified but key is missing
     /// - `SslKeyWithoutCert`: SSL key is specified but certificate is missing
+    /// - `Tls`: `ssl_client_cert_paths` is set without `ssl_client_ca` or has a
+    ///   relative entry, `tls_min_version` isn't `1.2`/`1.3`, or a `tls_alpn`
+    ///   entry is longer than 255 bytes
+    /// - `Cors`: a `cors_allow_*` entry isn't a valid origin, method, or header name
+    /// - `Header`: `server_header` isn't a valid header value
+    /// - `StaticFiles`: `static_dir` isn't a directory, or `static_prefix`
+    ///   doesn't start with `/`, is `/`, ends with `/`, has `:`/`*`, or
+    ///   overlaps a built-in route such as `/get` or `/kv`
+    /// - `LogFilter`: `log_filter` isn't a list of `target=level` directives
+    /// - `AccessLog`: `access_log_format` isn't `combined`, `common` or `json`,
+    ///   `access_log_rotation` isn't `daily`, `hourly`, `minutely` or `never`,
+    ///   or `access_log_sample_rate` is outside 0.0–1.0
     pub fn validate(&self) -> Result<(), ConfigValidationError> {
         match (&self.ssl_cert, &self.ssl_key) {
             (Some(_), None) => return Err(ConfigValidationError::SslCertWithoutKey),
@@ -600,9 +1106,304 @@ impl Config {
             _ => {}
         }
 
+        self.validate_tls()?;
         self.validate_connection()?;
+        self.validate_metrics()?;
+        self.validate_transforms()?;
+        self.validate_scenarios()?;
         self.validate_chaos()?;
+        self.cors()
+            .validate()
+            .map_err(ConfigValidationError::Cors)?;
+        if HeaderValue::from_str(&self.server_header).is_err() {
+            return Err(ConfigValidationError::Header(format!(
+                "server_header '{}' is not a valid header value",
+                self.server_header.escape_debug()
+            )));
+        }
+        if let Some(dir) = &self.static_dir {
+            if !Path::new(dir).is_dir() {
+                return Err(ConfigValidationError::StaticFiles(format!(
+                    "static_dir '{dir}' is not a directory"
+                )));
+            }
+            static_files::validate_prefix(&self.static_prefix)
+                .map_err(ConfigValidationError::StaticFiles)?;
+        }
+        if let Some(filter) = &self.log_filter {
+            filter.parse::<Targets>().map_err(|e| {
+                ConfigValidationError::LogFilter(format!("log_filter '{filter}': {e}"))
+            })?;
+        }
+        self.access_log_format
+            .parse::<AccessLogFormat>()
+            .map_err(ConfigValidationError::AccessLog)?;
+        self.access_log_rotation
+            .parse::<AccessLogRotation>()
+            .map_err(ConfigValidationError::AccessLog)?;
+        if !(0.0..=1.0).contains(&self.access_log_sample_rate) {
+            return Err(ConfigValidationError::AccessLog(
+                "access_log_sample_rate must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The global log filter: `log_filter`'s directives, with `level` (the
+    /// parsed `log_level`) for the targets they don't name unless they have a
+    /// bare level of their own. An unparsable `log_filter`, which
+    /// [`Config::validate`] rejects, is ignored.
+    pub fn log_targets(&self, level: LevelFilter) -> Targets {
+        let targets = self
+            .log_filter
+            .as_deref()
+            .and_then(|filter| filter.parse::<Targets>().ok())
+            .unwrap_or_default();
+        if targets.default_level().is_some() {
+            targets
+        } else {
+            targets.with_default(level)
+        }
+    }
+
+    /// The CORS policy from the `cors_allow_*` fields, as `build_app` takes it.
+    pub fn cors(&self) -> CorsConfig {
+        CorsConfig {
+            allow_origins: self.cors_allow_origins.clone(),
+            allow_methods: self.cors_allow_methods.clone(),
+            allow_headers: self.cors_allow_headers.clone(),
+        }
+    }
+
+    /// The static file mount from `static_dir` and `static_prefix`, as
+    /// `build_app` takes it; `None` when `static_dir` is unset.
+    pub fn static_files(&self) -> Option<StaticFiles> {
+        self.static_dir.as_ref().map(|dir| StaticFiles {
+            dir: PathBuf::from(dir),
+            prefix: self.static_prefix.clone(),
+        })
+    }
+
+    /// The per-request handler timeout from `request_timeout_secs`, as
+    /// `build_app` takes it; `None` when it is 0.
+    pub fn request_timeout(&self) -> Option<Duration> {
+        (self.request_timeout_secs > 0).then(|| Duration::from_secs(self.request_timeout_secs))
+    }
+
+    /// Every setting as a config-file `(key, value)` pair, in field order;
+    /// lists are comma-separated. The value is `None` for an unset optional
+    /// setting.
+    pub fn entries(&self) -> Vec<(String, Option<String>)> {
+        let list = |items: &[String]| Some(items.join(","));
+        let chaos = &self.chaos;
+        let mut entries: Vec<(String, Option<String>)> = [
+            ("prefix", Some(self.prefix.clone())),
+            ("log_level", Some(self.log_level.clone())),
+            ("log_filter", self.log_filter.clone()),
+            ("log_format", Some(self.log_format.clone())),
+            ("access_log", self.access_log.clone()),
+            ("access_log_format", Some(self.access_log_format.clone())),
+            (
+                "access_log_rotation",
+                Some(self.access_log_rotation.clone()),
+            ),
+            ("access_log_exclude", list(&self.access_log_exclude)),
+            (
+                "access_log_sample_rate",
+                Some(self.access_log_sample_rate.to_string()),
+            ),
+            (
+                "server_listen_primary",
+                Some(self.server_listen_primary.clone()),
+            ),
+            (
+                "server_listen_secondary",
+                Some(self.server_listen_secondary.clone()),
+            ),
+            ("server_listen", list(&self.server_listen)),
+            ("server_listen_tcp", self.server_listen_tcp.clone()),
+            ("server_listen_udp", self.server_listen_udp.clone()),
+            (
+                "tcp_accept_log_sample",
+                Some(self.tcp_accept_log_sample.to_string()),
+            ),
+            ("ssl_cert", self.ssl_cert.clone()),
+            ("ssl_key", self.ssl_key.clone()),
+            ("ssl_auto_cert", Some(self.ssl_auto_cert.to_string())),
+            ("ssl_client_ca", self.ssl_client_ca.clone()),
+            ("ssl_client_cert_paths", list(&self.ssl_client_cert_paths)),
+            ("tls_min_version", Some(self.tls_min_version.clone())),
+            ("tls_alpn", list(&self.tls_alpn)),
+            ("pid_file", Some(self.pid_file.clone())),
+            ("metrics_enabled", Some(self.metrics_enabled.to_string())),
+            (
+                "metrics_window_buckets",
+                Some(self.metrics_window_buckets.to_string()),
+            ),
+            (
+                "metrics_bucket_seconds",
+                Some(self.metrics_bucket_seconds.to_string()),
+            ),
+            (
+                "compression_enabled",
+                Some(self.compression_enabled.to_string()),
+            ),
+            (
+                "request_id_enabled",
+                Some(self.request_id_enabled.to_string()),
+            ),
+            ("trust_proxy", Some(self.trust_proxy.to_string())),
+            ("cors_allow_origins", list(&self.cors_allow_origins)),
+            ("cors_allow_methods", list(&self.cors_allow_methods)),
+            ("cors_allow_headers", list(&self.cors_allow_headers)),
+            ("server_header", Some(self.server_header.clone())),
+            ("welcome_message", Some(self.welcome_message.clone())),
+            ("static_dir", self.static_dir.clone()),
+            ("static_prefix", Some(self.static_prefix.clone())),
+            ("env_allow_prefixes", list(&self.env_allow_prefixes)),
+            (
+                "http_keep_alive_timeout",
+                Some(self.http_keep_alive_timeout.to_string()),
+            ),
+            (
+                "tcp_keepalive_time",
+                Some(self.tcp_keepalive_time.to_string()),
+            ),
+            (
+                "tcp_keepalive_interval",
+                Some(self.tcp_keepalive_interval.to_string()),
+            ),
+            (
+                "tcp_keepalive_retries",
+                Some(self.tcp_keepalive_retries.to_string()),
+            ),
+            ("tcp_nodelay", Some(self.tcp_nodelay.to_string())),
+            (
+                "header_read_timeout",
+                Some(self.header_read_timeout.to_string()),
+            ),
+            (
+                "request_timeout_secs",
+                Some(self.request_timeout_secs.to_string()),
+            ),
+            (
+                "shutdown_timeout_secs",
+                Some(self.shutdown_timeout_secs.to_string()),
+            ),
+            (
+                "streaming_shutdown_grace_secs",
+                Some(self.streaming_shutdown_grace_secs.to_string()),
+            ),
+            (
+                "max_streaming_responses",
+                Some(self.max_streaming_responses.to_string()),
+            ),
+            (
+                "max_body_size_bytes",
+                Some(self.max_body_size_bytes.to_string()),
+            ),
+            ("rate_limit_rps", Some(self.rate_limit_rps.to_string())),
+            ("rate_limit_burst", Some(self.rate_limit_burst.to_string())),
+            (
+                "rate_limit_trust_proxy",
+                Some(self.rate_limit_trust_proxy.to_string()),
+            ),
+            ("signing_secret", self.signing_secret.clone()),
+            (
+                "fail_midstream_enabled",
+                Some(self.fail_midstream_enabled.to_string()),
+            ),
+            ("response_transforms", list(&self.response_transforms)),
+            ("chaos_mode", list(&chaos.modes)),
+            ("chaos_failure_rate", Some(chaos.failure_rate.to_string())),
+            (
+                "chaos_failure_codes",
+                Some(
+                    chaos
+                        .failure_codes
+                        .iter()
+                        .map(u16::to_string)
+                        .collect::<Vec<_>>()
+                        .join(","),
+                ),
+            ),
+            ("chaos_delay_rate", Some(chaos.delay_rate.to_string())),
+            ("chaos_delay_ms", Some(chaos.delay_ms.clone())),
+            ("chaos_delay_max_ms", Some(chaos.delay_max_ms.to_string())),
+            (
+                "chaos_delay_distribution",
+                Some(chaos.delay_distribution.clone()),
+            ),
+            ("chaos_delay_mean_ms", Some(chaos.delay_mean_ms.to_string())),
+            (
+                "chaos_delay_stddev_ms",
+                Some(chaos.delay_stddev_ms.to_string()),
+            ),
+            ("chaos_delay_lambda", Some(chaos.delay_lambda.to_string())),
+            (
+                "chaos_corruption_rate",
+                Some(chaos.corruption_rate.to_string()),
+            ),
+            ("chaos_corruption_type", Some(chaos.corruption_type.clone())),
+            (
+                "chaos_corruption_bitflip_count",
+                Some(chaos.corruption_bitflip_count.to_string()),
+            ),
+            ("chaos_inform_header", Some(chaos.inform_header.to_string())),
+            ("chaos_seed", chaos.seed.map(|seed| seed.to_string())),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+        for (name, scenario) in &self.scenarios {
+            entries.extend([
+                (
+                    format!("scenario.{name}.status"),
+                    scenario.status.map(|status| status.to_string()),
+                ),
+                (
+                    format!("scenario.{name}.delay_ms"),
+                    scenario.delay_ms.map(|ms| ms.to_string()),
+                ),
+                (format!("scenario.{name}.body"), scenario.body.clone()),
+            ]);
+        }
+        entries
+    }
+
+    /// Validates the TLS protocol and mutual-TLS settings.
+    fn validate_tls(&self) -> Result<(), ConfigValidationError> {
+        if TlsMinVersion::parse(&self.tls_min_version).is_none() {
+            return Err(ConfigValidationError::Tls(format!(
+                "tls_min_version must be 1.2 or 1.3, got '{}'",
+                self.tls_min_version
+            )));
+        }
+        // ALPN protocol ids are length-prefixed with a single byte on the wire.
+        if let Some(proto) = self.tls_alpn.iter().find(|p| p.len() > 255) {
+            return Err(ConfigValidationError::Tls(format!(
+                "tls_alpn entry '{proto}' exceeds 255 bytes"
+            )));
+        }
 
+        if self.ssl_client_cert_paths.is_empty() {
+            return Ok(());
+        }
+        if self.ssl_client_ca.is_none() {
+            return Err(ConfigValidationError::Tls(
+                "ssl_client_cert_paths requires ssl_client_ca".to_string(),
+            ));
+        }
+        if let Some(path) = self
+            .ssl_client_cert_paths
+            .iter()
+            .find(|p| !p.starts_with('/'))
+        {
+            return Err(ConfigValidationError::Tls(format!(
+                "ssl_client_cert_paths entry '{path}' must start with '/'"
+            )));
+        }
         Ok(())
     }
 
@@ -638,6 +1439,69 @@ impl Config {
                 "max_body_size_bytes must be greater than 0".to_string(),
             ));
         }
+        // No stream rucho produces runs longer than MAX_DELAY_SECONDS.
+        if self.streaming_shutdown_grace_secs > MAX_DELAY_SECONDS {
+            return Err(ConfigValidationError::Connection(format!(
+                "streaming_shutdown_grace_secs must be at most {}",
+                MAX_DELAY_SECONDS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validates the `/metrics` rolling-window settings.
+    fn validate_metrics(&self) -> Result<(), ConfigValidationError> {
+        if self.metrics_window_buckets == 0
+            || self.metrics_window_buckets > MAX_METRICS_WINDOW_BUCKETS
+        {
+            return Err(ConfigValidationError::Metrics(format!(
+                "metrics_window_buckets must be between 1 and {}",
+                MAX_METRICS_WINDOW_BUCKETS
+            )));
+        }
+        if self.metrics_bucket_seconds == 0 {
+            return Err(ConfigValidationError::Metrics(
+                "metrics_bucket_seconds must be greater than 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Validates that every `response_transforms` entry names a known transform.
+    fn validate_transforms(&self) -> Result<(), ConfigValidationError> {
+        parse_pipeline(self.response_transforms.iter().map(String::as_str))
+            .map(|_| ())
+            .map_err(ConfigValidationError::Transform)
+    }
+
+    /// Validates the `scenario.*` definitions: each needs a behavior, a real
+    /// status code, and a delay within the delay cap.
+    fn validate_scenarios(&self) -> Result<(), ConfigValidationError> {
+        for (name, scenario) in &self.scenarios {
+            if name.is_empty() {
+                return Err(ConfigValidationError::Scenario(
+                    "scenario names must not be empty".to_string(),
+                ));
+            }
+            if !scenario.responds() && scenario.delay_ms.is_none() {
+                return Err(ConfigValidationError::Scenario(format!(
+                    "scenario '{name}' sets none of status, delay_ms, body"
+                )));
+            }
+            if let Some(status) = scenario.status {
+                if !(100..=599).contains(&status) {
+                    return Err(ConfigValidationError::Scenario(format!(
+                        "scenario '{name}' status must be 100-599, got {status}"
+                    )));
+                }
+            }
+            if scenario.delay_ms.unwrap_or(0) > MAX_DELAY_SECONDS * 1000 {
+                return Err(ConfigValidationError::Scenario(format!(
+                    "scenario '{name}' delay_ms exceeds {} ms",
+                    MAX_DELAY_SECONDS * 1000
+                )));
+            }
+        }
         Ok(())
     }
 
@@ -704,6 +1568,35 @@ impl Config {
                             .to_string(),
                     ));
                 }
+                match chaos.delay_distribution.as_str() {
+                    "uniform" => {}
+                    "normal" => {
+                        let non_negative = |v: f64| v.is_finite() && v >= 0.0;
+                        if !non_negative(chaos.delay_mean_ms)
+                            || !non_negative(chaos.delay_stddev_ms)
+                        {
+                            return Err(ConfigValidationError::Chaos(
+                                "chaos_delay_mean_ms and chaos_delay_stddev_ms must be >= 0 \
+                                 for the 'normal' distribution"
+                                    .to_string(),
+                            ));
+                        }
+                    }
+                    "exponential" => {
+                        if !(chaos.delay_lambda.is_finite() && chaos.delay_lambda > 0.0) {
+                            return Err(ConfigValidationError::Chaos(
+                                "chaos_delay_lambda must be > 0 for the 'exponential' distribution"
+                                    .to_string(),
+                            ));
+                        }
+                    }
+                    other => {
+                        return Err(ConfigValidationError::Chaos(format!(
+                            "Invalid chaos_delay_distribution '{}'. Valid distributions: uniform, normal, exponential",
+                            other
+                        )));
+                    }
+                }
             } else if chaos.delay_ms.parse::<u64>().is_err() {
                 return Err(ConfigValidationError::Chaos(
                     "chaos_delay_ms must be a number or 'random'".to_string(),
@@ -718,13 +1611,18 @@ impl Config {
                     "chaos_corruption_rate must be between 0.01 and 1.0".to_string(),
                 ));
             }
-            let valid_corruption_types = ["empty", "truncate", "garbage"];
+            let valid_corruption_types = ["empty", "truncate", "garbage", "bitflip"];
             if !valid_corruption_types.contains(&chaos.corruption_type.as_str()) {
                 return Err(ConfigValidationError::Chaos(format!(
-                    "Invalid chaos_corruption_type '{}'. Valid types: empty, truncate, garbage",
+                    "Invalid chaos_corruption_type '{}'. Valid types: empty, truncate, garbage, bitflip",
                     chaos.corruption_type
                 )));
             }
+            if chaos.corruption_type == "bitflip" && chaos.corruption_bitflip_count == 0 {
+                return Err(ConfigValidationError::Chaos(
+                    "chaos_corruption_bitflip_count must be at least 1".to_string(),
+                ));
+            }
         }
 
         Ok(())
@@ -746,48 +1644,147 @@ impl Config {
     /// Supported keys in config files and corresponding environment variables:
     /// - `prefix` (`RUCHO_PREFIX`)
     /// - `log_level` (`RUCHO_LOG_LEVEL`)
+    /// - `log_filter` (`RUCHO_LOG_FILTER`)
     /// - `log_format` (`RUCHO_LOG_FORMAT`)
+    /// - `access_log` (`RUCHO_ACCESS_LOG`)
+    /// - `access_log_format` (`RUCHO_ACCESS_LOG_FORMAT`)
+    /// - `access_log_rotation` (`RUCHO_ACCESS_LOG_ROTATION`)
+    /// - `access_log_exclude` (`RUCHO_ACCESS_LOG_EXCLUDE`, comma-separated)
+    /// - `access_log_sample_rate` (`RUCHO_ACCESS_LOG_SAMPLE_RATE`)
     /// - `server_listen_primary` (`RUCHO_SERVER_LISTEN_PRIMARY`)
     /// - `server_listen_secondary` (`RUCHO_SERVER_LISTEN_SECONDARY`)
+    /// - `server_listen` (`RUCHO_SERVER_LISTEN`, comma-separated; repeatable in files)
     /// - `server_listen_tcp` (`RUCHO_SERVER_LISTEN_TCP`)
     /// - `server_listen_udp` (`RUCHO_SERVER_LISTEN_UDP`)
+    /// - `tcp_accept_log_sample` (`RUCHO_TCP_ACCEPT_LOG_SAMPLE`)
     /// - `ssl_cert` (`RUCHO_SSL_CERT`)
     /// - `ssl_key` (`RUCHO_SSL_KEY`)
     /// - `ssl_auto_cert` (`RUCHO_SSL_AUTO_CERT`)
+    /// - `ssl_client_ca` (`RUCHO_SSL_CLIENT_CA`)
+    /// - `ssl_client_cert_paths` (`RUCHO_SSL_CLIENT_CERT_PATHS`, comma-separated)
+    /// - `tls_min_version` (`RUCHO_TLS_MIN_VERSION`)
+    /// - `tls_alpn` (`RUCHO_TLS_ALPN`, comma-separated)
     /// - `pid_file` (`RUCHO_PID_FILE`)
     /// - `metrics_enabled` (`RUCHO_METRICS_ENABLED`)
+    /// - `metrics_window_buckets` (`RUCHO_METRICS_WINDOW_BUCKETS`)
+    /// - `metrics_bucket_seconds` (`RUCHO_METRICS_BUCKET_SECONDS`)
     /// - `compression_enabled` (`RUCHO_COMPRESSION_ENABLED`)
     /// - `request_id_enabled` (`RUCHO_REQUEST_ID_ENABLED`)
+    /// - `trust_proxy` (`RUCHO_TRUST_PROXY`)
+    /// - `cors_allow_origins` (`RUCHO_CORS_ALLOW_ORIGINS`, comma-separated)
+    /// - `cors_allow_methods` (`RUCHO_CORS_ALLOW_METHODS`, comma-separated)
+    /// - `cors_allow_headers` (`RUCHO_CORS_ALLOW_HEADERS`, comma-separated)
+    /// - `server_header` (`RUCHO_SERVER_HEADER`, empty to disable)
+    /// - `welcome_message` (`RUCHO_WELCOME_MESSAGE`)
+    /// - `static_dir` (`RUCHO_STATIC_DIR`)
+    /// - `static_prefix` (`RUCHO_STATIC_PREFIX`)
+    /// - `env_allow_prefixes` (`RUCHO_ENV_ALLOW_PREFIXES`, comma-separated)
     /// - `http_keep_alive_timeout` (`RUCHO_HTTP_KEEP_ALIVE_TIMEOUT`)
     /// - `tcp_keepalive_time` (`RUCHO_TCP_KEEPALIVE_TIME`)
     /// - `tcp_keepalive_interval` (`RUCHO_TCP_KEEPALIVE_INTERVAL`)
     /// - `tcp_keepalive_retries` (`RUCHO_TCP_KEEPALIVE_RETRIES`)
     /// - `tcp_nodelay` (`RUCHO_TCP_NODELAY`)
     /// - `header_read_timeout` (`RUCHO_HEADER_READ_TIMEOUT`)
+    /// - `request_timeout_secs` (`RUCHO_REQUEST_TIMEOUT_SECS`, 0 to disable)
+    /// - `shutdown_timeout_secs` (`RUCHO_SHUTDOWN_TIMEOUT_SECS`)
+    /// - `streaming_shutdown_grace_secs` (`RUCHO_STREAMING_SHUTDOWN_GRACE_SECS`)
+    /// - `max_streaming_responses` (`RUCHO_MAX_STREAMING_RESPONSES`)
     /// - `max_body_size_bytes` (`RUCHO_MAX_BODY_SIZE_BYTES`)
+    /// - `rate_limit_rps` (`RUCHO_RATE_LIMIT_RPS`)
+    /// - `rate_limit_burst` (`RUCHO_RATE_LIMIT_BURST`)
+    /// - `rate_limit_trust_proxy` (`RUCHO_RATE_LIMIT_TRUST_PROXY`)
+    /// - `signing_secret` (`RUCHO_SIGNING_SECRET`)
+    /// - `fail_midstream_enabled` (`RUCHO_FAIL_MIDSTREAM_ENABLED`)
+    /// - `response_transforms` (`RUCHO_RESPONSE_TRANSFORMS`, comma-separated)
     /// - chaos keys (`RUCHO_CHAOS_*`) — see `config_samples/rucho.conf.default`
+    /// - `scenario.<name>.status` / `.delay_ms` / `.body` (config files only)
+    ///
+    /// Problems found while loading (see [`ConfigError`]) are printed as
+    /// warnings and skipped; use [`Config::load_checked`] to act on them.
     pub fn load() -> Self {
         Self::load_from_paths(None, None)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
-    use std::env;
-    use std::fs::{self, File};
-    use std::io::Write;
-    use std::path::PathBuf;
-    use tempfile::TempDir;
-
-    /// Manages temporary directories for config file tests.
+    /// Loads configuration like [`Config::load`], but returns every problem
+    /// found instead of printing it.
     ///
-    /// Unlike the previous version, this does NOT mutate process-global state
-    /// (no `env::set_var`, no `env::set_current_dir`). Tests pass explicit file
-    /// paths and a mock env reader instead.
-    struct TestEnv {
-        _etc_dir: TempDir,
+    /// # Errors
+    ///
+    /// Returns all [`ConfigError`]s found, in file order followed by listen
+    /// address errors, if there are any. Check [`ConfigError::is_fatal`] to
+    /// decide whether to carry on with [`Config::load_with_errors`].
+    pub fn load_checked() -> Result<Self, Vec<ConfigError>> {
+        let (config, errors) = Self::load_with_errors();
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Loads configuration and returns it together with every problem found,
+    /// fatal or not, so a caller can report warnings and still start.
+    pub fn load_with_errors() -> (Self, Vec<ConfigError>) {
+        Self::load_collecting_errors(None, None, &|key| env::var(key))
+    }
+
+    /// Loads configuration like [`Config::load_with_errors`], also returning
+    /// which source each setting came from (for `rucho config`).
+    pub fn load_with_sources() -> (Self, ConfigSources, Vec<ConfigError>) {
+        Self::load_tracking_sources(None, None, &|key| env::var(key))
+    }
+}
+
+/// Why a config entry was rejected; see [`Config::apply_entry`].
+enum EntryError {
+    UnknownKey,
+    InvalidValue,
+}
+
+/// Parses a config value, rejecting it as [`EntryError::InvalidValue`] if it
+/// doesn't parse as `T`.
+fn parse_value<T: std::str::FromStr>(value: &str) -> Result<T, EntryError> {
+    value.parse().map_err(|_| EntryError::InvalidValue)
+}
+
+/// Parses a comma-separated list config value, trimming each item and
+/// dropping empty ones.
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Parses a boolean config value: `true`/`1` or `false`/`0`, any case.
+fn parse_bool(value: &str) -> Result<bool, EntryError> {
+    if value.eq_ignore_ascii_case("true") || value == "1" {
+        Ok(true)
+    } else if value.eq_ignore_ascii_case("false") || value == "0" {
+        Ok(false)
+    } else {
+        Err(EntryError::InvalidValue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    /// Manages temporary directories for config file tests.
+    ///
+    /// Unlike the previous version, this does NOT mutate process-global state
+    /// (no `env::set_var`, no `env::set_current_dir`). Tests pass explicit file
+    /// paths and a mock env reader instead.
+    struct TestEnv {
+        _etc_dir: TempDir,
         etc_rucho_conf_path: PathBuf,
         _cwd_dir: TempDir,
         cwd_rucho_conf_path: PathBuf,
@@ -954,6 +1951,73 @@ mod tests {
         assert_eq!(config.server_listen_secondary, "0.0.0.0:9090");
     }
 
+    #[test]
+    fn test_sources_record_the_winning_stage() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.etc_rucho_conf_path,
+            "prefix = /etc/path\nlog_level = etc_level\npid_file = /etc/rucho.pid",
+        );
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "[chaos]\nmode = \"delay\"\n\n[scenario.outage]\nstatus = 503\n\n[log]\nlevel = \"cwd_level\"",
+        );
+        let env = mock_env(HashMap::from([
+            ("RUCHO_LOG_LEVEL", "env_level"),
+            ("RUCHO_CORS_ALLOW_ORIGINS", "https://a.example"),
+        ]));
+        let (config, sources, errors) = Config::load_tracking_sources(
+            Some(t.etc_rucho_conf_path.clone()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(config.log_level, "env_level");
+        assert_eq!(sources.get("log_level"), ConfigSource::Env);
+        assert_eq!(sources.get("log_level").to_string(), "env");
+        assert_eq!(sources.get("cors_allow_origins"), ConfigSource::Env);
+        assert_eq!(sources.get("chaos_mode"), ConfigSource::Cwd);
+        assert_eq!(sources.get("scenario.outage.status"), ConfigSource::Cwd);
+        assert_eq!(sources.get("prefix"), ConfigSource::Etc);
+        assert_eq!(sources.get("pid_file"), ConfigSource::Etc);
+        assert_eq!(sources.get("server_listen_primary"), ConfigSource::Default);
+    }
+
+    #[test]
+    fn test_entries_cover_every_setting() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "server_listen = 127.0.0.1:1,127.0.0.1:2\n\
+             ssl_cert = /c.pem\nssl_key = /k.pem\nsigning_secret = s3cret\n\
+             chaos_mode = failure,delay\nchaos_failure_codes = 500,503\n\
+             chaos_failure_rate = 0.25\nchaos_seed = 7\ntls_alpn =\n\
+             scenario.outage.status = 503\nscenario.outage.body = down",
+        );
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+
+        // Applying every entry to the defaults rebuilds the config, so no
+        // setting is missing from `entries` or written in a form it can't read.
+        let mut rebuilt = Config::default();
+        let mut server_listen_seen = false;
+        for (key, value) in config.entries() {
+            if let Some(value) = value {
+                Config::apply_entry(&mut rebuilt, &key, &value, &mut server_listen_seen)
+                    .unwrap_or_else(|_| panic!("entry {key} = {value} doesn't apply"));
+            }
+        }
+        assert_eq!(rebuilt, config);
+
+        let entries: HashMap<_, _> = config.entries().into_iter().collect();
+        assert_eq!(entries["chaos_failure_codes"].as_deref(), Some("500,503"));
+        assert_eq!(entries["static_dir"], None);
+        assert_eq!(entries["scenario.outage.delay_ms"], None);
+    }
+
     #[test]
     fn test_partial_configs_layering() {
         let t = TestEnv::new();
@@ -1168,6 +2232,484 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_ssl_client_cert_paths() {
+        let mut config = Config {
+            ssl_client_cert_paths: vec!["/admin".to_string()],
+            ..Config::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Tls(_))
+        ));
+
+        config.ssl_client_ca = Some("/path/to/ca.pem".to_string());
+        assert_eq!(config.validate(), Ok(()));
+
+        config.ssl_client_cert_paths = vec!["admin".to_string()];
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Tls(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_tls_min_version_and_alpn() {
+        let mut config = Config::default();
+        assert_eq!(config.tls_min_version, "1.2");
+        assert_eq!(config.tls_alpn, vec!["h2", "http/1.1"]);
+
+        config.tls_min_version = "1.3".to_string();
+        assert_eq!(config.validate(), Ok(()));
+
+        config.tls_min_version = "1.1".to_string();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Tls(_))
+        ));
+
+        config.tls_min_version = "1.2".to_string();
+        config.tls_alpn = vec!["x".repeat(256)];
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Tls(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_response_transforms() {
+        let mut config = Config::default();
+        assert!(config.response_transforms.is_empty());
+
+        config.response_transforms = vec!["gzip".to_string(), "Base64".to_string()];
+        assert_eq!(config.validate(), Ok(()));
+
+        config.response_transforms.push("rot13".to_string());
+        match config.validate() {
+            Err(ConfigValidationError::Transform(msg)) => assert!(msg.contains("rot13")),
+            other => panic!("expected a transform error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_load_response_transforms_from_file_and_env() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "response_transforms = gzip, base64");
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.response_transforms, vec!["gzip", "base64"]);
+
+        let env = mock_env(HashMap::from([("RUCHO_RESPONSE_TRANSFORMS", "uppercase")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.response_transforms, vec!["uppercase"]);
+    }
+
+    #[test]
+    fn test_load_scenarios_from_file() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "scenario.outage.status = 503\n\
+             scenario.outage.body = {\"error\": \"down\"}\n\
+             scenario.slow.delay_ms = 2000\n\
+             scenario.slow.color = blue",
+        );
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.scenarios.len(), 2);
+        assert_eq!(
+            config.scenarios["outage"],
+            Scenario {
+                status: Some(503),
+                delay_ms: None,
+                body: Some("{\"error\": \"down\"}".to_string()),
+            }
+        );
+        assert_eq!(config.scenarios["slow"].delay_ms, Some(2000));
+        assert!(!config.scenarios["slow"].responds());
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_load_collects_unknown_key_and_invalid_line() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "prefix = /srv\n\
+             # a comment\n\
+             prefx = /typo\n\
+             this line has no equals sign\n\
+             scenario.slow.delay_ms = soon",
+        );
+        let (config, errors) = Config::load_collecting_errors(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        // Good lines still apply.
+        assert_eq!(config.prefix, "/srv");
+        let path = t.cwd_rucho_conf_path.clone();
+        assert_eq!(
+            errors,
+            vec![
+                ConfigError::UnknownKey {
+                    path: path.clone(),
+                    line: Some(3),
+                    key: "prefx".to_string(),
+                },
+                ConfigError::InvalidLine {
+                    path: path.clone(),
+                    line: 4,
+                    content: "this line has no equals sign".to_string(),
+                },
+                ConfigError::InvalidValue {
+                    path,
+                    line: Some(5),
+                    key: "scenario.slow.delay_ms".to_string(),
+                    value: "soon".to_string(),
+                },
+            ]
+        );
+        assert!(!errors[0].is_fatal());
+        assert!(errors[1].is_fatal());
+        assert!(errors[0].to_string().ends_with(":3: unknown key 'prefx'"));
+    }
+
+    #[test]
+    fn test_load_rejects_values_that_do_not_parse() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "rate_limit_rps = ten\n\
+             max_body_size_bytes = 10MB\n\
+             metrics_enabled = yes\n\
+             chaos_failure_rate = half\n\
+             chaos_failure_codes = 500, oops\n\
+             chaos_seed = -1\n\
+             compression_enabled = 0\n\
+             trust_proxy = TRUE",
+        );
+        let (config, sources, errors) = Config::load_tracking_sources(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        let rejected: Vec<_> = errors
+            .iter()
+            .map(|e| match e {
+                ConfigError::InvalidValue {
+                    line: Some(line),
+                    key,
+                    ..
+                } => (*line, key.as_str()),
+                other => panic!("unexpected error {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            rejected,
+            [
+                (1, "rate_limit_rps"),
+                (2, "max_body_size_bytes"),
+                (3, "metrics_enabled"),
+                (4, "chaos_failure_rate"),
+                (5, "chaos_failure_codes"),
+                (6, "chaos_seed"),
+            ]
+        );
+        assert!(errors.iter().all(ConfigError::is_fatal));
+        assert!(errors[0]
+            .to_string()
+            .ends_with(":1: invalid value 'ten' for rate_limit_rps"));
+
+        // Rejected keys keep their defaults and aren't attributed to the file.
+        let defaults = Config::default();
+        assert_eq!(config.rate_limit_rps, defaults.rate_limit_rps);
+        assert_eq!(config.max_body_size_bytes, defaults.max_body_size_bytes);
+        assert_eq!(config.metrics_enabled, defaults.metrics_enabled);
+        assert_eq!(config.chaos, defaults.chaos);
+        for key in ["rate_limit_rps", "max_body_size_bytes", "metrics_enabled"] {
+            assert_eq!(sources.get(key), ConfigSource::Default, "{key}");
+        }
+        // `0` and any-case `true` are booleans.
+        assert!(!config.compression_enabled);
+        assert!(config.trust_proxy);
+        assert_eq!(sources.get("trust_proxy"), ConfigSource::Cwd);
+    }
+
+    #[test]
+    fn test_env_rejects_values_that_do_not_parse() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "rate_limit_rps = 5");
+        let env = mock_env(HashMap::from([
+            ("RUCHO_RATE_LIMIT_RPS", "lots"),
+            ("RUCHO_METRICS_ENABLED", "on"),
+            ("RUCHO_ACCESS_LOG_SAMPLE_RATE", "most"),
+            ("RUCHO_TCP_NODELAY", "false"),
+        ]));
+        let (config, sources, errors) = Config::load_tracking_sources(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        let mut rejected: Vec<_> = errors
+            .iter()
+            .map(|e| match e {
+                ConfigError::InvalidEnvValue { var, value } => (var.as_str(), value.as_str()),
+                other => panic!("unexpected error {other:?}"),
+            })
+            .collect();
+        rejected.sort();
+        assert_eq!(
+            rejected,
+            [
+                ("RUCHO_ACCESS_LOG_SAMPLE_RATE", "most"),
+                ("RUCHO_METRICS_ENABLED", "on"),
+                ("RUCHO_RATE_LIMIT_RPS", "lots"),
+            ]
+        );
+        // The file's value stands, and so does its source.
+        assert_eq!(config.rate_limit_rps, 5);
+        assert_eq!(sources.get("rate_limit_rps"), ConfigSource::Cwd);
+        assert!(!config.metrics_enabled);
+        assert_eq!(sources.get("metrics_enabled"), ConfigSource::Default);
+        assert_eq!(config.access_log_sample_rate, 1.0);
+        assert!(!config.tcp_nodelay);
+        assert_eq!(sources.get("tcp_nodelay"), ConfigSource::Env);
+    }
+
+    #[test]
+    fn test_load_collects_unreadable_file_and_invalid_listen_address() {
+        let t = TestEnv::new();
+        // A directory exists at the etc path but can't be read as a file.
+        fs::create_dir_all(&t.etc_rucho_conf_path).unwrap();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "server_listen_primary = 0.0.0.0:99999\n\
+             server_listen = ssl:[::1]:8443, ::1:8080",
+        );
+        let env = mock_env(HashMap::from([("RUCHO_SERVER_LISTEN_TCP", "127.0.0.1")]));
+        let (_, errors) = Config::load_collecting_errors(
+            Some(t.etc_rucho_conf_path.clone()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert!(matches!(
+            &errors[0],
+            ConfigError::UnreadableFile { path, .. } if *path == t.etc_rucho_conf_path
+        ));
+        let bad_keys: Vec<(&str, &str)> = errors[1..]
+            .iter()
+            .map(|e| match e {
+                ConfigError::InvalidListenAddress { key, value, .. } => {
+                    (key.as_str(), value.as_str())
+                }
+                other => panic!("unexpected error: {other}"),
+            })
+            .collect();
+        assert_eq!(
+            bad_keys,
+            vec![
+                ("server_listen_primary", "0.0.0.0:99999"),
+                ("server_listen_tcp", "127.0.0.1"),
+                ("server_listen", "::1:8080"),
+            ]
+        );
+        assert!(errors.iter().all(ConfigError::is_fatal));
+    }
+
+    #[test]
+    fn test_load_collects_no_errors_for_clean_config() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "server_listen_secondary =\nserver_listen = 127.0.0.1:8080 ssl",
+        );
+        let (_, errors) = Config::load_collecting_errors(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(errors, Vec::new());
+    }
+
+    const FLAT_CONFIG: &str = "\
+prefix = /srv/rucho
+log_level = debug
+server_listen = 127.0.0.1:8080, 127.0.0.1:8443 ssl
+ssl_cert = /etc/rucho/cert.pem
+ssl_key = /etc/rucho/key.pem
+tls_alpn = h2, http/1.1
+metrics_enabled = true
+max_body_size_bytes = 2048
+chaos_mode = failure,delay
+chaos_failure_rate = 0.25
+chaos_failure_codes = 500,503
+chaos_delay_rate = 0.5
+chaos_delay_ms = 100
+chaos_seed = 42
+scenario.outage.status = 503
+scenario.outage.body = {\"error\": \"down\"}
+scenario.slow.delay_ms = 2000";
+
+    const TOML_CONFIG: &str = r#"
+prefix = "/srv/rucho"
+log_level = "debug"
+server_listen = ["127.0.0.1:8080", "127.0.0.1:8443 ssl"]
+metrics_enabled = true
+max_body_size_bytes = 2048
+
+[ssl]
+cert = "/etc/rucho/cert.pem"
+key = "/etc/rucho/key.pem"
+
+[tls]
+alpn = ["h2", "http/1.1"]
+
+[chaos]
+mode = ["failure", "delay"]
+seed = 42
+failure = { rate = 0.25, codes = [500, 503] }
+
+[chaos.delay]
+rate = 0.5
+ms = 100
+
+[scenario.outage]
+status = 503
+body = '{"error": "down"}'
+
+[scenario.slow]
+delay_ms = 2000
+"#;
+
+    /// Loads `contents` written to `file_name` in a fresh temp dir.
+    fn load_single_file(file_name: &str, contents: &str) -> (Config, Vec<ConfigError>) {
+        let t = TestEnv::new();
+        let path = t.cwd_rucho_conf_path.with_file_name(file_name);
+        t.create_config_file(&path, contents);
+        Config::load_collecting_errors(Some(t.non_existent_etc()), Some(path), &empty_env())
+    }
+
+    #[test]
+    fn test_toml_and_flat_configs_load_identically() {
+        let (flat, flat_errors) = load_single_file("rucho.conf", FLAT_CONFIG);
+        let (toml, toml_errors) = load_single_file("rucho.toml", TOML_CONFIG);
+        assert_eq!(flat_errors, Vec::new());
+        assert_eq!(toml_errors, Vec::new());
+        assert_eq!(toml, flat);
+
+        // And neither is just the defaults.
+        assert_eq!(toml.prefix, "/srv/rucho");
+        assert_eq!(toml.server_listen, ["127.0.0.1:8080", "127.0.0.1:8443 ssl"]);
+        assert_eq!(toml.chaos.failure_codes, [500, 503]);
+        assert_eq!(toml.chaos.delay_ms, "100");
+        assert_eq!(toml.scenarios["outage"].status, Some(503));
+    }
+
+    #[test]
+    fn test_toml_detected_by_leading_section_header() {
+        let contents = "# rucho config\n\n[chaos]\nmode = \"delay\"\ndelay_ms = 250";
+        let (config, errors) = load_single_file("rucho.conf", contents);
+        assert_eq!(errors, Vec::new());
+        assert_eq!(config.chaos.modes, ["delay"]);
+        assert_eq!(config.chaos.delay_ms, "250");
+    }
+
+    #[test]
+    fn test_toml_errors_are_collected() {
+        let (_, errors) = load_single_file(
+            "rucho.toml",
+            "[chaos]\nfailure_rat = 0.5\n[scenario.outage]\nstatus = \"soon\"",
+        );
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(matches!(
+            &errors[0],
+            ConfigError::UnknownKey { key, line: None, .. } if key == "chaos.failure_rat"
+        ));
+        assert!(errors[0]
+            .to_string()
+            .ends_with("rucho.toml: unknown key 'chaos.failure_rat'"));
+        assert!(matches!(
+            &errors[1],
+            ConfigError::InvalidValue { key, line: None, .. } if key == "scenario.outage.status"
+        ));
+
+        let (_, errors) = load_single_file("rucho.toml", "prefix = \"unterminated");
+        assert!(matches!(&errors[..], [ConfigError::InvalidToml { .. }]));
+        assert!(errors[0].is_fatal());
+    }
+
+    #[test]
+    fn test_validate_scenarios() {
+        let mut config = Config::default();
+        config
+            .scenarios
+            .insert("empty".to_string(), Scenario::default());
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Scenario(_))
+        ));
+
+        let bad_status = Scenario {
+            status: Some(99),
+            ..Scenario::default()
+        };
+        config.scenarios.insert("empty".to_string(), bad_status);
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Scenario(_))
+        ));
+
+        let too_slow = Scenario {
+            delay_ms: Some(MAX_DELAY_SECONDS * 1000 + 1),
+            ..Scenario::default()
+        };
+        config.scenarios.insert("empty".to_string(), too_slow);
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Scenario(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_tls_min_version_and_alpn_from_file_and_env() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "tls_min_version = 1.3\ntls_alpn = http/1.1",
+        );
+
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.tls_min_version, "1.3");
+        assert_eq!(config.tls_alpn, vec!["http/1.1"]);
+
+        let env = mock_env(HashMap::from([
+            ("RUCHO_TLS_MIN_VERSION", "1.2"),
+            ("RUCHO_TLS_ALPN", "h2, http/1.1"),
+        ]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.tls_min_version, "1.2");
+        assert_eq!(config.tls_alpn, vec!["h2", "http/1.1"]);
+    }
+
     #[test]
     fn test_compression_enabled_default_false() {
         let env = empty_env();
@@ -1217,130 +2759,693 @@ mod tests {
             &env,
         );
 
-        assert!(config.compression_enabled);
-    }
-
-    #[test]
-    fn test_request_id_enabled_default_true() {
-        let env = empty_env();
-        let non_existent_etc = PathBuf::from("/tmp/non_existent_request_id_test_etc.conf");
-        let non_existent_cwd = PathBuf::from("/tmp/non_existent_request_id_test_cwd.conf");
-        let config =
-            Config::load_from_paths_with_env(Some(non_existent_etc), Some(non_existent_cwd), &env);
-
-        assert!(config.request_id_enabled);
+        assert!(config.compression_enabled);
+    }
+
+    #[test]
+    fn test_rate_limit_config() {
+        let defaults = Config::default();
+        assert_eq!(defaults.rate_limit_rps, 0, "rate limiting is opt-in");
+        assert_eq!(defaults.rate_limit_burst, 0);
+        assert!(
+            !defaults.rate_limit_trust_proxy,
+            "the limiter keys on the socket peer even though trust_proxy is on"
+        );
+
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "rate_limit_rps = 10\nrate_limit_burst = 20",
+        );
+        let env = mock_env(HashMap::from([
+            ("RUCHO_RATE_LIMIT_BURST", "30"),
+            ("RUCHO_RATE_LIMIT_TRUST_PROXY", "true"),
+        ]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.rate_limit_rps, 10);
+        assert_eq!(config.rate_limit_burst, 30); // env wins
+        assert!(config.rate_limit_trust_proxy);
+    }
+
+    #[test]
+    fn test_load_signing_secret_from_file_and_env() {
+        assert_eq!(Config::default().signing_secret, None);
+
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "signing_secret = from-file");
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.signing_secret, Some("from-file".to_string()));
+
+        let env = mock_env(HashMap::from([("RUCHO_SIGNING_SECRET", "from-env")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.signing_secret, Some("from-env".to_string()));
+    }
+
+    #[test]
+    fn test_trust_proxy_default_file_and_env() {
+        let t = TestEnv::new();
+        let env = empty_env();
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert!(config.trust_proxy);
+
+        t.create_config_file(&t.cwd_rucho_conf_path, "trust_proxy = false");
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert!(!config.trust_proxy);
+
+        let env = mock_env(HashMap::from([("RUCHO_TRUST_PROXY", "true")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert!(config.trust_proxy);
+    }
+
+    #[test]
+    fn test_request_id_enabled_default_true() {
+        let env = empty_env();
+        let non_existent_etc = PathBuf::from("/tmp/non_existent_request_id_test_etc.conf");
+        let non_existent_cwd = PathBuf::from("/tmp/non_existent_request_id_test_cwd.conf");
+        let config =
+            Config::load_from_paths_with_env(Some(non_existent_etc), Some(non_existent_cwd), &env);
+
+        assert!(config.request_id_enabled);
+    }
+
+    #[test]
+    fn test_load_request_id_disabled_from_file() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "request_id_enabled = false");
+
+        let env = empty_env();
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+
+        assert!(!config.request_id_enabled);
+    }
+
+    #[test]
+    fn test_env_overrides_file_for_request_id() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "request_id_enabled = true");
+
+        let env = mock_env(HashMap::from([("RUCHO_REQUEST_ID_ENABLED", "false")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+
+        assert!(!config.request_id_enabled);
+    }
+
+    #[test]
+    fn test_ssl_auto_cert_default_false() {
+        let env = empty_env();
+        let config = Config::load_from_paths_with_env(
+            Some(PathBuf::from("/tmp/non_existent_ssl_auto_etc.conf")),
+            Some(PathBuf::from("/tmp/non_existent_ssl_auto_cwd.conf")),
+            &env,
+        );
+        assert!(!config.ssl_auto_cert);
+    }
+
+    #[test]
+    fn test_load_ssl_auto_cert_from_file() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "ssl_auto_cert = true");
+
+        let env = empty_env();
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+
+        assert!(config.ssl_auto_cert);
+    }
+
+    #[test]
+    fn test_env_overrides_file_for_ssl_auto_cert() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "ssl_auto_cert = false");
+
+        let env = mock_env(HashMap::from([("RUCHO_SSL_AUTO_CERT", "true")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+
+        assert!(config.ssl_auto_cert);
+    }
+
+    #[test]
+    fn test_load_ssl_client_ca_from_file_and_env() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "ssl_client_ca = /file/ca.pem");
+
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.ssl_client_ca, Some("/file/ca.pem".to_string()));
+
+        let env = mock_env(HashMap::from([("RUCHO_SSL_CLIENT_CA", "/env/ca.pem")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.ssl_client_ca, Some("/env/ca.pem".to_string()));
+    }
+
+    #[test]
+    fn test_load_server_listen_list_from_file_and_env() {
+        assert!(Config::default().server_listen.is_empty());
+
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "server_listen = 127.0.0.1:8001, [::1]:8002\nserver_listen = 0.0.0.0:8443 ssl",
+        );
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(
+            config.server_listen,
+            vec!["127.0.0.1:8001", "[::1]:8002", "0.0.0.0:8443 ssl"]
+        );
+
+        let env = mock_env(HashMap::from([("RUCHO_SERVER_LISTEN", "127.0.0.1:9001 ,")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.server_listen, vec!["127.0.0.1:9001"]);
+    }
+
+    #[test]
+    fn test_server_listen_in_later_file_replaces_earlier() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.etc_rucho_conf_path, "server_listen = 127.0.0.1:8001");
+        t.create_config_file(&t.cwd_rucho_conf_path, "server_listen = 127.0.0.1:8002");
+        let config = Config::load_from_paths_with_env(
+            Some(t.etc_rucho_conf_path.clone()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.server_listen, vec!["127.0.0.1:8002"]);
+    }
+
+    #[test]
+    fn test_welcome_message_default_file_and_env() {
+        let t = TestEnv::new();
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.non_existent_cwd()),
+            &empty_env(),
+        );
+        assert_eq!(config.welcome_message, "Welcome to Echo Server!");
+
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "welcome_message = Hello from staging",
+        );
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.welcome_message, "Hello from staging");
+
+        let env = mock_env(HashMap::from([("RUCHO_WELCOME_MESSAGE", "Hi")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.welcome_message, "Hi");
+    }
+
+    #[test]
+    fn test_static_files_load_and_validate() {
+        let t = TestEnv::new();
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.non_existent_cwd()),
+            &empty_env(),
+        );
+        assert_eq!(config.static_files(), None);
+        assert_eq!(config.static_prefix, "/static");
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().to_string_lossy().into_owned();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            &format!("static_dir = {dir_path}\nstatic_prefix = /fixtures"),
+        );
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(
+            config.static_files(),
+            Some(StaticFiles {
+                dir: dir.path().to_path_buf(),
+                prefix: "/fixtures".to_string(),
+            })
+        );
+        assert!(config.validate().is_ok());
+
+        for prefix in ["/", "/get", "/kv"] {
+            let env = mock_env(HashMap::from([("RUCHO_STATIC_PREFIX", prefix)]));
+            let config = Config::load_from_paths_with_env(
+                Some(t.non_existent_etc()),
+                Some(t.cwd_rucho_conf_path.clone()),
+                &env,
+            );
+            assert!(
+                matches!(
+                    config.validate(),
+                    Err(ConfigValidationError::StaticFiles(_))
+                ),
+                "{prefix}"
+            );
+        }
+
+        let config = Config {
+            static_dir: Some(t.non_existent_cwd().to_string_lossy().into_owned()),
+            ..Config::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::StaticFiles(_))
+        ));
+    }
+
+    #[test]
+    fn test_server_header_default_file_env_and_validate() {
+        let t = TestEnv::new();
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.non_existent_cwd()),
+            &empty_env(),
+        );
+        assert_eq!(
+            config.server_header,
+            format!("rucho/{}", env!("CARGO_PKG_VERSION"))
+        );
+
+        t.create_config_file(&t.cwd_rucho_conf_path, "server_header = edge-upstream");
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.server_header, "edge-upstream");
+
+        let env = mock_env(HashMap::from([("RUCHO_SERVER_HEADER", "")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.server_header, "");
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            server_header: "bad\nvalue".to_string(),
+            ..Config::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Header(_))
+        ));
+    }
+
+    #[test]
+    fn test_cors_allow_lists_default_file_env_and_validate() {
+        let t = TestEnv::new();
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.non_existent_cwd()),
+            &empty_env(),
+        );
+        assert_eq!(config.cors(), CorsConfig::default());
+
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "cors_allow_origins = https://a.example, https://b.example\n\
+             cors_allow_methods = GET,POST\n\
+             cors_allow_headers = content-type",
+        );
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(
+            config.cors_allow_origins,
+            vec!["https://a.example", "https://b.example"]
+        );
+        assert_eq!(config.cors_allow_methods, vec!["GET", "POST"]);
+        assert_eq!(config.cors_allow_headers, vec!["content-type"]);
+        assert!(config.validate().is_ok());
+
+        let env = mock_env(HashMap::from([(
+            "RUCHO_CORS_ALLOW_METHODS",
+            "GET, BAD METHOD",
+        )]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Cors(msg)) if msg.contains("BAD METHOD")
+        ));
+    }
+
+    #[test]
+    fn test_load_ssl_client_cert_paths_from_file_and_env() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "ssl_client_cert_paths = /admin, /ops ,",
+        );
+
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.ssl_client_cert_paths, vec!["/admin", "/ops"]);
+
+        let env = mock_env(HashMap::from([(
+            "RUCHO_SSL_CLIENT_CERT_PATHS",
+            "/internal",
+        )]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.ssl_client_cert_paths, vec!["/internal"]);
+    }
+
+    #[test]
+    fn test_log_format_default_text() {
+        let env = empty_env();
+        let config = Config::load_from_paths_with_env(
+            Some(PathBuf::from("/tmp/non_existent_log_format_etc.conf")),
+            Some(PathBuf::from("/tmp/non_existent_log_format_cwd.conf")),
+            &env,
+        );
+        assert_eq!(config.log_format, "text");
+    }
+
+    #[test]
+    fn test_load_log_format_from_file() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "log_format = json");
+
+        let env = empty_env();
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+
+        assert_eq!(config.log_format, "json");
     }
 
     #[test]
-    fn test_load_request_id_disabled_from_file() {
+    fn test_env_overrides_file_for_log_format() {
         let t = TestEnv::new();
-        t.create_config_file(&t.cwd_rucho_conf_path, "request_id_enabled = false");
+        t.create_config_file(&t.cwd_rucho_conf_path, "log_format = text");
 
-        let env = empty_env();
+        let env = mock_env(HashMap::from([("RUCHO_LOG_FORMAT", "json")]));
         let config = Config::load_from_paths_with_env(
             Some(t.non_existent_etc()),
             Some(t.cwd_rucho_conf_path.clone()),
             &env,
         );
 
-        assert!(!config.request_id_enabled);
+        assert_eq!(config.log_format, "json");
     }
 
     #[test]
-    fn test_env_overrides_file_for_request_id() {
+    fn test_log_filter_from_file_and_env() {
+        assert_eq!(Config::default().log_filter, None);
+
         let t = TestEnv::new();
-        t.create_config_file(&t.cwd_rucho_conf_path, "request_id_enabled = true");
+        t.create_config_file(&t.cwd_rucho_conf_path, "log_filter = rucho=debug");
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.log_filter.as_deref(), Some("rucho=debug"));
 
-        let env = mock_env(HashMap::from([("RUCHO_REQUEST_ID_ENABLED", "false")]));
+        let env = mock_env(HashMap::from([(
+            "RUCHO_LOG_FILTER",
+            "rucho=trace,tower_http=info",
+        )]));
         let config = Config::load_from_paths_with_env(
             Some(t.non_existent_etc()),
             Some(t.cwd_rucho_conf_path.clone()),
             &env,
         );
-
-        assert!(!config.request_id_enabled);
+        assert_eq!(
+            config.log_filter.as_deref(),
+            Some("rucho=trace,tower_http=info")
+        );
     }
 
     #[test]
-    fn test_ssl_auto_cert_default_false() {
-        let env = empty_env();
-        let config = Config::load_from_paths_with_env(
-            Some(PathBuf::from("/tmp/non_existent_ssl_auto_etc.conf")),
-            Some(PathBuf::from("/tmp/non_existent_ssl_auto_cwd.conf")),
-            &env,
-        );
-        assert!(!config.ssl_auto_cert);
+    fn test_log_targets_apply_per_module_levels() {
+        use tracing::Level;
+
+        // No filter: everything at log_level.
+        let targets = Config::default().log_targets(LevelFilter::INFO);
+        assert!(targets.would_enable("rucho::server", &Level::INFO));
+        assert!(!targets.would_enable("rucho::server", &Level::DEBUG));
+
+        let config = Config {
+            log_filter: Some("rucho=debug,tower_http=warn".to_string()),
+            ..Config::default()
+        };
+        let targets = config.log_targets(LevelFilter::INFO);
+        assert!(targets.would_enable("rucho::server::http", &Level::DEBUG));
+        assert!(!targets.would_enable("rucho::server::http", &Level::TRACE));
+        assert!(targets.would_enable("tower_http::trace", &Level::WARN));
+        assert!(!targets.would_enable("tower_http::trace", &Level::INFO));
+        // Unnamed targets fall back to log_level...
+        assert!(targets.would_enable("hyper", &Level::INFO));
+        assert!(!targets.would_enable("hyper", &Level::DEBUG));
+
+        // ...unless the filter has a bare level of its own.
+        let config = Config {
+            log_filter: Some("error,rucho=debug".to_string()),
+            ..Config::default()
+        };
+        let targets = config.log_targets(LevelFilter::INFO);
+        assert!(!targets.would_enable("hyper", &Level::WARN));
+        assert!(targets.would_enable("rucho", &Level::DEBUG));
     }
 
     #[test]
-    fn test_load_ssl_auto_cert_from_file() {
-        let t = TestEnv::new();
-        t.create_config_file(&t.cwd_rucho_conf_path, "ssl_auto_cert = true");
+    fn test_access_log_default_file_env_and_validate() {
+        let config = Config::default();
+        assert_eq!(config.access_log, None);
+        assert_eq!(config.access_log_format, "combined");
 
-        let env = empty_env();
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "access_log = /var/log/rucho/access.log\naccess_log_format = common",
+        );
         let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(
+            config.access_log.as_deref(),
+            Some("/var/log/rucho/access.log")
+        );
+        assert_eq!(config.access_log_format, "common");
+
+        let env = mock_env(HashMap::from([
+            ("RUCHO_ACCESS_LOG", "-"),
+            ("RUCHO_ACCESS_LOG_FORMAT", "json"),
+        ]));
+        let mut config = Config::load_from_paths_with_env(
             Some(t.non_existent_etc()),
             Some(t.cwd_rucho_conf_path.clone()),
             &env,
         );
+        assert_eq!(config.access_log.as_deref(), Some("-"));
+        assert_eq!(config.access_log_format, "json");
+        assert_eq!(config.validate(), Ok(()));
 
-        assert!(config.ssl_auto_cert);
+        config.access_log_format = "apache".to_string();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::AccessLog(_))
+        ));
     }
 
     #[test]
-    fn test_env_overrides_file_for_ssl_auto_cert() {
+    fn test_access_log_exclude_default_file_and_env() {
+        assert_eq!(Config::default().access_log_exclude, vec!["/healthz"]);
+
         let t = TestEnv::new();
-        t.create_config_file(&t.cwd_rucho_conf_path, "ssl_auto_cert = false");
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "access_log_exclude = /healthz, /metrics",
+        );
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.access_log_exclude, vec!["/healthz", "/metrics"]);
 
-        let env = mock_env(HashMap::from([("RUCHO_SSL_AUTO_CERT", "true")]));
+        // Empty logs everything.
+        let env = mock_env(HashMap::from([("RUCHO_ACCESS_LOG_EXCLUDE", "")]));
         let config = Config::load_from_paths_with_env(
             Some(t.non_existent_etc()),
             Some(t.cwd_rucho_conf_path.clone()),
             &env,
         );
-
-        assert!(config.ssl_auto_cert);
+        assert!(config.access_log_exclude.is_empty());
     }
 
     #[test]
-    fn test_log_format_default_text() {
-        let env = empty_env();
+    fn test_env_allow_prefixes_default_file_and_env() {
+        assert_eq!(Config::default().env_allow_prefixes, vec!["RUCHO_"]);
+
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "env_allow_prefixes = RUCHO_, APP_");
         let config = Config::load_from_paths_with_env(
-            Some(PathBuf::from("/tmp/non_existent_log_format_etc.conf")),
-            Some(PathBuf::from("/tmp/non_existent_log_format_cwd.conf")),
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.env_allow_prefixes, vec!["RUCHO_", "APP_"]);
+
+        let env = mock_env(HashMap::from([("RUCHO_ENV_ALLOW_PREFIXES", "")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
             &env,
         );
-        assert_eq!(config.log_format, "text");
+        assert!(config.env_allow_prefixes.is_empty());
     }
 
     #[test]
-    fn test_load_log_format_from_file() {
-        let t = TestEnv::new();
-        t.create_config_file(&t.cwd_rucho_conf_path, "log_format = json");
+    fn test_access_log_sample_rate_file_env_and_validate() {
+        assert!((Config::default().access_log_sample_rate - 1.0).abs() < f64::EPSILON);
 
-        let env = empty_env();
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "access_log_sample_rate = 0.1");
         let config = Config::load_from_paths_with_env(
             Some(t.non_existent_etc()),
             Some(t.cwd_rucho_conf_path.clone()),
-            &env,
+            &empty_env(),
         );
+        assert!((config.access_log_sample_rate - 0.1).abs() < f64::EPSILON);
 
-        assert_eq!(config.log_format, "json");
+        let env = mock_env(HashMap::from([("RUCHO_ACCESS_LOG_SAMPLE_RATE", "0")]));
+        let mut config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.access_log_sample_rate, 0.0);
+        assert_eq!(config.validate(), Ok(()));
+
+        for rate in [-0.1, 1.5, f64::NAN] {
+            config.access_log_sample_rate = rate;
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigValidationError::AccessLog(_))
+            ));
+        }
     }
 
     #[test]
-    fn test_env_overrides_file_for_log_format() {
-        let t = TestEnv::new();
-        t.create_config_file(&t.cwd_rucho_conf_path, "log_format = text");
+    fn test_access_log_rotation_file_env_and_validate() {
+        assert_eq!(Config::default().access_log_rotation, "never");
 
-        let env = mock_env(HashMap::from([("RUCHO_LOG_FORMAT", "json")]));
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "access_log_rotation = hourly");
         let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.access_log_rotation, "hourly");
+
+        let env = mock_env(HashMap::from([("RUCHO_ACCESS_LOG_ROTATION", "daily")]));
+        let mut config = Config::load_from_paths_with_env(
             Some(t.non_existent_etc()),
             Some(t.cwd_rucho_conf_path.clone()),
             &env,
         );
+        assert_eq!(config.access_log_rotation, "daily");
+        assert_eq!(config.validate(), Ok(()));
 
-        assert_eq!(config.log_format, "json");
+        config.access_log_rotation = "weekly".to_string();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::AccessLog(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_log_filter() {
+        let mut config = Config {
+            log_filter: Some("rucho=debug,info".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(config.validate(), Ok(()));
+        config.log_filter = Some("rucho=loud".to_string());
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::LogFilter(_))
+        ));
     }
 
     #[test]
@@ -1392,6 +3497,27 @@ mod tests {
         assert!(!config.chaos.is_enabled());
         assert!(config.chaos.modes.is_empty());
         assert!(config.chaos.inform_header); // default true
+        assert_eq!(config.chaos.seed, None);
+    }
+
+    #[test]
+    fn test_chaos_seed_from_file_and_env() {
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "chaos_seed = 42");
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.chaos.seed, Some(42));
+
+        let env = mock_env(HashMap::from([("RUCHO_CHAOS_SEED", "7")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.chaos.seed, Some(7)); // env wins
     }
 
     #[test]
@@ -1561,6 +3687,60 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_chaos_validate_delay_distribution() {
+        let mut config = Config::default();
+        config.chaos.modes = vec!["delay".to_string()];
+        config.chaos.delay_rate = 0.5;
+        config.chaos.delay_ms = "random".to_string();
+        config.chaos.delay_max_ms = 3000;
+
+        config.chaos.delay_distribution = "normal".to_string();
+        config.chaos.delay_mean_ms = 200.0;
+        config.chaos.delay_stddev_ms = 50.0;
+        assert!(config.validate().is_ok());
+        config.chaos.delay_stddev_ms = -1.0;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Chaos(_))
+        ));
+
+        config.chaos.delay_distribution = "exponential".to_string();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Chaos(_))
+        ));
+        config.chaos.delay_lambda = 0.01;
+        assert!(config.validate().is_ok());
+
+        config.chaos.delay_distribution = "pareto".to_string();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Chaos(_))
+        ));
+    }
+
+    #[test]
+    fn test_chaos_delay_distribution_from_file() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "chaos_delay_distribution = normal\n\
+             chaos_delay_mean_ms = 150\n\
+             chaos_delay_stddev_ms = 25.5\n\
+             chaos_delay_lambda = 0.02",
+        );
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.chaos.delay_distribution, "normal");
+        assert!((config.chaos.delay_mean_ms - 150.0).abs() < f64::EPSILON);
+        assert!((config.chaos.delay_stddev_ms - 25.5).abs() < f64::EPSILON);
+        assert!((config.chaos.delay_lambda - 0.02).abs() < f64::EPSILON);
+    }
+
     #[test]
     fn test_chaos_validate_invalid_corruption_type() {
         let mut config = Config::default();
@@ -1584,6 +3764,36 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_chaos_validate_bitflip_count() {
+        let mut config = Config::default();
+        config.chaos.modes = vec!["corruption".to_string()];
+        config.chaos.corruption_rate = 0.5;
+        config.chaos.corruption_type = "bitflip".to_string();
+        assert!(config.validate().is_ok());
+
+        config.chaos.corruption_bitflip_count = 0;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Chaos(_))
+        ));
+    }
+
+    #[test]
+    fn test_chaos_bitflip_count_from_env() {
+        let env = mock_env(HashMap::from([(
+            "RUCHO_CHAOS_CORRUPTION_BITFLIP_COUNT",
+            "8",
+        )]));
+        let t = TestEnv::new();
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.chaos.corruption_bitflip_count, 8);
+    }
+
     #[test]
     fn test_chaos_validate_unknown_type() {
         let mut config = Config::default();
@@ -1618,6 +3828,11 @@ mod tests {
         assert_eq!(config.tcp_keepalive_retries, 5);
         assert!(config.tcp_nodelay);
         assert_eq!(config.header_read_timeout, 30);
+        assert_eq!(config.request_timeout_secs, 0);
+        assert_eq!(config.request_timeout(), None);
+        assert_eq!(config.shutdown_timeout_secs, 5);
+        assert_eq!(config.streaming_shutdown_grace_secs, 5);
+        assert_eq!(config.max_streaming_responses, 1000);
     }
 
     #[test]
@@ -1630,7 +3845,11 @@ mod tests {
              tcp_keepalive_interval = 20\n\
              tcp_keepalive_retries = 3\n\
              tcp_nodelay = false\n\
-             header_read_timeout = 45",
+             header_read_timeout = 45\n\
+             request_timeout_secs = 10\n\
+             shutdown_timeout_secs = 60\n\
+             streaming_shutdown_grace_secs = 30\n\
+             max_streaming_responses = 8",
         );
 
         let env = empty_env();
@@ -1646,6 +3865,10 @@ mod tests {
         assert_eq!(config.tcp_keepalive_retries, 3);
         assert!(!config.tcp_nodelay);
         assert_eq!(config.header_read_timeout, 45);
+        assert_eq!(config.request_timeout(), Some(Duration::from_secs(10)));
+        assert_eq!(config.shutdown_timeout_secs, 60);
+        assert_eq!(config.streaming_shutdown_grace_secs, 30);
+        assert_eq!(config.max_streaming_responses, 8);
     }
 
     #[test]
@@ -1657,6 +3880,10 @@ mod tests {
             ("RUCHO_TCP_KEEPALIVE_RETRIES", "8"),
             ("RUCHO_TCP_NODELAY", "false"),
             ("RUCHO_HEADER_READ_TIMEOUT", "60"),
+            ("RUCHO_REQUEST_TIMEOUT_SECS", "3"),
+            ("RUCHO_SHUTDOWN_TIMEOUT_SECS", "0"),
+            ("RUCHO_STREAMING_SHUTDOWN_GRACE_SECS", "0"),
+            ("RUCHO_MAX_STREAMING_RESPONSES", "0"),
         ]));
         let non_existent_etc = PathBuf::from("/tmp/non_existent_conn_env_etc.conf");
         let non_existent_cwd = PathBuf::from("/tmp/non_existent_conn_env_cwd.conf");
@@ -1669,6 +3896,10 @@ mod tests {
         assert_eq!(config.tcp_keepalive_retries, 8);
         assert!(!config.tcp_nodelay);
         assert_eq!(config.header_read_timeout, 60);
+        assert_eq!(config.request_timeout_secs, 3);
+        assert_eq!(config.shutdown_timeout_secs, 0);
+        assert_eq!(config.streaming_shutdown_grace_secs, 0);
+        assert_eq!(config.max_streaming_responses, 0);
     }
 
     #[test]
@@ -1783,4 +4014,96 @@ mod tests {
             Err(ConfigValidationError::Connection(_))
         ));
     }
+
+    #[test]
+    fn test_validate_streaming_shutdown_grace_bound() {
+        let config = Config {
+            streaming_shutdown_grace_secs: MAX_DELAY_SECONDS,
+            ..Config::default()
+        };
+        assert_eq!(config.validate(), Ok(()));
+        let config = Config {
+            streaming_shutdown_grace_secs: MAX_DELAY_SECONDS + 1,
+            ..Config::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Connection(_))
+        ));
+    }
+
+    #[test]
+    fn test_metrics_window_from_file_and_env() {
+        let t = TestEnv::new();
+        t.create_config_file(
+            &t.cwd_rucho_conf_path,
+            "metrics_window_buckets = 30\n\
+             metrics_bucket_seconds = 10",
+        );
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.metrics_window_buckets, 30);
+        assert_eq!(config.metrics_bucket_seconds, 10);
+
+        let env = mock_env(HashMap::from([("RUCHO_METRICS_BUCKET_SECONDS", "5")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.metrics_window_buckets, 30); // file value
+        assert_eq!(config.metrics_bucket_seconds, 5); // env wins
+    }
+
+    #[test]
+    fn test_validate_metrics_window() {
+        let defaults = Config::default();
+        assert_eq!(defaults.metrics_window_buckets, 60);
+        assert_eq!(defaults.metrics_bucket_seconds, 60);
+
+        for config in [
+            Config {
+                metrics_window_buckets: 0,
+                ..Config::default()
+            },
+            Config {
+                metrics_window_buckets: MAX_METRICS_WINDOW_BUCKETS + 1,
+                ..Config::default()
+            },
+            Config {
+                metrics_bucket_seconds: 0,
+                ..Config::default()
+            },
+        ] {
+            assert!(matches!(
+                config.validate(),
+                Err(ConfigValidationError::Metrics(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_tcp_accept_log_sample_from_file_and_env() {
+        assert_eq!(Config::default().tcp_accept_log_sample, 1);
+
+        let t = TestEnv::new();
+        t.create_config_file(&t.cwd_rucho_conf_path, "tcp_accept_log_sample = 100");
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &empty_env(),
+        );
+        assert_eq!(config.tcp_accept_log_sample, 100);
+
+        let env = mock_env(HashMap::from([("RUCHO_TCP_ACCEPT_LOG_SAMPLE", "0")]));
+        let config = Config::load_from_paths_with_env(
+            Some(t.non_existent_etc()),
+            Some(t.cwd_rucho_conf_path.clone()),
+            &env,
+        );
+        assert_eq!(config.tcp_accept_log_sample, 0); // env wins
+    }
 }
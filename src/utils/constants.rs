@@ -12,6 +12,21 @@ pub const DEFAULT_LOG_LEVEL: &str = "info";
 /// Default log output format (`"text"` for human-readable, `"json"` for structured).
 pub const DEFAULT_LOG_FORMAT: &str = "text";
 
+/// Default access log line format (`"combined"`, `"common"`, or `"json"`).
+pub const DEFAULT_ACCESS_LOG_FORMAT: &str = "combined";
+
+/// Default access log rotation (`"daily"`, `"hourly"`, `"minutely"`, or `"never"`).
+pub const DEFAULT_ACCESS_LOG_ROTATION: &str = "never";
+
+/// Default path prefix left out of the access log (health checks).
+pub const DEFAULT_ACCESS_LOG_EXCLUDE: &str = "/healthz";
+
+/// Default fraction of requests written to the access log (all of them).
+pub const DEFAULT_ACCESS_LOG_SAMPLE_RATE: f64 = 1.0;
+
+/// Default environment variable name prefix `/env` shows.
+pub const DEFAULT_ENV_ALLOW_PREFIX: &str = "RUCHO_";
+
 /// Default primary server listen address.
 pub const DEFAULT_SERVER_LISTEN_PRIMARY: &str = "0.0.0.0:8080";
 
@@ -21,10 +36,24 @@ pub const DEFAULT_SERVER_LISTEN_SECONDARY: &str = "0.0.0.0:9090";
 /// Path to the PID file used for process management.
 pub const PID_FILE_PATH: &str = "/var/run/rucho/rucho.pid";
 
+/// Default `welcome_message`: the plain-text banner served at `/`.
+pub const DEFAULT_WELCOME_MESSAGE: &str = "Welcome to Echo Server!";
+
+/// Default `static_prefix`: where `static_dir` is mounted.
+pub const DEFAULT_STATIC_PREFIX: &str = "/static";
+
 /// Maximum delay allowed in seconds for the `/delay/:n` endpoint.
 /// This prevents denial-of-service attacks by limiting how long a request can be held.
 pub const MAX_DELAY_SECONDS: u64 = 300;
 
+/// `Retry-After` seconds sent with a `429` or `503` from `/status/:code` when
+/// no `?retry_after=` is given.
+pub const DEFAULT_STATUS_RETRY_AFTER_SECONDS: u64 = 1;
+
+/// Largest weight a `/status/:code` entry (`code:weight`) may carry; larger
+/// weights return 400.
+pub const MAX_STATUS_WEIGHT: u32 = 1_000_000;
+
 /// Maximum number of redirect hops allowed for the `/redirect/:n` endpoint.
 /// This prevents abuse through excessively long redirect chains.
 pub const MAX_REDIRECT_HOPS: u32 = 20;
@@ -33,22 +62,134 @@ pub const MAX_REDIRECT_HOPS: u32 = 20;
 /// This prevents DoS from oversized decode operations on the path segment.
 pub const MAX_BASE64_INPUT_BYTES: usize = 4096;
 
+/// Default TCP echo accept-log sampling: log one in every N accepted
+/// connections. `1` logs every accept.
+pub const DEFAULT_TCP_ACCEPT_LOG_SAMPLE: u64 = 1;
+
 /// Default maximum request body size in bytes (2 MiB).
 /// Enforced globally via `DefaultBodyLimit` and applies to all extractor-based
 /// handlers, including `anything_handler`. Protects against OOM from unbounded bodies.
 pub const DEFAULT_MAX_BODY_SIZE_BYTES: usize = 2 * 1024 * 1024;
 
+/// Default number of buckets in the `/metrics` rolling window.
+/// With [`DEFAULT_METRICS_BUCKET_SECONDS`] this gives a one-hour window.
+pub const DEFAULT_METRICS_WINDOW_BUCKETS: usize = 60;
+
+/// Default duration of each `/metrics` rolling-window bucket in seconds.
+pub const DEFAULT_METRICS_BUCKET_SECONDS: u64 = 60;
+
+/// Upper bound on `metrics_window_buckets`. Every bucket holds its own
+/// per-endpoint map, so this caps the rolling window's memory footprint.
+pub const MAX_METRICS_WINDOW_BUCKETS: usize = 10_000;
+
 /// Maximum number of random bytes the `/bytes/:n` endpoint will emit (10 MiB).
 /// Requests for more return 400. Prevents a single request from allocating
 /// unbounded memory to generate the response body.
 pub const MAX_BYTES_RESPONSE_SIZE: usize = 10 * 1024 * 1024;
 
+/// Default `chunk_size` for `/stream-bytes/:n`.
+pub const DEFAULT_STREAM_CHUNK_SIZE: usize = 1024;
+
 /// Maximum total bytes the `/drip` endpoint will emit per request.
 /// Smaller than `MAX_BYTES_RESPONSE_SIZE` because `/drip` is for testing slow
 /// streaming behavior, not bulk transfer. Capping here also bounds how many
 /// chunk-sleep iterations the streaming task performs.
 pub const MAX_DRIP_NUMBYTES: usize = 10_000;
 
+/// Default number of events `/events` emits before ending the stream.
+pub const DEFAULT_SSE_EVENTS: u64 = 10;
+
+/// Most events `/events` emits per request; larger `count`s return 400, so
+/// every stream terminates.
+pub const MAX_SSE_EVENTS: u64 = 1_000;
+
+/// Default `interval_ms` between `/events` events.
+pub const DEFAULT_SSE_INTERVAL_MS: u64 = 1_000;
+
+/// Largest `interval_ms` accepted by `/events`; larger values return 400.
+pub const MAX_SSE_INTERVAL_MS: u64 = MAX_DELAY_SECONDS * 1_000;
+
+/// Maximum number of sub-requests accepted in one `POST /batch`. Sub-requests
+/// run sequentially, so this bounds both the handler's runtime and the size of
+/// the aggregated response.
+pub const MAX_BATCH_SIZE: usize = 50;
+
+/// Largest sub-response body `POST /batch` buffers; a larger one becomes an
+/// inline error entry. With `MAX_BATCH_SIZE`, this bounds the aggregated
+/// response.
+pub const MAX_BATCH_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// How long one `POST /batch` sub-request may take, body included, before it
+/// becomes an inline 504 entry, so a streaming route like `/events` can't
+/// hold the batch open.
+pub const BATCH_SUB_REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Maximum number of `?preload=` targets `/anything` turns into `Link`
+/// preload hints; further occurrences are ignored.
+pub const MAX_PRELOAD_LINKS: usize = 16;
+
+/// Most chunks `/anything?fragment=true` splits a body into; larger bodies
+/// get proportionally larger chunks instead of more flushes.
+pub const MAX_FRAGMENTS: usize = 4096;
+
+/// Largest `n` accepted by `/countdown/:n`; larger values return 400.
+pub const MAX_COUNTDOWN_POLLS: u64 = 1_000;
+
+/// Maximum number of in-progress countdowns `/countdown/:n` tracks at once.
+/// When full, idle countdowns are evicted first, then the least recently polled.
+pub const MAX_COUNTDOWN_CLIENTS: usize = 10_000;
+
+/// A countdown not polled for this many seconds is forgotten; the client's
+/// next poll starts over.
+pub const COUNTDOWN_IDLE_SECS: u64 = 300;
+
+/// Longest `X-Client-Id` accepted by `/countdown/:n`, in bytes, so tracked
+/// keys stay small.
+pub const MAX_COUNTDOWN_CLIENT_ID_BYTES: usize = 128;
+
+/// Maximum number of deleted ids `/resource/:id` remembers. When full, the
+/// oldest deletion is forgotten and that id deletes with 200 again.
+pub const MAX_DELETED_RESOURCES: usize = 10_000;
+
+/// Longest id accepted by `DELETE /resource/:id`, in bytes.
+pub const MAX_RESOURCE_ID_BYTES: usize = 128;
+
+/// Maximum number of entries the `/kv/:key` store holds. When full, storing a
+/// new key evicts the oldest entry.
+pub const MAX_KV_ENTRIES: usize = 1_000;
+
+/// Maximum total size of the values the `/kv/:key` store holds, in bytes.
+/// Storing past it evicts the oldest entries; a single value larger than this
+/// is rejected with 413.
+pub const MAX_KV_TOTAL_BYTES: usize = 64 * 1024 * 1024;
+
+/// Longest key accepted by `/kv/:key`, in bytes.
+pub const MAX_KV_KEY_BYTES: usize = 128;
+
+/// Maximum `?ttl=` for `PUT /kv/:key`, in seconds (one day).
+pub const MAX_KV_TTL_SECONDS: u64 = 86_400;
+
+/// Most UUIDs `/uuid/:count` generates in one response; larger counts
+/// return 400.
+pub const MAX_UUID_COUNT: usize = 1_000;
+
+/// Default `timeout_ms` for `/long-poll`: how long it waits for a trigger.
+pub const DEFAULT_LONG_POLL_TIMEOUT_MS: u64 = 30_000;
+
+/// Largest `timeout_ms` accepted by `/long-poll`; larger values return 400.
+pub const MAX_LONG_POLL_TIMEOUT_MS: u64 = MAX_DELAY_SECONDS * 1_000;
+
+/// Maximum number of client IPs the rate limiter tracks at once. When full,
+/// buckets that have refilled to capacity are evicted before adding a new IP,
+/// then the least recently seen ones until [`RATE_LIMIT_KEEP_AFTER_EVICTION`]
+/// remain, so memory stays bounded however many addresses a client sprays.
+pub const RATE_LIMIT_MAX_TRACKED_IPS: usize = 10_000;
+
+/// Buckets the rate limiter keeps after an eviction pass: 90% of
+/// [`RATE_LIMIT_MAX_TRACKED_IPS`], so a pass happens at most once per 1,000
+/// new addresses.
+pub const RATE_LIMIT_KEEP_AFTER_EVICTION: usize = RATE_LIMIT_MAX_TRACKED_IPS / 10 * 9;
+
 /// Maximum buffer size in bytes for TCP/UDP connections.
 /// This prevents memory exhaustion from malicious large payloads.
 pub const MAX_BUFFER_SIZE: usize = 65536;
@@ -59,6 +200,17 @@ pub const UDP_ERROR_BACKOFF_BASE_MS: u64 = 100;
 /// Maximum backoff duration in milliseconds for UDP error recovery.
 pub const UDP_ERROR_BACKOFF_MAX_MS: u64 = 5000;
 
+/// Default time in seconds in-flight requests get to finish once shutdown
+/// starts, before connections are force-closed.
+pub const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 5;
+
+/// Default shutdown grace for streaming responses in seconds; matches the
+/// HTTP drain timeout.
+pub const DEFAULT_STREAMING_SHUTDOWN_GRACE_SECS: u64 = 5;
+
+/// Default cap on simultaneously open streaming responses; more get 503.
+pub const DEFAULT_MAX_STREAMING_RESPONSES: usize = 1000;
+
 /// Default HTTP keep-alive timeout in seconds.
 /// Controls how long an idle HTTP connection stays open before closing.
 pub const DEFAULT_HTTP_KEEP_ALIVE_TIMEOUT_SECS: u64 = 75;
@@ -75,6 +227,11 @@ pub const DEFAULT_TCP_KEEPALIVE_INTERVAL_SECS: u64 = 15;
 /// Number of failed probes before dropping the connection.
 pub const DEFAULT_TCP_KEEPALIVE_RETRIES: u32 = 5;
 
+/// Most request headers accepted over HTTP/1.1; requests with more get 431.
+/// Set explicitly on the listener so `/anything?limits=true` reports what is
+/// enforced.
+pub const HTTP1_MAX_HEADERS: usize = 100;
+
 /// Default header read timeout in seconds.
 /// Maximum time to wait for a client to send complete request headers.
 pub const DEFAULT_HEADER_READ_TIMEOUT_SECS: u64 = 30;
@@ -1,7 +1,10 @@
 // Utility to create standardized JSON error responses
 
-use axum::{http::StatusCode, response::Response};
-use serde_json::json;
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde_json::{json, Value};
 
 /// Formats a JSON error response.
 ///
@@ -17,24 +20,28 @@ use serde_json::json;
 ///
 /// An Axum `Response` object. Falls back to a plain text error if JSON serialization fails.
 pub fn format_error_response(status: StatusCode, message: &str) -> Response {
-    let error_body = json!({
-        "error": message
-    });
+    error_json_response(status, json!({ "error": message }))
+}
+
+/// Formats a JSON error response that also echoes the request path.
+///
+/// Same as [`format_error_response`], with a `"path"` field next to `"error"`
+/// (e.g. `{"error": "Not Found", "path": "/does-not-exist"}`).
+pub fn format_error_response_with_path(status: StatusCode, message: &str, path: &str) -> Response {
+    error_json_response(status, json!({ "error": message, "path": path }))
+}
 
+/// Serializes `error_body` compactly with `status` and a JSON content type.
+fn error_json_response(status: StatusCode, error_body: Value) -> Response {
     let body_bytes = serde_json::to_vec(&error_body).unwrap_or_else(|_| {
+        let message = error_body["error"].as_str().unwrap_or_default();
         format!(r#"{{"error":"{}"}}"#, message.replace('"', "\\\"")).into_bytes()
     });
 
-    Response::builder()
-        .status(status)
-        .header("Content-Type", "application/json")
-        .body(axum::body::Body::from(body_bytes))
-        .unwrap_or_else(|_| {
-            Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(axum::body::Body::from(
-                    r#"{"error":"Failed to build error response"}"#,
-                ))
-                .expect("fallback response should always build")
-        })
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        body_bytes,
+    )
+        .into_response()
 }
@@ -0,0 +1,96 @@
+//! Header serialization for the echo endpoints.
+//!
+//! Every endpoint that echoes headers (`/get`, `/anything`, `/headers`, the
+//! body echoes, `/cache`, the forced-encoding trio, `/batch`) goes through
+//! [`headers_to_json`], so repeated headers are reported the same way
+//! everywhere.
+
+use axum::http::{HeaderMap, HeaderValue};
+use base64::Engine;
+use serde_json::{json, Value};
+
+/// Serializes HTTP headers into a JSON object.
+///
+/// Each header name becomes a key. A name sent once maps to its value as a
+/// string; a name sent more than once (several `Accept` or `Set-Cookie`
+/// lines, say) maps to an array of its values in the order received, rather
+/// than keeping only the last. A value that isn't valid UTF-8 is echoed as
+/// `{"base64": "<raw bytes>"}` (see [`header_value_to_json`]).
+pub fn headers_to_json(headers: &HeaderMap) -> Value {
+    headers
+        .keys()
+        .map(|name| {
+            let mut values = headers.get_all(name).iter().map(header_value_to_json);
+            let value = match (values.next(), values.next()) {
+                (Some(only), None) => only,
+                (first, second) => {
+                    Value::Array(first.into_iter().chain(second).chain(values).collect())
+                }
+            };
+            (name.to_string(), value)
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// Serializes one header value: a JSON string when the bytes are valid UTF-8
+/// (including non-ASCII text that `HeaderValue::to_str` rejects), otherwise
+/// `{"base64": "..."}` carrying the raw bytes in standard base64, so binary
+/// values survive the echo intact.
+pub fn header_value_to_json(value: &HeaderValue) -> Value {
+    match std::str::from_utf8(value.as_bytes()) {
+        Ok(text) => Value::String(text.to_string()),
+        Err(_) => json!({
+            "base64": base64::engine::general_purpose::STANDARD.encode(value.as_bytes())
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_values_are_strings() {
+        let mut headers = HeaderMap::new();
+        headers.insert("accept", HeaderValue::from_static("*/*"));
+        headers.insert(
+            "x-name",
+            HeaderValue::from_bytes("caf\u{e9}".as_bytes()).unwrap(),
+        );
+        assert_eq!(
+            headers_to_json(&headers),
+            json!({ "accept": "*/*", "x-name": "caf\u{e9}" })
+        );
+    }
+
+    #[test]
+    fn invalid_utf8_values_round_trip_as_base64() {
+        let raw: &[u8] = b"ok\xff\xfe\x80end";
+        let mut headers = HeaderMap::new();
+        headers.insert("x-bin", HeaderValue::from_bytes(raw).unwrap());
+        headers.append("x-multi", HeaderValue::from_static("text"));
+        headers.append("x-multi", HeaderValue::from_bytes(b"\xc3").unwrap());
+
+        let json = headers_to_json(&headers);
+        let encoded = json["x-bin"]["base64"].as_str().unwrap();
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        assert_eq!(decoded, raw);
+        assert_eq!(json["x-multi"], json!(["text", { "base64": "ww==" }]));
+    }
+
+    #[test]
+    fn repeated_names_become_arrays_in_order() {
+        let mut headers = HeaderMap::new();
+        headers.append("x-multi", HeaderValue::from_static("one"));
+        headers.append("host", HeaderValue::from_static("example.test"));
+        headers.append("x-multi", HeaderValue::from_static("two"));
+        headers.append("x-multi", HeaderValue::from_static("three"));
+        assert_eq!(
+            headers_to_json(&headers),
+            json!({ "host": "example.test", "x-multi": ["one", "two", "three"] })
+        );
+    }
+}
@@ -1,7 +1,12 @@
 // Import necessary types from Axum and Serde
-use axum::{http::StatusCode, response::Response};
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
 use serde_json::{json, Value};
 
+use crate::utils::xml::json_to_xml;
+
 /// Formats a `serde_json::Value` into an Axum `Response`.
 ///
 /// This function serializes the given JSON `Value` into a pretty-printed byte buffer.
@@ -43,24 +48,343 @@ pub fn format_json_response_with_timing(mut data: Value, duration_ms: Option<f64
     let body = serde_json::to_vec_pretty(&data);
 
     match body {
-        Ok(json_bytes) => Response::builder()
-            .status(StatusCode::OK)
-            .header("Content-Type", "application/json")
-            .body(axum::body::Body::from(json_bytes))
-            .unwrap_or_else(|_| {
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body(axum::body::Body::from(
-                        r#"{"error":"Failed to build response"}"#,
-                    ))
-                    .expect("fallback response should always build")
-            }),
-        Err(_) => Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .header("Content-Type", "application/json")
-            .body(axum::body::Body::from(
-                r#"{"error":"Failed to serialize response"}"#,
-            ))
-            .expect("fallback response should always build"),
+        Ok(json_bytes) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "application/json")],
+            json_bytes,
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CONTENT_TYPE, "application/json")],
+            r#"{"error":"Failed to serialize response"}"#,
+        )
+            .into_response(),
+    }
+}
+
+/// Serialization of an echo payload, chosen from the request's `Accept`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    /// `application/json` (the default).
+    Json,
+    /// `application/xml`, rendered by [`json_to_xml`].
+    Xml,
+    /// `application/msgpack`: MessagePack, objects as maps keyed by name.
+    MessagePack,
+    /// `application/cbor`: CBOR (RFC 8949).
+    Cbor,
+}
+
+impl ResponseFormat {
+    /// Picks the format for a request.
+    ///
+    /// A non-JSON format is served only when one of its media types is the
+    /// client's most preferred explicitly listed type (highest q-value,
+    /// earliest on a tie): `application/xml` or `text/xml` for XML,
+    /// `application/msgpack` (or the older `application/x-msgpack` and
+    /// `application/vnd.msgpack`) for MessagePack, `application/cbor` for
+    /// CBOR. Anything else gets JSON: no `Accept`, wildcards, or a browser's
+    /// `text/html`-first header, which also lists XML at a lower q.
+    pub fn from_accept(headers: &HeaderMap) -> Self {
+        match preferred_media_type(headers).as_deref() {
+            Some("application/xml" | "text/xml") => Self::Xml,
+            Some("application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack") => {
+                Self::MessagePack
+            }
+            Some("application/cbor") => Self::Cbor,
+            _ => Self::Json,
+        }
+    }
+
+    /// The `Content-Type` this format is served with.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Xml => "application/xml",
+            Self::MessagePack => "application/msgpack",
+            Self::Cbor => "application/cbor",
+        }
+    }
+
+    /// Serializes `data` in this format.
+    fn encode(self, data: &Value) -> Option<Vec<u8>> {
+        match self {
+            Self::Json => serde_json::to_vec_pretty(data).ok(),
+            Self::Xml => json_to_xml(data).ok(),
+            Self::MessagePack => rmp_serde::to_vec_named(data).ok(),
+            Self::Cbor => {
+                let mut body = Vec::new();
+                ciborium::into_writer(data, &mut body).ok()?;
+                Some(body)
+            }
+        }
+    }
+}
+
+/// The most preferred non-wildcard media type in `Accept` (lowercased, without
+/// parameters): highest q-value, earliest on a tie; `q=0` types are skipped.
+pub(crate) fn preferred_media_type(headers: &HeaderMap) -> Option<String> {
+    let mut best: Option<(f32, String)> = None;
+    for accept in headers.get_all(header::ACCEPT) {
+        let Ok(accept) = accept.to_str() else {
+            continue;
+        };
+        for range in accept.split(',') {
+            let mut parts = range.split(';');
+            let media = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if media.is_empty() || media.contains('*') || q <= 0.0 {
+                continue;
+            }
+            if best.as_ref().is_none_or(|(best_q, _)| q > *best_q) {
+                best = Some((q, media));
+            }
+        }
+    }
+    best.map(|(_, media)| media)
+}
+
+/// Like [`format_json_response_with_timing`], but serialized in the format the
+/// request's `Accept` header asks for (see [`ResponseFormat::from_accept`]).
+///
+/// Adds `Vary: Accept`, since the representation depends on it. Used by the
+/// request-echo endpoints.
+pub fn format_response(
+    mut data: Value,
+    duration_ms: Option<f64>,
+    request_headers: &HeaderMap,
+) -> Response {
+    let format = ResponseFormat::from_accept(request_headers);
+    if let Some(ms) = duration_ms {
+        if let Some(obj) = data.as_object_mut() {
+            obj.insert("timing".to_string(), json!({ "duration_ms": ms }));
+        }
+    }
+
+    let mut response = match format.encode(&data) {
+        Some(body) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, format.content_type())],
+            body,
+        )
+            .into_response(),
+        None => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CONTENT_TYPE, "application/json")],
+            r#"{"error":"Failed to serialize response"}"#,
+        )
+            .into_response(),
+    };
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept"));
+    response
+}
+
+/// Computes a weak ETag (`W/"<16 hex digits>"`) from a 64-bit FNV-1a hash of
+/// `content`.
+///
+/// FNV-1a is fixed by definition, so the tag is stable across processes and
+/// releases for the same content. The tag is weak because opted-in endpoints
+/// hash their deterministic content, not the final bytes (e.g. `/endpoints`
+/// still appends per-request `timing`).
+pub fn content_etag(content: &[u8]) -> HeaderValue {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    let hash = content.iter().fold(FNV_OFFSET, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    // ASCII hex inside quotes is always a valid header value; the fallback
+    // only keeps this path free of panics.
+    HeaderValue::from_str(&format!("W/\"{hash:016x}\""))
+        .unwrap_or_else(|_| HeaderValue::from_static("W/\"0\""))
+}
+
+/// Returns true if the request's `If-None-Match` matches `etag` under the weak
+/// comparison (RFC 9110 §13.1.2): `*`, or any listed tag equal to `etag` once
+/// `W/` prefixes are ignored.
+fn if_none_match_matches(request_headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let Ok(ours) = etag.to_str().map(opaque) else {
+        return false;
+    };
+    request_headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == ours)
+}
+
+/// Makes `response` conditional on a content-hash ETag of `content`.
+///
+/// Returns `304 Not Modified` (carrying only the `ETag`) when the request's
+/// `If-None-Match` matches; otherwise returns `response` with the `ETag`
+/// header added. For deterministic endpoints that opt in to conditional GET.
+pub fn with_content_etag(
+    request_headers: &HeaderMap,
+    content: &[u8],
+    response: Response,
+) -> Response {
+    let etag = content_etag(content);
+    if if_none_match_matches(request_headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+    let mut response = response;
+    response.headers_mut().insert(header::ETAG, etag);
+    response
+}
+
+/// Like [`format_json_response_with_timing`], with a conditional-GET ETag.
+///
+/// The ETag hashes `data` before `timing` is added, so it stays stable across
+/// requests for the same content; a matching `If-None-Match` gets `304`.
+pub fn format_json_response_with_etag(
+    data: Value,
+    duration_ms: Option<f64>,
+    request_headers: &HeaderMap,
+) -> Response {
+    let content = serde_json::to_vec(&data).unwrap_or_default();
+    with_content_etag(
+        request_headers,
+        &content,
+        format_json_response_with_timing(data, duration_ms),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn if_none_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn content_etag_is_stable_and_content_sensitive() {
+        // FNV-1a 64 of the empty input is the offset basis.
+        assert_eq!(content_etag(b""), "W/\"cbf29ce484222325\"");
+        assert_eq!(content_etag(b"abc"), content_etag(b"abc"));
+        assert_ne!(content_etag(b"abc"), content_etag(b"abd"));
+    }
+
+    #[test]
+    fn with_content_etag_returns_304_on_match() {
+        let etag = content_etag(b"body");
+        let tag = etag.to_str().unwrap().to_string();
+        let strong = tag.trim_start_matches("W/").to_string();
+
+        for header_value in [
+            tag.as_str(),
+            strong.as_str(),
+            "*",
+            &format!("\"other\", {tag}"),
+        ] {
+            let response = with_content_etag(
+                &if_none_match(header_value),
+                b"body",
+                format_json_response(json!({})),
+            );
+            assert_eq!(
+                response.status(),
+                StatusCode::NOT_MODIFIED,
+                "{header_value}"
+            );
+            assert_eq!(response.headers()[header::ETAG], etag);
+        }
+    }
+
+    #[test]
+    fn with_content_etag_passes_through_on_mismatch() {
+        for headers in [HeaderMap::new(), if_none_match("\"stale\"")] {
+            let response =
+                with_content_etag(&headers, b"body", format_json_response(json!({"a": 1})));
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[header::ETAG], content_etag(b"body"));
+        }
+    }
+
+    #[test]
+    fn json_etag_ignores_timing() {
+        let headers = HeaderMap::new();
+        let a = format_json_response_with_etag(json!({"k": "v"}), Some(1.0), &headers);
+        let b = format_json_response_with_etag(json!({"k": "v"}), Some(2.0), &headers);
+        assert_eq!(a.headers()[header::ETAG], b.headers()[header::ETAG]);
+    }
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn response_format_follows_the_top_accept_preference() {
+        let xml = |value| ResponseFormat::from_accept(&accept(value)) == ResponseFormat::Xml;
+        assert!(xml("application/xml"));
+        assert!(xml("text/xml"));
+        assert!(xml("application/json;q=0.5, application/xml"));
+        assert!(xml("application/xml, application/json"));
+        assert!(!xml("application/json, application/xml"));
+        assert!(!xml("application/xml;q=0"));
+        assert!(!xml("*/*"));
+        // Browsers list XML below text/html; they still get JSON.
+        assert!(!xml(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+        ));
+        assert_eq!(
+            ResponseFormat::from_accept(&HeaderMap::new()),
+            ResponseFormat::Json
+        );
+        for (value, format) in [
+            ("application/msgpack", ResponseFormat::MessagePack),
+            ("application/x-msgpack", ResponseFormat::MessagePack),
+            (
+                "application/cbor, application/json;q=0.9",
+                ResponseFormat::Cbor,
+            ),
+            (
+                "application/cbor;q=0.1, application/json",
+                ResponseFormat::Json,
+            ),
+        ] {
+            assert_eq!(
+                ResponseFormat::from_accept(&accept(value)),
+                format,
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn binary_formats_decode_back_to_the_payload() {
+        let data = json!({
+            "method": "POST",
+            "headers": { "accept": ["a", "b"] },
+            "body": { "n": -3, "f": 1.5, "ok": true, "none": null },
+        });
+        let body = |format: ResponseFormat| format.encode(&data).unwrap();
+
+        let msgpack: Value = rmp_serde::from_slice(&body(ResponseFormat::MessagePack)).unwrap();
+        assert_eq!(msgpack, data);
+        let cbor: Value = ciborium::from_reader(&body(ResponseFormat::Cbor)[..]).unwrap();
+        assert_eq!(cbor, data);
+    }
+
+    #[test]
+    fn format_response_serializes_per_accept_with_vary() {
+        let json = format_response(json!({"a": 1}), Some(1.0), &HeaderMap::new());
+        assert_eq!(json.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(json.headers()[header::VARY], "accept");
+
+        let xml = format_response(json!({"a": 1}), Some(1.0), &accept("application/xml"));
+        assert_eq!(xml.headers()[header::CONTENT_TYPE], "application/xml");
+        assert_eq!(xml.headers()[header::VARY], "accept");
     }
 }
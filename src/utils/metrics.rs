@@ -4,18 +4,19 @@
 //! - Total request counts (all time)
 //! - Per-endpoint hit counts
 //! - Success (2xx) vs failure (4xx/5xx) counts
-//! - Rolling 1-hour window for all above metrics
+//! - Rolling window (1 hour by default, configurable) for all above metrics
+//! - Server start time and uptime
+//!
+//! All time is read from the store's [`Clock`], so tests can drive the window
+//! and uptime with a `ManualClock`.
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
-use std::sync::RwLock;
-use std::time::{Duration, Instant};
-
-/// Number of buckets for the rolling window (one per minute for 60 minutes).
-const ROLLING_WINDOW_BUCKETS: usize = 60;
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-/// Duration of each bucket in the rolling window.
-const BUCKET_DURATION: Duration = Duration::from_secs(60);
+use crate::utils::clock::Clock;
+use crate::utils::constants::{DEFAULT_METRICS_BUCKET_SECONDS, DEFAULT_METRICS_WINDOW_BUCKETS};
 
 /// A single time bucket for rolling window metrics.
 #[derive(Debug, Default)]
@@ -45,9 +46,9 @@ impl TimeBucket {
         self.endpoint_hits.clear();
     }
 
-    fn is_expired(&self, now: Instant) -> bool {
+    fn is_expired(&self, now: Instant, bucket_duration: Duration) -> bool {
         match self.start_time {
-            Some(start) => now.duration_since(start) >= BUCKET_DURATION,
+            Some(start) => now.duration_since(start) >= bucket_duration,
             None => true,
         }
     }
@@ -62,7 +63,8 @@ impl TimeBucket {
 
 /// Thread-safe metrics storage for request statistics.
 ///
-/// Provides both all-time counters and rolling 1-hour window statistics.
+/// Provides both all-time counters and rolling window statistics. The window
+/// is one hour (60 × 60s buckets) by default; see [`Metrics::with_window`].
 pub struct Metrics {
     /// Total requests since server start.
     total_requests: AtomicU64,
@@ -78,6 +80,16 @@ pub struct Metrics {
     /// the `rolling_buckets` write lock, so an atomic (not its own lock) is
     /// enough — see `update_rolling_window`.
     current_bucket_idx: AtomicUsize,
+    /// Duration of each rolling-window bucket.
+    bucket_duration: Duration,
+    /// Total span of the rolling window (`buckets × bucket_duration`).
+    window: Duration,
+    /// Monotonic instant the store was created, used for uptime.
+    started_at: Instant,
+    /// Wall-clock time the store was created, reported as `started_at`.
+    started_at_wall: SystemTime,
+    /// Source of every timestamp above and of "now" for the rolling window.
+    clock: Clock,
 }
 
 impl Default for Metrics {
@@ -87,11 +99,30 @@ impl Default for Metrics {
 }
 
 impl Metrics {
-    /// Creates a new Metrics instance with all counters initialized to zero.
+    /// Creates a new Metrics instance with all counters initialized to zero and
+    /// the default one-hour rolling window (60 buckets of 60 seconds).
     pub fn new() -> Self {
-        let buckets: Vec<TimeBucket> = (0..ROLLING_WINDOW_BUCKETS)
-            .map(|_| TimeBucket::new())
-            .collect();
+        Self::with_window(
+            DEFAULT_METRICS_WINDOW_BUCKETS,
+            Duration::from_secs(DEFAULT_METRICS_BUCKET_SECONDS),
+        )
+    }
+
+    /// Creates a new Metrics instance whose rolling window spans `num_buckets`
+    /// buckets of `bucket_duration` each — e.g. `with_window(30, 10s)` tracks
+    /// the last five minutes at 10-second resolution.
+    ///
+    /// Zero values are clamped (to one bucket / one second) rather than
+    /// producing a window that can never hold a request.
+    pub fn with_window(num_buckets: usize, bucket_duration: Duration) -> Self {
+        let num_buckets = num_buckets.max(1);
+        let bucket_duration = if bucket_duration.is_zero() {
+            Duration::from_secs(1)
+        } else {
+            bucket_duration
+        };
+        let buckets: Vec<TimeBucket> = (0..num_buckets).map(|_| TimeBucket::new()).collect();
+        let clock = Clock::default();
         Self {
             total_requests: AtomicU64::new(0),
             total_successes: AtomicU64::new(0),
@@ -99,9 +130,42 @@ impl Metrics {
             endpoint_hits: RwLock::new(HashMap::new()),
             rolling_buckets: RwLock::new(buckets),
             current_bucket_idx: AtomicUsize::new(0),
+            bucket_duration,
+            window: bucket_duration.saturating_mul(u32::try_from(num_buckets).unwrap_or(u32::MAX)),
+            started_at: clock.now(),
+            started_at_wall: clock.system_time(),
+            clock,
         }
     }
 
+    /// Switches the store to `clock` (the system clock by default), restarting
+    /// its uptime from that clock's current time.
+    pub fn with_clock(self, clock: Clock) -> Self {
+        Self {
+            started_at: clock.now(),
+            started_at_wall: clock.system_time(),
+            clock,
+            ..self
+        }
+    }
+
+    /// Returns how long this metrics store (i.e. the server) has been running.
+    ///
+    /// Measured on the monotonic clock, so wall-clock adjustments don't skew it.
+    pub fn uptime(&self) -> Duration {
+        self.clock.elapsed_since(self.started_at)
+    }
+
+    /// Returns the wall-clock time this metrics store was created.
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at_wall
+    }
+
+    /// Returns the total span of the rolling window.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
     /// Records a request to the metrics store.
     ///
     /// # Arguments
@@ -109,7 +173,6 @@ impl Metrics {
     /// * `endpoint` - The endpoint path that was requested (e.g., "/get", "/post")
     /// * `status_code` - The HTTP status code returned
     pub fn record_request(&self, endpoint: &str, status_code: u16) {
-        let now = Instant::now();
         let is_success = (200..300).contains(&status_code);
 
         // Update all-time counters
@@ -122,12 +185,15 @@ impl Metrics {
 
         // Update all-time endpoint hits
         {
-            let mut hits = self.endpoint_hits.write().unwrap();
+            let mut hits = self
+                .endpoint_hits
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
             *hits.entry(endpoint.to_string()).or_insert(0) += 1;
         }
 
         // Update rolling window
-        self.update_rolling_window(now, endpoint, is_success, status_code >= 400);
+        self.update_rolling_window(self.clock.now(), endpoint, is_success, status_code >= 400);
     }
 
     fn update_rolling_window(
@@ -137,15 +203,18 @@ impl Metrics {
         is_success: bool,
         is_failure: bool,
     ) {
-        let mut buckets = self.rolling_buckets.write().unwrap();
+        let mut buckets = self
+            .rolling_buckets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
         // `current_bucket_idx` is only ever touched here, under the buckets write
         // lock, which already serializes it — so Relaxed atomics suffice (the
         // AtomicUsize just provides interior mutability across `&self`).
         let mut idx = self.current_bucket_idx.load(Ordering::Relaxed);
 
         // Check if current bucket is expired and we need to move to the next
-        if buckets[idx].is_expired(now) {
-            idx = (idx + 1) % ROLLING_WINDOW_BUCKETS;
+        if buckets[idx].is_expired(now, self.bucket_duration) {
+            idx = (idx + 1) % buckets.len();
             buckets[idx].reset(now);
             self.current_bucket_idx.store(idx, Ordering::Relaxed);
         }
@@ -182,33 +251,41 @@ impl Metrics {
 
     /// Returns all-time per-endpoint hit counts.
     pub fn get_endpoint_hits(&self) -> HashMap<String, u64> {
-        self.endpoint_hits.read().unwrap().clone()
+        self.endpoint_hits
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
     }
 
-    /// Returns request count for the last hour.
+    /// Returns request count within the rolling window (last hour by default).
     pub fn get_last_hour_requests(&self) -> u64 {
-        self.sum_rolling_window(|b| b.requests)
+        self.sum_rolling_window(self.clock.now(), |b| b.requests)
     }
 
-    /// Returns success count for the last hour.
+    /// Returns success count within the rolling window (last hour by default).
     pub fn get_last_hour_successes(&self) -> u64 {
-        self.sum_rolling_window(|b| b.successes)
+        self.sum_rolling_window(self.clock.now(), |b| b.successes)
     }
 
-    /// Returns failure count for the last hour.
+    /// Returns failure count within the rolling window (last hour by default).
     pub fn get_last_hour_failures(&self) -> u64 {
-        self.sum_rolling_window(|b| b.failures)
+        self.sum_rolling_window(self.clock.now(), |b| b.failures)
     }
 
-    /// Returns per-endpoint hit counts for the last hour.
+    /// Returns per-endpoint hit counts within the rolling window (last hour by default).
     pub fn get_last_hour_endpoint_hits(&self) -> HashMap<String, u64> {
-        let now = Instant::now();
-        let window = Duration::from_secs(3600);
-        let buckets = self.rolling_buckets.read().unwrap();
+        self.window_endpoint_hits(self.clock.now())
+    }
+
+    fn window_endpoint_hits(&self, now: Instant) -> HashMap<String, u64> {
+        let buckets = self
+            .rolling_buckets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
 
         let mut result: HashMap<String, u64> = HashMap::new();
         for bucket in buckets.iter() {
-            if bucket.is_within_window(now, window) {
+            if bucket.is_within_window(now, self.window) {
                 for (endpoint, count) in &bucket.endpoint_hits {
                     *result.entry(endpoint.clone()).or_insert(0) += count;
                 }
@@ -217,24 +294,28 @@ impl Metrics {
         result
     }
 
-    fn sum_rolling_window<F>(&self, extractor: F) -> u64
+    fn sum_rolling_window<F>(&self, now: Instant, extractor: F) -> u64
     where
         F: Fn(&TimeBucket) -> u64,
     {
-        let now = Instant::now();
-        let window = Duration::from_secs(3600);
-        let buckets = self.rolling_buckets.read().unwrap();
+        let buckets = self
+            .rolling_buckets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
 
         buckets
             .iter()
-            .filter(|b| b.is_within_window(now, window))
+            .filter(|b| b.is_within_window(now, self.window))
             .map(&extractor)
             .sum()
     }
 
     /// Returns a snapshot of all metrics as a serializable structure.
     pub fn snapshot(&self) -> MetricsSnapshot {
+        let now = self.clock.now();
         MetricsSnapshot {
+            started_at: format_rfc3339(self.started_at_wall),
+            uptime_seconds: self.uptime().as_secs(),
             all_time: AllTimeMetrics {
                 total_requests: self.get_total_requests(),
                 successes: self.get_total_successes(),
@@ -242,10 +323,11 @@ impl Metrics {
                 endpoint_hits: self.get_endpoint_hits(),
             },
             last_hour: LastHourMetrics {
-                total_requests: self.get_last_hour_requests(),
-                successes: self.get_last_hour_successes(),
-                failures: self.get_last_hour_failures(),
-                endpoint_hits: self.get_last_hour_endpoint_hits(),
+                window_seconds: self.window.as_secs(),
+                total_requests: self.sum_rolling_window(now, |b| b.requests),
+                successes: self.sum_rolling_window(now, |b| b.successes),
+                failures: self.sum_rolling_window(now, |b| b.failures),
+                endpoint_hits: self.window_endpoint_hits(now),
             },
         }
     }
@@ -254,12 +336,43 @@ impl Metrics {
 /// A serializable snapshot of all metrics.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct MetricsSnapshot {
+    /// Server start time as an RFC 3339 UTC timestamp (e.g. `2026-01-01T00:00:00Z`).
+    pub started_at: String,
+    /// Whole seconds elapsed since server start.
+    pub uptime_seconds: u64,
     /// All-time metrics since server start.
     pub all_time: AllTimeMetrics,
-    /// Rolling metrics for the last hour.
+    /// Rolling-window metrics (the last hour by default; the key is kept for
+    /// compatibility when the window is configured to another span).
     pub last_hour: LastHourMetrics,
 }
 
+/// Formats a `SystemTime` as an RFC 3339 UTC timestamp with second precision.
+///
+/// Times before the Unix epoch clamp to `1970-01-01T00:00:00Z`.
+fn format_rfc3339(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (hour, minute, second) = (rem / 3600, (rem % 3600) / 60, rem % 60);
+
+    // Civil-from-days (Howard Hinnant's algorithm), proleptic Gregorian.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+}
+
 /// All-time metrics since server start.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct AllTimeMetrics {
@@ -273,9 +386,11 @@ pub struct AllTimeMetrics {
     pub endpoint_hits: HashMap<String, u64>,
 }
 
-/// Rolling metrics for the last hour.
+/// Rolling metrics for the configured window (the last hour by default).
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct LastHourMetrics {
+    /// Span of the rolling window in seconds (3600 by default).
+    pub window_seconds: u64,
     /// Total request count in the last hour.
     pub total_requests: u64,
     /// Success response count (2xx) in the last hour.
@@ -289,6 +404,19 @@ pub struct LastHourMetrics {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::clock::ManualClock;
+    use std::sync::Arc;
+
+    /// A metrics store on a manual clock, plus the handle that drives it.
+    fn manual_metrics(
+        num_buckets: usize,
+        bucket_duration: Duration,
+    ) -> (Metrics, Arc<ManualClock>) {
+        let manual = Arc::new(ManualClock::new());
+        let metrics = Metrics::with_window(num_buckets, bucket_duration)
+            .with_clock(Clock::new(manual.clone()));
+        (metrics, manual)
+    }
 
     #[test]
     fn test_new_metrics_are_zero() {
@@ -310,6 +438,25 @@ mod tests {
         assert_eq!(metrics.get_endpoint_hits().get("/get"), Some(&1));
     }
 
+    #[test]
+    fn test_poisoned_locks_do_not_panic() {
+        let metrics = Metrics::new();
+        // Poison both locks by panicking while holding them.
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let _hits = metrics.endpoint_hits.write();
+                let _buckets = metrics.rolling_buckets.write();
+                panic!("poison");
+            })
+            .join()
+            .unwrap_err();
+        });
+        assert!(metrics.endpoint_hits.is_poisoned());
+
+        metrics.record_request("/get", 200);
+        assert_eq!(metrics.get_endpoint_hits().get("/get"), Some(&1));
+    }
+
     #[test]
     fn test_record_failure_request() {
         let metrics = Metrics::new();
@@ -361,6 +508,91 @@ mod tests {
         assert_eq!(snapshot.all_time.failures, 1);
     }
 
+    #[test]
+    fn test_uptime_increases_between_snapshots() {
+        let (metrics, clock) = manual_metrics(60, Duration::from_secs(60));
+        assert_eq!(metrics.uptime(), Duration::ZERO);
+        let first = metrics.snapshot();
+        clock.advance(Duration::from_millis(1100));
+        let second = metrics.snapshot();
+
+        assert_eq!(metrics.uptime(), Duration::from_millis(1100));
+        assert_eq!(first.uptime_seconds, 0);
+        assert_eq!(second.uptime_seconds, 1);
+        assert_eq!(first.started_at, second.started_at, "start time is fixed");
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        // 2024-02-29T12:34:56Z — leap day.
+        let leap = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(format_rfc3339(leap), "2024-02-29T12:34:56Z");
+        // 2000-12-31T23:59:59Z — end of a leap century year.
+        let y2k = UNIX_EPOCH + Duration::from_secs(978_307_199);
+        assert_eq!(format_rfc3339(y2k), "2000-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_default_window_is_one_hour() {
+        let metrics = Metrics::new();
+        assert_eq!(metrics.window(), Duration::from_secs(3600));
+        assert_eq!(metrics.snapshot().last_hour.window_seconds, 3600);
+    }
+
+    #[test]
+    fn test_custom_window_expires_old_requests() {
+        // 3 buckets × 10s = 30s window.
+        let (metrics, clock) = manual_metrics(3, Duration::from_secs(10));
+        metrics.record_request("/get", 200);
+        clock.advance(Duration::from_secs(15));
+        metrics.record_request("/post", 500);
+
+        clock.advance(Duration::from_secs(5));
+        let snap = metrics.snapshot();
+        assert_eq!(snap.last_hour.window_seconds, 30);
+        assert_eq!(snap.last_hour.total_requests, 2);
+        assert_eq!(snap.uptime_seconds, 20);
+
+        // At t0+30s the first bucket has aged out of the 30s window.
+        clock.advance(Duration::from_secs(10));
+        let snap = metrics.snapshot();
+        assert_eq!(snap.last_hour.total_requests, 1);
+        assert_eq!(snap.last_hour.failures, 1);
+        assert_eq!(snap.last_hour.endpoint_hits.get("/get"), None);
+
+        // Well past the window nothing remains, but all-time counts persist.
+        clock.advance(Duration::from_secs(90));
+        let snap = metrics.snapshot();
+        assert_eq!(snap.last_hour.total_requests, 0);
+        assert_eq!(snap.all_time.total_requests, 2);
+    }
+
+    #[test]
+    fn test_custom_window_rolls_over_bucket_ring() {
+        // 2 buckets × 5s: writing a third bucket must recycle the oldest.
+        let (metrics, clock) = manual_metrics(2, Duration::from_secs(5));
+        metrics.record_request("/a", 200);
+        clock.advance(Duration::from_secs(5));
+        metrics.record_request("/b", 200);
+        clock.advance(Duration::from_secs(5));
+        metrics.record_request("/c", 200);
+
+        clock.advance(Duration::from_secs(1));
+        let hits = metrics.get_last_hour_endpoint_hits();
+        assert_eq!(hits.get("/a"), None, "oldest bucket was recycled");
+        assert_eq!(hits.get("/b"), Some(&1));
+        assert_eq!(hits.get("/c"), Some(&1));
+    }
+
+    #[test]
+    fn test_zero_window_values_are_clamped() {
+        let metrics = Metrics::with_window(0, Duration::ZERO);
+        assert_eq!(metrics.window(), Duration::from_secs(1));
+        metrics.record_request("/get", 200);
+        assert_eq!(metrics.get_last_hour_requests(), 1);
+    }
+
     #[test]
     fn test_3xx_is_neither_success_nor_failure() {
         let metrics = Metrics::new();
@@ -4,13 +4,17 @@
 //! the application, including configuration management, response formatting,
 //! and server setup helpers.
 
+/// Module for the time source abstraction (system and manual clocks).
+pub mod clock;
 /// Module for application configuration loading and management.
 pub mod config;
 /// Module for centralized constants used throughout the application.
 pub mod constants;
 /// Module for creating standardized JSON error responses.
 pub mod error_response;
-/// Module for creating standardized JSON responses.
+/// Module for serializing request headers into echo JSON.
+pub mod headers;
+/// Module for creating standardized JSON (or negotiated XML/MessagePack/CBOR) responses.
 pub mod json_response;
 /// Module for metrics collection and storage.
 pub mod metrics;
@@ -18,5 +22,9 @@ pub mod metrics;
 pub mod pid;
 /// Module for server-specific configurations, including listener parsing and SSL setup.
 pub mod server_config;
+/// Module for HMAC signing of response bodies.
+pub mod signature;
 /// Module for request timing utilities.
 pub mod timing;
+/// Module for rendering JSON echo payloads as XML.
+pub mod xml;
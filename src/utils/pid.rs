@@ -151,6 +151,30 @@ pub fn stop_process(pid_val: usize) -> StopResult {
     }
 }
 
+/// Asks a running process to reload its configuration by sending SIGHUP.
+///
+/// # Arguments
+///
+/// * `pid_val` - The process ID to signal
+///
+/// # Returns
+///
+/// `Ok(())` once the signal is sent, `Err(PidError::ProcessNotFound)` if no
+/// such process exists, or `Err(PidError::SignalFailed)` if it can't be
+/// signalled (e.g. it belongs to another user).
+pub fn reload_process(pid_val: usize) -> Result<(), PidError> {
+    let mut system = System::new_all();
+    system.refresh_processes();
+
+    match system.process(Pid::from(pid_val)) {
+        Some(process) => match process.kill_with(Signal::Hangup) {
+            Some(true) => Ok(()),
+            Some(false) | None => Err(PidError::SignalFailed(pid_val)),
+        },
+        None => Err(PidError::ProcessNotFound(pid_val)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,4 +222,31 @@ mod tests {
         let result = write_pid_file("/nonexistent-rucho-dir/sub/rucho.pid", process::id());
         assert!(matches!(result, Err(PidError::CreateFailed(_))));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn reload_signals_process_from_pid_file() {
+        use std::os::unix::process::ExitStatusExt;
+
+        // `sleep` has no SIGHUP handler, so the signal ends it: its exit
+        // status shows the signal reached the process named in the PID file.
+        let mut child = process::Command::new("sleep").arg("30").spawn().unwrap();
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rucho.pid");
+        let path = path.to_str().unwrap();
+        write_pid_file(path, child.id()).unwrap();
+
+        let pid = read_pid_file(path).unwrap();
+        reload_process(pid).expect("signal should be sent");
+        let status = child.wait().unwrap();
+        assert_eq!(status.signal(), Some(1)); // SIGHUP
+    }
+
+    #[test]
+    fn reload_missing_process_errors() {
+        assert!(matches!(
+            reload_process(999_999_999),
+            Err(PidError::ProcessNotFound(999_999_999))
+        ));
+    }
 }
@@ -4,13 +4,154 @@
 // If the certificates are not found, it falls back to plain HTTP with HTTP/1.1.
 
 use axum_server::tls_rustls::RustlsConfig;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::utils::config::Config;
+use crate::utils::constants::{DEFAULT_SERVER_LISTEN_PRIMARY, DEFAULT_SERVER_LISTEN_SECONDARY};
+
+const TLS13_ONLY: &[&rustls::SupportedProtocolVersion] = &[&rustls::version::TLS13];
+
+/// Lowest TLS protocol version the server negotiates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TlsMinVersion {
+    /// TLS 1.2 and 1.3 (rustls' default).
+    #[default]
+    Tls12,
+    /// TLS 1.3 only.
+    Tls13,
+}
+
+impl TlsMinVersion {
+    /// Parses a `tls_min_version` value (`"1.2"` or `"1.3"`).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "1.2" => Some(Self::Tls12),
+            "1.3" => Some(Self::Tls13),
+            _ => None,
+        }
+    }
+
+    /// The protocol versions rustls may negotiate.
+    fn protocol_versions(self) -> &'static [&'static rustls::SupportedProtocolVersion] {
+        match self {
+            Self::Tls12 => rustls::ALL_VERSIONS,
+            Self::Tls13 => TLS13_ONLY,
+        }
+    }
+}
+
+/// TLS settings applied to every HTTPS listener on top of its certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsOptions {
+    /// Path to a PEM bundle of CA certificates. When set, clients must present
+    /// a certificate chaining to one of them (mutual TLS).
+    pub client_ca: Option<String>,
+    /// Accept handshakes without a client certificate (presented ones are still
+    /// verified against `client_ca`), leaving enforcement to the per-path
+    /// client-certificate middleware.
+    pub client_cert_optional: bool,
+    /// Lowest protocol version accepted from clients.
+    pub min_version: TlsMinVersion,
+    /// ALPN protocol ids advertised, in preference order. Empty disables ALPN.
+    pub alpn: Vec<String>,
+}
+
+impl Default for TlsOptions {
+    fn default() -> Self {
+        Self {
+            client_ca: None,
+            client_cert_optional: false,
+            min_version: TlsMinVersion::default(),
+            alpn: vec!["h2".to_string(), "http/1.1".to_string()],
+        }
+    }
+}
+
+impl TlsOptions {
+    /// Extracts the TLS options from the loaded configuration.
+    ///
+    /// An unrecognized `tls_min_version` (rejected by `Config::validate`) falls
+    /// back to TLS 1.2.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            client_ca: config.ssl_client_ca.clone(),
+            client_cert_optional: !config.ssl_client_cert_paths.is_empty(),
+            min_version: TlsMinVersion::parse(&config.tls_min_version).unwrap_or_default(),
+            alpn: config.tls_alpn.clone(),
+        }
+    }
+}
+
+/// Builds a rustls server config from PEM-encoded certificate chain and key,
+/// applying `options`: accepted protocol versions, advertised ALPN protocols
+/// (by default `h2` and `http/1.1`, as `RustlsConfig::from_pem` does), and
+/// client certificate verification.
+///
+/// # Errors
+///
+/// Returns a message describing the first problem: unparseable PEM, a missing
+/// key, an unreadable or empty client CA bundle, or a key/cert mismatch.
+pub fn build_server_config(
+    cert_pem: &[u8],
+    key_pem: &[u8],
+    options: &TlsOptions,
+) -> Result<ServerConfig, String> {
+    let certs = rustls_pemfile::certs(&mut &cert_pem[..])
+        .collect::<Result<Vec<CertificateDer<'static>>, _>>()
+        .map_err(|e| format!("invalid certificate PEM: {e}"))?;
+    let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut &key_pem[..])
+        .map_err(|e| format!("invalid private key PEM: {e}"))?
+        .ok_or_else(|| "no private key found in key PEM".to_string())?;
+
+    let builder =
+        ServerConfig::builder_with_protocol_versions(options.min_version.protocol_versions());
+    let builder = match &options.client_ca {
+        Some(ca_path) => {
+            let ca_pem = std::fs::read(ca_path)
+                .map_err(|e| format!("cannot read ssl_client_ca {ca_path}: {e}"))?;
+            let mut roots = RootCertStore::empty();
+            for cert in rustls_pemfile::certs(&mut &ca_pem[..]) {
+                let cert = cert.map_err(|e| format!("invalid ssl_client_ca PEM: {e}"))?;
+                roots
+                    .add(cert)
+                    .map_err(|e| format!("invalid CA certificate in ssl_client_ca: {e}"))?;
+            }
+            if roots.is_empty() {
+                return Err(format!("ssl_client_ca {ca_path} contains no certificates"));
+            }
+            let mut verifier = WebPkiClientVerifier::builder(Arc::new(roots));
+            if options.client_cert_optional {
+                verifier = verifier.allow_unauthenticated();
+            }
+            let verifier = verifier
+                .build()
+                .map_err(|e| format!("cannot build client certificate verifier: {e}"))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("certificate/key rejected: {e}"))?;
+    config.alpn_protocols = options
+        .alpn
+        .iter()
+        .map(|proto| proto.as_bytes().to_vec())
+        .collect();
+    Ok(config)
+}
 
 /// Attempts to load Rustls configuration for enabling HTTPS.
 ///
 /// This function checks for the existence of SSL certificate and key files at the
 /// paths provided. If both files are found and valid, it returns a `RustlsConfig`
-/// suitable for configuring an Axum server with TLS.
+/// suitable for configuring an Axum server with TLS, with `options` applied.
 ///
 /// If either path is not provided, or if the files are not found or are invalid,
 /// this function logs a warning/error and returns `None`, indicating that TLS
@@ -20,6 +161,7 @@ use std::path::PathBuf;
 ///
 /// * `ssl_cert_path_opt`: An `Option<&str>` containing the path to the SSL certificate file.
 /// * `ssl_key_path_opt`: An `Option<&str>` containing the path to the SSL private key file.
+/// * `options`: Additional TLS settings (e.g. the mTLS client CA).
 ///
 /// # Returns
 ///
@@ -27,6 +169,7 @@ use std::path::PathBuf;
 pub async fn try_load_rustls_config(
     ssl_cert_path_opt: Option<&str>,
     ssl_key_path_opt: Option<&str>,
+    options: &TlsOptions,
 ) -> Option<RustlsConfig> {
     // Check if both paths are provided
     let (cert_p, key_p) = match (ssl_cert_path_opt, ssl_key_path_opt) {
@@ -44,27 +187,64 @@ pub async fn try_load_rustls_config(
     let key_path = PathBuf::from(key_p);
 
     // Check if both certificate and key files exist at the provided paths
-    if cert_path.exists() && key_path.exists() {
-        match RustlsConfig::from_pem_file(&cert_path, &key_path).await {
-            Ok(config) => Some(config),
-            Err(err) => {
-                tracing::error!(
-                    "Failed to load TLS config from {} and {}: {}",
-                    cert_path.display(),
-                    key_path.display(),
-                    err
-                );
-                None
-            }
-        }
-    } else {
+    if !(cert_path.exists() && key_path.exists()) {
         tracing::warn!("TLS certificate or key file not found at the specified path(s): {} or {}. Cannot enable TLS.", cert_path.display(), key_path.display());
-        None
+        return None;
+    }
+
+    match load_server_config(cert_p, key_p, options).await {
+        Ok(config) => Some(RustlsConfig::from_config(Arc::new(config))),
+        Err(err) => {
+            tracing::error!(
+                "Failed to load TLS config from {} and {}: {}",
+                cert_path.display(),
+                key_path.display(),
+                err
+            );
+            None
+        }
     }
 }
 
+/// Reads the cert/key PEM files and builds a server config from them.
+async fn load_server_config(
+    cert_path: &str,
+    key_path: &str,
+    options: &TlsOptions,
+) -> Result<ServerConfig, String> {
+    let cert_pem = tokio::fs::read(cert_path)
+        .await
+        .map_err(|e| format!("cannot read {cert_path}: {e}"))?;
+    let key_pem = tokio::fs::read(key_path)
+        .await
+        .map_err(|e| format!("cannot read {key_path}: {e}"))?;
+    build_server_config(&cert_pem, &key_pem, options)
+}
+
+/// Reloads `rustls_config` in place from the cert/key files (e.g. after a
+/// certificate rotation).
+///
+/// New TLS handshakes use the new certificate immediately; established
+/// connections keep the one they negotiated. On error `rustls_config` is left
+/// unchanged, so a bad rotation never takes HTTPS down.
+///
+/// # Errors
+///
+/// Returns a message if the files can't be read or don't form a valid
+/// certificate/key pair (see [`build_server_config`]).
+pub async fn reload_rustls_config(
+    rustls_config: &RustlsConfig,
+    cert_path: &str,
+    key_path: &str,
+    options: &TlsOptions,
+) -> Result<(), String> {
+    let config = load_server_config(cert_path, key_path, options).await?;
+    rustls_config.reload_from_config(Arc::new(config));
+    Ok(())
+}
+
 /// Generates an ephemeral, in-memory self-signed `RustlsConfig` for zero-setup
-/// HTTPS (the `ssl_auto_cert` option).
+/// HTTPS (the `ssl_auto_cert` option), with `options` applied.
 ///
 /// The certificate covers `localhost`, `127.0.0.1`, and `::1`. It is generated
 /// fresh on every process start (not persisted) and is **self-signed**, so
@@ -75,7 +255,7 @@ pub async fn try_load_rustls_config(
 ///
 /// `Some(RustlsConfig)` on success, or `None` (with an error logged) if cert
 /// generation or parsing fails.
-pub async fn generate_self_signed_rustls_config() -> Option<RustlsConfig> {
+pub async fn generate_self_signed_rustls_config(options: &TlsOptions) -> Option<RustlsConfig> {
     let subject_alt_names = vec![
         "localhost".to_string(),
         "127.0.0.1".to_string(),
@@ -93,14 +273,14 @@ pub async fn generate_self_signed_rustls_config() -> Option<RustlsConfig> {
     let cert_pem = cert_key.cert.pem();
     let key_pem = cert_key.key_pair.serialize_pem();
 
-    match RustlsConfig::from_pem(cert_pem.into_bytes(), key_pem.into_bytes()).await {
+    match build_server_config(cert_pem.as_bytes(), key_pem.as_bytes(), options) {
         Ok(config) => {
             tracing::warn!(
                 "ssl_auto_cert: serving HTTPS with an ephemeral self-signed certificate \
                 (localhost / 127.0.0.1 / ::1). Clients must skip verification (e.g. `curl -k`). \
                 For dev/test only — use ssl_cert/ssl_key files in production."
             );
-            Some(config)
+            Some(RustlsConfig::from_config(Arc::new(config)))
         }
         Err(e) => {
             tracing::error!(
@@ -114,8 +294,9 @@ pub async fn generate_self_signed_rustls_config() -> Option<RustlsConfig> {
 
 /// Parses a server listen address string to extract the address and SSL flag.
 ///
-/// The input string can be in the format "IP:PORT" or "IP:PORT ssl".
-/// The SSL suffix is case-insensitive (accepts "ssl", "SSL", "Ssl", etc.).
+/// The input string can be in the format "IP:PORT", "IP:PORT ssl", or
+/// "ssl:IP:PORT". The SSL marker is case-insensitive (accepts "ssl", "SSL",
+/// "Ssl", etc.).
 ///
 /// # Arguments
 ///
@@ -125,7 +306,7 @@ pub async fn generate_self_signed_rustls_config() -> Option<RustlsConfig> {
 ///
 /// An `Option<(String, bool)>`.
 /// - `Some((address, is_ssl))` where `address` is the IP:PORT part and `is_ssl`
-///   is true if " ssl" was present.
+///   is true if an `ssl:` prefix or ` ssl` suffix was present.
 /// - `None` if the input `listen_str` is empty.
 ///
 /// # Examples
@@ -135,6 +316,7 @@ pub async fn generate_self_signed_rustls_config() -> Option<RustlsConfig> {
 ///
 /// assert_eq!(parse_listen_address("0.0.0.0:443 ssl"), Some(("0.0.0.0:443".to_string(), true)));
 /// assert_eq!(parse_listen_address("0.0.0.0:443 SSL"), Some(("0.0.0.0:443".to_string(), true)));
+/// assert_eq!(parse_listen_address("ssl:0.0.0.0:443"), Some(("0.0.0.0:443".to_string(), true)));
 /// assert_eq!(parse_listen_address("0.0.0.0:8080"), Some(("0.0.0.0:8080".to_string(), false)));
 /// assert_eq!(parse_listen_address(""), None);
 /// ```
@@ -143,21 +325,211 @@ pub fn parse_listen_address(listen_str: &str) -> Option<(String, bool)> {
         return None;
     }
 
-    // Case-insensitive check for " ssl" suffix
-    let lower = listen_str.to_lowercase();
-    if lower.ends_with(" ssl") {
-        // Remove the last 4 characters (" ssl") from the original string
-        let addr = listen_str[..listen_str.len() - 4].to_string();
-        Some((addr, true))
-    } else {
-        Some((listen_str.to_string(), false))
+    const PREFIX: &str = "ssl:";
+    const SUFFIX: &str = " ssl";
+
+    // Case-insensitive ASCII comparisons on char-boundary-checked slices, so
+    // non-ASCII input can't shift the split point.
+    let prefixed = listen_str
+        .get(..PREFIX.len())
+        .filter(|head| head.eq_ignore_ascii_case(PREFIX))
+        .and_then(|_| listen_str.get(PREFIX.len()..));
+    let suffixed = || {
+        let split = listen_str.len().checked_sub(SUFFIX.len())?;
+        listen_str
+            .get(split..)
+            .filter(|tail| tail.eq_ignore_ascii_case(SUFFIX))
+            .and_then(|_| listen_str.get(..split))
+    };
+
+    match prefixed.or_else(suffixed) {
+        Some(addr) => Some((addr.to_string(), true)),
+        None => Some((listen_str.to_string(), false)),
+    }
+}
+
+/// Collects every configured HTTP(S) listen address, parsed with
+/// [`parse_listen_address`], in order: `server_listen_primary`,
+/// `server_listen_secondary`, then each `server_listen` entry. Blank entries
+/// and repeats of an earlier address are dropped.
+///
+/// For backward compatibility the two legacy fields are always merged in once
+/// changed from their defaults. While they are at their defaults they only
+/// apply when `server_listen` is empty, so setting `server_listen` alone
+/// doesn't also open ports 8080 and 9090.
+pub fn listen_addresses(config: &Config) -> Vec<(String, bool)> {
+    let legacy = [
+        (&config.server_listen_primary, DEFAULT_SERVER_LISTEN_PRIMARY),
+        (
+            &config.server_listen_secondary,
+            DEFAULT_SERVER_LISTEN_SECONDARY,
+        ),
+    ]
+    .into_iter()
+    .filter(|(value, default)| config.server_listen.is_empty() || value.as_str() != *default)
+    .map(|(value, _)| value);
+
+    let mut addresses: Vec<(String, bool)> = Vec::new();
+    for parsed in legacy
+        .chain(&config.server_listen)
+        .filter_map(|address| parse_listen_address(address))
+    {
+        if !addresses.iter().any(|(addr, _)| *addr == parsed.0) {
+            addresses.push(parsed);
+        }
+    }
+    addresses
+}
+
+/// Why a listen address failed to parse as a socket address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddressError {
+    /// No `:PORT` after the host, e.g. `0.0.0.0` or `[::1]`.
+    MissingPort,
+    /// The port is not a number in `0..=65535`.
+    InvalidPort(String),
+    /// An IPv6 literal without brackets, e.g. `::1:8080`.
+    UnbracketedIpv6,
+    /// The bracketed part is not a valid IPv6 address, or the `]` is missing.
+    InvalidIpv6(String),
+    /// The host is not an IPv4 address (hostnames are not resolved).
+    InvalidHost(String),
+}
+
+impl std::fmt::Display for ListenAddressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingPort => {
+                write!(f, "missing port (expected e.g. 0.0.0.0:8080 or [::]:8080)")
+            }
+            Self::InvalidPort(port) => write!(f, "invalid port '{port}'"),
+            Self::UnbracketedIpv6 => write!(
+                f,
+                "IPv6 addresses must be bracketed before the port (e.g. [::1]:8080)"
+            ),
+            Self::InvalidIpv6(host) => write!(f, "invalid IPv6 address '{host}'"),
+            Self::InvalidHost(host) => write!(
+                f,
+                "invalid IPv4 address '{host}' (hostnames are not supported)"
+            ),
+        }
     }
 }
 
+impl std::error::Error for ListenAddressError {}
+
+/// Parses a listen address (as left by [`parse_listen_address`]) into a
+/// socket address: `IPv4:PORT` or `[IPv6]:PORT`, e.g. `0.0.0.0:8080` or
+/// `[::]:8443`. Surrounding whitespace is ignored.
+///
+/// # Errors
+///
+/// Returns a [`ListenAddressError`] saying what is wrong, so a log line can
+/// tell a missing port from a malformed IPv6 literal.
+///
+/// # Examples
+///
+/// ```
+/// use rucho::utils::server_config::{parse_socket_addr, ListenAddressError};
+///
+/// assert_eq!(parse_socket_addr("[::1]:8080").unwrap().to_string(), "[::1]:8080");
+/// assert_eq!(parse_socket_addr("[::1]"), Err(ListenAddressError::MissingPort));
+/// ```
+pub fn parse_socket_addr(addr: &str) -> Result<SocketAddr, ListenAddressError> {
+    let addr = addr.trim();
+    if let Ok(parsed) = addr.parse::<SocketAddr>() {
+        return Ok(parsed);
+    }
+
+    // Not a valid socket address; work out why.
+    let port = if let Some(bracketed) = addr.strip_prefix('[') {
+        let (host, rest) = bracketed
+            .split_once(']')
+            .ok_or_else(|| ListenAddressError::InvalidIpv6(addr.to_string()))?;
+        if host.parse::<Ipv6Addr>().is_err() {
+            return Err(ListenAddressError::InvalidIpv6(host.to_string()));
+        }
+        rest.strip_prefix(':')
+            .ok_or(ListenAddressError::MissingPort)?
+    } else if addr.parse::<Ipv6Addr>().is_ok() || addr.matches(':').count() > 1 {
+        return Err(ListenAddressError::UnbracketedIpv6);
+    } else {
+        let (host, port) = addr
+            .split_once(':')
+            .ok_or(ListenAddressError::MissingPort)?;
+        if host.parse::<Ipv4Addr>().is_err() {
+            return Err(ListenAddressError::InvalidHost(host.to_string()));
+        }
+        port
+    };
+    Err(ListenAddressError::InvalidPort(port.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn fixture(name: &str) -> Vec<u8> {
+        std::fs::read(format!(
+            "{}/tests/fixtures/tls/{name}",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_server_config_advertises_h2_and_http11() {
+        let config = build_server_config(
+            &fixture("cert.pem"),
+            &fixture("key.pem"),
+            &TlsOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            config.alpn_protocols,
+            vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_build_server_config_applies_configured_alpn() {
+        let options = TlsOptions {
+            alpn: vec!["http/1.1".to_string()],
+            ..TlsOptions::default()
+        };
+        let config =
+            build_server_config(&fixture("cert.pem"), &fixture("key.pem"), &options).unwrap();
+        assert_eq!(config.alpn_protocols, vec![b"http/1.1".to_vec()]);
+    }
+
+    #[test]
+    fn test_tls_min_version_parse() {
+        assert_eq!(TlsMinVersion::parse("1.2"), Some(TlsMinVersion::Tls12));
+        assert_eq!(TlsMinVersion::parse(" 1.3 "), Some(TlsMinVersion::Tls13));
+        assert_eq!(TlsMinVersion::parse("1.1"), None);
+        assert_eq!(TlsMinVersion::parse("tls1.3"), None);
+    }
+
+    #[test]
+    fn test_build_server_config_rejects_bad_client_ca() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty.pem");
+        std::fs::write(&empty, "").unwrap();
+
+        for (path, expected) in [
+            (dir.path().join("missing.pem"), "cannot read"),
+            (empty, "contains no certificates"),
+        ] {
+            let options = TlsOptions {
+                client_ca: Some(path.to_string_lossy().into_owned()),
+                ..TlsOptions::default()
+            };
+            let err = build_server_config(&fixture("cert.pem"), &fixture("key.pem"), &options)
+                .unwrap_err();
+            assert!(err.contains(expected), "{err}");
+        }
+    }
+
     #[test]
     fn test_parse_listen_address_empty() {
         assert_eq!(parse_listen_address(""), None);
@@ -210,4 +582,145 @@ mod tests {
             Some(("[::1]:443".to_string(), true))
         );
     }
+
+    #[test]
+    fn test_parse_listen_address_ipv6_any_with_ssl_to_socket_addr() {
+        let (addr, is_ssl) = parse_listen_address("[::]:8443 ssl").unwrap();
+        assert!(is_ssl);
+        let sock = parse_socket_addr(&addr).unwrap();
+        assert!(sock.is_ipv6());
+        assert!(sock.ip().is_unspecified());
+        assert_eq!(sock.port(), 8443);
+    }
+
+    fn listen_config(primary: &str, secondary: &str, list: &[&str]) -> Config {
+        Config {
+            server_listen_primary: primary.to_string(),
+            server_listen_secondary: secondary.to_string(),
+            server_listen: list.iter().map(|s| s.to_string()).collect(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_listen_addresses_defaults_without_list() {
+        let config = Config::default();
+        assert_eq!(
+            listen_addresses(&config),
+            vec![
+                ("0.0.0.0:8080".to_string(), false),
+                ("0.0.0.0:9090".to_string(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_listen_addresses_list_replaces_default_legacy_fields() {
+        let config = listen_config(
+            DEFAULT_SERVER_LISTEN_PRIMARY,
+            DEFAULT_SERVER_LISTEN_SECONDARY,
+            &["127.0.0.1:8001", "[::1]:8002", "0.0.0.0:8443 ssl"],
+        );
+        assert_eq!(
+            listen_addresses(&config),
+            vec![
+                ("127.0.0.1:8001".to_string(), false),
+                ("[::1]:8002".to_string(), false),
+                ("0.0.0.0:8443".to_string(), true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_listen_addresses_merges_customized_legacy_fields() {
+        let config = listen_config(
+            "ssl:0.0.0.0:443",
+            DEFAULT_SERVER_LISTEN_SECONDARY,
+            &["127.0.0.1:8001"],
+        );
+        assert_eq!(
+            listen_addresses(&config),
+            vec![
+                ("0.0.0.0:443".to_string(), true),
+                ("127.0.0.1:8001".to_string(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_listen_addresses_drops_blanks_and_repeats() {
+        let config = listen_config(
+            "127.0.0.1:8001",
+            "",
+            &["127.0.0.1:8001", "127.0.0.1:8002", "127.0.0.1:8002"],
+        );
+        assert_eq!(
+            listen_addresses(&config),
+            vec![
+                ("127.0.0.1:8001".to_string(), false),
+                ("127.0.0.1:8002".to_string(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_socket_addr_valid() {
+        for (input, expected) in [
+            ("0.0.0.0:8080", "0.0.0.0:8080"),
+            ("[::1]:8080", "[::1]:8080"),
+            ("[::]:8443", "[::]:8443"),
+            (" [2001:db8::1]:443 ", "[2001:db8::1]:443"),
+            ("[::ffff:127.0.0.1]:80", "[::ffff:127.0.0.1]:80"),
+        ] {
+            assert_eq!(
+                parse_socket_addr(input).unwrap().to_string(),
+                expected,
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_socket_addr_explains_failures() {
+        use ListenAddressError::*;
+        for (input, expected) in [
+            ("0.0.0.0", MissingPort),
+            ("[::1]", MissingPort),
+            ("[::1]8080", MissingPort),
+            ("::1:8080", UnbracketedIpv6),
+            ("::1", UnbracketedIpv6),
+            ("[::1:8080", InvalidIpv6("[::1:8080".to_string())),
+            ("[::g]:8080", InvalidIpv6("::g".to_string())),
+            ("[::1]:http", InvalidPort("http".to_string())),
+            ("0.0.0.0:70000", InvalidPort("70000".to_string())),
+            ("0.0.0.0:", InvalidPort(String::new())),
+            ("localhost:8080", InvalidHost("localhost".to_string())),
+        ] {
+            assert_eq!(parse_socket_addr(input), Err(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_listen_address_with_ssl_prefix() {
+        for input in ["ssl:0.0.0.0:8443", "SSL:0.0.0.0:8443"] {
+            assert_eq!(
+                parse_listen_address(input),
+                Some(("0.0.0.0:8443".to_string(), true)),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            parse_listen_address("ssl:[::1]:8443"),
+            Some(("[::1]:8443".to_string(), true))
+        );
+    }
+
+    #[test]
+    fn test_parse_listen_address_non_ascii_does_not_panic() {
+        assert_eq!(
+            parse_listen_address("é:1 ßl"),
+            Some(("é:1 ßl".to_string(), false))
+        );
+        assert_eq!(parse_listen_address("ss"), Some(("ss".to_string(), false)));
+    }
 }
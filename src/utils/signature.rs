@@ -0,0 +1,118 @@
+//! HMAC response signing for `/anything?sign=true`.
+//!
+//! With `signing_secret` configured, `/anything?sign=true` adds an
+//! `X-Signature: sha256=<hex>` header carrying the HMAC-SHA256 of the response
+//! body under that secret, so clients can test their signature-verification
+//! path against a known-good signer. The signature covers the body as the
+//! handler produced it, before any `Content-Encoding` is applied; chaos
+//! corruption happens afterwards, so a corrupted body fails verification.
+
+use axum::{
+    body::Body,
+    http::{HeaderValue, StatusCode},
+    response::Response,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::utils::error_response::format_error_response;
+
+/// Response header carrying the body signature.
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// A keyed HMAC-SHA256 signer built from the configured `signing_secret`.
+#[derive(Clone)]
+pub struct SigningKey {
+    mac: Hmac<Sha256>,
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SigningKey(<redacted>)")
+    }
+}
+
+impl SigningKey {
+    /// Creates a signer for `secret`, or `None` if the secret is empty.
+    pub fn new(secret: &str) -> Option<Self> {
+        if secret.is_empty() {
+            return None;
+        }
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .ok()
+            .map(|mac| Self { mac })
+    }
+
+    /// Returns the `X-Signature` value for `body`: `sha256=` followed by the
+    /// lowercase hex HMAC-SHA256.
+    pub fn sign(&self, body: &[u8]) -> String {
+        let mut mac = self.mac.clone();
+        mac.update(body);
+        let digest = mac.finalize().into_bytes();
+        let mut signature = String::with_capacity(7 + digest.len() * 2);
+        signature.push_str("sha256=");
+        for byte in digest {
+            signature.push_str(&format!("{byte:02x}"));
+        }
+        signature
+    }
+}
+
+/// Buffers `response`'s body and adds an `X-Signature` header over it.
+///
+/// Returns 500 if the body cannot be read.
+pub async fn sign_response(response: Response, key: &SigningKey) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return format_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to read response body for signing",
+        );
+    };
+    if let Ok(value) = HeaderValue::try_from(key.sign(&bytes)) {
+        parts.headers.insert(SIGNATURE_HEADER, value);
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_matches_rfc4231_vector() {
+        // RFC 4231, test case 2.
+        let key = SigningKey::new("Jefe").unwrap();
+        assert_eq!(
+            key.sign(b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn empty_secret_disables_signing() {
+        assert!(SigningKey::new("").is_none());
+    }
+
+    #[test]
+    fn debug_does_not_leak_secret() {
+        let key = SigningKey::new("hunter2").unwrap();
+        assert!(!format!("{key:?}").contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn sign_response_preserves_body_and_adds_header() {
+        let key = SigningKey::new("secret").unwrap();
+        let response = Response::new(Body::from("{\"a\":1}"));
+        let signed = sign_response(response, &key).await;
+        let signature = signed.headers()[SIGNATURE_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(signed.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"{\"a\":1}");
+        assert_eq!(signature, key.sign(&body));
+    }
+}
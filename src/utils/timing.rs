@@ -5,26 +5,31 @@
 
 use std::time::Instant;
 
+use crate::utils::clock::Clock;
+
 /// Stores the start time of a request for timing calculations.
 ///
 /// This struct is inserted as a request extension by the timing middleware
 /// and can be extracted by handlers to calculate request duration.
-#[derive(Clone, Copy)]
+#[derive(Clone, Debug)]
 pub struct RequestTiming {
     /// The instant when the request started processing.
     pub start: Instant,
+    /// The clock `start` was read from, and elapsed time is measured on.
+    clock: Clock,
 }
 
 impl RequestTiming {
-    /// Creates a new RequestTiming with the current instant.
-    pub fn now() -> Self {
+    /// Creates a new RequestTiming starting at `clock`'s current instant.
+    pub fn new(clock: &Clock) -> Self {
         Self {
-            start: Instant::now(),
+            start: clock.now(),
+            clock: clock.clone(),
         }
     }
 
     /// Returns the elapsed time since the request started in milliseconds.
     pub fn elapsed_ms(&self) -> f64 {
-        self.start.elapsed().as_secs_f64() * 1000.0
+        self.clock.elapsed_since(self.start).as_secs_f64() * 1000.0
     }
 }
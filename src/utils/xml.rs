@@ -0,0 +1,153 @@
+//! JSON-to-XML rendering for echo responses negotiated as `application/xml`.
+//!
+//! An echo payload is a `serde_json::Value`; [`json_to_xml`] writes the same
+//! tree as an XML document under a `<response>` root:
+//!
+//! - an object key becomes an element of that name — or, when the key isn't a
+//!   usable XML name (a query parameter like `1st` or `a b`), a
+//!   `<field name="...">` element carrying it as an attribute;
+//! - an array becomes one `<item>` child per element, in order;
+//! - strings, numbers and booleans become the element's text, and `null` an
+//!   empty element.
+//!
+//! Characters XML 1.0 can't carry at all (most C0 controls) are replaced with
+//! U+FFFD, so any echo renders as a well-formed document.
+
+use std::borrow::Cow;
+use std::io;
+
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+use serde_json::Value;
+
+/// Renders `value` as an indented XML document rooted at `<response>`.
+pub fn json_to_xml(value: &Value) -> io::Result<Vec<u8>> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    write_element(&mut writer, BytesStart::new("response"), value)?;
+    Ok(writer.into_inner())
+}
+
+/// Writes `value` as the content of the element `start`.
+fn write_element(writer: &mut Writer<Vec<u8>>, start: BytesStart, value: &Value) -> io::Result<()> {
+    let end = BytesEnd::new(String::from_utf8_lossy(start.name().as_ref()).into_owned());
+    match value {
+        Value::Null => return writer.write_event(Event::Empty(start)),
+        Value::Object(map) if map.is_empty() => return writer.write_event(Event::Empty(start)),
+        Value::Array(items) if items.is_empty() => return writer.write_event(Event::Empty(start)),
+        Value::Object(map) => {
+            writer.write_event(Event::Start(start))?;
+            for (key, child) in map {
+                write_element(writer, field_start(key), child)?;
+            }
+        }
+        Value::Array(items) => {
+            writer.write_event(Event::Start(start))?;
+            for item in items {
+                write_element(writer, BytesStart::new("item"), item)?;
+            }
+        }
+        Value::String(text) => {
+            writer.write_event(Event::Start(start))?;
+            writer.write_event(Event::Text(BytesText::new(&xml_safe(text))))?;
+        }
+        Value::Number(_) | Value::Bool(_) => {
+            writer.write_event(Event::Start(start))?;
+            writer.write_event(Event::Text(BytesText::new(&value.to_string())))?;
+        }
+    }
+    writer.write_event(Event::End(end))
+}
+
+/// The start tag for object key `key`: `<key>` when it is a valid XML name,
+/// otherwise `<field name="key">`.
+fn field_start(key: &str) -> BytesStart<'static> {
+    if is_xml_name(key) {
+        BytesStart::new(key.to_string())
+    } else {
+        let mut start = BytesStart::new("field");
+        start.push_attribute(("name", xml_safe(key).as_ref()));
+        start
+    }
+}
+
+/// Whether `name` can be used as an element name as-is: an ASCII letter or `_`
+/// followed by ASCII letters, digits, `-`, `_` or `.` (no `:`, which would
+/// declare a namespace prefix).
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Replaces the characters XML 1.0 forbids even when escaped with U+FFFD.
+fn xml_safe(text: &str) -> Cow<'_, str> {
+    let allowed = |c: char| {
+        matches!(c, '\t' | '\n' | '\r')
+            || ('\u{20}'..='\u{D7FF}').contains(&c)
+            || ('\u{E000}'..='\u{FFFD}').contains(&c)
+            || c >= '\u{10000}'
+    };
+    if text.chars().all(allowed) {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(
+            text.chars()
+                .map(|c| if allowed(c) { c } else { '\u{FFFD}' })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(value: &Value) -> String {
+        String::from_utf8(json_to_xml(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn renders_objects_arrays_and_scalars() {
+        let xml = render(&json!({
+            "method": "GET",
+            "args": { "tag": ["a", "b"] },
+            "count": 2,
+            "ok": true,
+            "body": null,
+        }));
+        assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(xml.contains("<method>GET</method>"));
+        assert!(xml.contains("<item>a</item>"));
+        assert!(xml.contains("<item>b</item>"));
+        assert!(xml.contains("<count>2</count>"));
+        assert!(xml.contains("<ok>true</ok>"));
+        assert!(xml.contains("<body/>"));
+        assert!(xml.trim_end().ends_with("</response>"));
+    }
+
+    #[test]
+    fn escapes_text_and_falls_back_for_invalid_names() {
+        let xml = render(&json!({
+            "x-custom": "<b>&\"",
+            "1st key": "v",
+            "ctl": "a\u{1}b",
+        }));
+        assert!(xml.contains("<x-custom>&lt;b&gt;&amp;&quot;</x-custom>"));
+        assert!(xml.contains(r#"<field name="1st key">v</field>"#));
+        assert!(xml.contains("<ctl>a\u{FFFD}b</ctl>"));
+    }
+
+    #[test]
+    fn is_xml_name_accepts_header_style_names_only() {
+        assert!(is_xml_name("user-agent"));
+        assert!(is_xml_name("_x.y"));
+        assert!(!is_xml_name(""));
+        assert!(!is_xml_name("1st"));
+        assert!(!is_xml_name("a b"));
+        assert!(!is_xml_name("ns:tag"));
+    }
+}
@@ -6,9 +6,10 @@
 
 use axum::{extract::DefaultBodyLimit, middleware, Router};
 use rucho::routes::{
-    base64, bytes, cache, content_types, cookies, core_routes, delay, drip, encoding, healthz,
-    image, range, redirect, response_headers,
+    base64, batch, bytes, cache, content_types, cookies, core_routes, countdown, delay, drip, echo,
+    encoding, events, healthz, image, kv, range, redirect, response_headers, time, ws,
 };
+use rucho::server::panic_layer::catch_panic_layer;
 use rucho::server::timing_layer::timing_middleware;
 use rucho::utils::constants::DEFAULT_MAX_BODY_SIZE_BYTES;
 
@@ -25,7 +26,7 @@ async fn spawn_app_with_body_limit(max_body_size: usize) -> String {
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
     let addr = listener.local_addr().unwrap();
 
-    let app = Router::new()
+    let routes = Router::new()
         .merge(core_routes::router())
         .merge(healthz::router())
         .merge(delay::router())
@@ -35,13 +36,26 @@ async fn spawn_app_with_body_limit(max_body_size: usize) -> String {
         .merge(bytes::router())
         .merge(cache::router())
         .merge(drip::router())
+        .merge(events::router())
+        .merge(echo::router())
         .merge(encoding::router())
         .merge(response_headers::router())
         .merge(content_types::router())
         .merge(image::router())
+        .merge(kv::router())
         .merge(range::router())
+        .merge(time::router())
+        .merge(ws::router())
+        .merge(countdown::router())
+        .fallback(core_routes::not_found_handler);
+    let app = routes
+        .clone()
+        .merge(batch::router(routes))
         .layer(DefaultBodyLimit::max(max_body_size))
-        .layer(middleware::from_fn(timing_middleware));
+        .layer(catch_panic_layer())
+        .layer(middleware::from_fn(|req, next| {
+            timing_middleware(req, next, Default::default())
+        }));
 
     tokio::spawn(async move {
         axum::serve(
@@ -61,19 +75,38 @@ async fn spawn_app_with_body_limit(max_body_size: usize) -> String {
 /// catches middleware-interaction regressions. Metrics are force-enabled so the
 /// `/metrics` endpoint and its collection middleware are exercised.
 async fn spawn_full_app() -> String {
+    spawn_full_app_with_rate_limit(None).await
+}
+
+/// Variant of `spawn_full_app` with the per-IP rate limiter wired in.
+async fn spawn_full_app_with_rate_limit(
+    rate_limiter: Option<std::sync::Arc<rucho::server::rate_limit_layer::RateLimiter>>,
+) -> String {
+    spawn_full_app_with(rate_limiter, None, false, Default::default(), true).await
+}
+
+/// Variant of `spawn_full_app` with optional rate limiter, response signer,
+/// `fail_midstream` support and scenarios.
+async fn spawn_full_app_with(
+    rate_limiter: Option<std::sync::Arc<rucho::server::rate_limit_layer::RateLimiter>>,
+    signing_key: Option<rucho::utils::signature::SigningKey>,
+    fail_midstream_enabled: bool,
+    scenarios: std::collections::BTreeMap<String, rucho::utils::config::Scenario>,
+    trust_proxy: bool,
+) -> String {
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
     let addr = listener.local_addr().unwrap();
 
-    let config = rucho::utils::config::Config::default();
     let metrics = Some(std::sync::Arc::new(rucho::utils::metrics::Metrics::new()));
-    let chaos = std::sync::Arc::new(config.chaos.clone());
-    let app = rucho::app::build_app(
+    let app = rucho::app::build_app(rucho::app::AppOptions {
         metrics,
-        config.compression_enabled,
-        chaos,
-        config.max_body_size_bytes,
-        config.request_id_enabled,
-    );
+        rate_limiter,
+        signing_key,
+        fail_midstream_enabled,
+        scenarios,
+        trust_proxy,
+        ..rucho::app::AppOptions::default()
+    });
 
     tokio::spawn(async move {
         axum::serve(
@@ -108,6 +141,86 @@ async fn test_get_echo() {
     assert!(body["headers"].is_object());
 }
 
+#[tokio::test]
+async fn test_get_negotiates_xml() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("{base}/get?q=1"))
+        .header("accept", "application/xml")
+        .header("x-custom-test", "a&b")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "application/xml");
+    assert_eq!(resp.headers()["vary"], "accept");
+    let xml = resp.text().await.unwrap();
+
+    // Walk the whole document: any well-formedness error fails the test.
+    let mut reader = quick_xml::Reader::from_str(&xml);
+    reader.config_mut().check_end_names = true;
+    let mut root = None;
+    loop {
+        match reader.read_event().expect("well-formed XML") {
+            quick_xml::events::Event::Start(e) if root.is_none() => {
+                root = Some(String::from_utf8(e.name().as_ref().to_vec()).unwrap());
+            }
+            quick_xml::events::Event::Eof => break,
+            _ => {}
+        }
+    }
+    assert_eq!(root.as_deref(), Some("response"));
+    assert!(xml.contains("<method>GET</method>"));
+    assert!(xml.contains("<q>1</q>"));
+    assert!(xml.contains("<x-custom-test>a&amp;b</x-custom-test>"));
+
+    // Without an XML preference the same endpoint answers JSON.
+    let resp = client.get(format!("{base}/get")).send().await.unwrap();
+    assert_eq!(resp.headers()["content-type"], "application/json");
+}
+
+#[tokio::test]
+async fn test_get_negotiates_msgpack_and_cbor() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    for accept in ["application/msgpack", "application/cbor"] {
+        let resp = client
+            .get(format!("{base}/get?q=1"))
+            .header("accept", accept)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers()["content-type"], accept);
+        assert_eq!(resp.headers()["vary"], "accept");
+        let body = resp.bytes().await.unwrap();
+        let json: serde_json::Value = if accept == "application/msgpack" {
+            rmp_serde::from_slice(&body).unwrap()
+        } else {
+            ciborium::from_reader(&body[..]).unwrap()
+        };
+        assert_eq!(json["method"], "GET");
+        assert_eq!(json["args"]["q"], "1");
+    }
+}
+
+#[tokio::test]
+async fn test_get_echoes_args_origin_and_url() {
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!("{base}/get?foo=bar&foo=baz&single=1"))
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["args"]["foo"], serde_json::json!(["bar", "baz"]));
+    assert_eq!(body["args"]["single"], "1");
+    assert_eq!(body["origin"], "127.0.0.1");
+    assert_eq!(body["url"], format!("{base}/get?foo=bar&foo=baz&single=1"));
+}
+
 #[tokio::test]
 async fn test_x_response_time_header() {
     let base = spawn_app().await;
@@ -237,6 +350,66 @@ async fn test_uuid() {
     );
 }
 
+#[tokio::test]
+async fn test_uuid_bulk_with_version() {
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!("{base}/uuid/20?version=v7"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let uuids: Vec<uuid::Uuid> = body["uuids"]
+        .as_array()
+        .expect("uuids should be an array")
+        .iter()
+        .map(|u| uuid::Uuid::parse_str(u.as_str().unwrap()).unwrap())
+        .collect();
+    assert_eq!(uuids.len(), 20);
+    assert!(uuids.iter().all(|u| u.get_version_num() == 7));
+    assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let resp = reqwest::get(format!("{base}/uuid/1001")).await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let resp = reqwest::get(format!("{base}/uuid/2?version=v1"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_time_formats() {
+    use ::time::format_description::well_known::Rfc3339;
+
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!("{base}/time")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let unix = body["unix"].as_u64().expect("unix should be an integer");
+    assert!(now.abs_diff(unix) <= 2, "unix {unix} vs now {now}");
+    let rfc3339 = body["rfc3339"]
+        .as_str()
+        .expect("rfc3339 should be a string");
+    let parsed = ::time::OffsetDateTime::parse(rfc3339, &Rfc3339).unwrap();
+    assert_eq!(parsed.unix_timestamp() as u64, unix);
+    assert!(body["unix_ms"].is_u64());
+    assert!(body["iso8601"].is_string());
+
+    let resp = reqwest::get(format!("{base}/time?format=rfc3339"))
+        .await
+        .unwrap();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body.as_object().unwrap().len(), 1);
+    let resp = reqwest::get(format!("{base}/time?format=nope"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
 #[tokio::test]
 async fn test_ip() {
     let base = spawn_app().await;
@@ -270,6 +443,38 @@ async fn test_ip_respects_forwarded_header() {
     assert_eq!(body["origin"], "203.0.113.42");
 }
 
+#[tokio::test]
+async fn test_ip_trust_proxy_controls_forwarded_headers() {
+    async fn ip(base: &str, forwarded: Option<&str>) -> serde_json::Value {
+        let mut req = reqwest::Client::new().get(format!("{base}/ip"));
+        if let Some(xff) = forwarded {
+            req = req.header("x-forwarded-for", xff);
+        }
+        let resp = req.send().await.unwrap();
+        assert_eq!(resp.status(), 200);
+        resp.json().await.unwrap()
+    }
+
+    // Trusted: the left-most entry is the client; later hops and the
+    // socket peer make up `via`.
+    let trusting = spawn_full_app_with(None, None, false, Default::default(), true).await;
+    let body = ip(&trusting, Some("203.0.113.42, 10.0.0.1")).await;
+    assert_eq!(body["origin"], "203.0.113.42");
+    assert_eq!(body["via"], serde_json::json!(["10.0.0.1", "127.0.0.1"]));
+    let body = ip(&trusting, None).await;
+    assert_eq!(body["origin"], "127.0.0.1");
+    assert_eq!(body["via"], serde_json::json!([]));
+
+    // Untrusted: a spoofed header is ignored and the peer is the origin.
+    let direct = spawn_full_app_with(None, None, false, Default::default(), false).await;
+    let body = ip(&direct, Some("203.0.113.42, 10.0.0.1")).await;
+    assert_eq!(body["origin"], "127.0.0.1");
+    assert_eq!(body["via"], serde_json::json!([]));
+    let body = ip(&direct, None).await;
+    assert_eq!(body["origin"], "127.0.0.1");
+    assert_eq!(body["via"], serde_json::json!([]));
+}
+
 #[tokio::test]
 async fn test_headers() {
     let base = spawn_app().await;
@@ -286,6 +491,73 @@ async fn test_headers() {
     assert_eq!(body["headers"]["x-custom-test"], "hello-rucho");
 }
 
+#[tokio::test]
+async fn test_headers_any_method() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    for method in [reqwest::Method::POST, reqwest::Method::DELETE] {
+        let resp = client
+            .request(method.clone(), format!("{base}/headers"))
+            .header("X-Custom-Test", "hello-rucho")
+            .body("not echoed")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status(), 200, "{method}");
+        let body: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(body["headers"]["x-custom-test"], "hello-rucho", "{method}");
+        assert!(body.get("body").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_repeated_headers_are_echoed_as_arrays() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("{base}/anything"))
+        .header("x-multi", "first")
+        .header("x-multi", "second")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(
+        body["headers"]["x-multi"],
+        serde_json::json!(["first", "second"])
+    );
+    assert!(body["headers"]["host"].is_string());
+}
+
+#[tokio::test]
+async fn test_invalid_utf8_header_is_echoed_as_base64() {
+    // `base64` alone names the rucho route module imported above.
+    use ::base64::Engine;
+
+    let base = spawn_app().await;
+    let raw: &[u8] = b"\xffbinary\x80";
+    let value = reqwest::header::HeaderValue::from_bytes(raw).unwrap();
+    let resp = reqwest::Client::new()
+        .get(format!("{base}/headers"))
+        .header("x-binary", value)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let encoded = body["headers"]["x-binary"]["base64"]
+        .as_str()
+        .expect("non-UTF-8 value must be echoed as {\"base64\": ...}");
+    let decoded = ::base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .unwrap();
+    assert_eq!(decoded, raw);
+}
+
 #[tokio::test]
 async fn test_user_agent() {
     let base = spawn_app().await;
@@ -302,6 +574,22 @@ async fn test_user_agent() {
     assert_eq!(body["user-agent"], "rucho-integration-test/1.0");
 }
 
+#[tokio::test]
+async fn test_user_agent_absent_is_null() {
+    let base = spawn_app().await;
+    // reqwest sends no User-Agent unless one is configured.
+    let resp = reqwest::Client::new()
+        .get(format!("{base}/user-agent"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["user-agent"], serde_json::Value::Null);
+    assert!(body.as_object().unwrap().contains_key("user-agent"));
+}
+
 #[tokio::test]
 async fn test_status_codes() {
     let base = spawn_app().await;
@@ -494,6 +782,33 @@ async fn test_response_headers_invalid_returns_400() {
     assert_eq!(resp.status(), 400);
 }
 
+#[tokio::test]
+async fn test_stream_bytes_is_chunked_and_reproducible() {
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!("{base}/stream-bytes/10000?chunk_size=512&seed=9"))
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert!(resp.headers().get("content-length").is_none());
+    assert_eq!(resp.headers()["transfer-encoding"], "chunked");
+    let first = resp.bytes().await.unwrap();
+    assert_eq!(first.len(), 10000);
+
+    let again = reqwest::get(format!("{base}/stream-bytes/10000?chunk_size=512&seed=9"))
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(first, again);
+
+    let resp = reqwest::get(format!("{base}/stream-bytes/10?chunk_size=0"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
 #[tokio::test]
 async fn test_drip_streams_correct_byte_count() {
     let base = spawn_app().await;
@@ -550,6 +865,119 @@ async fn test_drip_custom_status_code() {
     assert_eq!(body.len(), 3);
 }
 
+/// Serves the REAL `build_app()` through `axum_server` with `streaming` wired
+/// in, returning the base URL and the server handle for driving shutdown.
+async fn spawn_full_app_with_shutdown(
+    streaming: rucho::server::shutdown::StreamingShutdown,
+) -> (String, axum_server::Handle) {
+    let app = rucho::app::build_app(rucho::app::AppOptions {
+        streaming,
+        trust_proxy: true,
+        ..rucho::app::AppOptions::default()
+    });
+
+    let handle = axum_server::Handle::new();
+    let bind_handle = handle.clone();
+    tokio::spawn(async move {
+        axum_server::Server::bind("127.0.0.1:0".parse().unwrap())
+            .handle(bind_handle)
+            .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>())
+            .await
+            .unwrap()
+    });
+
+    let addr = handle.listening().await.expect("HTTP listener bound");
+    (format!("http://{addr}"), handle)
+}
+
+#[tokio::test]
+async fn test_streaming_shutdown_grace_cuts_open_drip_stream() {
+    use rucho::server::shutdown::{begin_graceful_shutdown, StreamingShutdown};
+    use std::time::{Duration, Instant};
+
+    let streaming = StreamingShutdown::new(Duration::from_secs(1));
+    let (base, handle) = spawn_full_app_with_shutdown(streaming.clone()).await;
+
+    // One byte a second for 20 seconds: still streaming when shutdown starts.
+    let mut resp = reqwest::get(format!("{base}/drip?numbytes=20&duration=20"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let mut received = resp.chunk().await.unwrap().unwrap().len();
+    assert_eq!(streaming.active(), 1);
+
+    let start = Instant::now();
+    let drain = Duration::from_secs(5);
+    begin_graceful_shutdown(&handle, Some(drain), &streaming);
+    let outcome = loop {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => received += chunk.len(),
+            Ok(None) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+    };
+    let elapsed = start.elapsed();
+
+    assert!(
+        outcome.is_err(),
+        "stream ended cleanly instead of being cut"
+    );
+    assert!(received < 20, "received all {received} bytes");
+    // Cut at the 1s streaming grace, well before the HTTP drain deadline.
+    assert!(
+        elapsed >= Duration::from_millis(900),
+        "cut after {elapsed:?}"
+    );
+    assert!(
+        elapsed < drain - Duration::from_secs(1),
+        "cut after {elapsed:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_streaming_limit_rejects_extra_streams_with_503() {
+    use rucho::server::shutdown::StreamingShutdown;
+    use std::time::Duration;
+
+    let streaming = StreamingShutdown::with_max_streams(Duration::from_secs(1), 2);
+    let (base, _handle) = spawn_full_app_with_shutdown(streaming.clone()).await;
+    let slow = format!("{base}/drip?numbytes=20&duration=20");
+
+    // Saturate the limit with two streams that stay open.
+    let mut open = Vec::new();
+    for _ in 0..2 {
+        let mut resp = reqwest::get(&slow).await.unwrap();
+        assert_eq!(resp.status(), 200);
+        resp.chunk().await.unwrap();
+        open.push(resp);
+    }
+    assert_eq!(streaming.active(), 2);
+
+    let resp = reqwest::get(&slow).await.unwrap();
+    assert_eq!(resp.status(), 503);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("too many streaming responses"));
+
+    // Closing one stream frees its slot for the next request.
+    drop(open.pop());
+    let mut freed = false;
+    for _ in 0..50 {
+        if streaming.active() < 2 {
+            freed = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(freed, "dropped stream never released its slot");
+    let resp = reqwest::get(format!("{base}/drip?numbytes=1&duration=0"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
 #[tokio::test]
 async fn test_anything_body_limit_returns_413() {
     let base = spawn_app_with_body_limit(1024).await;
@@ -678,6 +1106,66 @@ async fn test_range_partial_content() {
     assert_eq!(resp.text().await.unwrap(), "abcde");
 }
 
+#[tokio::test]
+async fn test_range_partial_content_first_ten_bytes() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("{base}/range/26"))
+        .header(reqwest::header::RANGE, "bytes=0-9")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 206);
+    assert_eq!(
+        resp.headers().get(reqwest::header::CONTENT_RANGE).unwrap(),
+        "bytes 0-9/26"
+    );
+    assert_eq!(resp.text().await.unwrap(), "abcdefghij");
+}
+
+#[tokio::test]
+async fn test_countdown_polls_until_ready() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let poll = || {
+        client
+            .get(format!("{base}/countdown/3"))
+            .header("x-client-id", "poller")
+            .send()
+    };
+
+    let mut polls = 0;
+    let final_resp = loop {
+        let resp = poll().await.unwrap();
+        polls += 1;
+        if resp.status() != 202 {
+            break resp;
+        }
+        assert_eq!(resp.headers()["retry-after"], "1");
+        let remaining: u64 = resp.headers()["x-countdown"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(remaining, 3 - polls);
+        assert!(polls < 10, "countdown never completed");
+    };
+
+    assert_eq!(polls, 3);
+    assert_eq!(final_resp.status(), 200);
+    assert_eq!(final_resp.headers()["x-countdown"], "0");
+    let body: serde_json::Value = final_resp.json().await.unwrap();
+    assert_eq!(body["complete"], true);
+    assert_eq!(body["client_id"], "poller");
+
+    // Completing forgets the countdown: the next poll starts over.
+    let resp = poll().await.unwrap();
+    assert_eq!(resp.status(), 202);
+    assert_eq!(resp.headers()["x-countdown"], "2");
+}
+
 #[tokio::test]
 async fn test_range_unsatisfiable_returns_416() {
     let base = spawn_app().await;
@@ -690,6 +1178,10 @@ async fn test_range_unsatisfiable_returns_416() {
         .unwrap();
 
     assert_eq!(resp.status(), 416);
+    assert_eq!(
+        resp.headers().get(reqwest::header::CONTENT_RANGE).unwrap(),
+        "bytes */26"
+    );
 }
 
 // --- Full-app (real build_app) regression tests ---
@@ -823,54 +1315,278 @@ async fn test_request_id_does_not_clobber_handler_set_value() {
 }
 
 #[tokio::test]
-async fn test_gzip_endpoint_forces_encoding() {
-    use std::io::Read;
-    let base = spawn_app().await;
-    let resp = reqwest::get(format!("{base}/gzip")).await.unwrap();
-
-    assert_eq!(resp.status(), 200);
-    assert_eq!(
-        resp.headers()
-            .get(reqwest::header::CONTENT_ENCODING)
-            .unwrap(),
-        "gzip"
-    );
-    // The test reqwest client has no gzip decompression feature, so we get the
-    // raw gzip bytes and gunzip them ourselves.
-    let raw = resp.bytes().await.unwrap();
-    let mut s = String::new();
-    flate2::read::GzDecoder::new(&raw[..])
-        .read_to_string(&mut s)
-        .unwrap();
-    let body: serde_json::Value = serde_json::from_str(&s).unwrap();
-    assert_eq!(body["gzipped"], true);
-}
-
-#[tokio::test]
-async fn test_cache_conditional_request() {
-    let base = spawn_app().await;
+async fn test_request_id_echoed_in_body() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
 
-    // No conditional header → 200 with validators.
-    let resp = reqwest::get(format!("{base}/cache")).await.unwrap();
-    assert_eq!(resp.status(), 200);
-    assert!(resp.headers().get(reqwest::header::ETAG).is_some());
-    assert!(resp.headers().get(reqwest::header::LAST_MODIFIED).is_some());
+    // Generated id: body and header agree.
+    let resp = client.get(format!("{base}/get")).send().await.unwrap();
+    let header = resp.headers()["x-request-id"].to_str().unwrap().to_string();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["request_id"], header);
 
-    // With If-None-Match → 304 Not Modified.
-    let client = reqwest::Client::new();
+    // Client-supplied id is echoed as sent.
     let resp = client
-        .get(format!("{base}/cache"))
-        .header(reqwest::header::IF_NONE_MATCH, "\"rucho-cache-v1\"")
+        .post(format!("{base}/anything"))
+        .header("x-request-id", "kong-correlation-abc")
         .send()
         .await
         .unwrap();
-    assert_eq!(resp.status(), 304);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["request_id"], "kong-correlation-abc");
 }
 
-#[tokio::test]
-async fn test_cache_seconds_sets_cache_control() {
-    let base = spawn_app().await;
-    let resp = reqwest::get(format!("{base}/cache/120")).await.unwrap();
+// --- Per-IP rate limiting ---
+
+#[tokio::test]
+async fn test_rate_limit_burst_then_429() {
+    let limiter = rucho::server::rate_limit_layer::RateLimiter::new(1, 5);
+    let base = spawn_full_app_with_rate_limit(Some(std::sync::Arc::new(limiter))).await;
+    let client = reqwest::Client::new();
+
+    // Keyed on the TCP peer (127.0.0.1): the burst passes, then 429s.
+    for _ in 0..5 {
+        let resp = client.get(format!("{base}/get")).send().await.unwrap();
+        assert_eq!(resp.status(), 200);
+    }
+    let resp = client.get(format!("{base}/get")).send().await.unwrap();
+    assert_eq!(resp.status(), 429);
+    assert_eq!(resp.headers()["retry-after"], "1");
+    assert!(
+        resp.headers().contains_key("x-request-id"),
+        "429s still carry a request id"
+    );
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Rate limit exceeded");
+}
+
+#[tokio::test]
+async fn test_rate_limit_disabled_by_default() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+    for _ in 0..50 {
+        let resp = client.get(format!("{base}/get")).send().await.unwrap();
+        assert_eq!(resp.status(), 200);
+    }
+}
+
+// --- /anything slow headers (?header_delay_ms=) ---
+
+#[tokio::test]
+async fn test_anything_header_delay_delays_first_byte() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Warm the connection so the measurement isn't dominated by connect time.
+    client.get(format!("{base}/anything")).send().await.unwrap();
+
+    let start = std::time::Instant::now();
+    // `send()` resolves once the status line and headers arrive.
+    let resp = client
+        .get(format!("{base}/anything?header_delay_ms=400"))
+        .send()
+        .await
+        .unwrap();
+    let ttfb = start.elapsed();
+
+    assert_eq!(resp.status(), 200);
+    assert!(
+        ttfb >= std::time::Duration::from_millis(400),
+        "headers arrived after {ttfb:?}, expected >= 400ms"
+    );
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["header_delay_ms"], 400);
+}
+
+#[tokio::test]
+async fn test_anything_without_header_delay_is_fast() {
+    let base = spawn_app().await;
+    let start = std::time::Instant::now();
+    let resp = reqwest::get(format!("{base}/anything")).await.unwrap();
+    assert!(start.elapsed() < std::time::Duration::from_millis(400));
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert!(body.get("header_delay_ms").is_none());
+}
+
+// --- Body reflection (/echo) ---
+
+#[tokio::test]
+async fn test_echo_reflects_exact_body_with_status() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let payload = r#"{"order":  42, "items": [1,2]}"#;
+    let resp = client
+        .post(format!("{base}/echo?status=201"))
+        .header("content-type", "application/json")
+        .body(payload)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 201);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+    assert_eq!(
+        resp.text().await.unwrap(),
+        payload,
+        "body must be reflected byte-for-byte"
+    );
+}
+
+#[tokio::test]
+async fn test_echo_reflects_binary_body() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let payload: Vec<u8> = (0..=255u8).rev().collect();
+    let resp = client
+        .put(format!("{base}/echo?status=418"))
+        .header("content-type", "application/octet-stream")
+        .body(payload.clone())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 418);
+    assert_eq!(resp.bytes().await.unwrap().to_vec(), payload);
+}
+
+// --- Batch sub-requests (/batch) ---
+
+#[tokio::test]
+async fn test_batch_aggregates_sub_responses() {
+    let base = spawn_app().await;
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("{base}/batch"))
+        .json(&serde_json::json!([
+            { "path": "/get", "headers": { "x-batch": "yes" } },
+            { "method": "GET", "path": "/status/500" },
+        ]))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let results = body.as_array().unwrap();
+    assert_eq!(results.len(), 2);
+
+    assert_eq!(results[0]["status"], 200);
+    assert_eq!(results[0]["body"]["method"], "GET");
+    assert_eq!(results[0]["body"]["headers"]["x-batch"], "yes");
+
+    assert_eq!(results[1]["status"], 500);
+    assert_eq!(results[1]["body"]["status"], 500);
+}
+
+#[tokio::test]
+async fn test_full_app_batch_rejects_oversized_batch() {
+    let base = spawn_full_app().await;
+    let batch: Vec<_> = (0..=rucho::utils::constants::MAX_BATCH_SIZE)
+        .map(|_| serde_json::json!({ "path": "/get" }))
+        .collect();
+    let resp = reqwest::Client::new()
+        .post(format!("{base}/batch"))
+        .json(&batch)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+// --- Trace context propagation (traceparent / B3) ---
+
+#[tokio::test]
+async fn test_traceparent_echoed_by_full_app() {
+    let base = spawn_full_app().await;
+    let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("{base}/get"))
+        .header("traceparent", traceparent)
+        .header("tracestate", "rojo=00f067aa0ba902b7")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("traceparent").unwrap(), traceparent);
+    assert_eq!(
+        resp.headers().get("tracestate").unwrap(),
+        "rojo=00f067aa0ba902b7"
+    );
+}
+
+#[tokio::test]
+async fn test_malformed_traceparent_not_echoed() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("{base}/get"))
+        .header("traceparent", "00-not-a-valid-context")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        resp.status(),
+        200,
+        "a bad tracing header must never fail a request"
+    );
+    assert!(resp.headers().get("traceparent").is_none());
+}
+
+#[tokio::test]
+async fn test_gzip_endpoint_forces_encoding() {
+    use std::io::Read;
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!("{base}/gzip")).await.unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .unwrap(),
+        "gzip"
+    );
+    // The test reqwest client has no gzip decompression feature, so we get the
+    // raw gzip bytes and gunzip them ourselves.
+    let raw = resp.bytes().await.unwrap();
+    let mut s = String::new();
+    flate2::read::GzDecoder::new(&raw[..])
+        .read_to_string(&mut s)
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_str(&s).unwrap();
+    assert_eq!(body["gzipped"], true);
+}
+
+#[tokio::test]
+async fn test_cache_conditional_request() {
+    let base = spawn_app().await;
+
+    // No conditional header → 200 with validators.
+    let resp = reqwest::get(format!("{base}/cache")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(resp.headers().get(reqwest::header::ETAG).is_some());
+    assert!(resp.headers().get(reqwest::header::LAST_MODIFIED).is_some());
+
+    // With If-None-Match → 304 Not Modified.
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("{base}/cache"))
+        .header(reqwest::header::IF_NONE_MATCH, "\"rucho-cache-v1\"")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 304);
+}
+
+#[tokio::test]
+async fn test_cache_seconds_sets_cache_control() {
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!("{base}/cache/120")).await.unwrap();
 
     assert_eq!(resp.status(), 200);
     assert_eq!(
@@ -890,26 +1606,40 @@ async fn test_cache_seconds_sets_cache_control() {
 /// base URL. Exercises the same acceptor the binary's HTTPS listener uses, so
 /// the negotiated TLS parameters genuinely flow through to the handlers.
 async fn spawn_https_app() -> String {
+    spawn_https_app_with(&Default::default(), &[]).await
+}
+
+/// Like `spawn_https_app`, with the given TLS options (e.g. an mTLS client CA)
+/// and client-certificate-guarded path prefixes.
+async fn spawn_https_app_with(
+    options: &rucho::utils::server_config::TlsOptions,
+    client_cert_paths: &[&str],
+) -> String {
     let manifest = env!("CARGO_MANIFEST_DIR");
     let cert = format!("{manifest}/tests/fixtures/tls/cert.pem");
     let key = format!("{manifest}/tests/fixtures/tls/key.pem");
 
     let rustls_config =
-        rucho::utils::server_config::try_load_rustls_config(Some(&cert), Some(&key))
+        rucho::utils::server_config::try_load_rustls_config(Some(&cert), Some(&key), options)
             .await
             .expect("load self-signed TLS fixture");
+    spawn_https_app_with_config(rustls_config, client_cert_paths).await
+}
+
+/// Serves the REAL `build_app()` over HTTPS with the given TLS config.
+async fn spawn_https_app_with_config(
+    rustls_config: axum_server::tls_rustls::RustlsConfig,
+    client_cert_paths: &[&str],
+) -> String {
     let acceptor = rucho::server::tls::TlsInfoAcceptor::new(rustls_config);
 
-    let config = rucho::utils::config::Config::default();
     let metrics = Some(std::sync::Arc::new(rucho::utils::metrics::Metrics::new()));
-    let chaos = std::sync::Arc::new(config.chaos.clone());
-    let app = rucho::app::build_app(
+    let app = rucho::app::build_app(rucho::app::AppOptions {
         metrics,
-        config.compression_enabled,
-        chaos,
-        config.max_body_size_bytes,
-        config.request_id_enabled,
-    );
+        client_cert_paths: client_cert_paths.iter().map(|p| p.to_string()).collect(),
+        trust_proxy: true,
+        ..rucho::app::AppOptions::default()
+    });
 
     let handle = axum_server::Handle::new();
     let bind_handle = handle.clone();
@@ -938,21 +1668,18 @@ fn insecure_https_client() -> reqwest::Client {
 /// generated in-memory by `generate_self_signed_rustls_config()` (the
 /// `ssl_auto_cert` path), returning the `https://127.0.0.1:PORT` base URL.
 async fn spawn_https_app_auto_cert() -> String {
-    let rustls_config = rucho::utils::server_config::generate_self_signed_rustls_config()
-        .await
-        .expect("generate self-signed TLS config");
+    let rustls_config =
+        rucho::utils::server_config::generate_self_signed_rustls_config(&Default::default())
+            .await
+            .expect("generate self-signed TLS config");
     let acceptor = rucho::server::tls::TlsInfoAcceptor::new(rustls_config);
 
-    let config = rucho::utils::config::Config::default();
     let metrics = Some(std::sync::Arc::new(rucho::utils::metrics::Metrics::new()));
-    let chaos = std::sync::Arc::new(config.chaos.clone());
-    let app = rucho::app::build_app(
+    let app = rucho::app::build_app(rucho::app::AppOptions {
         metrics,
-        config.compression_enabled,
-        chaos,
-        config.max_body_size_bytes,
-        config.request_id_enabled,
-    );
+        trust_proxy: true,
+        ..rucho::app::AppOptions::default()
+    });
 
     let handle = axum_server::Handle::new();
     let bind_handle = handle.clone();
@@ -1021,80 +1748,284 @@ async fn test_get_echoes_tls_info_over_https() {
     assert_eq!(tls["client_certs"].as_array().unwrap().len(), 0);
 }
 
+/// Generates a test CA and a client certificate (CN `test-client`) signed by
+/// it, returning `(ca_pem, client_cert_pem, client_key_pem)`.
+fn generate_client_ca_and_cert() -> (String, String, String) {
+    use rcgen::{
+        BasicConstraints, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+    };
+
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    ca_params
+        .distinguished_name
+        .push(DnType::CommonName, "rucho test CA");
+    let ca = ca_params.self_signed(&ca_key).unwrap();
+
+    let client_key = KeyPair::generate().unwrap();
+    let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "test-client");
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+    let client = params.signed_by(&client_key, &ca, &ca_key).unwrap();
+
+    (ca.pem(), client.pem(), client_key.serialize_pem())
+}
+
 #[tokio::test]
-async fn test_anything_echoes_tls_info_over_https() {
-    let base = spawn_https_app().await;
-    let client = insecure_https_client();
+async fn test_mtls_rejects_anonymous_and_echoes_client_subject() {
+    let (ca_pem, client_cert_pem, client_key_pem) = generate_client_ca_and_cert();
+    let dir = tempfile::tempdir().unwrap();
+    let ca_path = dir.path().join("client-ca.pem");
+    std::fs::write(&ca_path, ca_pem).unwrap();
+    let options = rucho::utils::server_config::TlsOptions {
+        client_ca: Some(ca_path.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    let base = spawn_https_app_with(&options, &[]).await;
+
+    // No client certificate: the handshake (or first read) fails.
+    let anonymous = insecure_https_client()
+        .get(format!("{base}/get"))
+        .send()
+        .await;
+    assert!(anonymous.is_err(), "anonymous client must be rejected");
 
+    let identity =
+        reqwest::Identity::from_pkcs8_pem(client_cert_pem.as_bytes(), client_key_pem.as_bytes())
+            .unwrap();
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .identity(identity)
+        .build()
+        .unwrap();
     let body: serde_json::Value = client
         .post(format!("{base}/anything"))
-        .body("hello over tls")
         .send()
         .await
         .unwrap()
         .json()
         .await
         .unwrap();
-
-    assert_eq!(body["body"], "hello over tls");
-    assert!(
-        body["tls"]["version"]
-            .as_str()
-            .unwrap_or("")
-            .starts_with("TLSv1."),
-        "expected tls.version on /anything, got: {body}"
-    );
+    assert_eq!(body["tls"]["client_cert_present"], true);
+    assert_eq!(body["tls"]["client_cert_subject"], "CN=test-client");
 }
 
 #[tokio::test]
-async fn test_get_omits_tls_info_over_plain_http() {
-    // Over plain HTTP the extension is absent, so the `tls` key must not appear.
-    let base = spawn_full_app().await;
-    let body: serde_json::Value = reqwest::get(format!("{base}/get"))
+async fn test_client_cert_paths_guard_only_listed_routes() {
+    let (ca_pem, client_cert_pem, client_key_pem) = generate_client_ca_and_cert();
+    let dir = tempfile::tempdir().unwrap();
+    let ca_path = dir.path().join("client-ca.pem");
+    std::fs::write(&ca_path, ca_pem).unwrap();
+    let options = rucho::utils::server_config::TlsOptions {
+        client_ca: Some(ca_path.to_string_lossy().into_owned()),
+        client_cert_optional: true,
+        ..Default::default()
+    };
+    let base = spawn_https_app_with(&options, &["/anything/admin"]).await;
+
+    // Anonymous clients complete the handshake and reach public routes...
+    let anonymous = insecure_https_client();
+    let resp = anonymous.get(format!("{base}/get")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    // ...but not guarded ones.
+    let resp = anonymous
+        .get(format!("{base}/anything/admin/users"))
+        .send()
         .await
-        .unwrap()
-        .json()
+        .unwrap();
+    assert_eq!(resp.status(), 403);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Client certificate required");
+
+    let identity =
+        reqwest::Identity::from_pkcs8_pem(client_cert_pem.as_bytes(), client_key_pem.as_bytes())
+            .unwrap();
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .identity(identity)
+        .build()
+        .unwrap();
+    let resp = client
+        .get(format!("{base}/anything/admin/users"))
+        .send()
         .await
         .unwrap();
-    assert!(
-        body.get("tls").is_none(),
-        "plain HTTP must omit tls, got: {body}"
-    );
+    assert_eq!(resp.status(), 200);
 }
 
-// --- Coverage-gap tests (T4) ---
-
-/// Like `spawn_full_app` but with response compression enabled, for exercising
-/// the `CompressionLayer` (which `Config::default()` leaves off).
-async fn spawn_app_with_compression() -> String {
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
-    let addr = listener.local_addr().unwrap();
-
-    let config = rucho::utils::config::Config::default();
-    let metrics = Some(std::sync::Arc::new(rucho::utils::metrics::Metrics::new()));
-    let chaos = std::sync::Arc::new(config.chaos.clone());
-    let app = rucho::app::build_app(
-        metrics,
-        true, // compression_enabled
-        chaos,
-        config.max_body_size_bytes,
-        config.request_id_enabled,
-    );
-
-    tokio::spawn(async move {
-        axum::serve(
-            listener,
-            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
-        )
-        .await
+/// An HTTPS client that accepts the self-signed fixture and speaks at most TLS 1.2.
+fn tls12_only_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .max_tls_version(reqwest::tls::Version::TLS_1_2)
+        .build()
         .unwrap()
-    });
-
-    format!("http://{addr}")
 }
 
 #[tokio::test]
-async fn test_delay_fires() {
+async fn test_tls_min_version_13_rejects_tls12_client() {
+    use rucho::utils::server_config::{TlsMinVersion, TlsOptions};
+
+    // Control: the default minimum (1.2) serves a TLS 1.2 client.
+    let base = spawn_https_app().await;
+    let body: serde_json::Value = tls12_only_client()
+        .get(format!("{base}/get"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["tls"]["version"], "TLSv1.2");
+
+    let options = TlsOptions {
+        min_version: TlsMinVersion::Tls13,
+        ..TlsOptions::default()
+    };
+    let base = spawn_https_app_with(&options, &[]).await;
+    let result = tls12_only_client().get(format!("{base}/get")).send().await;
+    assert!(result.is_err(), "TLS 1.2 client must fail the handshake");
+
+    let body: serde_json::Value = insecure_https_client()
+        .get(format!("{base}/get"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["tls"]["version"], "TLSv1.3");
+}
+
+/// Writes a freshly generated self-signed cert/key pair to `dir`, returning
+/// the cert's DER bytes.
+fn write_self_signed_cert(dir: &std::path::Path) -> Vec<u8> {
+    let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    std::fs::write(dir.join("cert.pem"), generated.cert.pem()).unwrap();
+    std::fs::write(dir.join("key.pem"), generated.key_pair.serialize_pem()).unwrap();
+    generated.cert.der().to_vec()
+}
+
+/// DER of the certificate the server presents on a fresh connection.
+async fn served_cert(base: &str) -> Vec<u8> {
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .tls_info(true)
+        .build()
+        .unwrap();
+    let resp = client.get(format!("{base}/get")).send().await.unwrap();
+    resp.extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .expect("peer certificate")
+        .to_vec()
+}
+
+#[tokio::test]
+async fn test_tls_reload_serves_rotated_certificate() {
+    use rucho::utils::server_config::{reload_rustls_config, try_load_rustls_config};
+
+    let dir = tempfile::tempdir().unwrap();
+    let (cert, key) = (dir.path().join("cert.pem"), dir.path().join("key.pem"));
+    let (cert, key) = (cert.to_str().unwrap(), key.to_str().unwrap());
+    let options = Default::default();
+
+    let first = write_self_signed_cert(dir.path());
+    let rustls_config = try_load_rustls_config(Some(cert), Some(key), &options)
+        .await
+        .unwrap();
+    let base = spawn_https_app_with_config(rustls_config.clone(), &[]).await;
+    assert_eq!(served_cert(&base).await, first);
+
+    // Rotate the files on disk, then reload (what SIGHUP triggers).
+    let second = write_self_signed_cert(dir.path());
+    reload_rustls_config(&rustls_config, cert, key, &options)
+        .await
+        .unwrap();
+    assert_eq!(served_cert(&base).await, second);
+
+    // A broken rotation is rejected and the current certificate kept.
+    std::fs::write(dir.path().join("cert.pem"), "not a cert").unwrap();
+    assert!(reload_rustls_config(&rustls_config, cert, key, &options)
+        .await
+        .is_err());
+    assert_eq!(served_cert(&base).await, second);
+}
+
+#[tokio::test]
+async fn test_anything_echoes_tls_info_over_https() {
+    let base = spawn_https_app().await;
+    let client = insecure_https_client();
+
+    let body: serde_json::Value = client
+        .post(format!("{base}/anything"))
+        .body("hello over tls")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(body["body"], "hello over tls");
+    assert!(
+        body["tls"]["version"]
+            .as_str()
+            .unwrap_or("")
+            .starts_with("TLSv1."),
+        "expected tls.version on /anything, got: {body}"
+    );
+}
+
+#[tokio::test]
+async fn test_get_omits_tls_info_over_plain_http() {
+    // Over plain HTTP the extension is absent, so the `tls` key must not appear.
+    let base = spawn_full_app().await;
+    let body: serde_json::Value = reqwest::get(format!("{base}/get"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(
+        body.get("tls").is_none(),
+        "plain HTTP must omit tls, got: {body}"
+    );
+}
+
+// --- Coverage-gap tests (T4) ---
+
+/// Like `spawn_full_app` but with response compression enabled, for exercising
+/// the `CompressionLayer` (which `Config::default()` leaves off).
+async fn spawn_app_with_compression() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let metrics = Some(std::sync::Arc::new(rucho::utils::metrics::Metrics::new()));
+    let app = rucho::app::build_app(rucho::app::AppOptions {
+        metrics,
+        compression_enabled: true,
+        trust_proxy: true,
+        ..rucho::app::AppOptions::default()
+    });
+
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .unwrap()
+    });
+
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_delay_fires() {
     let base = spawn_app().await;
     let start = std::time::Instant::now();
     let resp = reqwest::get(format!("{base}/delay/1")).await.unwrap();
@@ -1114,6 +2045,46 @@ async fn test_delay_exceeds_max_returns_400() {
     assert_eq!(resp.status(), 400);
 }
 
+#[tokio::test]
+async fn test_manual_clock_drives_delay_timing_and_metrics() {
+    use rucho::utils::clock::{Clock, ManualClock};
+    use std::sync::Arc;
+
+    let manual = Arc::new(ManualClock::new());
+    let clock = Clock::new(manual.clone());
+    let metrics = Arc::new(rucho::utils::metrics::Metrics::new().with_clock(clock.clone()));
+    let app = rucho::app::build_app(rucho::app::AppOptions {
+        metrics: Some(metrics),
+        request_id_enabled: false,
+        clock,
+        trust_proxy: true,
+        ..rucho::app::AppOptions::default()
+    });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let base = format!("http://{addr}");
+
+    // A five-minute delay completes at once on the manual clock...
+    let start = std::time::Instant::now();
+    let resp = reqwest::get(format!("{base}/delay/300")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    // ...but is timed as exactly five minutes,
+    assert_eq!(resp.headers()["x-response-time"], "300000.000ms");
+    assert_eq!(manual.elapsed(), std::time::Duration::from_secs(300));
+
+    // and the metrics uptime moved with it.
+    let body: serde_json::Value = reqwest::get(format!("{base}/metrics"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["uptime_seconds"], 300);
+    assert_eq!(body["last_hour"]["endpoint_hits"]["/delay/:n"], 1);
+}
+
 #[tokio::test]
 async fn test_head_get_returns_no_body() {
     let base = spawn_app().await;
@@ -1155,6 +2126,55 @@ async fn test_endpoints_shape() {
     );
 }
 
+#[tokio::test]
+async fn test_endpoints_conditional_get_returns_304() {
+    // Full app: the timing layer adds per-request `timing`, which must not
+    // perturb the ETag.
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+
+    let first = client
+        .get(format!("{base}/endpoints"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(first.status(), 200);
+    let etag = first
+        .headers()
+        .get("etag")
+        .expect("/endpoints should carry an ETag")
+        .clone();
+
+    let second = client
+        .get(format!("{base}/endpoints"))
+        .header("if-none-match", etag.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(second.status(), 304);
+    assert_eq!(second.headers()["etag"], etag);
+    assert!(second.bytes().await.unwrap().is_empty());
+
+    let stale = client
+        .get(format!("{base}/endpoints"))
+        .header("if-none-match", "W/\"0000000000000000\"")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(stale.status(), 200);
+
+    // The HTML representation has its own tag.
+    let html = client
+        .get(format!("{base}/endpoints"))
+        .header("accept", "text/html")
+        .header("if-none-match", etag.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(html.status(), 200);
+    assert_ne!(html.headers()["etag"], etag);
+}
+
 #[tokio::test]
 async fn test_post_malformed_json_returns_400() {
     let base = spawn_app().await;
@@ -1216,3 +2236,1016 @@ async fn test_response_compression_gzip() {
     let body: serde_json::Value = serde_json::from_str(&s).unwrap();
     assert_eq!(body["method"], "GET");
 }
+
+#[tokio::test]
+async fn test_response_compression_skips_forced_encoding_endpoints() {
+    use std::io::Read;
+    let base = spawn_app_with_compression().await;
+
+    // /gzip already sets Content-Encoding, so CompressionLayer must leave it
+    // alone: a single gunzip yields the JSON body.
+    let resp = reqwest::Client::new()
+        .get(format!("{base}/gzip"))
+        .header(reqwest::header::ACCEPT_ENCODING, "gzip, br")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()[reqwest::header::CONTENT_ENCODING], "gzip");
+    let raw = resp.bytes().await.unwrap();
+    let mut s = String::new();
+    flate2::read::GzDecoder::new(&raw[..])
+        .read_to_string(&mut s)
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_str(&s).unwrap();
+    assert_eq!(body["gzipped"], true);
+}
+
+#[tokio::test]
+async fn test_anything_sign_verifies_with_configured_key() {
+    use hmac::{Hmac, Mac};
+
+    let secret = "integration-secret";
+    let base = spawn_full_app_with(
+        None,
+        rucho::utils::signature::SigningKey::new(secret),
+        false,
+        Default::default(),
+        true,
+    )
+    .await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{base}/anything?sign=true"))
+        .body("payload")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let signature = resp.headers()["x-signature"].to_str().unwrap().to_string();
+    let body = resp.bytes().await.unwrap();
+
+    // Verify independently of rucho's signer, as a client would.
+    let hex = signature.strip_prefix("sha256=").unwrap();
+    let expected: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect();
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(&body);
+    mac.verify_slice(&expected).unwrap();
+
+    // A different key must not verify.
+    let mut wrong = Hmac::<sha2::Sha256>::new_from_slice(b"other").unwrap();
+    wrong.update(&body);
+    assert!(wrong.verify_slice(&expected).is_err());
+}
+
+#[tokio::test]
+async fn test_anything_raw_path_preserves_encoded_slash() {
+    let base = spawn_full_app().await;
+
+    let resp = reqwest::get(format!("{base}/anything/a%2Fb/?q=%20x"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["raw_path"], "/anything/a%2Fb/?q=%20x");
+    assert_eq!(body["path"], "/anything/a%2Fb/");
+    assert_eq!(body["decoded_path"], "/anything/a/b/");
+}
+
+#[tokio::test]
+async fn test_anything_fail_midstream_truncates_json_body() {
+    let base = spawn_full_app_with(None, None, true, Default::default(), true).await;
+
+    let resp = reqwest::get(format!("{base}/anything?fail_midstream=true"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    assert!(resp.headers().get("content-length").is_none());
+
+    // Read chunk by chunk so the bytes received before the abort are kept.
+    let mut resp = resp;
+    let mut received = Vec::new();
+    let mut aborted = false;
+    loop {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => received.extend_from_slice(&chunk),
+            Ok(None) => break,
+            Err(_) => {
+                aborted = true;
+                break;
+            }
+        }
+    }
+    assert!(aborted, "body should end in a transport error");
+    assert!(received.starts_with(b"{"), "body should start as JSON");
+    assert!(serde_json::from_slice::<serde_json::Value>(&received).is_err());
+}
+
+#[tokio::test]
+async fn test_anything_fail_midstream_reports_when_disabled() {
+    let base = spawn_full_app().await;
+
+    let resp = reqwest::get(format!("{base}/anything?fail_midstream=true"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(
+        body["fail_midstream_error"],
+        "fail_midstream_enabled is off"
+    );
+}
+
+#[tokio::test]
+async fn test_anything_fragment_reassembles_over_the_wire() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/anything?fragment=true"))
+        .body("fragmented payload")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["x-fragment-bytes"], "1");
+    assert!(resp.headers().get("content-length").is_none());
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["body"], "fragmented payload");
+    assert_eq!(body["method"], "POST");
+}
+
+#[tokio::test]
+async fn test_anything_limits_match_configuration() {
+    use rucho::utils::constants::{MAX_DELAY_SECONDS, MAX_REDIRECT_HOPS};
+
+    let base = spawn_full_app().await;
+    let config = rucho::utils::config::Config::default();
+
+    let body: serde_json::Value = reqwest::get(format!("{base}/anything?limits=true"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let limits = &body["limits"];
+    assert_eq!(limits["max_body_size_bytes"], config.max_body_size_bytes);
+    assert_eq!(limits["max_delay_seconds"], MAX_DELAY_SECONDS);
+    assert_eq!(limits["max_redirect_hops"], MAX_REDIRECT_HOPS);
+    assert!(limits["max_query_params"].is_null());
+
+    // The reported caps are the ones enforced.
+    let over = reqwest::get(format!("{base}/delay/{}", MAX_DELAY_SECONDS + 1))
+        .await
+        .unwrap();
+    assert_eq!(over.status(), 400);
+}
+
+#[tokio::test]
+async fn test_x_transform_header_applies_pipeline_in_order() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+
+    // `/echo` returns the request body verbatim, so the output is exactly the
+    // transforms applied to "Hello, rucho!": upper-case, then base64.
+    let resp = client
+        .post(format!("{base}/echo"))
+        .header("x-transform", "uppercase, base64")
+        .body("Hello, rucho!")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["x-transform-applied"], "uppercase, base64");
+    assert_eq!(resp.text().await.unwrap(), "SEVMTE8sIFJVQ0hPIQ==");
+
+    let resp = client
+        .get(format!("{base}/get"))
+        .header("x-transform", "rot13")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_rucho_scenario_header_selects_configured_behavior() {
+    use rucho::utils::config::Scenario;
+
+    let scenarios = std::collections::BTreeMap::from([
+        (
+            "outage".to_string(),
+            Scenario {
+                status: Some(503),
+                body: Some("maintenance".to_string()),
+                ..Scenario::default()
+            },
+        ),
+        (
+            "slow".to_string(),
+            Scenario {
+                delay_ms: Some(300),
+                ..Scenario::default()
+            },
+        ),
+    ]);
+    let base = spawn_full_app_with(None, None, false, scenarios, true).await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .get(format!("{base}/get"))
+        .header("x-rucho-scenario", "outage")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 503);
+    assert_eq!(resp.text().await.unwrap(), "maintenance");
+
+    let start = std::time::Instant::now();
+    let resp = client
+        .get(format!("{base}/get"))
+        .header("x-rucho-scenario", "slow")
+        .send()
+        .await
+        .unwrap();
+    assert!(start.elapsed() >= std::time::Duration::from_millis(300));
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["method"], "GET");
+
+    let resp = client
+        .get(format!("{base}/get"))
+        .header("x-rucho-scenario", "unknown")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+/// Connects a WebSocket client to `/ws` on the server at `base` (`http://...`).
+async fn connect_ws(
+    base: &str,
+) -> tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>> {
+    let url = format!("{}/ws", base.replacen("http://", "ws://", 1));
+    let (socket, response) = tokio_tungstenite::connect_async(url).await.unwrap();
+    assert_eq!(response.status(), 101);
+    socket
+}
+
+#[tokio::test]
+async fn test_ws_echoes_messages_and_answers_pings() {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let base = spawn_app().await;
+    let mut socket = connect_ws(&base).await;
+
+    socket.send(Message::text("hello rucho")).await.unwrap();
+    assert_eq!(
+        socket.next().await.unwrap().unwrap(),
+        Message::text("hello rucho")
+    );
+    socket
+        .send(Message::binary(vec![0u8, 159, 146, 150]))
+        .await
+        .unwrap();
+    assert_eq!(
+        socket.next().await.unwrap().unwrap(),
+        Message::binary(vec![0u8, 159, 146, 150])
+    );
+    socket
+        .send(Message::Ping("are you there".into()))
+        .await
+        .unwrap();
+    assert_eq!(
+        socket.next().await.unwrap().unwrap(),
+        Message::Pong("are you there".into())
+    );
+
+    // A client close is acknowledged, then the stream ends.
+    socket.close(None).await.unwrap();
+    assert!(matches!(
+        socket.next().await.unwrap().unwrap(),
+        Message::Close(_)
+    ));
+    assert!(socket.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_ws_upgrades_through_full_middleware_stack() {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let base = spawn_full_app().await;
+    let mut socket = connect_ws(&base).await;
+    socket
+        .send(Message::text("through the stack"))
+        .await
+        .unwrap();
+    assert_eq!(
+        socket.next().await.unwrap().unwrap(),
+        Message::text("through the stack")
+    );
+
+    // Without an upgrade, /ws answers a JSON 400.
+    let resp = reqwest::get(format!("{base}/ws")).await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert!(json["error"].is_string());
+}
+
+#[tokio::test]
+async fn test_events_streams_incrementing_counter() {
+    let base = spawn_app().await;
+    let mut resp = reqwest::get(format!("{base}/events?count=5&interval_ms=50"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+
+    // Read only until two events have arrived; the stream is still open.
+    let mut text = String::new();
+    let mut data = Vec::new();
+    while data.len() < 2 {
+        let chunk = resp.chunk().await.unwrap().expect("stream ended early");
+        text.push_str(std::str::from_utf8(&chunk).unwrap());
+        data = text
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(|n| n.parse::<u64>().unwrap())
+            .collect();
+    }
+    assert_eq!(data[..2], [1, 2]);
+}
+
+#[tokio::test]
+async fn test_events_are_not_compressed_by_full_stack() {
+    let base = spawn_full_app().await;
+    let body = reqwest::Client::new()
+        .get(format!("{base}/events?count=3&interval_ms=0"))
+        .header("accept-encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert!(body.headers().get("content-encoding").is_none());
+    let text = body.text().await.unwrap();
+    assert_eq!(
+        text,
+        "id: 1\ndata: 1\n\nid: 2\ndata: 2\n\nid: 3\ndata: 3\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_anything_stream_echo_round_trips_a_large_body() {
+    // 16 MiB: well past the 2 MiB body limit a buffered echo would enforce.
+    let payload: Vec<u8> = (0..16 * 1024 * 1024u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+        .collect();
+    let base = spawn_full_app().await;
+    let resp = reqwest::Client::new()
+        .put(format!("{base}/anything/upload?echo=stream"))
+        .body(payload.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "application/octet-stream");
+
+    let body = resp.bytes().await.unwrap();
+    let split = body.iter().position(|&b| b == b'\n').unwrap();
+    let metadata: serde_json::Value = serde_json::from_slice(&body[..split]).unwrap();
+    assert_eq!(metadata["method"], "PUT");
+    assert_eq!(metadata["path"], "/anything/upload");
+    assert!(body[split + 1..] == payload[..], "echoed body differs");
+}
+
+#[tokio::test]
+async fn test_options_reports_each_routes_allow_set() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+    let options = |path: &str| client.request(reqwest::Method::OPTIONS, format!("{base}{path}"));
+
+    for (path, allow) in [
+        ("/post", "POST, OPTIONS"),
+        ("/get", "GET, HEAD, OPTIONS"),
+        ("/", "GET, HEAD, OPTIONS"),
+        ("/uuid", "GET, HEAD, OPTIONS"),
+        ("/metrics", "GET, HEAD, OPTIONS"),
+    ] {
+        let resp = options(path).send().await.unwrap();
+        assert_eq!(resp.status(), 204, "{path}");
+        assert_eq!(resp.headers()["allow"], allow, "{path}");
+        // Outer middleware still stamps the rewritten response.
+        assert!(resp.headers().contains_key("x-request-id"), "{path}");
+        assert!(resp.bytes().await.unwrap().is_empty(), "{path}");
+    }
+
+    // Routes that take OPTIONS themselves keep their own answers.
+    let resp = options("/anything").send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["method"], "OPTIONS");
+    let resp = options("/options").send().await.unwrap();
+    assert_eq!(resp.status(), 204);
+    assert_eq!(
+        resp.headers()["allow"],
+        "GET, POST, PUT, PATCH, DELETE, OPTIONS, HEAD"
+    );
+    assert_eq!(options("/no-such-path").send().await.unwrap().status(), 404);
+
+    // A CORS preflight is still answered by the CORS layer.
+    let resp = options("/post")
+        .header("origin", "https://example.com")
+        .header("access-control-request-method", "POST")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(resp.headers().contains_key("access-control-allow-methods"));
+}
+
+#[tokio::test]
+async fn test_wrong_method_returns_json_405_with_allow() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+
+    let resp = client.get(format!("{base}/post")).send().await.unwrap();
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers()["allow"], "POST, OPTIONS");
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    assert!(resp.headers().contains_key("x-request-id"));
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(
+        json["error"],
+        "Method GET is not allowed on /post; allowed: POST, OPTIONS"
+    );
+
+    let resp = client.post(format!("{base}/get")).send().await.unwrap();
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers()["allow"], "GET, HEAD, OPTIONS");
+
+    // `/` builds its own 405 body; only the Allow set is completed.
+    let resp = client.put(format!("{base}/")).send().await.unwrap();
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers()["allow"], "GET, HEAD, OPTIONS");
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert!(json["error"].as_str().unwrap().contains("see /endpoints"));
+}
+
+#[tokio::test]
+async fn test_unknown_path_returns_json_404() {
+    for base in [spawn_app().await, spawn_full_app().await] {
+        let resp = reqwest::get(format!("{base}/does-not-exist?x=1"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 404);
+        assert_eq!(resp.headers()["content-type"], "application/json");
+        let json: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"error": "Not Found", "path": "/does-not-exist"})
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_handler_panic_returns_json_500_instead_of_dropping() {
+    async fn panics() -> &'static str {
+        panic!("deliberate test panic")
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = core_routes::router()
+        .route("/panic", axum::routing::get(panics))
+        .layer(catch_panic_layer());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("http://{addr}/panic"))
+        .send()
+        .await
+        .expect("a panic must still produce a response");
+    assert_eq!(resp.status(), 500);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["error"], "Internal Server Error");
+
+    // The server keeps answering afterwards.
+    let resp = client
+        .get(format!("http://{addr}/get"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
+/// Like `spawn_full_app` but with the given `cors` policy and `Server` header.
+async fn spawn_full_app_with_response_policy(
+    cors: rucho::server::cors::CorsConfig,
+    server_header: &str,
+) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let app = rucho::app::build_app(rucho::app::AppOptions {
+        trust_proxy: true,
+        cors,
+        server_header: server_header.to_string(),
+        ..rucho::app::AppOptions::default()
+    });
+
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .unwrap()
+    });
+
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_cors_allow_list_rejects_other_origins() {
+    let base = spawn_full_app_with_response_policy(
+        rucho::server::cors::CorsConfig {
+            allow_origins: vec!["https://app.example".to_string()],
+            allow_methods: vec!["GET".to_string(), "POST".to_string()],
+            ..Default::default()
+        },
+        "",
+    )
+    .await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .get(format!("{base}/get"))
+        .header("origin", "https://evil.example")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(!resp.headers().contains_key("access-control-allow-origin"));
+
+    let resp = client
+        .get(format!("{base}/get"))
+        .header("origin", "https://app.example")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        resp.headers()["access-control-allow-origin"],
+        "https://app.example"
+    );
+
+    let preflight = |origin: &'static str| {
+        client
+            .request(reqwest::Method::OPTIONS, format!("{base}/post"))
+            .header("origin", origin)
+            .header("access-control-request-method", "POST")
+            .send()
+    };
+    let resp = preflight("https://evil.example").await.unwrap();
+    assert!(!resp.headers().contains_key("access-control-allow-origin"));
+    let resp = preflight("https://app.example").await.unwrap();
+    assert_eq!(
+        resp.headers()["access-control-allow-origin"],
+        "https://app.example"
+    );
+    assert_eq!(resp.headers()["access-control-allow-methods"], "GET,POST");
+}
+
+#[tokio::test]
+async fn test_server_header_default_custom_and_disabled() {
+    let client = reqwest::Client::new();
+
+    let base = spawn_full_app().await;
+    for path in ["/get", "/does-not-exist", "/status/503"] {
+        let resp = client.get(format!("{base}{path}")).send().await.unwrap();
+        assert_eq!(
+            resp.headers()["server"],
+            format!("rucho/{}", env!("CARGO_PKG_VERSION")),
+            "{path}"
+        );
+    }
+    // A handler's own Server header wins.
+    let resp = client
+        .get(format!("{base}/response-headers?Server=custom"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["server"], "custom");
+
+    let base = spawn_full_app_with_response_policy(Default::default(), "edge-upstream").await;
+    let resp = client.get(format!("{base}/get")).send().await.unwrap();
+    assert_eq!(resp.headers()["server"], "edge-upstream");
+
+    let base = spawn_full_app_with_response_policy(Default::default(), "").await;
+    let resp = client.get(format!("{base}/get")).send().await.unwrap();
+    assert!(!resp.headers().contains_key("server"));
+}
+
+#[tokio::test]
+async fn test_every_response_has_a_valid_date_header() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+    let format = ::time::format_description::parse(
+        "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT",
+    )
+    .unwrap();
+
+    for path in ["/get", "/does-not-exist", "/status/418"] {
+        let resp = client.get(format!("{base}{path}")).send().await.unwrap();
+        let date = resp.headers()["date"].to_str().unwrap();
+        assert!(
+            ::time::PrimitiveDateTime::parse(date, &format).is_ok(),
+            "{path}: {date}"
+        );
+    }
+
+    // A Date the handler set is not overwritten.
+    let resp = client
+        .get(format!(
+            "{base}/response-headers?Date=Mon,%2001%20Jan%202024%2000:00:00%20GMT"
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["date"], "Mon, 01 Jan 2024 00:00:00 GMT");
+}
+
+#[tokio::test]
+async fn test_kv_put_get_delete_round_trip() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+    let url = format!("{base}/kv/session");
+
+    assert_eq!(client.get(&url).send().await.unwrap().status(), 404);
+
+    let resp = client
+        .put(&url)
+        .header("content-type", "application/json")
+        .body(r#"{"user":"ada"}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 201);
+
+    let resp = client.get(&url).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    assert_eq!(resp.text().await.unwrap(), r#"{"user":"ada"}"#);
+
+    assert_eq!(client.delete(&url).send().await.unwrap().status(), 200);
+    assert_eq!(client.get(&url).send().await.unwrap().status(), 404);
+}
+
+#[tokio::test]
+async fn test_status_custom_body_and_code_list() {
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!(
+        "{base}/status/429?body=slow%20down&header=Retry-After:7"
+    ))
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), 429);
+    assert_eq!(resp.headers()["retry-after"], "7");
+    assert_eq!(resp.text().await.unwrap(), "slow down");
+
+    for _ in 0..20 {
+        let resp = reqwest::get(format!("{base}/status/201,202"))
+            .await
+            .unwrap();
+        let status = resp.status().as_u16();
+        assert!([201, 202].contains(&status));
+        let body: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(body["status"], status);
+    }
+}
+
+#[tokio::test]
+async fn test_weighted_status_rejects_non_positive_weights() {
+    let base = spawn_app().await;
+    for path in ["/status/200:0,500:1", "/status/200:-2", "/status/200:abc"] {
+        let resp = reqwest::get(format!("{base}{path}")).await.unwrap();
+        assert_eq!(resp.status(), 400, "{path}");
+    }
+    let resp = reqwest::get(format!("{base}/status/204:5")).await.unwrap();
+    assert_eq!(resp.status(), 204);
+}
+
+#[tokio::test]
+async fn test_status_503_sends_retry_after() {
+    let base = spawn_app().await;
+    let resp = reqwest::get(format!("{base}/status/503?retry_after=7"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 503);
+    assert_eq!(resp.headers()["retry-after"], "7");
+}
+
+#[tokio::test]
+async fn test_root_welcome_message_negotiates_json() {
+    let base = spawn_full_app().await;
+    let client = reqwest::Client::new();
+    let resp = client.get(format!("{base}/")).send().await.unwrap();
+    assert_eq!(resp.headers()["vary"], "Accept");
+    assert_eq!(resp.text().await.unwrap(), "Welcome to Echo Server!\n");
+
+    let resp = client
+        .get(format!("{base}/"))
+        .header("accept", "application/json")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["message"], "Welcome to Echo Server!");
+}
+
+/// Like `spawn_full_app` but built from `config` via `AppOptions::from_config`,
+/// as the real server does (static files, request timeout, ...).
+async fn spawn_full_app_with_config(config: rucho::utils::config::Config) -> String {
+    config.validate().unwrap();
+    spawn_full_app_with_options(rucho::app::AppOptions::from_config(&config)).await
+}
+
+#[tokio::test]
+async fn test_static_dir_serves_fixture_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("hello.txt"), "hello from disk\n").unwrap();
+    let base = spawn_full_app_with_config(rucho::utils::config::Config {
+        static_dir: Some(dir.path().to_string_lossy().into_owned()),
+        static_prefix: "/fixtures".to_string(),
+        ..Default::default()
+    })
+    .await;
+
+    let resp = reqwest::get(format!("{base}/fixtures/hello.txt"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(resp.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+    assert_eq!(resp.text().await.unwrap(), "hello from disk\n");
+
+    let resp = reqwest::get(format!("{base}/fixtures/%2e%2e/Cargo.toml"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Not Found");
+
+    // Echo endpoints are unaffected.
+    let resp = reqwest::get(format!("{base}/get")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn test_static_prefix_is_unmounted_without_static_dir() {
+    let base = spawn_full_app().await;
+    let resp = reqwest::get(format!("{base}/static/anything.txt"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn test_static_dir_directories_are_404_not_a_redirect_loop() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("site")).unwrap();
+    std::fs::write(dir.path().join("site/index.html"), "<h1>hi</h1>").unwrap();
+    let base = spawn_full_app_with_config(rucho::utils::config::Config {
+        static_dir: Some(dir.path().to_string_lossy().into_owned()),
+        ..Default::default()
+    })
+    .await;
+
+    for path in ["/static/site", "/static/site/"] {
+        let resp = reqwest::get(format!("{base}{path}")).await.unwrap();
+        assert_eq!(resp.status(), 404, "{path}");
+    }
+    let resp = reqwest::get(format!("{base}/static/site/index.html"))
+        .await
+        .unwrap();
+    assert_eq!(resp.text().await.unwrap(), "<h1>hi</h1>");
+}
+
+#[tokio::test]
+async fn test_access_log_excludes_healthz_but_metrics_count_it() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("access.log");
+    let log = rucho::server::access_log::AccessLog::open(
+        path.to_str().unwrap(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap()
+    .with_exclude(vec!["/healthz".to_string()]);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let metrics = std::sync::Arc::new(rucho::utils::metrics::Metrics::new());
+    let app = rucho::app::build_app(rucho::app::AppOptions {
+        metrics: Some(metrics.clone()),
+        trust_proxy: true,
+        access_log: Some(std::sync::Arc::new(log)),
+        ..rucho::app::AppOptions::default()
+    });
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .unwrap()
+    });
+
+    let base = format!("http://{addr}");
+    assert_eq!(
+        reqwest::get(format!("{base}/healthz"))
+            .await
+            .unwrap()
+            .status(),
+        200
+    );
+    assert_eq!(
+        reqwest::get(format!("{base}/get")).await.unwrap().status(),
+        200
+    );
+
+    let lines = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(lines.lines().count(), 1, "{lines}");
+    assert!(lines.contains("\"GET /get HTTP/1.1\" 200"), "{lines}");
+    assert_eq!(metrics.get_endpoint_hits().get("/healthz"), Some(&1));
+}
+
+#[tokio::test]
+async fn test_request_timeout_504s_slow_delay() {
+    let base = spawn_full_app_with_config(rucho::utils::config::Config {
+        request_timeout_secs: 1,
+        ..Default::default()
+    })
+    .await;
+    let client = reqwest::Client::new();
+
+    let resp = client.get(format!("{base}/get")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let start = std::time::Instant::now();
+    let resp = client.get(format!("{base}/delay/3")).send().await.unwrap();
+    assert_eq!(resp.status(), 504);
+    assert!(start.elapsed() < std::time::Duration::from_secs(3));
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "Request timed out");
+}
+
+// --- Probes and introspection (real build_app) ---
+
+/// Like `spawn_full_app` but built from the given `options`.
+async fn spawn_full_app_with_options(options: rucho::app::AppOptions) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let app = rucho::app::build_app(options);
+
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .unwrap()
+    });
+
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_readyz_follows_readiness_livez_does_not() {
+    let readiness = rucho::routes::healthz::Readiness::new();
+    let base = spawn_full_app_with_options(rucho::app::AppOptions {
+        readiness: readiness.clone(),
+        ..rucho::app::AppOptions::default()
+    })
+    .await;
+
+    let resp = reqwest::get(format!("{base}/readyz")).await.unwrap();
+    assert_eq!(resp.status(), 503);
+    assert_eq!(resp.text().await.unwrap(), "Not Ready");
+    let resp = reqwest::get(format!("{base}/livez")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+
+    readiness.set_ready();
+    let resp = reqwest::get(format!("{base}/readyz")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.text().await.unwrap(), "OK");
+}
+
+#[tokio::test]
+async fn test_env_honours_env_allow_prefixes() {
+    // Names unique to this test, so parallel tests can't disturb them.
+    std::env::set_var("ENV_IT_ALLOWED_VAR", "visible");
+    std::env::set_var("ENV_IT_OTHER_VAR", "hidden");
+    let base = spawn_full_app_with_options(rucho::app::AppOptions {
+        env_allow_prefixes: vec!["ENV_IT_ALLOWED_".to_string()],
+        ..rucho::app::AppOptions::default()
+    })
+    .await;
+
+    let resp = reqwest::get(format!("{base}/env")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(
+        body["env"],
+        serde_json::json!({ "ENV_IT_ALLOWED_VAR": "visible" })
+    );
+}
+
+#[tokio::test]
+async fn test_hostname_through_full_stack() {
+    let base = spawn_full_app().await;
+    let resp = reqwest::get(format!("{base}/hostname")).await.unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert!(!body["hostname"].as_str().unwrap().is_empty(), "{body}");
+}
+
+#[tokio::test]
+async fn test_version_through_full_stack() {
+    let base = spawn_full_app().await;
+    let resp = reqwest::get(format!("{base}/version")).await.unwrap();
+
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["name"], env!("CARGO_PKG_NAME"));
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    for field in ["git_commit", "build_timestamp", "rustc_version"] {
+        assert!(body[field].is_string(), "{field}: {body}");
+    }
+}
+
+#[tokio::test]
+async fn test_dump_through_full_stack() {
+    use std::io::Read;
+    let base = spawn_app_with_compression().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/dump?x=1"))
+        .header("x-custom", "hello")
+        .body("ping")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/plain; charset=utf-8");
+    assert!(resp.headers().contains_key("x-response-time"));
+    assert!(!resp.headers().contains_key("content-encoding"));
+    let dump = resp.text().await.unwrap();
+    assert!(dump.starts_with("POST /dump?x=1 HTTP/1.1\r\n"), "{dump}");
+    assert!(dump.contains("\r\nx-custom: hello\r\n"), "{dump}");
+    assert!(dump.ends_with("\r\n\r\nping"), "{dump}");
+
+    // Compressed on the wire, the same text once gunzipped.
+    let resp = client
+        .post(format!("{base}/dump?x=1"))
+        .header("x-custom", "hello")
+        .header(reqwest::header::ACCEPT_ENCODING, "gzip")
+        .body("ping")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["content-encoding"], "gzip");
+    assert_eq!(resp.headers()["content-type"], "text/plain; charset=utf-8");
+    let raw = resp.bytes().await.unwrap();
+    let mut gunzipped = String::new();
+    flate2::read::GzDecoder::new(&raw[..])
+        .read_to_string(&mut gunzipped)
+        .unwrap();
+    assert!(
+        gunzipped.starts_with("POST /dump?x=1 HTTP/1.1\r\n"),
+        "{gunzipped}"
+    );
+    assert!(
+        gunzipped.contains("\r\naccept-encoding: gzip\r\n"),
+        "{gunzipped}"
+    );
+    assert_eq!(
+        gunzipped.replace("accept-encoding: gzip\r\n", ""),
+        dump,
+        "only the extra request header differs"
+    );
+}
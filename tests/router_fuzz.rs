@@ -0,0 +1,194 @@
+//! Property test: no request can panic the router.
+//!
+//! Throws malformed methods, paths, query strings, headers, and bodies at the
+//! REAL `build_app()` (chaos and rate limiting aside) in-process and drains
+//! every response body, so a panic anywhere in a handler, middleware, or body
+//! stream fails the test. Time is paused, so `/delay` and `/drip` cost nothing.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Method, Request};
+use proptest::prelude::*;
+use tower::ServiceExt;
+
+/// Route templates with `{}` standing in for a fuzzed path segment.
+const TEMPLATES: &[&str] = &[
+    "/",
+    "/get",
+    "/post",
+    "/put",
+    "/patch",
+    "/delete",
+    "/options",
+    "/status/{}",
+    "/anything",
+    "/anything/{}",
+    "/healthz",
+    "/livez",
+    "/readyz",
+    "/delay/{}",
+    "/redirect/{}",
+    "/cookies",
+    "/cookies/set",
+    "/cookies/delete",
+    "/uuid",
+    "/uuid/{}",
+    "/time",
+    "/ip",
+    "/user-agent",
+    "/headers",
+    "/endpoints",
+    "/base64/{}",
+    "/bytes/{}",
+    "/stream-bytes/{}",
+    "/response-headers",
+    "/drip",
+    "/events",
+    "/xml",
+    "/html",
+    "/image/{}",
+    "/range/{}",
+    "/gzip",
+    "/deflate",
+    "/brotli",
+    "/cache",
+    "/cache/{}",
+    "/countdown/{}",
+    "/echo",
+    "/dump",
+    "/batch",
+    "/metrics",
+    "/env",
+    "/hostname",
+    "/version",
+    "/kv/{}",
+    "/{}",
+];
+
+fn method() -> impl Strategy<Value = Method> {
+    prop_oneof![
+        Just(Method::GET),
+        Just(Method::HEAD),
+        Just(Method::POST),
+        Just(Method::PUT),
+        Just(Method::PATCH),
+        Just(Method::DELETE),
+        Just(Method::OPTIONS),
+        "[A-Z]{1,8}".prop_map(|m| Method::from_bytes(m.as_bytes()).unwrap_or(Method::GET)),
+    ]
+}
+
+/// Path segments: plausible numbers (including overflowing ones), plus
+/// arbitrary URI-safe junk with percent escapes.
+fn segment() -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<u64>().prop_map(|n| n.to_string()),
+        (0u32..1000).prop_map(|n| n.to_string()),
+        "-?[0-9]{1,30}",
+        "[A-Za-z0-9+/=_~.%-]{0,40}",
+        "[a-z0-9/]{0,20}",
+    ]
+}
+
+fn query() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just(String::new()),
+        "[a-z_]{1,12}=[A-Za-z0-9%+,.:/=&-]{0,40}",
+        "(numbytes|duration|delay|code|n|pretty|preload|sign|fail_midstream|Content-Type)=[-0-9a-z%]{0,20}",
+    ]
+}
+
+/// Header names the handlers interpret, plus random ones.
+fn header_name() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("accept".to_string()),
+        Just("accept-encoding".to_string()),
+        Just("content-type".to_string()),
+        Just("cookie".to_string()),
+        Just("range".to_string()),
+        Just("if-none-match".to_string()),
+        Just("x-forwarded-for".to_string()),
+        Just("x-request-id".to_string()),
+        Just("traceparent".to_string()),
+        Just("b3".to_string()),
+        Just("x-transform".to_string()),
+        Just("x-rucho-scenario".to_string()),
+        "x-[a-z-]{1,16}",
+    ]
+}
+
+fn headers() -> impl Strategy<Value = Vec<(String, Vec<u8>)>> {
+    prop::collection::vec(
+        (
+            header_name(),
+            prop::collection::vec(prop_oneof![0x20u8..0x7f, 0x80u8..=0xff], 0..60),
+        ),
+        0..6,
+    )
+}
+
+fn body() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        prop::collection::vec(any::<u8>(), 0..256),
+        "[\\[\\]{}\":,0-9a-z ]{0,200}".prop_map(String::into_bytes),
+    ]
+}
+
+fn app() -> axum::Router {
+    rucho::app::build_app(rucho::app::AppOptions {
+        metrics: Some(Arc::new(rucho::utils::metrics::Metrics::new())),
+        compression_enabled: true,
+        signing_key: rucho::utils::signature::SigningKey::new("fuzz"),
+        fail_midstream_enabled: true,
+        scenarios: std::collections::BTreeMap::from([(
+            "outage".to_string(),
+            rucho::utils::config::Scenario {
+                status: Some(503),
+                delay_ms: Some(100),
+                body: Some("down".to_string()),
+            },
+        )]),
+        trust_proxy: true,
+        ..rucho::app::AppOptions::default()
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn malformed_requests_never_panic(
+        method in method(),
+        template in prop::sample::select(TEMPLATES),
+        segment in segment(),
+        query in query(),
+        headers in headers(),
+        body in body(),
+    ) {
+        let mut uri = template.replace("{}", &segment);
+        if !query.is_empty() {
+            uri = format!("{uri}?{query}");
+        }
+        let mut builder = Request::builder().method(method).uri(uri);
+        for (name, value) in &headers {
+            builder = builder.header(name.as_str(), value.as_slice());
+        }
+        // Inputs hyper would refuse to parse never reach the router.
+        let Ok(request) = builder.body(Body::from(body)) else {
+            return Ok(());
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let response = app().oneshot(request).await.unwrap();
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .ok();
+        });
+    }
+}